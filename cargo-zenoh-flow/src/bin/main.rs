@@ -249,8 +249,11 @@ async fn main() {
                         outputs: outputs.clone(),
                         uri: Some(uri.clone()),
                         configuration: None,
+                        schema: node_info.schema.clone(),
                         runtime: None,
                         deadline: None,
+                        on_deadline_miss: None,
+                        executor: None,
                     };
 
                     let metadata_arch = RegistryNodeArchitecture {
@@ -318,8 +321,11 @@ async fn main() {
                         output: output.clone(),
                         uri: Some(uri.clone()),
                         configuration: None,
+                        schema: node_info.schema.clone(),
                         runtime: None,
                         period: None,
+                        deadline: None,
+                        on_deadline_miss: None,
                     };
 
                     let metadata_arch = RegistryNodeArchitecture {
@@ -387,7 +393,10 @@ async fn main() {
                         input: input.clone(),
                         uri: Some(uri.clone()),
                         configuration: None,
+                        schema: node_info.schema.clone(),
                         runtime: None,
+                        deadline: None,
+                        on_deadline_miss: None,
                     };
 
                     let metadata_arch = RegistryNodeArchitecture {
@@ -22,6 +22,7 @@ use serde::Deserialize;
 use std::process::Command;
 use zenoh_flow::model::link::PortDescriptor;
 use zenoh_flow::model::{NodeKind, RegistryNode};
+use zenoh_flow::Configuration;
 
 pub static ZF_OUTPUT_DIRECTORY: &str = "zenoh-flow";
 static ZF_CPP_REPO: &str = "https://github.com/ZettaScaleLabs/zenoh-flow-cxx";
@@ -72,6 +73,9 @@ pub struct CargoZenohFlow {
     pub kind: NodeKind,
     pub inputs: Option<Vec<PortDescriptor>>,
     pub outputs: Option<Vec<PortDescriptor>>,
+    /// JSON Schema the configuration of the node must comply with, copied in the generated
+    /// descriptor.
+    pub schema: Option<Configuration>,
 }
 
 pub fn from_manifest(
@@ -15,8 +15,10 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
+use futures::StreamExt;
 use uhlc::HLC;
 use uuid::Uuid;
 use zenoh::prelude::*;
@@ -26,24 +28,36 @@ use zenoh_flow::model::{
     dataflow::record::DataFlowRecord,
     node::{OperatorDescriptor, SinkDescriptor, SourceDescriptor},
 };
-use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::checkpoint::{CheckpointConfig, NodeCheckpoint};
+use zenoh_flow::runtime::dataflow::instance::runners::replay::ReplayOptions;
+use zenoh_flow::runtime::dataflow::instance::{wait_all_idle, DataflowInstance, NodeCheckpointer};
 use zenoh_flow::runtime::dataflow::loader::{
-    ExtensibleImplementation, Loader, LoaderConfig, EXT_FILE_EXTENSION,
+    ExtensibleImplementation, IsolationMode, Loader, LoaderConfig, NodeHostCommand,
+    EXT_FILE_EXTENSION,
 };
 use zenoh_flow::runtime::dataflow::Dataflow;
+use zenoh_flow::runtime::deadline::DeadlineMissStats;
+use zenoh_flow::runtime::liveliness::{
+    LivelinessConfig, LivelinessTracker, RuntimeFailurePolicy, RuntimeHeartbeat,
+    RuntimeRestartPolicy,
+};
 use zenoh_flow::runtime::message::ControlMessage;
+use zenoh_flow::runtime::recording::RecordingInfo;
 use zenoh_flow::runtime::resources::DataStore;
+use zenoh_flow::runtime::watchdog::{NodeHealth, WatchdogConfig};
 use zenoh_flow::runtime::RuntimeClient;
 use zenoh_flow::runtime::RuntimeContext;
 use zenoh_flow::serde::{Deserialize, Serialize};
 
-use zenoh_flow::runtime::{Runtime, RuntimeConfig, RuntimeInfo, RuntimeStatus, RuntimeStatusKind};
+use zenoh_flow::runtime::{
+    ReloadConfig, Runtime, RuntimeConfig, RuntimeInfo, RuntimeStatus, RuntimeStatusKind,
+};
 use zenoh_flow::types::{ZFError, ZFResult};
-use zenoh_flow::NodeId;
+use zenoh_flow::{NodeId, PortId, RuntimeId};
 use zrpc::ZServe;
 use zrpc_macros::znserver;
 
-use crate::util::{get_zenoh_config, read_file};
+use crate::util::{get_zenoh_config, read_file, sd_notify};
 
 /// The daemon configuration file.
 /// The daemon loads this file and uses the informations it contains to
@@ -63,6 +77,26 @@ pub struct DaemonConfig {
     pub zenoh_config: String,
     /// Where to locate the extension files.
     pub extensions: String,
+    /// The watchdog detecting the hung nodes, disabled if None.
+    pub watchdog: Option<WatchdogConfig>,
+    /// Where the recordings are also written as files, if any.
+    #[serde(default)]
+    pub recordings_dir: Option<String>,
+    /// The periodic checkpoints and the restoration of the nodes, disabled if None.
+    #[serde(default)]
+    pub checkpoint: Option<CheckpointConfig>,
+    /// The heartbeat of the runtime and the detection of the failed runtimes, disabled if None.
+    #[serde(default)]
+    pub liveliness: Option<LivelinessConfig>,
+    /// The tags of the runtime.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The hot reload of the configuration and of the extensions, disabled if None.
+    #[serde(default)]
+    pub reload: Option<ReloadConfig>,
+    /// Where the dynamically loaded nodes are executed, within the daemon if None.
+    #[serde(default)]
+    pub isolation: Option<IsolationMode>,
 }
 
 /// The internal runtime state.
@@ -78,11 +112,15 @@ pub struct RTState {
 /// It keeps track of the state, with an `Arc<RTState>`
 /// and the `RuntimeContext`, it has an handle to the `DataStore`
 /// for storing/retrieving data from Zenoh.
+/// Unlike the instances, the daemon always needs its Zenoh session
+/// to serve the requests.
 #[derive(Clone)]
 pub struct Daemon {
+    pub session: Arc<zenoh::Session>,
     pub store: DataStore,
     pub state: Arc<Mutex<RTState>>,
     pub ctx: RuntimeContext,
+    pub config_file: Option<PathBuf>,
 }
 
 impl Daemon {
@@ -94,12 +132,21 @@ impl Daemon {
         }));
 
         Self {
-            store: DataStore::new(z),
+            store: DataStore::new(z.clone()),
+            session: z,
             ctx,
             state,
+            config_file: None,
         }
     }
 
+    /// Sets the configuration file of the daemon, watched for changes when the hot reload is
+    /// enabled.
+    pub fn with_config_file(mut self, path: &Path) -> Self {
+        self.config_file = Some(path.to_path_buf());
+        self
+    }
+
     /// The daemon run.
     ///
     /// It starts the zenoh-rpc services.
@@ -114,7 +161,7 @@ impl Daemon {
 
         let rt_server = self
             .clone()
-            .get_runtime_server(self.ctx.session.clone(), Some(self.ctx.runtime_uuid));
+            .get_runtime_server(self.session.clone(), Some(self.ctx.runtime_uuid));
         let (rt_stopper, _hrt) = rt_server
             .connect()
             .await
@@ -152,12 +199,77 @@ impl Daemon {
             .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
             .await?;
 
+        if let Err(e) = self.reconcile().await {
+            log::error!("[Reconcile] Unable to recover the instances: {:?}", e);
+        }
+
+        sd_notify("READY=1").unwrap_or_else(|e| log::warn!("Unable to notify systemd: {:?}", e));
+
+        let watchdog = self.state.lock().await.config.watchdog.clone();
+        let watchdog = watchdog.map(|config| {
+            let daemon = self.clone();
+            async_std::task::spawn(async move { daemon.watchdog(config).await })
+        });
+
+        let checkpoint_period = self
+            .state
+            .lock()
+            .await
+            .config
+            .checkpoint
+            .as_ref()
+            .and_then(|config| config.period.as_ref())
+            .map(|period| period.to_duration());
+        let checkpointer = checkpoint_period.map(|period| {
+            let daemon = self.clone();
+            async_std::task::spawn(async move { daemon.checkpointer(period).await })
+        });
+
+        let liveliness = self.state.lock().await.config.liveliness.clone();
+        let liveliness = liveliness.map(|config| {
+            let daemon = self.clone();
+            async_std::task::spawn(async move { daemon.liveliness(config).await })
+        });
+
+        let reload = self.state.lock().await.config.reload.clone();
+        let reloaders = reload.map(|config| {
+            let daemon = self.clone();
+            let listener = async_std::task::spawn(async move { daemon.config_listener().await });
+            let watcher = self.config_file.clone().map(|config_file| {
+                let daemon = self.clone();
+                let period = config.period.to_duration();
+                async_std::task::spawn(
+                    async move { daemon.config_watcher(config_file, period).await },
+                )
+            });
+            (listener, watcher)
+        });
+
         log::trace!("Running...");
 
         stop.recv()
             .await
             .map_err(|e| ZFError::RecvError(format!("{}", e)))?;
 
+        if let Some(watchdog) = watchdog {
+            watchdog.cancel().await;
+        }
+
+        if let Some(checkpointer) = checkpointer {
+            checkpointer.cancel().await;
+        }
+
+        if let Some(liveliness) = liveliness {
+            liveliness.cancel().await;
+        }
+
+        if let Some((listener, watcher)) = reloaders {
+            listener.cancel().await;
+            if let Some(watcher) = watcher {
+                watcher.cancel().await;
+            }
+        }
+
         rt_server
             .stop(srt)
             .await
@@ -175,6 +287,698 @@ impl Daemon {
         Ok(())
     }
 
+    /// Reconciles the instances stored for this runtime with its state, empty after a start.
+    ///
+    /// The instances whose record still maps nodes or connectors to this runtime are recovered
+    /// according to their [`RuntimeRestartPolicy`](`RuntimeRestartPolicy`). The records of the
+    /// others, and of the instances that cannot be recovered, are removed.
+    ///
+    /// # Errors
+    /// Returns an error variant if zenoh fails.
+    async fn reconcile(&self) -> ZFResult<()> {
+        for record in self.store.get_runtime_flows(&self.ctx.runtime_uuid).await? {
+            let policy = record.on_runtime_restart.clone().unwrap_or_default();
+            if policy != RuntimeRestartPolicy::Never
+                && record.get_runtimes().contains(&self.ctx.runtime_name)
+            {
+                log::info!("[Reconcile] Recovering instance < {} >", record.uuid);
+                match self.recover_instance(record.uuid, &policy).await {
+                    Ok(()) => continue,
+                    Err(e) => {
+                        log::error!(
+                            "[Reconcile] Unable to recover instance < {} >: {:?}",
+                            record.uuid,
+                            e
+                        );
+                        self.state.lock().await.graphs.remove(&record.uuid);
+                    }
+                }
+            }
+
+            log::info!(
+                "[Reconcile] Removing the record of instance < {} >",
+                record.uuid
+            );
+            self.store
+                .remove_runtime_flow_instance(&self.ctx.runtime_uuid, &record.flow, &record.uuid)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Prepares again the given instance and, if the policy requires it, starts its nodes.
+    ///
+    /// # Errors
+    /// Returns an error variant if the instance cannot be prepared or started.
+    async fn recover_instance(
+        &self,
+        record_id: Uuid,
+        policy: &RuntimeRestartPolicy,
+    ) -> ZFResult<()> {
+        Runtime::prepare(self, record_id).await?;
+
+        if *policy == RuntimeRestartPolicy::Always {
+            Runtime::start(self, record_id).await?;
+            Runtime::start_sources(self, record_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The loop watching the configuration file and the extensions directory.
+    ///
+    /// Every period, if one of the files was added, removed or modified, the configuration is
+    /// read again, stored in Zenoh and applied.
+    async fn config_watcher(&self, config_file: PathBuf, period: Duration) {
+        let mut fingerprint = get_config_fingerprint(&config_file);
+
+        loop {
+            async_std::task::sleep(period).await;
+
+            let current = get_config_fingerprint(&config_file);
+            if current == fingerprint {
+                continue;
+            }
+            fingerprint = current;
+
+            log::info!("[Reload] Reloading {}", config_file.display());
+            let config = match self.read_config(&config_file).await {
+                Ok(config) => config,
+                Err(e) => {
+                    log::error!("[Reload] Unable to read the configuration: {:?}", e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = self
+                .store
+                .add_runtime_config(&self.ctx.runtime_uuid, &config)
+                .await
+            {
+                log::error!("[Reload] Unable to store the configuration: {:?}", e);
+            }
+            self.apply_config(config).await;
+        }
+    }
+
+    /// The loop applying the configurations stored in Zenoh for this runtime.
+    async fn config_listener(&self) {
+        let mut configs = match self
+            .store
+            .subscribe_runtime_config(&self.ctx.runtime_uuid)
+            .await
+        {
+            Ok(configs) => configs,
+            Err(e) => {
+                log::error!("[Reload] Unable to subscribe to the configuration: {:?}", e);
+                return;
+            }
+        };
+
+        while let Some(config) = configs.next().await {
+            self.apply_config(config).await;
+        }
+    }
+
+    /// Reads the configuration file of the daemon.
+    ///
+    /// The name, the uuid and the Zenoh configuration of the runtime are kept.
+    ///
+    /// # Errors
+    /// Returns an error variant if the file cannot be read or parsed.
+    async fn read_config(&self, config_file: &Path) -> ZFResult<RuntimeConfig> {
+        let daemon_config = serde_yaml::from_str::<DaemonConfig>(&read_file(config_file)?)
+            .map_err(|e| ZFError::ParsingError(format!("{}", e)))?;
+
+        let mut config = self.state.lock().await.config.clone();
+        config.pid_file = daemon_config.pid_file;
+        config.path = daemon_config.path;
+        config.loader = load_extensions(Path::new(&daemon_config.extensions))?;
+        config.extensions = Some(daemon_config.extensions);
+        config.watchdog = daemon_config.watchdog;
+        config.recordings_dir = daemon_config.recordings_dir;
+        config.checkpoint = daemon_config.checkpoint;
+        config.liveliness = daemon_config.liveliness;
+        config.tags = daemon_config.tags;
+        config.reload = daemon_config.reload;
+        config.isolation = daemon_config.isolation;
+
+        Ok(config)
+    }
+
+    /// Applies the given configuration while the runtime is running.
+    ///
+    /// The extensions of the loader and the tags are updated, the running instances are left
+    /// untouched: only the nodes loaded afterward use the new extensions. The other changes are
+    /// ignored until the runtime restarts.
+    async fn apply_config(&self, config: RuntimeConfig) {
+        // The lock is not held while the extensions are reloaded and the tags stored.
+        let (tags_changed, mut applied) = {
+            let state = self.state.lock().await;
+            (state.config.tags != config.tags, state.config.clone())
+        };
+        applied.loader = config.loader.clone();
+        applied.tags = config.tags.clone();
+        if serde_json::to_value(&applied).ok() != serde_json::to_value(&config).ok() {
+            log::warn!(
+                "[Reload] Only the extensions and the tags are applied while running, restart the runtime to apply the other changes"
+            );
+        }
+
+        let changed = self.ctx.loader.reload_extensions(config.loader.clone());
+        if !changed.is_empty() {
+            log::info!("[Reload] Extensions {:?} changed", changed);
+        }
+
+        if tags_changed {
+            log::info!("[Reload] Tags changed to {:?}", config.tags);
+            match self.store.get_runtime_info(&self.ctx.runtime_uuid).await {
+                Ok(mut rt_info) => {
+                    rt_info.tags = config.tags.clone();
+                    if let Err(e) = self
+                        .store
+                        .add_runtime_info(&self.ctx.runtime_uuid, &rt_info)
+                        .await
+                    {
+                        log::error!("[Reload] Unable to update the tags: {:?}", e);
+                    }
+                }
+                Err(e) => log::error!("[Reload] Unable to update the tags: {:?}", e),
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        state.config.loader = applied.loader;
+        state.config.tags = applied.tags;
+    }
+
+    /// The watchdog loop.
+    ///
+    /// Every period, it looks for the hung nodes of all the instances, logs them and, if
+    /// configured, restarts them.
+    async fn watchdog(&self, config: WatchdogConfig) {
+        let period = config.period.to_duration();
+        let hang_threshold = config.hang_threshold.to_duration();
+
+        loop {
+            async_std::task::sleep(period).await;
+
+            // The hung nodes to restart are detached while holding the lock, they are stopped
+            // once it is released: a hung node can take up to `period` to stop.
+            let (restore, hung_nodes) = {
+                let mut state = self.state.lock().await;
+                let restore = state
+                    .config
+                    .checkpoint
+                    .as_ref()
+                    .map_or(false, |config| config.restore);
+                let mut hung_nodes = vec![];
+                for (instance_id, instance) in state.graphs.iter_mut() {
+                    for (node_id, elapsed) in instance.get_hung_nodes(hang_threshold).await {
+                        log::warn!(
+                            "[Watchdog] Node < {} > of instance < {} > is hung: `run` started {:?} ago",
+                            node_id,
+                            instance_id,
+                            elapsed
+                        );
+
+                        if config.restart {
+                            match instance.detach_node(&node_id) {
+                                Ok(manager) => hung_nodes.push((*instance_id, node_id, manager)),
+                                Err(e) => log::error!(
+                                    "[Watchdog] Unable to stop node < {} >: {:?}",
+                                    node_id,
+                                    e
+                                ),
+                            }
+                        }
+                    }
+                }
+                (restore, hung_nodes)
+            };
+
+            for (instance_id, node_id, manager) in hung_nodes {
+                log::info!("[Watchdog] Restarting node < {} >", node_id);
+                // A detached node is still counted as running, until it fails to restart.
+                let detached = [(node_id.clone(), manager.get_kind())];
+                if let Err(e) = manager.stop_within(period).await {
+                    log::warn!("[Watchdog] Unable to stop node < {} >: {:?}", node_id, e);
+                }
+
+                let checkpoint = if restore {
+                    self.get_node_checkpoint(&instance_id, &node_id)
+                        .await
+                        .unwrap_or_else(|e| {
+                            log::error!(
+                                "[Watchdog] Unable to retrieve the checkpoint of node < {} >: {:?}",
+                                node_id,
+                                e
+                            );
+                            None
+                        })
+                } else {
+                    None
+                };
+
+                let mut state = self.state.lock().await;
+                // The instance may have been stopped in the meantime.
+                let restarted = match state.graphs.get_mut(&instance_id) {
+                    Some(instance) => {
+                        if let Some(checkpoint) = checkpoint {
+                            if let Err(e) = instance.restore_node(&checkpoint).await {
+                                log::error!(
+                                    "[Watchdog] Unable to restore node < {} >: {:?}",
+                                    node_id,
+                                    e
+                                );
+                            }
+                        }
+
+                        match instance.start_node(&node_id).await {
+                            Ok(()) => true,
+                            Err(e) => {
+                                log::error!(
+                                    "[Watchdog] Unable to restart node < {} >: {:?}",
+                                    node_id,
+                                    e
+                                );
+                                false
+                            }
+                        }
+                    }
+                    None => false,
+                };
+
+                if !restarted {
+                    if let Err(e) = self
+                        .update_runtime_status(|status| status.update_running_nodes(&detached, &[]))
+                        .await
+                    {
+                        log::error!("[Watchdog] Unable to update the runtime status: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The checkpoint loop.
+    ///
+    /// Every period, it stores a checkpoint of the nodes of all the instances that support it. A
+    /// node that cannot be checkpointed is logged and skipped until the next period.
+    async fn checkpointer(&self, period: Duration) {
+        loop {
+            async_std::task::sleep(period).await;
+
+            // The nodes are checkpointed once the lock is released: each one can take up to
+            // `CHECKPOINT_TIMEOUT` to release its state.
+            let checkpointers: Vec<NodeCheckpointer> = {
+                let state = self.state.lock().await;
+                state
+                    .graphs
+                    .values()
+                    .flat_map(|instance| instance.get_checkpointers())
+                    .collect()
+            };
+
+            for checkpointer in checkpointers {
+                let checkpoint = match checkpointer.checkpoint().await {
+                    Ok(Some(checkpoint)) => checkpoint,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log::warn!(
+                            "[Checkpoint] Unable to checkpoint node < {} > of instance < {} >: {:?}",
+                            checkpointer.get_id(),
+                            checkpointer.get_instance_id(),
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                if let Err(e) = self.store.add_node_checkpoint(&checkpoint).await {
+                    log::error!(
+                        "[Checkpoint] Unable to store the checkpoint of node < {} >: {:?}",
+                        checkpoint.node_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// The liveliness loop.
+    ///
+    /// Every period, it publishes the heartbeat of the runtime and looks for the runtimes whose
+    /// heartbeat did not change for longer than the lease. The failures are handled by the alive
+    /// runtime with the lowest identifier, the others only log them.
+    async fn liveliness(&self, config: LivelinessConfig) {
+        let period = config.period.to_duration();
+        let lease = config.lease.to_duration();
+        let mut tracker = LivelinessTracker::default();
+
+        loop {
+            let heartbeat = RuntimeHeartbeat {
+                id: self.ctx.runtime_uuid,
+                timestamp: self.ctx.hlc.new_timestamp(),
+            };
+            if let Err(e) = self.store.add_runtime_heartbeat(&heartbeat).await {
+                log::error!("[Liveliness] Unable to publish the heartbeat: {:?}", e);
+            }
+
+            match self.store.get_all_runtime_heartbeats().await {
+                Ok(heartbeats) => {
+                    let failed = tracker.update(&heartbeats, Instant::now(), lease);
+                    let coordinator = tracker.is_coordinator(&self.ctx.runtime_uuid);
+                    for runtime_id in failed {
+                        log::warn!(
+                            "[Liveliness] Runtime < {} > did not publish its heartbeat for {:?}",
+                            runtime_id,
+                            lease
+                        );
+
+                        if coordinator {
+                            if let Err(e) = self
+                                .handle_runtime_failure(runtime_id, &tracker.get_alive())
+                                .await
+                            {
+                                log::error!(
+                                    "[Liveliness] Unable to handle the failure of runtime < {} >: {:?}",
+                                    runtime_id,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+                Err(e) => log::error!("[Liveliness] Unable to get the heartbeats: {:?}", e),
+            }
+
+            async_std::task::sleep(period).await;
+        }
+    }
+
+    /// Handles the failure of the given runtime.
+    ///
+    /// The nodes of the instances it was running are re-mapped to the `alive` runtimes when the
+    /// policy of their instance requires it. Its entries are then removed from the `DataStore`.
+    ///
+    /// # Errors
+    /// Returns an error variant if zenoh fails.
+    async fn handle_runtime_failure(&self, runtime_id: Uuid, alive: &[Uuid]) -> ZFResult<()> {
+        let runtime_name = match self.store.get_runtime_info(&runtime_id).await {
+            Ok(info) => Some(info.name),
+            Err(e) => {
+                log::warn!(
+                    "[Liveliness] No information on runtime < {} >, its nodes cannot be re-mapped: {:?}",
+                    runtime_id,
+                    e
+                );
+                None
+            }
+        };
+
+        for record in self.store.get_runtime_flows(&runtime_id).await? {
+            self.store
+                .remove_runtime_flow_instance(&runtime_id, &record.flow, &record.uuid)
+                .await?;
+
+            if let Some(runtime_name) = &runtime_name {
+                if let Err(e) = self
+                    .remap_orphaned_nodes(&record, runtime_name, alive)
+                    .await
+                {
+                    log::error!(
+                        "[Liveliness] Unable to re-map the nodes of instance < {} >: {:?}",
+                        record.uuid,
+                        e
+                    );
+                }
+            }
+        }
+
+        self.store.remove_runtime_config(&runtime_id).await?;
+        self.store.remove_runtime_info(&runtime_id).await?;
+        self.store.remove_runtime_status(&runtime_id).await?;
+        self.store.remove_runtime_heartbeat(&runtime_id).await?;
+
+        log::info!("[Liveliness] Removed failed runtime < {} >", runtime_id);
+
+        Ok(())
+    }
+
+    /// Re-maps the nodes of the given instance that were mapped to the failed runtime, according
+    /// to the policy of the instance.
+    ///
+    /// Each node goes to the alive and ready runtime, allowed by the policy, that runs the
+    /// fewest nodes of the instance; the runtimes already involved in the instance are preferred.
+    /// The connectors of all the involved runtimes are then rebuilt and the nodes are restored
+    /// from their latest checkpoint, if any.
+    ///
+    /// # Errors
+    /// Returns an error variant if no runtime can take the nodes or if a runtime cannot be
+    /// reconfigured.
+    async fn remap_orphaned_nodes(
+        &self,
+        record: &DataFlowRecord,
+        failed: &str,
+        alive: &[Uuid],
+    ) -> ZFResult<()> {
+        let orphans = record.get_runtime_nodes(failed);
+        if orphans.is_empty() {
+            return Ok(());
+        }
+
+        let allowed = match &record.on_runtime_failure {
+            Some(RuntimeFailurePolicy::Remap { runtimes }) => runtimes,
+            _ => {
+                log::error!(
+                    "[Liveliness] Nodes {:?} of instance < {} > are orphaned",
+                    orphans,
+                    record.uuid
+                );
+                return Ok(());
+            }
+        };
+
+        let runtimes_info = self.store.get_all_runtime_info().await?;
+        let candidates: Vec<&RuntimeInfo> = runtimes_info
+            .iter()
+            .filter(|rt| {
+                rt.name.as_ref() != failed
+                    && matches!(rt.status, RuntimeStatusKind::Ready)
+                    && (alive.contains(&rt.id) || rt.id == self.ctx.runtime_uuid)
+                    && (allowed.is_empty() || allowed.contains(&rt.name))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return Err(ZFError::Uncompleted(format!(
+                "No runtime can take the nodes {:?}",
+                orphans
+            )));
+        }
+
+        let involved = record.get_runtimes();
+        let mut load: HashMap<RuntimeId, usize> = candidates
+            .iter()
+            .map(|rt| (rt.name.clone(), record.get_runtime_nodes(&rt.name).len()))
+            .collect();
+        let mut mapping = HashMap::with_capacity(orphans.len());
+        for node_id in orphans.iter() {
+            let target = candidates
+                .iter()
+                .min_by_key(|rt| (!involved.contains(&rt.name), load[&rt.name]))
+                .map(|rt| rt.name.clone())
+                .ok_or(ZFError::NotFound)?;
+            *load.entry(target.clone()).or_default() += 1;
+            log::info!(
+                "[Liveliness] Re-mapping node < {} > of instance < {} > to < {} >",
+                node_id,
+                record.uuid,
+                target
+            );
+            mapping.insert(node_id.clone(), target);
+        }
+
+        let remapped = record.try_remap_nodes(&mapping)?;
+        let runtime_id = |name: &RuntimeId| {
+            runtimes_info
+                .iter()
+                .find(|rt| rt.name == *name)
+                .map(|rt| rt.id)
+                .ok_or(ZFError::NotFound)
+        };
+
+        // The re-mapped nodes are started only if the rest of the instance is.
+        let mut running = true;
+        for rt in involved.iter().filter(|rt| rt.as_ref() != failed) {
+            if let Some(node_id) = record.get_runtime_nodes(rt).first() {
+                let health = self
+                    .get_remote_node_state(runtime_id(rt)?, record.uuid, node_id)
+                    .await?;
+                running = health != NodeHealth::Stopped;
+                break;
+            }
+        }
+
+        let mut runtimes = remapped.get_runtimes();
+        for rt in involved {
+            if !runtimes.contains(&rt) {
+                runtimes.push(rt);
+            }
+        }
+        for rt in runtimes.iter().filter(|rt| rt.as_ref() != failed) {
+            self.reconfigure_runtime(runtime_id(rt)?, record.uuid, &remapped, running)
+                .await?;
+        }
+
+        for (node_id, rt) in mapping.iter() {
+            let rt_id = runtime_id(rt)?;
+            let restored = if rt_id == self.ctx.runtime_uuid {
+                Runtime::restore_node(self, record.uuid, node_id.to_string()).await
+            } else {
+                RuntimeClient::new(self.session.clone(), rt_id)
+                    .restore_node(record.uuid, node_id.to_string())
+                    .await?
+            };
+            match restored {
+                Ok(()) | Err(ZFError::Empty) | Err(ZFError::Unsupported) => (),
+                Err(e) => return Err(e),
+            }
+
+            if running {
+                if rt_id == self.ctx.runtime_uuid {
+                    Runtime::start_node(self, record.uuid, node_id.to_string()).await?;
+                } else {
+                    RuntimeClient::new(self.session.clone(), rt_id)
+                        .start_node(record.uuid, node_id.to_string())
+                        .await??;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the health of the given node on the given runtime, locally or through zenoh-rpc.
+    ///
+    /// # Errors
+    /// Returns an error variant if zenoh-rpc fails or if the node is not found.
+    async fn get_remote_node_state(
+        &self,
+        runtime_id: Uuid,
+        record_id: Uuid,
+        node_id: &NodeId,
+    ) -> ZFResult<NodeHealth> {
+        if runtime_id == self.ctx.runtime_uuid {
+            Runtime::get_node_state(self, record_id, node_id.to_string()).await
+        } else {
+            RuntimeClient::new(self.session.clone(), runtime_id)
+                .get_node_state(record_id, node_id.to_string())
+                .await?
+        }
+    }
+
+    /// Reconfigures the instance on the given runtime, locally or through zenoh-rpc.
+    ///
+    /// # Errors
+    /// Returns an error variant if zenoh-rpc fails or if the runtime cannot reconfigure.
+    async fn reconfigure_runtime(
+        &self,
+        runtime_id: Uuid,
+        record_id: Uuid,
+        record: &DataFlowRecord,
+        start: bool,
+    ) -> ZFResult<Vec<NodeId>> {
+        if runtime_id == self.ctx.runtime_uuid {
+            Runtime::reconfigure(self, record_id, record.clone(), start).await
+        } else {
+            RuntimeClient::new(self.session.clone(), runtime_id)
+                .reconfigure(record_id, record.clone(), start)
+                .await?
+        }
+    }
+
+    /// Returns the latest checkpoint of the given node of the given instance, if any.
+    ///
+    /// # Errors
+    /// Returns an error variant if the checkpoint cannot be retrieved.
+    async fn get_node_checkpoint(
+        &self,
+        instance_id: &Uuid,
+        node_id: &NodeId,
+    ) -> ZFResult<Option<NodeCheckpoint>> {
+        match self.store.get_node_checkpoint(instance_id, node_id).await {
+            Ok(checkpoint) => Ok(Some(checkpoint)),
+            Err(ZFError::Empty) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Updates the stored status of this runtime.
+    ///
+    /// # Errors
+    /// Returns an error variant if the status cannot be retrieved or stored.
+    async fn update_runtime_status<F>(&self, update: F) -> ZFResult<()>
+    where
+        F: FnOnce(&mut RuntimeStatus),
+    {
+        let mut rt_status = self
+            .store
+            .get_runtime_status(&self.ctx.runtime_uuid)
+            .await?;
+        update(&mut rt_status);
+        self.store
+            .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            .await
+    }
+
+    /// Checks if the nodes of the given instance are idle on all the given runtimes, and on this
+    /// one if `local` is set.
+    ///
+    /// # Errors
+    /// Returns an error variant if a runtime cannot be reached or does not run the instance.
+    async fn is_instance_idle(
+        &self,
+        record_id: Uuid,
+        local: bool,
+        clients: &[RuntimeClient],
+    ) -> ZFResult<bool> {
+        if local && !Runtime::is_idle(self, record_id).await? {
+            return Ok(false);
+        }
+        for client in clients {
+            if !client.is_idle(record_id).await?? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Restores the nodes of the given instance from their latest checkpoints, through their
+    /// `checkpointers`: the instance is not held while the checkpoints are retrieved and the
+    /// nodes restored.
+    ///
+    /// # Errors
+    /// Returns an error variant if the checkpoints cannot be retrieved or restored.
+    async fn restore_checkpoints(
+        &self,
+        instance_id: &Uuid,
+        checkpointers: &[NodeCheckpointer],
+    ) -> ZFResult<Vec<NodeId>> {
+        let mut restored = vec![];
+        for checkpoint in self.store.get_instance_checkpoints(instance_id).await? {
+            if let Some(checkpointer) = checkpointers
+                .iter()
+                .find(|checkpointer| checkpointer.get_id() == checkpoint.node_id)
+            {
+                checkpointer.restore(&checkpoint).await?;
+                restored.push(checkpoint.node_id);
+            }
+        }
+        Ok(restored)
+    }
+
     /// Starts the daemon.
     ///
     /// It stores the configuration and runtime information in Zenoh.
@@ -197,7 +1001,7 @@ impl Daemon {
         let rt_info = RuntimeInfo {
             id: self.ctx.runtime_uuid,
             name: self.ctx.runtime_name.clone(),
-            tags: Vec::new(),
+            tags: self.state.lock().await.config.tags.clone(),
             status: RuntimeStatusKind::NotReady,
         };
 
@@ -232,7 +1036,7 @@ impl Daemon {
 
     /// Stops the daemon.
     ///
-    /// Removes information, configuration and status from Zenoh.
+    /// Removes information, configuration, status and heartbeat from Zenoh.
     ///
     /// # Errors
     /// Returns an error variant if zenoh fails, or if the stop
@@ -251,6 +1055,9 @@ impl Daemon {
         self.store
             .remove_runtime_status(&self.ctx.runtime_uuid)
             .await?;
+        self.store
+            .remove_runtime_heartbeat(&self.ctx.runtime_uuid)
+            .await?;
 
         Ok(())
     }
@@ -266,110 +1073,153 @@ pub fn get_machine_uuid() -> ZFResult<Uuid> {
     Uuid::parse_str(node_str).map_err(|e| ZFError::ParsingError(format!("{}", e)))
 }
 
-/// Creates a new `Daemon` from a configuration file.
+/// Returns the modification time and the size of the configuration file and of the files of
+/// its extensions directory: it changes whenever one of them is added, removed or modified.
+pub(crate) fn get_config_fingerprint(
+    config_file: &Path,
+) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let mut paths = vec![config_file.to_path_buf()];
+    let ext_dir = read_file(config_file)
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<DaemonConfig>(&content).ok())
+        .map(|config| PathBuf::from(config.extensions));
+    if let Some(entries) = ext_dir.and_then(|ext_dir| fs::read_dir(ext_dir).ok()) {
+        paths.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path()),
+        );
+    }
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map_or(0, |m| m.len());
+            (path, modified, len)
+        })
+        .collect()
+}
+
+/// Loads the extensions found in the given directory.
+///
+/// The files that cannot be read or parsed, and the extensions that are
+/// invalid or cannot be added, are logged and skipped.
 ///
 /// # Errors
 /// This function can fail if:
-/// - unable to configure zenoh.
-/// - unable to get the machine hostname.
-/// - unable to get the machine uuid.
-/// - unable to open the zenoh session.
-impl TryFrom<DaemonConfig> for Daemon {
-    type Error = ZFError;
-
-    fn try_from(config: DaemonConfig) -> Result<Self, Self::Error> {
-        // If Uuid is not specified uses machine id.
-        let uuid = match &config.uuid {
-            Some(u) => *u,
-            None => get_machine_uuid()?,
-        };
+/// - unable to read the directory.
+pub(crate) fn load_extensions(ext_dir: &Path) -> ZFResult<LoaderConfig> {
+    let mut extensions = LoaderConfig::new();
+
+    // Loading extensions, if an error happens, we do not return
+    // instead we log it.
+    if ext_dir.is_dir() {
+        let ext_dir_entries = fs::read_dir(ext_dir)?;
+        for entry in ext_dir_entries {
+            match entry {
+                Ok(entry) => {
+                    let entry_path = entry.path();
+                    if entry_path.is_file() {
+                        match entry_path.extension() {
+                            Some(entry_ext) => {
+                                if entry_ext != EXT_FILE_EXTENSION {
+                                    log::warn!(
+                                        "Skipping {} as it does not match the extension {}",
+                                        entry_path.display(),
+                                        EXT_FILE_EXTENSION
+                                    );
+                                    continue;
+                                }
 
-        // If name is not specified uses hostname.
-        let name = match &config.name {
-            Some(n) => n.clone(),
-            None => String::from(hostname::get()?.to_str().ok_or(ZFError::GenericError)?),
-        };
-
-        // Loading Zenoh configuration
-        let zconfig = get_zenoh_config(&config.zenoh_config)?;
-
-        // Generates the loader configuration.
-        let mut extensions = LoaderConfig::new();
-
-        let ext_dir = Path::new(&config.extensions);
-
-        // Loading extensions, if an error happens, we do not return
-        // instead we log it.
-        if ext_dir.is_dir() {
-            let ext_dir_entries = fs::read_dir(ext_dir)?;
-            for entry in ext_dir_entries {
-                match entry {
-                    Ok(entry) => {
-                        let entry_path = entry.path();
-                        if entry_path.is_file() {
-                            match entry_path.extension() {
-                                Some(entry_ext) => {
-                                    if entry_ext != EXT_FILE_EXTENSION {
-                                        log::warn!(
-                                            "Skipping {} as it does not match the extension {}",
-                                            entry_path.display(),
-                                            EXT_FILE_EXTENSION
-                                        );
-                                        continue;
-                                    }
-
-                                    // Read the files.
-
-                                    match read_file(&entry_path) {
-                                        Ok(ext_file_content) => {
-                                            match serde_yaml::from_str::<ExtensibleImplementation>(
-                                                &ext_file_content,
-                                            ) {
-                                                Ok(ext) => {
-                                                    match extensions.try_add_extension(ext) {
-                                                        Ok(_) => log::info!(
-                                                            "Loaded extension {}",
-                                                            entry_path.display()
-                                                        ),
-                                                        Err(e) => log::warn!(
-                                                            "Unable to load extension {}: {}",
-                                                            entry_path.display(),
-                                                            e
-                                                        ),
-                                                    }
-                                                }
-                                                Err(e) => log::warn!(
-                                                    "Unable to parse extension file {}: {}",
+                                // Read the files.
+
+                                match read_file(&entry_path) {
+                                    Ok(ext_file_content) => {
+                                        match serde_yaml::from_str::<ExtensibleImplementation>(
+                                            &ext_file_content,
+                                        ) {
+                                            Ok(ext) => match ext
+                                                .validate()
+                                                .and_then(|_| extensions.try_add_extension(ext))
+                                            {
+                                                Ok(_) => log::info!(
+                                                    "Loaded extension {}",
+                                                    entry_path.display()
+                                                ),
+                                                Err(e) => log::error!(
+                                                    "Unable to load extension {}: {}",
                                                     entry_path.display(),
                                                     e
                                                 ),
-                                            }
+                                            },
+                                            Err(e) => log::warn!(
+                                                "Unable to parse extension file {}: {}",
+                                                entry_path.display(),
+                                                e
+                                            ),
                                         }
-                                        Err(e) => log::warn!(
-                                            "Unable to read extension file {}: {}",
-                                            entry_path.display(),
-                                            e
-                                        ),
                                     }
+                                    Err(e) => log::warn!(
+                                        "Unable to read extension file {}: {}",
+                                        entry_path.display(),
+                                        e
+                                    ),
                                 }
-                                None => log::warn!(
-                                    "Skipping {} as it as no extension",
-                                    entry_path.display()
-                                ),
                             }
-                        } else {
-                            log::warn!("Skipping {} as it is not a file", entry_path.display());
+                            None => log::warn!(
+                                "Skipping {} as it as no extension",
+                                entry_path.display()
+                            ),
                         }
+                    } else {
+                        log::warn!("Skipping {} as it is not a file", entry_path.display());
                     }
-                    Err(e) => log::warn!("Unable to access extension file: {}", e),
                 }
+                Err(e) => log::warn!("Unable to access extension file: {}", e),
             }
-        } else {
-            log::warn!(
-                "The extension parameter: {} is not a directory",
-                ext_dir.display()
-            );
         }
+    } else {
+        log::warn!(
+            "The extension parameter: {} is not a directory",
+            ext_dir.display()
+        );
+    }
+
+    Ok(extensions)
+}
+
+/// Creates a new `Daemon` from a configuration file.
+///
+/// # Errors
+/// This function can fail if:
+/// - unable to configure zenoh.
+/// - unable to get the machine hostname.
+/// - unable to get the machine uuid.
+/// - unable to open the zenoh session.
+impl TryFrom<DaemonConfig> for Daemon {
+    type Error = ZFError;
+
+    fn try_from(config: DaemonConfig) -> Result<Self, Self::Error> {
+        // If Uuid is not specified uses machine id.
+        let uuid = match &config.uuid {
+            Some(u) => *u,
+            None => get_machine_uuid()?,
+        };
+
+        // If name is not specified uses hostname.
+        let name = match &config.name {
+            Some(n) => n.clone(),
+            None => String::from(hostname::get()?.to_str().ok_or(ZFError::GenericError)?),
+        };
+
+        // Loading Zenoh configuration
+        let zconfig = get_zenoh_config(&config.zenoh_config)?;
+
+        // Loading the extensions.
+        let extensions = load_extensions(Path::new(&config.extensions))?;
 
         // Generates the RuntimeConfig
         let rt_config = RuntimeConfig {
@@ -379,6 +1229,14 @@ impl TryFrom<DaemonConfig> for Daemon {
             uuid,
             zenoh: zconfig.clone(),
             loader: extensions.clone(),
+            watchdog: config.watchdog,
+            recordings_dir: config.recordings_dir,
+            checkpoint: config.checkpoint,
+            liveliness: config.liveliness,
+            tags: config.tags,
+            reload: config.reload,
+            extensions: Some(config.extensions),
+            isolation: config.isolation,
         };
 
         // Creates the zenoh session.
@@ -387,15 +1245,24 @@ impl TryFrom<DaemonConfig> for Daemon {
         // Creates the HLC.
         let hlc = Arc::new(HLC::default());
 
-        // Creates the loader.
-        let loader = Arc::new(Loader::new(extensions));
+        // Creates the loader, the isolated nodes are executed by the `node-host` command of the
+        // daemon itself.
+        let mut loader = Loader::new(extensions);
+        if rt_config.isolation == Some(IsolationMode::Process) {
+            loader = loader.with_node_host(NodeHostCommand {
+                program: std::env::current_exe()?,
+                args: vec!["node-host".to_string()],
+            });
+        }
+        let loader = Arc::new(loader);
 
         let ctx = RuntimeContext {
-            session: session.clone(),
+            session: session.clone().into(),
             hlc,
             loader,
             runtime_name: rt_config.name.clone().into(),
             runtime_uuid: uuid,
+            recordings_dir: rt_config.recordings_dir.as_ref().map(PathBuf::from),
         };
 
         Ok(Self::new(session, ctx, rt_config))
@@ -449,7 +1316,7 @@ impl Runtime for Daemon {
         // Creating clients to talk with other runtimes
         for rt in involved_runtimes {
             let rt_info = self.store.get_runtime_info_by_name(&rt).await?;
-            let client = RuntimeClient::new(self.ctx.session.clone(), rt_info.id);
+            let client = RuntimeClient::new(self.session.clone(), rt_info.id);
             rt_clients.push(client);
         }
 
@@ -485,7 +1352,7 @@ impl Runtime for Daemon {
             .filter(|rt| *rt != self.ctx.runtime_uuid);
 
         for rt in remote_involved_runtimes {
-            let client = RuntimeClient::new(self.ctx.session.clone(), rt);
+            let client = RuntimeClient::new(self.session.clone(), rt);
             rt_clients.push(client);
         }
 
@@ -503,6 +1370,15 @@ impl Runtime for Daemon {
             .remove_runtime_flow_instance(&self.ctx.runtime_uuid, &record.flow, &record.uuid)
             .await?;
 
+        // The checkpoints are kept per instance: no other instance can restore them.
+        if let Err(e) = self.store.remove_instance_checkpoints(&record_id).await {
+            log::warn!(
+                "Unable to remove the checkpoints of Instance UUID {}: {:?}",
+                record_id,
+                e
+            );
+        }
+
         log::info!("Done delete Instance UUID: {}", record_id);
 
         Ok(record)
@@ -537,11 +1413,15 @@ impl Runtime for Daemon {
         Ok(dfr)
     }
 
-    async fn teardown(&self, record_id: Uuid) -> ZFResult<DataFlowRecord> {
+    async fn teardown(
+        &self,
+        record_id: Uuid,
+        drain_timeout: Option<Duration>,
+    ) -> ZFResult<DataFlowRecord> {
         log::info!("Tearing down Instance UUID: {}", record_id);
 
         // Stopping
-        Runtime::stop_instance(self, record_id).await?;
+        Runtime::stop_instance(self, record_id, drain_timeout).await?;
 
         // Clean-up
         let dfr = Runtime::delete_instance(self, record_id).await?;
@@ -559,12 +1439,31 @@ impl Runtime for Daemon {
             .add_runtime_flow(&self.ctx.runtime_uuid, &dfr)
             .await?;
 
-        let mut dataflow = Dataflow::try_new(self.ctx.clone(), dfr.clone())?;
+        // Loading the nodes can block, e.g. to start their node hosts.
+        let (ctx, record) = (self.ctx.clone(), dfr.clone());
+        let mut dataflow =
+            async_std::task::spawn_blocking(move || Dataflow::try_new(ctx, record)).await?;
         let mut instance = DataflowInstance::try_instantiate(dataflow)?;
 
-        let mut self_state = self.state.lock().await;
-        self_state.graphs.insert(dfr.uuid, instance);
-        drop(self_state);
+        let restore = self
+            .state
+            .lock()
+            .await
+            .config
+            .checkpoint
+            .as_ref()
+            .map_or(false, |config| config.restore);
+        // The instance is not shared yet: it is restored before it is inserted.
+        if restore {
+            match self
+                .restore_checkpoints(&instance.get_uuid(), &instance.get_checkpointers())
+                .await
+            {
+                Ok(nodes) => log::info!("Restored nodes {:?} from their checkpoints", nodes),
+                Err(e) => log::warn!("Unable to restore the checkpoints: {:?}", e),
+            }
+        }
+        self.state.lock().await.graphs.insert(dfr.uuid, instance);
 
         log::info!("Done preparation for Instance UUID: {}", record_id);
 
@@ -652,7 +1551,7 @@ impl Runtime for Daemon {
             .filter(|rt| *rt != self.ctx.runtime_uuid);
 
         for rt in all_involved_runtimes {
-            let client = RuntimeClient::new(self.ctx.session.clone(), rt);
+            let client = RuntimeClient::new(self.session.clone(), rt);
             rt_clients.push(client);
         }
 
@@ -681,7 +1580,11 @@ impl Runtime for Daemon {
         Ok(())
     }
 
-    async fn stop_instance(&self, record_id: Uuid) -> ZFResult<DataFlowRecord> {
+    async fn stop_instance(
+        &self,
+        record_id: Uuid,
+        drain_timeout: Option<Duration>,
+    ) -> ZFResult<DataFlowRecord> {
         log::info!("Stopping Instance UUID: {}", record_id);
         let record = self.store.get_flow_by_instance(&record_id).await?;
 
@@ -696,7 +1599,7 @@ impl Runtime for Daemon {
             .filter(|rt| *rt != self.ctx.runtime_uuid);
 
         for rt in all_involved_runtimes {
-            let client = RuntimeClient::new(self.ctx.session.clone(), rt);
+            let client = RuntimeClient::new(self.session.clone(), rt);
             rt_clients.push(client);
         }
 
@@ -710,14 +1613,31 @@ impl Runtime for Daemon {
             self.stop_sources(record_id).await?;
         }
 
+        // The runtimes are drained together, they are only stopped once they are all idle: a
+        // runtime stopped while another one still sends it messages would discard them.
+        if let Some(timeout) = drain_timeout {
+            let clients = &rt_clients;
+            let drained = wait_all_idle(timeout, move || {
+                self.is_instance_idle(record_id, is_also_local, clients)
+            })
+            .await?;
+            if !drained {
+                log::warn!(
+                    "Instance UUID: {} not drained within {:?}",
+                    record_id,
+                    timeout
+                );
+            }
+        }
+
         // remote stop
         for client in rt_clients.iter() {
-            client.stop(record_id).await??;
+            client.stop(record_id, None).await??;
         }
 
         // local stop
         if is_also_local {
-            Runtime::stop(self, record_id).await?;
+            Runtime::stop(self, record_id, None).await?;
         }
 
         log::info!("Stopped Instance UUID: {}", record_id);
@@ -733,36 +1653,15 @@ impl Runtime for Daemon {
 
         let mut _state = self.state.lock().await;
 
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
-
         match _state.graphs.get_mut(&record_id) {
-            Some(mut instance) => {
-                let mut sinks = instance.get_sinks();
-                for id in sinks.drain(..) {
-                    instance.start_node(&id).await?;
-                    rt_status.running_sinks += 1;
-                }
-
-                let mut operators = instance.get_operators();
-                for id in operators.drain(..) {
-                    instance.start_node(&id).await?;
-                    rt_status.running_operators += 1;
-                }
-
-                let mut connectors = instance.get_connectors();
-                for id in connectors.drain(..) {
-                    instance.start_node(&id).await?;
-                    rt_status.running_connectors += 1;
-                }
-
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let started = instance.start_nodes().await;
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
                     .await?;
 
-                Ok(())
+                started
             }
             None => Err(ZFError::InstanceNotFound(record_id)),
         }
@@ -772,126 +1671,576 @@ impl Runtime for Daemon {
 
         let mut _state = self.state.lock().await;
 
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
-
         match _state.graphs.get_mut(&record_id) {
-            Some(mut instance) => {
-                let mut sources = instance.get_sources();
-                for id in sources.drain(..) {
-                    instance.start_node(&id).await?;
-                    rt_status.running_sources += 1;
-                }
-
-                rt_status.running_flows += 1;
-
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let started = instance.start_sources().await;
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| {
+                    status.update_running_nodes(&before, &after);
+                    if started.is_ok() {
+                        status.running_flows += 1;
+                    }
+                })
+                .await?;
 
-                Ok(())
+                started
             }
             None => Err(ZFError::InstanceNotFound(record_id)),
         }
     }
-    async fn stop(&self, record_id: Uuid) -> ZFResult<()> {
+    async fn stop(&self, record_id: Uuid, drain_timeout: Option<Duration>) -> ZFResult<()> {
         log::info!(
             "Stopping nodes (not sources) for Instance UUID: {}",
             record_id
         );
 
-        let mut _state = self.state.lock().await;
-
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
-
-        match _state.graphs.get_mut(&record_id) {
-            Some(mut instance) => {
-                let mut sinks = instance.get_sinks();
-                for id in sinks.drain(..) {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_sinks -= 1;
-                }
+        // While draining, the lock is only taken to stop the sources and to check the instance:
+        // the other requests are served in the meantime.
+        if let Some(timeout) = drain_timeout {
+            match self.state.lock().await.graphs.get_mut(&record_id) {
+                Some(instance) => instance.stop_running_sources().await?,
+                None => return Err(ZFError::InstanceNotFound(record_id)),
+            }
 
-                let mut operators = instance.get_operators();
-                for id in operators.drain(..) {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_operators -= 1;
-                }
+            let drained = wait_all_idle(timeout, move || Runtime::is_idle(self, record_id)).await?;
+            if !drained {
+                log::warn!(
+                    "Instance UUID: {} not drained within {:?}",
+                    record_id,
+                    timeout
+                );
+            }
+        }
 
-                let mut connectors = instance.get_connectors();
-                for id in connectors.drain(..) {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_connectors -= 1;
-                }
+        let mut _state = self.state.lock().await;
 
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
+        match _state.graphs.get_mut(&record_id) {
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let stopped = match drain_timeout {
+                    // Stopped upstream first.
+                    Some(_) => instance.stop_running_nodes().await,
+                    None => instance.stop_nodes().await,
+                };
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
                     .await?;
 
-                Ok(())
+                stopped
             }
             None => Err(ZFError::InstanceNotFound(record_id)),
         }
     }
+
+    async fn is_idle(&self, record_id: Uuid) -> ZFResult<bool> {
+        match self.state.lock().await.graphs.get(&record_id) {
+            Some(instance) => Ok(instance.is_idle().await),
+            None => Err(ZFError::InstanceNotFound(record_id)),
+        }
+    }
+
     async fn stop_sources(&self, record_id: Uuid) -> ZFResult<()> {
         log::info!("Stopping sources for Instance UUID: {}", record_id);
 
         let mut _state = self.state.lock().await;
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
 
         match _state.graphs.get_mut(&record_id) {
-            Some(mut instance) => {
-                let mut sources = instance.get_sources();
-                for id in sources.drain(..) {
-                    instance.stop_node(&id).await?;
-                    rt_status.running_sources -= 1;
-                }
-
-                rt_status.running_flows -= 1;
-
-                self.store
-                    .add_runtime_status(&self.ctx.runtime_uuid, &rt_status)
-                    .await?;
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let stopped = instance.stop_sources().await;
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| {
+                    status.update_running_nodes(&before, &after);
+                    if stopped.is_ok() {
+                        status.running_flows = status.running_flows.saturating_sub(1);
+                    }
+                })
+                .await?;
 
-                Ok(())
+                stopped
             }
             None => Err(ZFError::InstanceNotFound(record_id)),
         }
     }
     async fn start_node(&self, instance_id: Uuid, node: String) -> ZFResult<()> {
         let mut _state = self.state.lock().await;
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
 
         match _state.graphs.get_mut(&instance_id) {
-            Some(mut instance) => Ok(instance.start_node(&node.into()).await?),
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let started = instance.start_node(&node.into()).await;
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
+                    .await?;
+
+                started
+            }
             None => Err(ZFError::InstanceNotFound(instance_id)),
         }
     }
     async fn stop_node(&self, instance_id: Uuid, node: String) -> ZFResult<()> {
         let mut _state = self.state.lock().await;
-        let mut rt_status = self
-            .store
-            .get_runtime_status(&self.ctx.runtime_uuid)
-            .await?;
 
         match _state.graphs.get_mut(&instance_id) {
-            Some(mut instance) => Ok(instance.stop_node(&node.into()).await?),
+            Some(instance) => {
+                let before = instance.get_started_nodes();
+                let stopped = instance.stop_node(&node.into()).await;
+                let after = instance.get_started_nodes();
+                self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
+                    .await?;
+
+                stopped
+            }
+            None => Err(ZFError::InstanceNotFound(instance_id)),
+        }
+    }
+
+    async fn get_node_state(&self, instance_id: Uuid, node: String) -> ZFResult<NodeHealth> {
+        let _state = self.state.lock().await;
+        let hang_threshold = _state
+            .config
+            .watchdog
+            .as_ref()
+            .map(|watchdog| watchdog.hang_threshold.to_duration());
+
+        match _state.graphs.get(&instance_id) {
+            Some(instance) => Ok(instance
+                .get_node_health(&node.into(), hang_threshold)
+                .await?),
+            None => Err(ZFError::InstanceNotFound(instance_id)),
+        }
+    }
+
+    async fn get_deadline_miss_stats(
+        &self,
+        instance_id: Uuid,
+        node: String,
+    ) -> ZFResult<DeadlineMissStats> {
+        let record = self.store.get_flow_by_instance(&instance_id).await?;
+        let runtime = record
+            .find_node_runtime(&node)
+            .ok_or_else(|| ZFError::NodeNotFound(node.clone().into()))?;
+        let runtime_id = self.store.get_runtime_info_by_name(&runtime).await?.id;
+        if runtime_id != self.ctx.runtime_uuid {
+            return RuntimeClient::new(self.session.clone(), runtime_id)
+                .get_deadline_miss_stats(instance_id, node)
+                .await?;
+        }
+
+        let _state = self.state.lock().await;
+        match _state.graphs.get(&instance_id) {
+            Some(instance) => instance.get_deadline_miss_stats(&node.into()).await,
+            None => Err(ZFError::InstanceNotFound(instance_id)),
+        }
+    }
+
+    async fn pause_instance(&self, record_id: Uuid) -> ZFResult<()> {
+        log::info!("Pausing Instance UUID: {}", record_id);
+
+        let all_involved_runtimes = self.store.get_flow_instance_runtimes(&record_id).await?;
+
+        let is_also_local = all_involved_runtimes.contains(&self.ctx.runtime_uuid);
+
+        let rt_clients = all_involved_runtimes
+            .into_iter()
+            .filter(|rt| *rt != self.ctx.runtime_uuid)
+            .map(|rt| RuntimeClient::new(self.session.clone(), rt))
+            .collect::<Vec<_>>();
+
+        // remote pause
+        for client in rt_clients.iter() {
+            client.pause(record_id).await??;
+        }
+
+        if is_also_local {
+            // self pause
+            Runtime::pause(self, record_id).await?;
+        }
+
+        log::info!("Paused Instance UUID: {}", record_id);
+
+        Ok(())
+    }
+
+    async fn resume_instance(&self, record_id: Uuid) -> ZFResult<()> {
+        log::info!("Resuming Instance UUID: {}", record_id);
+
+        let all_involved_runtimes = self.store.get_flow_instance_runtimes(&record_id).await?;
+
+        let is_also_local = all_involved_runtimes.contains(&self.ctx.runtime_uuid);
+
+        let rt_clients = all_involved_runtimes
+            .into_iter()
+            .filter(|rt| *rt != self.ctx.runtime_uuid)
+            .map(|rt| RuntimeClient::new(self.session.clone(), rt))
+            .collect::<Vec<_>>();
+
+        // remote resume
+        for client in rt_clients.iter() {
+            client.resume(record_id).await??;
+        }
+
+        if is_also_local {
+            // self resume
+            Runtime::resume(self, record_id).await?;
+        }
+
+        log::info!("Resumed Instance UUID: {}", record_id);
+
+        Ok(())
+    }
+
+    async fn pause(&self, record_id: Uuid) -> ZFResult<()> {
+        log::info!("Pausing nodes for Instance UUID: {}", record_id);
+
+        let _state = self.state.lock().await;
+        match _state.graphs.get(&record_id) {
+            Some(instance) => instance.pause().await,
+            None => Err(ZFError::InstanceNotFound(record_id)),
+        }
+    }
+
+    async fn resume(&self, record_id: Uuid) -> ZFResult<()> {
+        log::info!("Resuming nodes for Instance UUID: {}", record_id);
+
+        let _state = self.state.lock().await;
+        match _state.graphs.get(&record_id) {
+            Some(instance) => instance.resume().await,
+            None => Err(ZFError::InstanceNotFound(record_id)),
+        }
+    }
+
+    async fn pause_node(&self, instance_id: Uuid, node: String) -> ZFResult<()> {
+        let _state = self.state.lock().await;
+        match _state.graphs.get(&instance_id) {
+            Some(instance) => instance.pause_node(&node.into()).await,
             None => Err(ZFError::InstanceNotFound(instance_id)),
         }
     }
 
-    async fn start_record(&self, instance_id: Uuid, source_id: NodeId) -> ZFResult<String> {
+    async fn resume_node(&self, instance_id: Uuid, node: String) -> ZFResult<()> {
+        let _state = self.state.lock().await;
+        match _state.graphs.get(&instance_id) {
+            Some(instance) => instance.resume_node(&node.into()).await,
+            None => Err(ZFError::InstanceNotFound(instance_id)),
+        }
+    }
+
+    async fn checkpoint_instance(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>> {
+        log::info!("Checkpointing Instance UUID: {}", record_id);
+
+        let all_involved_runtimes = self.store.get_flow_instance_runtimes(&record_id).await?;
+
+        let is_also_local = all_involved_runtimes.contains(&self.ctx.runtime_uuid);
+
+        let rt_clients = all_involved_runtimes
+            .into_iter()
+            .filter(|rt| *rt != self.ctx.runtime_uuid)
+            .map(|rt| RuntimeClient::new(self.session.clone(), rt))
+            .collect::<Vec<_>>();
+
+        let mut nodes = vec![];
+
+        // remote checkpoint
+        for client in rt_clients.iter() {
+            nodes.extend(client.checkpoint(record_id).await??);
+        }
+
+        if is_also_local {
+            // self checkpoint
+            nodes.extend(Runtime::checkpoint(self, record_id).await?);
+        }
+
+        log::info!("Checkpointed Instance UUID: {}", record_id);
+
+        Ok(nodes)
+    }
+
+    async fn restore_instance(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>> {
+        log::info!("Restoring Instance UUID: {}", record_id);
+
+        let all_involved_runtimes = self.store.get_flow_instance_runtimes(&record_id).await?;
+
+        let is_also_local = all_involved_runtimes.contains(&self.ctx.runtime_uuid);
+
+        let rt_clients = all_involved_runtimes
+            .into_iter()
+            .filter(|rt| *rt != self.ctx.runtime_uuid)
+            .map(|rt| RuntimeClient::new(self.session.clone(), rt))
+            .collect::<Vec<_>>();
+
+        let mut nodes = vec![];
+
+        // remote restore
+        for client in rt_clients.iter() {
+            nodes.extend(client.restore(record_id).await??);
+        }
+
+        if is_also_local {
+            // self restore
+            nodes.extend(Runtime::restore(self, record_id).await?);
+        }
+
+        log::info!("Restored Instance UUID: {}", record_id);
+
+        Ok(nodes)
+    }
+
+    async fn checkpoint(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>> {
+        log::info!("Checkpointing nodes for Instance UUID: {}", record_id);
+
+        // The nodes are checkpointed once the lock is released.
+        let checkpointers = match self.state.lock().await.graphs.get(&record_id) {
+            Some(instance) => instance.get_checkpointers(),
+            None => return Err(ZFError::InstanceNotFound(record_id)),
+        };
+
+        let mut nodes = vec![];
+        for checkpointer in checkpointers {
+            if let Some(checkpoint) = checkpointer.checkpoint().await? {
+                self.store.add_node_checkpoint(&checkpoint).await?;
+                nodes.push(checkpoint.node_id);
+            }
+        }
+        Ok(nodes)
+    }
+
+    async fn checkpoint_node(&self, instance_id: Uuid, node: String) -> ZFResult<bool> {
+        // The node is checkpointed once the lock is released.
+        let checkpointer = match self.state.lock().await.graphs.get(&instance_id) {
+            Some(instance) => instance.get_checkpointer(&node.into())?,
+            None => return Err(ZFError::InstanceNotFound(instance_id)),
+        };
+
+        match checkpointer.checkpoint().await? {
+            Some(checkpoint) => {
+                self.store.add_node_checkpoint(&checkpoint).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn restore(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>> {
+        log::info!("Restoring nodes for Instance UUID: {}", record_id);
+
+        let checkpointers = match self.state.lock().await.graphs.get(&record_id) {
+            Some(instance) => instance.get_checkpointers(),
+            None => return Err(ZFError::InstanceNotFound(record_id)),
+        };
+        self.restore_checkpoints(&record_id, &checkpointers).await
+    }
+
+    async fn restore_node(&self, instance_id: Uuid, node: String) -> ZFResult<()> {
+        // The instance is only held to skip the messages and to get the checkpointer of the node,
+        // not while the store is accessed or the node restored.
+        let watermarks = match self.store.get_input_watermarks(&instance_id, &node).await {
+            Ok(watermarks) => Some(watermarks),
+            Err(ZFError::Empty) => None,
+            Err(e) => return Err(e),
+        };
+
+        let checkpointer = match self.state.lock().await.graphs.get(&instance_id) {
+            Some(instance) => {
+                if let Some(watermarks) = &watermarks {
+                    instance.skip_received(watermarks).await?;
+                }
+                instance.get_checkpointer(&node.clone().into())?
+            }
+            None => return Err(ZFError::InstanceNotFound(instance_id)),
+        };
+
+        if watermarks.is_some() {
+            self.store
+                .remove_input_watermarks(&instance_id, &node)
+                .await?;
+        }
+
+        let checkpoint = self.store.get_node_checkpoint(&instance_id, &node).await?;
+        checkpointer.restore(&checkpoint).await
+    }
+
+    async fn migrate_node(
+        &self,
+        record_id: Uuid,
+        node: String,
+        runtime: String,
+    ) -> ZFResult<DataFlowRecord> {
+        log::info!(
+            "Migrating node {} of Instance UUID: {} to {}",
+            node,
+            record_id,
+            runtime
+        );
+
+        let node_id: NodeId = node.clone().into();
+        let record = self.store.get_flow_by_instance(&record_id).await?;
+        let current = record
+            .find_node_runtime(&node)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        if current.as_ref() == runtime {
+            return Ok(record);
+        }
+
+        let current_id = self.store.get_runtime_info_by_name(&current).await?.id;
+        let target_id = self.store.get_runtime_info_by_name(&runtime).await?.id;
+        let migrated = record.try_migrate_node(&node_id, runtime.clone().into())?;
+
+        let health = if current_id == self.ctx.runtime_uuid {
+            Runtime::get_node_state(self, record_id, node.clone()).await?
+        } else {
+            RuntimeClient::new(self.session.clone(), current_id)
+                .get_node_state(record_id, node.clone())
+                .await??
+        };
+        let running = health != NodeHealth::Stopped;
+
+        // The target runtime goes first: the node is loaded there and the messages sent to it wait
+        // in its links until the copy on the current runtime is removed.
+        self.reconfigure_runtime(target_id, record_id, &migrated, running)
+            .await?;
+
+        let mut others = vec![];
+        for rt in record
+            .get_runtimes()
+            .into_iter()
+            .chain(migrated.get_runtimes().into_iter())
+        {
+            if rt != current && rt.as_ref() != runtime && !others.contains(&rt) {
+                others.push(rt);
+            }
+        }
+        for rt in others {
+            let rt_id = self.store.get_runtime_info_by_name(&rt).await?.id;
+            self.reconfigure_runtime(rt_id, record_id, &migrated, running)
+                .await?;
+        }
+
+        self.reconfigure_runtime(current_id, record_id, &migrated, running)
+            .await?;
+
+        // The messages the node received on the current runtime are skipped and the state
+        // checkpointed when it was removed is restored before it is started.
+        let restored = if target_id == self.ctx.runtime_uuid {
+            Runtime::restore_node(self, record_id, node.clone()).await
+        } else {
+            RuntimeClient::new(self.session.clone(), target_id)
+                .restore_node(record_id, node.clone())
+                .await?
+        };
+        match restored {
+            Ok(()) | Err(ZFError::Empty) => (),
+            Err(e) => return Err(e),
+        }
+
+        if running {
+            if target_id == self.ctx.runtime_uuid {
+                Runtime::start_node(self, record_id, node.clone()).await?;
+            } else {
+                RuntimeClient::new(self.session.clone(), target_id)
+                    .start_node(record_id, node.clone())
+                    .await??;
+            }
+        }
+
+        log::info!(
+            "Migrated node {} of Instance UUID: {} to {}",
+            node,
+            record_id,
+            runtime
+        );
+
+        Ok(migrated)
+    }
+
+    async fn reconfigure(
+        &self,
+        record_id: Uuid,
+        record: DataFlowRecord,
+        start: bool,
+    ) -> ZFResult<Vec<NodeId>> {
+        log::info!("Reconfiguring Instance UUID: {}", record_id);
+
+        let involved = record.get_runtimes().contains(&self.ctx.runtime_name);
+
+        let mut _state = self.state.lock().await;
+        if !_state.graphs.contains_key(&record_id) {
+            if !involved {
+                return Ok(vec![]);
+            }
+
+            // This runtime was not involved yet, it starts from an empty instance.
+            let dataflow = Dataflow::new(
+                self.ctx.clone(),
+                record.flow.as_str().into(),
+                Some(record_id),
+            );
+            let instance = DataflowInstance::try_instantiate(dataflow)?;
+            _state.graphs.insert(record_id, instance);
+        }
+
+        let instance = _state
+            .graphs
+            .get_mut(&record_id)
+            .ok_or(ZFError::InstanceNotFound(record_id))?;
+        let before = instance.get_started_nodes();
+        let changes = instance.try_apply_record(&record).await;
+        let after = instance.get_started_nodes();
+        self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
+            .await?;
+        let changes = changes?;
+
+        for checkpoint in changes.checkpoints.iter() {
+            self.store.add_node_checkpoint(checkpoint).await?;
+        }
+
+        for watermarks in changes.watermarks.iter() {
+            self.store.add_input_watermarks(watermarks).await?;
+        }
+
+        // The new connectors are started right away so that nothing sent to a new node is lost.
+        if start {
+            let before = instance.get_started_nodes();
+            let mut started = Ok(());
+            for id in changes
+                .added
+                .iter()
+                .filter(|id| record.connectors.iter().any(|c| &c.id == *id))
+            {
+                started = instance.start_node(id).await;
+                if started.is_err() {
+                    break;
+                }
+            }
+            let after = instance.get_started_nodes();
+            self.update_runtime_status(|status| status.update_running_nodes(&before, &after))
+                .await?;
+            started?;
+        }
+
+        if involved {
+            self.store
+                .add_runtime_flow(&self.ctx.runtime_uuid, &record)
+                .await?;
+        } else {
+            _state.graphs.remove(&record_id);
+            self.store
+                .remove_runtime_flow_instance(&self.ctx.runtime_uuid, &record.flow, &record.uuid)
+                .await?;
+        }
+
+        log::info!("Done reconfiguration of Instance UUID: {}", record_id);
+
+        Ok(changes.added)
+    }
+
+    async fn start_record(
+        &self,
+        instance_id: Uuid,
+        node_id: NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String> {
         let mut _state = self.state.lock().await;
         let mut rt_status = self
             .store
@@ -900,14 +2249,19 @@ impl Runtime for Daemon {
 
         match _state.graphs.get(&instance_id) {
             Some(instance) => {
-                let key_expr = instance.start_recording(&source_id).await?;
+                let key_expr = instance.start_recording(&node_id, port_id).await?;
                 Ok(key_expr)
             }
             None => Err(ZFError::InstanceNotFound(instance_id)),
         }
     }
 
-    async fn stop_record(&self, instance_id: Uuid, source_id: NodeId) -> ZFResult<String> {
+    async fn stop_record(
+        &self,
+        instance_id: Uuid,
+        node_id: NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String> {
         let mut _state = self.state.lock().await;
         let mut rt_status = self
             .store
@@ -916,7 +2270,7 @@ impl Runtime for Daemon {
 
         match _state.graphs.get(&instance_id) {
             Some(instance) => {
-                let key_expr = instance.stop_recording(&source_id).await?;
+                let key_expr = instance.stop_recording(&node_id, port_id).await?;
                 Ok(key_expr)
             }
             None => Err(ZFError::InstanceNotFound(instance_id)),
@@ -928,6 +2282,7 @@ impl Runtime for Daemon {
         instance_id: Uuid,
         source_id: NodeId,
         key_expr: String,
+        options: ReplayOptions,
     ) -> ZFResult<NodeId> {
         let mut _state = self.state.lock().await;
         let mut rt_status = self
@@ -938,7 +2293,7 @@ impl Runtime for Daemon {
         match _state.graphs.get_mut(&instance_id) {
             Some(mut instance) => {
                 if !(instance.is_node_running(&source_id).await?) {
-                    let replay_id = instance.start_replay(&source_id, key_expr).await?;
+                    let replay_id = instance.start_replay(&source_id, key_expr, options).await?;
                     Ok(replay_id)
                 } else {
                     Err(ZFError::InvalidState)
@@ -969,6 +2324,47 @@ impl Runtime for Daemon {
         }
     }
 
+    async fn list_recordings(&self, instance_id: Option<Uuid>) -> ZFResult<Vec<RecordingInfo>> {
+        match instance_id {
+            Some(instance_id) => self.store.get_instance_recordings(&instance_id).await,
+            None => self.store.get_all_recordings().await,
+        }
+    }
+
+    async fn get_recording(&self, recording_id: Uuid) -> ZFResult<RecordingInfo> {
+        self.store.get_recording(&recording_id).await
+    }
+
+    async fn delete_recording(&self, recording_id: Uuid) -> ZFResult<RecordingInfo> {
+        let recording = self.store.get_recording(&recording_id).await?;
+        if recording.stop.is_none() {
+            return Err(ZFError::InvalidState);
+        }
+
+        // The file can only be deleted by the runtime that wrote it.
+        if recording.file.is_some() && recording.runtime_id != self.ctx.runtime_uuid {
+            let client = RuntimeClient::new(self.session.clone(), recording.runtime_id);
+            return client.delete_recording(recording_id).await?;
+        }
+
+        if let Some(file) = &recording.file {
+            match fs::remove_file(file) {
+                Ok(_) => (),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    log::warn!("Recording file {} already deleted", file)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        self.session.delete(&recording.key_expr).await?;
+        self.store
+            .remove_recording(&recording.instance_id, &recording.id)
+            .await?;
+
+        Ok(recording)
+    }
+
     async fn notify_runtime(
         &self,
         record_id: Uuid,
@@ -977,6 +2373,89 @@ impl Runtime for Daemon {
     ) -> ZFResult<()> {
         Err(ZFError::Unimplemented)
     }
+    async fn list_extensions(&self) -> ZFResult<Vec<ExtensibleImplementation>> {
+        Ok(self.ctx.loader.get_config().get_extensions().to_vec())
+    }
+
+    async fn get_extension(&self, name: String) -> ZFResult<ExtensibleImplementation> {
+        self.ctx
+            .loader
+            .get_config()
+            .get_extension_by_name(&name)
+            .cloned()
+            .ok_or(ZFError::NotFound)
+    }
+
+    async fn install_extension(&self, extension: ExtensibleImplementation) -> ZFResult<()> {
+        log::info!("Installing extension {}", extension.name);
+
+        extension.validate()?;
+
+        let mut state = self.state.lock().await;
+        let mut loader_config = self.ctx.loader.get_config();
+        loader_config.try_add_extension(extension.clone())?;
+
+        if let Some(ext_dir) = &state.config.extensions {
+            let path =
+                Path::new(ext_dir).join(format!("{}.{}", extension.name, EXT_FILE_EXTENSION));
+            let content =
+                serde_yaml::to_string(&extension).map_err(|_| ZFError::SerializationError)?;
+            fs::write(&path, content)?;
+        }
+
+        self.ctx.loader.reload_extensions(loader_config.clone());
+        state.config.loader = loader_config;
+        self.store
+            .add_runtime_config(&self.ctx.runtime_uuid, &state.config)
+            .await?;
+
+        log::info!("Done installation of extension {}", extension.name);
+
+        Ok(())
+    }
+
+    async fn remove_extension(&self, name: String) -> ZFResult<ExtensibleImplementation> {
+        log::info!("Removing extension {}", name);
+
+        let mut state = self.state.lock().await;
+        let mut loader_config = self.ctx.loader.get_config();
+        let extension = loader_config
+            .remove_extension(&name)
+            .ok_or(ZFError::NotFound)?;
+
+        if let Some(ext_dir) = &state.config.extensions {
+            for entry in fs::read_dir(ext_dir)? {
+                let path = entry?.path();
+                if path
+                    .extension()
+                    .map_or(true, |ext| ext != EXT_FILE_EXTENSION)
+                {
+                    continue;
+                }
+
+                let is_extension = read_file(&path)
+                    .ok()
+                    .and_then(|content| {
+                        serde_yaml::from_str::<ExtensibleImplementation>(&content).ok()
+                    })
+                    .map_or(false, |ext| ext.name == name);
+                if is_extension {
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+
+        self.ctx.loader.reload_extensions(loader_config.clone());
+        state.config.loader = loader_config;
+        self.store
+            .add_runtime_config(&self.ctx.runtime_uuid, &state.config)
+            .await?;
+
+        log::info!("Done removal of extension {}", name);
+
+        Ok(extension)
+    }
+
     async fn check_operator_compatibility(&self, operator: OperatorDescriptor) -> ZFResult<bool> {
         Err(ZFError::Unimplemented)
     }
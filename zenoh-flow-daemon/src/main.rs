@@ -12,19 +12,27 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use async_ctrlc::CtrlC;
 use std::convert::TryFrom;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str;
+use std::time::Duration;
 use zenoh_flow::async_std::prelude::*;
+use zenoh_flow::runtime::dataflow::isolation::run_node_host;
 
 mod daemon;
+#[cfg(unix)]
+mod pid;
+mod standalone;
 mod util;
 use daemon::Daemon;
 use daemon::DaemonConfig;
-use util::read_file;
+#[cfg(unix)]
+use pid::PidFile;
+use standalone::StandaloneOpt;
+use util::{read_file, sd_notify};
 
 /// Default path for the runtime configuration file.
 static RUNTIME_CONFIG_FILE: &str = "/etc/zenoh-flow/runtime.yaml";
@@ -42,12 +50,59 @@ struct RuntimeOpt {
     print_version: bool,
     #[clap(short = 'i', long = "node_uuid")]
     node_uuid: bool,
+    #[clap(subcommand)]
+    command: Option<RuntimeCommand>,
+}
+
+/// The commands accepted by the runtime, without any it runs as a daemon.
+#[derive(Debug, Subcommand)]
+enum RuntimeCommand {
+    #[clap(about = "Runs a flow in the foreground, without a daemon")]
+    Run {
+        #[clap(
+            parse(from_os_str),
+            name = "Flow descriptor path",
+            help = "The flow descriptor, all its nodes run in this process"
+        )]
+        descriptor: PathBuf,
+        #[clap(
+            short = 'e',
+            long = "extensions",
+            help = "Where to locate the extension files"
+        )]
+        extensions: Option<PathBuf>,
+        #[clap(
+            short = 'z',
+            long = "zenoh-config",
+            help = "The Zenoh configuration, used only if a node needs the network"
+        )]
+        zenoh_config: Option<String>,
+        #[clap(
+            short = 'r',
+            long = "recordings-dir",
+            help = "Where the recordings are also written as files"
+        )]
+        recordings_dir: Option<PathBuf>,
+        #[clap(
+            long = "drain-ms",
+            help = "On Ctrl-C, processes the messages in flight for at most these milliseconds"
+        )]
+        drain_ms: Option<u64>,
+    },
+    #[clap(
+        hide = true,
+        about = "Executes an isolated node, started by the daemon itself"
+    )]
+    NodeHost {
+        #[clap(parse(from_os_str))]
+        socket: PathBuf,
+    },
 }
 
 /// The runtime main function.
 ///
 /// It initializes the `env_logger`, parses the arguments and then creates
-/// a runtime.
+/// a runtime, or runs a single flow with the `run` command.
 ///
 /// It is able to catch SIGINT for graceful termination.
 #[async_std::main]
@@ -70,11 +125,50 @@ async fn main() {
         std::process::exit(0);
     }
 
+    if let Some(RuntimeCommand::NodeHost { socket }) = &args.command {
+        if let Err(e) = run_node_host(socket).await {
+            log::error!("The node host failed: {:?}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    if let Some(RuntimeCommand::Run {
+        descriptor,
+        extensions,
+        zenoh_config,
+        recordings_dir,
+        drain_ms,
+    }) = args.command
+    {
+        let code = standalone::run(StandaloneOpt {
+            descriptor,
+            extensions,
+            zenoh_config,
+            recordings_dir,
+            drain_timeout: drain_ms.map(Duration::from_millis),
+        })
+        .await;
+        std::process::exit(code);
+    }
+
     let conf_file_path = Path::new(&args.config);
     let config =
         serde_yaml::from_str::<DaemonConfig>(&(read_file(conf_file_path).unwrap())).unwrap();
 
-    let rt = Daemon::try_from(config).unwrap();
+    // Held until the end of `main`: another daemon using the same PID file cannot start.
+    #[cfg(unix)]
+    let _pid_file = lock_pid_file(&config.pid_file);
+    // The lock relies on `flock`, only available on Unix.
+    #[cfg(not(unix))]
+    log::warn!(
+        "PID files are only supported on Unix, {} is not used",
+        config.pid_file
+    );
+
+    let rt = Daemon::try_from(config)
+        .unwrap()
+        .with_config_file(conf_file_path);
 
     let (s, h) = rt.start().await.unwrap();
 
@@ -82,6 +176,7 @@ async fn main() {
     let mut stream = ctrlc.enumerate().take(1);
     stream.next().await;
     log::trace!("Received Ctrl-C start teardown");
+    sd_notify("STOPPING=1").unwrap_or_else(|e| log::warn!("Unable to notify systemd: {:?}", e));
 
     //Here we send the stop signal to the rt object and waits that it ends
     rt.stop(s).await.unwrap();
@@ -89,3 +184,15 @@ async fn main() {
     //wait for the futures to ends
     h.await.unwrap();
 }
+
+/// Locks the PID file of the daemon, exiting if it is locked by another daemon.
+#[cfg(unix)]
+fn lock_pid_file(path: &str) -> PidFile {
+    match PidFile::try_lock(Path::new(path)) {
+        Ok(pid_file) => pid_file,
+        Err(e) => {
+            log::error!("Unable to start the daemon: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use zenoh_flow::{ZFError, ZFResult};
+
+/// The PID file of the daemon, locked for as long as it exists.
+///
+/// The lock is an advisory `flock`: it is released by the operating system when the process
+/// exits, even if it is killed, so a stale file never prevents a daemon from starting.
+/// The file is removed when the `PidFile` is dropped.
+pub(crate) struct PidFile {
+    path: PathBuf,
+    // Holds the lock.
+    _file: File,
+}
+
+impl PidFile {
+    /// Creates and locks the PID file at `path`, then writes the PID of the daemon in it.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be created or written, or if it is locked
+    /// by another daemon.
+    pub(crate) fn try_lock(path: &Path) -> ZFResult<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+
+        // SAFETY: the file descriptor is valid for as long as `file` is alive.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let error = std::io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::EWOULDBLOCK) {
+                return Err(error.into());
+            }
+
+            let mut pid = String::new();
+            file.read_to_string(&mut pid)?;
+            return Err(ZFError::IOError(format!(
+                "PID file {} is locked by another daemon (PID {})",
+                path.display(),
+                pid.trim()
+            )));
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        file.sync_all()?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            _file: file,
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // The file is removed while still locked, a daemon starting meanwhile creates a new one.
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!(
+                "Unable to remove the PID file {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pid_file_is_exclusive() {
+        let path = std::env::temp_dir().join(format!("zenoh-flow-{}.pid", uuid::Uuid::new_v4()));
+
+        let pid_file = PidFile::try_lock(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+
+        // `flock` locks are per open file description: a second open fails to lock it.
+        match PidFile::try_lock(&path) {
+            Err(ZFError::IOError(e)) => assert!(e.contains("locked")),
+            _ => panic!("The PID file should be locked"),
+        }
+
+        drop(pid_file);
+        assert!(!path.exists());
+
+        let pid_file = PidFile::try_lock(&path).unwrap();
+        drop(pid_file);
+    }
+}
@@ -0,0 +1,428 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use async_ctrlc::CtrlC;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uhlc::HLC;
+use uuid::Uuid;
+use zenoh_flow::async_std::prelude::*;
+use zenoh_flow::async_std::sync::Arc;
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::dataflow::record::DataFlowRecord;
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::Dataflow;
+use zenoh_flow::runtime::session::LazySession;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::types::{ZFError, ZFResult};
+use zenoh_flow::NodeId;
+
+use crate::daemon::load_extensions;
+use crate::util::{get_zenoh_config, read_file};
+
+/// Exit code when the flow ended, or was interrupted and torn down cleanly.
+pub(crate) const EXIT_SUCCESS: i32 = 0;
+/// Exit code when the flow could not be loaded, started or torn down, or when a node failed.
+pub(crate) const EXIT_FAILURE: i32 = 1;
+
+/// Name of the runtime the whole flow is mapped to.
+static STANDALONE_RUNTIME_NAME: &str = "standalone";
+
+/// How often the runner checks whether the nodes are still running.
+const MONITOR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The options of a standalone run.
+#[derive(Debug)]
+pub(crate) struct StandaloneOpt {
+    /// The flow descriptor.
+    pub descriptor: PathBuf,
+    /// Where to locate the extension files, if any.
+    pub extensions: Option<PathBuf>,
+    /// The Zenoh configuration, used only if a node needs the network.
+    pub zenoh_config: Option<String>,
+    /// Where the recordings are also written as files, if any.
+    pub recordings_dir: Option<PathBuf>,
+    /// For how long the messages in flight are processed when stopping, if
+    /// `None` they are discarded.
+    pub drain_timeout: Option<Duration>,
+}
+
+/// Why the flow stopped running.
+enum Outcome {
+    Interrupted,
+    Ended,
+}
+
+/// Runs the flow described in `opt.descriptor` in the foreground, without a
+/// daemon.
+///
+/// All the nodes are mapped to this process, whatever the mapping of the
+/// descriptor says. The flow runs until Ctrl-C is received or all its nodes
+/// have ended, it is then torn down.
+///
+/// It returns the exit code of the process.
+pub(crate) async fn run(opt: StandaloneOpt) -> i32 {
+    exit_code(try_run(opt).await)
+}
+
+/// Returns the exit code of the process given the result of the run.
+fn exit_code(result: ZFResult<()>) -> i32 {
+    match result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(e) => {
+            log::error!("Standalone run failed: {:?}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Loads, starts, monitors and tears down the flow.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - unable to read or parse the descriptor
+/// - unable to load the extensions or the Zenoh configuration
+/// - unable to instantiate or start the flow
+/// - unable to install the Ctrl-C handler
+/// - a node failed, or failed to stop or to clean
+async fn try_run(opt: StandaloneOpt) -> ZFResult<()> {
+    let mut descriptor = DataFlowDescriptor::from_yaml(&read_file(&opt.descriptor)?)?;
+    // Everything runs here: the mapping of the descriptor is discarded.
+    descriptor.mapping = None;
+    let mapped =
+        zenoh_flow::runtime::map_to_infrastructure(descriptor, STANDALONE_RUNTIME_NAME).await?;
+
+    let instance_id = Uuid::new_v4();
+    let record = DataFlowRecord::try_from((mapped, instance_id))?;
+
+    let ctx = RuntimeContext {
+        session: match &opt.zenoh_config {
+            Some(path) => LazySession::new(get_zenoh_config(path)?),
+            None => LazySession::new(zenoh::config::Config::default()),
+        },
+        hlc: Arc::new(HLC::default()),
+        loader: Arc::new(Loader::new(match &opt.extensions {
+            Some(dir) => load_extensions(Path::new(dir))?,
+            None => LoaderConfig::new(),
+        })),
+        runtime_name: STANDALONE_RUNTIME_NAME.into(),
+        runtime_uuid: Uuid::new_v4(),
+        recordings_dir: opt.recordings_dir.clone(),
+    };
+
+    log::info!(
+        "Running Flow {} - Instance UUID: {}",
+        record.flow,
+        instance_id
+    );
+
+    let dataflow = Dataflow::try_new(ctx, record)?;
+    let mut instance = DataflowInstance::try_instantiate(dataflow)?;
+
+    let ctrlc = CtrlC::new().map_err(|e| ZFError::IOError(format!("{}", e)))?;
+    run_instance(&mut instance, ctrlc, opt.drain_timeout).await?;
+
+    log::info!("Flow torn down - Instance UUID: {}", instance_id);
+    Ok(())
+}
+
+/// Starts and monitors the instance until `interrupted` resolves or all its nodes have ended,
+/// then tears it down.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - unable to start the flow
+/// - a node failed, or failed to stop or to clean
+async fn run_instance(
+    instance: &mut DataflowInstance,
+    interrupted: impl Future<Output = ()>,
+    drain_timeout: Option<Duration>,
+) -> ZFResult<()> {
+    // As the daemon does: the sources start last so that no data is lost.
+    let started = async {
+        instance.start_nodes().await?;
+        instance.start_sources().await
+    }
+    .await;
+
+    if let Err(e) = started {
+        log::error!("Unable to start the flow: {:?}", e);
+        teardown(instance, None).await;
+        return Err(e);
+    }
+
+    match wait(instance, interrupted).await {
+        Outcome::Interrupted => log::info!("Received Ctrl-C, tearing down the flow"),
+        Outcome::Ended => log::info!("All the nodes have ended, tearing down the flow"),
+    }
+
+    let clean = teardown(instance, drain_timeout).await;
+    // Collected once the nodes are stopped: a node can also fail while the flow is drained.
+    let failed = take_failed_nodes(instance).await;
+
+    if !clean {
+        Err(ZFError::Uncompleted(
+            "Some nodes could not be stopped or cleaned".to_string(),
+        ))
+    } else if !failed.is_empty() {
+        Err(ZFError::Uncompleted(format!(
+            "Node(s) {} failed",
+            failed
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Waits until `interrupted` resolves or no node of the instance is running.
+async fn wait(instance: &DataflowInstance, interrupted: impl Future<Output = ()>) -> Outcome {
+    let interrupted = async {
+        interrupted.await;
+        Outcome::Interrupted
+    };
+
+    let ended = async {
+        loop {
+            zenoh_flow::async_std::task::sleep(MONITOR_INTERVAL).await;
+            let mut running = false;
+            for id in instance.get_nodes() {
+                if instance.is_node_running(&id).await.unwrap_or(false) {
+                    running = true;
+                    break;
+                }
+            }
+            if !running {
+                return Outcome::Ended;
+            }
+        }
+    };
+
+    interrupted.race(ended).await
+}
+
+/// Logs the error of the last failed run of each node of the instance, and returns the nodes
+/// that failed.
+///
+/// A failed run is restarted, a node can thus have failed and still be running.
+async fn take_failed_nodes(instance: &DataflowInstance) -> Vec<NodeId> {
+    let mut failed = vec![];
+    for id in instance.get_nodes() {
+        if let Ok(Some(e)) = instance.take_node_failure(&id).await {
+            log::error!("Node {} failed, got error: {:?}", id, e);
+            failed.push(id);
+        }
+    }
+    failed
+}
+
+/// Stops the sources first, then the other nodes, and cleans all of them.
+///
+/// If `drain_timeout` is provided, the messages in flight are processed
+/// before the other nodes are stopped.
+///
+/// Errors are logged and the teardown continues, it returns `false` if any
+/// occurred.
+async fn teardown(instance: &mut DataflowInstance, drain_timeout: Option<Duration>) -> bool {
+    let mut clean = true;
+
+    if let Some(timeout) = drain_timeout {
+        match instance.drain(timeout).await {
+            Ok(true) => (),
+            Ok(false) => log::warn!("Flow not drained within {:?}", timeout),
+            Err(e) => {
+                log::error!("Unable to drain the flow, got error: {:?}", e);
+                clean = false;
+            }
+        }
+    }
+
+    let mut nodes = instance.get_sources();
+    nodes.extend(instance.get_sinks());
+    nodes.extend(instance.get_operators());
+    nodes.extend(instance.get_connectors());
+
+    for id in nodes.iter() {
+        if instance.is_node_running(id).await.unwrap_or(false) {
+            if let Err(e) = instance.stop_node(id).await {
+                log::error!("Unable to stop node {}, got error: {:?}", id, e);
+                clean = false;
+            }
+        }
+    }
+
+    for id in nodes.iter() {
+        if let Err(e) = instance.clean_node(id).await {
+            log::error!("Unable to clean node {}, got error: {:?}", id, e);
+            clean = false;
+        }
+    }
+
+    clean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use zenoh_flow::async_std::channel::{bounded, Sender};
+    use zenoh_flow::model::link::PortDescriptor;
+    use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+    use zenoh_flow::{
+        Configuration, Context, Data, DataMessage, DurationDescriptor, EmptyState, Node, Sink,
+        Source, State,
+    };
+
+    struct TickSource;
+
+    impl Node for TickSource {
+        fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+            Ok(State::from(EmptyState {}))
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Source for TickSource {
+        async fn run(&self, _context: &mut Context, _state: &mut State) -> ZFResult<Data> {
+            Ok(Data::from_bytes(vec![0]))
+        }
+    }
+
+    /// Signals each message it receives, its first run fails if `fail` is set.
+    struct SignalSink {
+        tx: Sender<()>,
+        fail: AtomicBool,
+    }
+
+    impl Node for SignalSink {
+        fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+            Ok(State::from(EmptyState {}))
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Sink for SignalSink {
+        async fn run(
+            &self,
+            _context: &mut Context,
+            _state: &mut State,
+            _input: DataMessage,
+        ) -> ZFResult<()> {
+            if self.fail.swap(false, Ordering::SeqCst) {
+                return Err(ZFError::GenericError);
+            }
+            let _ = self.tx.try_send(());
+            Ok(())
+        }
+    }
+
+    fn build_instance(fail: bool, tx: Sender<()>) -> DataflowInstance {
+        let ctx = RuntimeContext {
+            session: LazySession::disabled(),
+            hlc: Arc::new(HLC::default()),
+            loader: Arc::new(Loader::new(LoaderConfig::new())),
+            runtime_name: STANDALONE_RUNTIME_NAME.into(),
+            runtime_uuid: Uuid::new_v4(),
+            recordings_dir: None,
+        };
+        let mut dataflow = Dataflow::new(ctx, "standalone".into(), None);
+
+        let source = Arc::new(TickSource);
+        let sink = Arc::new(SignalSink {
+            tx,
+            fail: AtomicBool::new(fail),
+        });
+        let period: DurationDescriptor = serde_yaml::from_str("{length: 10, unit: ms}").unwrap();
+
+        dataflow
+            .try_add_static_source(
+                "source".into(),
+                Some(period),
+                PortDescriptor {
+                    port_id: "out".into(),
+                    port_type: "tick".into(),
+                },
+                source.initialize(&None).unwrap(),
+                source,
+            )
+            .unwrap();
+        dataflow
+            .try_add_static_sink(
+                "sink".into(),
+                PortDescriptor {
+                    port_id: "in".into(),
+                    port_type: "tick".into(),
+                },
+                sink.initialize(&None).unwrap(),
+                sink,
+            )
+            .unwrap();
+        dataflow
+            .try_add_link(
+                OutputDescriptor {
+                    node: "source".into(),
+                    output: "out".into(),
+                },
+                InputDescriptor {
+                    node: "sink".into(),
+                    input: "in".into(),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        DataflowInstance::try_instantiate(dataflow).unwrap()
+    }
+
+    /// Runs the instance until the sink signals that it received a message.
+    fn run_until_received(fail: bool) -> i32 {
+        let (tx, rx) = bounded::<()>(1);
+        let mut instance = build_instance(fail, tx);
+        let received = async move {
+            let _ = rx.recv().await;
+        };
+
+        zenoh_flow::async_std::task::block_on(async move {
+            exit_code(run_instance(&mut instance, received, None).await)
+        })
+    }
+
+    #[test]
+    fn standalone_clean_end() {
+        assert_eq!(run_until_received(false), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn standalone_failed_node() {
+        // The sink is restarted after its failure, the run still fails.
+        assert_eq!(run_until_received(true), EXIT_FAILURE);
+    }
+}
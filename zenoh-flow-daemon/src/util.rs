@@ -14,6 +14,10 @@
 
 use std::fs;
 use std::io::Write;
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+#[cfg(target_os = "linux")]
+use std::os::unix::net::{SocketAddr, UnixDatagram};
 use std::path::Path;
 use zenoh::config::Config;
 use zenoh_flow::ZFResult;
@@ -38,6 +42,42 @@ pub(crate) fn _write_file(path: &Path, content: Vec<u8>) -> ZFResult<()> {
     Ok(file.sync_all()?)
 }
 
+/// Notifies systemd of the state of the daemon, see `sd_notify(3)`.
+///
+/// It does nothing if the daemon was not started by systemd with `Type=notify`, i.e. if the
+/// `NOTIFY_SOCKET` environment variable is not set.
+///
+/// # Errors
+///
+/// It returns an error variant if the notification cannot be sent.
+#[cfg(target_os = "linux")]
+pub(crate) fn sd_notify(state: &str) -> ZFResult<()> {
+    let socket = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => socket,
+        None => return Ok(()),
+    };
+
+    let sender = UnixDatagram::unbound()?;
+    let socket = socket.to_string_lossy();
+    match socket.strip_prefix('@') {
+        Some(name) => {
+            let address = SocketAddr::from_abstract_name(name.as_bytes())?;
+            sender.send_to_addr(state.as_bytes(), &address)?;
+        }
+        None => {
+            sender.send_to(state.as_bytes(), socket.as_ref())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Notifies systemd of the state of the daemon: systemd is only available on Linux.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn sd_notify(_state: &str) -> ZFResult<()> {
+    Ok(())
+}
+
 pub(crate) fn get_zenoh_config(path: &str) -> ZFResult<Config> {
     Ok(zenoh::config::Config::from_file(path)?)
 }
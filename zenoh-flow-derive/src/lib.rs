@@ -14,7 +14,7 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Attribute, DeriveInput, Meta, NestedMeta};
 /// The `ZFData` derive macro is provided to help the users
 /// in implementing the `DowncastAny` trait.
 ///
@@ -55,10 +55,51 @@ pub fn zf_data_derive(input: TokenStream) -> TokenStream {
 /// pub struct MyState;
 /// ```
 ///
-#[proc_macro_derive(ZFState)]
+/// Adding `#[zf_state(checkpoint)]` also implements `try_serialize` and
+/// `try_restore` using `bincode`, the type must then implement `Serialize`
+/// and `Deserialize`.
+///
+/// Example:
+/// ```no_compile
+/// use zenoh_flow::serde::{Deserialize, Serialize};
+/// use zenoh_flow_derive::ZFState;
+///
+/// #[derive(Debug, Clone, Serialize, Deserialize, ZFState)]
+/// #[serde(crate = "zenoh_flow::serde")]
+/// #[zf_state(checkpoint)]
+/// pub struct MyCounter {
+///     count: u64,
+/// }
+/// ```
+///
+#[proc_macro_derive(ZFState, attributes(zf_state))]
 pub fn zf_state_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let ident = &ast.ident;
+
+    let checkpoint = match has_checkpoint_attribute(&ast.attrs) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let checkpoint_impl = if checkpoint {
+        quote! {
+            fn try_serialize(&self) -> zenoh_flow::ZFResult<Option<Vec<u8>>> {
+                zenoh_flow::bincode::serialize(self)
+                    .map(Some)
+                    .map_err(|_| zenoh_flow::ZFError::SerializationError)
+            }
+
+            fn try_restore(&mut self, bytes: &[u8]) -> zenoh_flow::ZFResult<()> {
+                *self = zenoh_flow::bincode::deserialize::<#ident>(bytes)
+                    .map_err(|_| zenoh_flow::ZFError::DeseralizationError)?;
+                Ok(())
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let gen = quote! {
 
         impl zenoh_flow::ZFState for #ident {
@@ -69,7 +110,40 @@ pub fn zf_state_derive(input: TokenStream) -> TokenStream {
             fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
                 self
             }
+
+            #checkpoint_impl
         }
     };
     gen.into()
 }
+
+/// Looks for `#[zf_state(checkpoint)]` among the attributes.
+fn has_checkpoint_attribute(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut checkpoint = false;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("zf_state")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested.iter() {
+                    match nested {
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("checkpoint") => {
+                            checkpoint = true
+                        }
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                nested,
+                                "unknown `zf_state` option, expected `checkpoint`",
+                            ))
+                        }
+                    }
+                }
+            }
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected `#[zf_state(checkpoint)]`",
+                ))
+            }
+        }
+    }
+    Ok(checkpoint)
+}
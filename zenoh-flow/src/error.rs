@@ -47,6 +47,8 @@ pub enum ZFError {
     IOError(String),
     ZenohError(String),
     LoadingError(String),
+    WasmError(String),
+    FfiError(String),
     ParsingError(String),
     #[serde(skip_serializing, skip_deserializing)]
     RunnerStopError(crate::async_std::channel::RecvError),
@@ -47,12 +47,13 @@ pub use ::typetag;
 
 pub mod model;
 pub mod runtime;
-pub use runtime::deadline::LocalDeadlineMiss;
+pub use runtime::deadline::{DeadlineMiss, DeadlineMissStats, LocalDeadlineMiss};
 pub use runtime::loops::*;
 pub use runtime::message::*;
 pub use runtime::token::*;
 pub mod types;
 pub use types::*;
+pub mod testing;
 pub mod traits;
 pub use traits::*;
 
@@ -197,6 +197,144 @@ macro_rules! export_sink {
     };
 }
 
+/// This macros should be used in order to provide the symbols for the dynamic load of an
+/// Operator through the stable C ABI, see [`ffi`](`crate::runtime::dataflow::ffi`). Along with a
+/// register function, as for [`export_operator`](`crate::export_operator`).
+///
+/// Unlike [`export_operator`](`crate::export_operator`), the library can be loaded by a runtime
+/// built with other versions of the Rust compiler and of Zenoh Flow, as long as they implement a
+/// compatible version of the C ABI.
+#[macro_export]
+macro_rules! export_c_operator {
+    ($register:expr) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static zfoperator_c_declaration:
+            $crate::runtime::dataflow::ffi::FfiOperatorDeclaration = {
+            unsafe extern "C" fn initialize_node(
+                configuration: $crate::runtime::dataflow::ffi::FfiSlice,
+                state: *mut *mut ::std::os::raw::c_void,
+                error: *mut $crate::runtime::dataflow::ffi::FfiBuffer,
+            ) -> i32 {
+                $crate::runtime::dataflow::ffi::plugin::initialize_operator(
+                    $register,
+                    configuration,
+                    state,
+                    error,
+                )
+            }
+
+            $crate::runtime::dataflow::ffi::FfiOperatorDeclaration {
+                header: $crate::runtime::dataflow::ffi::FfiDeclarationHeader::new::<
+                    $crate::runtime::dataflow::ffi::FfiOperatorDeclaration,
+                >(),
+                node: $crate::runtime::dataflow::ffi::FfiNodeVTable {
+                    initialize: initialize_node,
+                    finalize: $crate::runtime::dataflow::ffi::plugin::finalize,
+                    free_buffer: $crate::runtime::dataflow::ffi::plugin::free_buffer,
+                },
+                input_rule: $crate::runtime::dataflow::ffi::plugin::input_rule,
+                run: $crate::runtime::dataflow::ffi::plugin::run_operator,
+                output_rule: $crate::runtime::dataflow::ffi::plugin::output_rule,
+                checkpoints: $crate::runtime::dataflow::ffi::FfiCheckpointVTable {
+                    checkpoint: Some($crate::runtime::dataflow::ffi::plugin::checkpoint),
+                    restore: Some($crate::runtime::dataflow::ffi::plugin::restore),
+                },
+            }
+        };
+    };
+}
+
+/// This macros should be used in order to provide the symbols for the dynamic load of a
+/// Source through the stable C ABI, see [`ffi`](`crate::runtime::dataflow::ffi`). Along with a
+/// register function, as for [`export_source`](`crate::export_source`).
+///
+/// Unlike [`export_source`](`crate::export_source`), the library can be loaded by a runtime built
+/// with other versions of the Rust compiler and of Zenoh Flow, as long as they implement a
+/// compatible version of the C ABI.
+#[macro_export]
+macro_rules! export_c_source {
+    ($register:expr) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static zfsource_c_declaration: $crate::runtime::dataflow::ffi::FfiSourceDeclaration = {
+            unsafe extern "C" fn initialize_node(
+                configuration: $crate::runtime::dataflow::ffi::FfiSlice,
+                state: *mut *mut ::std::os::raw::c_void,
+                error: *mut $crate::runtime::dataflow::ffi::FfiBuffer,
+            ) -> i32 {
+                $crate::runtime::dataflow::ffi::plugin::initialize_source(
+                    $register,
+                    configuration,
+                    state,
+                    error,
+                )
+            }
+
+            $crate::runtime::dataflow::ffi::FfiSourceDeclaration {
+                header: $crate::runtime::dataflow::ffi::FfiDeclarationHeader::new::<
+                    $crate::runtime::dataflow::ffi::FfiSourceDeclaration,
+                >(),
+                node: $crate::runtime::dataflow::ffi::FfiNodeVTable {
+                    initialize: initialize_node,
+                    finalize: $crate::runtime::dataflow::ffi::plugin::finalize,
+                    free_buffer: $crate::runtime::dataflow::ffi::plugin::free_buffer,
+                },
+                run: $crate::runtime::dataflow::ffi::plugin::run_source,
+                checkpoints: $crate::runtime::dataflow::ffi::FfiCheckpointVTable {
+                    checkpoint: Some($crate::runtime::dataflow::ffi::plugin::checkpoint),
+                    restore: Some($crate::runtime::dataflow::ffi::plugin::restore),
+                },
+            }
+        };
+    };
+}
+
+/// This macros should be used in order to provide the symbols for the dynamic load of a
+/// Sink through the stable C ABI, see [`ffi`](`crate::runtime::dataflow::ffi`). Along with a
+/// register function, as for [`export_sink`](`crate::export_sink`).
+///
+/// Unlike [`export_sink`](`crate::export_sink`), the library can be loaded by a runtime built
+/// with other versions of the Rust compiler and of Zenoh Flow, as long as they implement a
+/// compatible version of the C ABI.
+#[macro_export]
+macro_rules! export_c_sink {
+    ($register:expr) => {
+        #[doc(hidden)]
+        #[no_mangle]
+        pub static zfsink_c_declaration: $crate::runtime::dataflow::ffi::FfiSinkDeclaration = {
+            unsafe extern "C" fn initialize_node(
+                configuration: $crate::runtime::dataflow::ffi::FfiSlice,
+                state: *mut *mut ::std::os::raw::c_void,
+                error: *mut $crate::runtime::dataflow::ffi::FfiBuffer,
+            ) -> i32 {
+                $crate::runtime::dataflow::ffi::plugin::initialize_sink(
+                    $register,
+                    configuration,
+                    state,
+                    error,
+                )
+            }
+
+            $crate::runtime::dataflow::ffi::FfiSinkDeclaration {
+                header: $crate::runtime::dataflow::ffi::FfiDeclarationHeader::new::<
+                    $crate::runtime::dataflow::ffi::FfiSinkDeclaration,
+                >(),
+                node: $crate::runtime::dataflow::ffi::FfiNodeVTable {
+                    initialize: initialize_node,
+                    finalize: $crate::runtime::dataflow::ffi::plugin::finalize,
+                    free_buffer: $crate::runtime::dataflow::ffi::plugin::free_buffer,
+                },
+                run: $crate::runtime::dataflow::ffi::plugin::run_sink,
+                checkpoints: $crate::runtime::dataflow::ffi::FfiCheckpointVTable {
+                    checkpoint: Some($crate::runtime::dataflow::ffi::plugin::checkpoint),
+                    restore: Some($crate::runtime::dataflow::ffi::plugin::restore),
+                },
+            }
+        };
+    };
+}
+
 /// Spin lock over an [`async_std::sync::Mutex`](`async_std::sync::Mutex`)
 /// Note: This is intended for internal usage.
 #[macro_export]
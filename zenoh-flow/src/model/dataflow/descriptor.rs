@@ -13,14 +13,17 @@
 //
 
 use crate::model::dataflow::flag::Flag;
+use crate::model::dataflow::graph::Graph;
 use crate::model::dataflow::validator::DataflowValidator;
-use crate::model::deadline::E2EDeadlineDescriptor;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineDescriptor};
 use crate::model::link::LinkDescriptor;
 use crate::model::loops::LoopDescriptor;
 use crate::model::node::{OperatorDescriptor, SinkDescriptor, SourceDescriptor};
+use crate::model::schema::validate_configuration;
+use crate::runtime::liveliness::{RuntimeFailurePolicy, RuntimeRestartPolicy};
 use crate::serde::{Deserialize, Serialize};
 use crate::types::{NodeId, RuntimeId, ZFError, ZFResult};
-use crate::Configuration;
+use crate::{merge_configurations, Configuration};
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::convert::TryFrom;
@@ -79,6 +82,27 @@ use std::hash::{Hash, Hasher};
 ///     runtime: runtime0
 /// ```
 ///
+/// The configuration of a node is obtained by (deeply) merging, in order of increasing precedence:
+/// the `configuration` of the flow, the entry of `runtime_configurations` associated to the runtime
+/// the node is mapped to, and the `configuration` of the node itself.
+///
+/// ```yaml
+/// configuration:
+///   log-level: info
+/// runtime_configurations:
+///   runtime0:
+///     device: /dev/ttyUSB0
+/// ```
+///
+/// The nodes mapped to a runtime that fails can be re-mapped to the runtimes still alive, see
+/// [`RuntimeFailurePolicy`](`RuntimeFailurePolicy`), and a runtime that restarts can recover its
+/// part of the instance, see [`RuntimeRestartPolicy`](`RuntimeRestartPolicy`).
+///
+/// ```yaml
+/// on_runtime_failure:
+///   remap: {}
+/// on_runtime_restart: always
+/// ```
 ///
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DataFlowDescriptor {
@@ -92,7 +116,10 @@ pub struct DataFlowDescriptor {
     pub loops: Option<Vec<LoopDescriptor>>,
     #[serde(alias = "configuration")]
     pub global_configuration: Option<Configuration>,
+    pub runtime_configurations: Option<HashMap<RuntimeId, Configuration>>,
     pub flags: Option<Vec<Flag>>,
+    pub on_runtime_failure: Option<RuntimeFailurePolicy>,
+    pub on_runtime_restart: Option<RuntimeRestartPolicy>,
 }
 
 impl DataFlowDescriptor {
@@ -134,6 +161,21 @@ impl DataFlowDescriptor {
         serde_yaml::to_string(&self).map_err(|_| ZFError::SerializationError)
     }
 
+    /// Returns the representation of the `DataFlowDescriptor` in the Graphviz DOT language.
+    ///
+    /// The diagram shows the kind and the ports of each node, the links, the loops and the
+    /// end-to-end deadlines; the nodes disabled by a flag are drawn with a dashed border.
+    pub fn to_dot(&self) -> String {
+        Graph::from(self).to_dot()
+    }
+
+    /// Returns the representation of the `DataFlowDescriptor` as a Mermaid flowchart.
+    ///
+    /// The content is the same as the one of [`to_dot`](`DataFlowDescriptor::to_dot`).
+    pub fn to_mermaid(&self) -> String {
+        Graph::from(self).to_mermaid()
+    }
+
     /// Gets all the `RuntimeId` mapped to nodes of this `DataFlowDescriptor`.
     pub fn get_runtimes(&self) -> Vec<RuntimeId> {
         match &self.mapping {
@@ -142,6 +184,34 @@ impl DataFlowDescriptor {
         }
     }
 
+    /// Returns the configuration of the given node, merged with the global configuration and
+    /// the configuration of the runtime it is mapped to (if any).
+    ///
+    /// The precedence is: global < runtime < node.
+    ///
+    ///  # Errors
+    /// A variant error is returned if the configurations cannot be merged.
+    pub(crate) fn get_node_configuration(
+        &self,
+        node_id: &NodeId,
+        node_configuration: Option<Configuration>,
+    ) -> ZFResult<Option<Configuration>> {
+        let runtime_configuration = self
+            .mapping
+            .as_ref()
+            .and_then(|mapping| mapping.get(node_id))
+            .and_then(|runtime| {
+                self.runtime_configurations
+                    .as_ref()
+                    .and_then(|configurations| configurations.get(runtime))
+            })
+            .cloned();
+
+        let configuration =
+            merge_configurations(self.global_configuration.clone(), runtime_configuration)?;
+        merge_configurations(configuration, node_configuration)
+    }
+
     /// This method checks that the dataflow graph is correct.
     ///
     /// In particular it verifies that:
@@ -151,7 +221,10 @@ impl DataFlowDescriptor {
     /// - connected ports are declared with the same type,
     /// - the dataflow, without the loops, is a DAG,
     /// - the end-to-end deadlines are correct,
-    /// - the loops are valid.
+    /// - the deadline miss policies are applicable,
+    /// - the executors of the operators are valid,
+    /// - the loops are valid,
+    /// - the configuration of each node complies with its schema (if any).
     ///
     ///  # Errors
     /// A variant error is returned if validation fails.
@@ -168,10 +241,132 @@ impl DataFlowDescriptor {
             })?
         }
 
+        self.validate_deadline_policies()?;
+
+        self.operators
+            .iter()
+            .try_for_each(|operator| match &operator.executor {
+                Some(executor) => executor.validate(&operator.id),
+                None => Ok(()),
+            })?;
+
         if let Some(loops) = &self.loops {
-            loops.iter().try_for_each(|ciclo| {
-                validator.validate_loop(&ciclo.ingress, &ciclo.egress, &ciclo.feedback_port)
-            })?
+            loops
+                .iter()
+                .try_for_each(|ciclo| validator.validate_loop(ciclo))?
+        }
+
+        self.validate_configurations()
+    }
+
+    /// Checks that the deadline miss policies can be applied.
+    ///
+    /// The `fallback` output of an end-to-end deadline must be an output of the operator it
+    /// ends on, with the same type as the input of the deadline. A local deadline miss policy
+    /// requires a local deadline and cannot be a `fallback`.
+    ///
+    ///  # Errors
+    /// A variant error is returned if a policy cannot be applied.
+    fn validate_deadline_policies(&self) -> ZFResult<()> {
+        if let Some(deadlines) = &self.deadlines {
+            for deadline in deadlines {
+                if let Some(DeadlineMissPolicy::Fallback(output)) = &deadline.on_miss {
+                    let node = &deadline.to.node;
+                    let operator = match self.operators.iter().find(|o| o.id == *node) {
+                        Some(operator) => operator,
+                        None => {
+                            return Err(ZFError::ConfigurationError(format!(
+                                "Node < {} >: only operators support `fallback`",
+                                node
+                            )))
+                        }
+                    };
+                    let output_type = operator
+                        .outputs
+                        .iter()
+                        .find(|port| port.port_id == *output)
+                        .map(|port| &port.port_type)
+                        .ok_or_else(|| ZFError::PortNotFound((node.clone(), output.clone())))?;
+                    let input_type = operator
+                        .inputs
+                        .iter()
+                        .find(|port| port.port_id == deadline.to.input)
+                        .map(|port| &port.port_type)
+                        .ok_or_else(|| {
+                            ZFError::PortNotFound((node.clone(), deadline.to.input.clone()))
+                        })?;
+                    if output_type != input_type {
+                        return Err(ZFError::PortTypeNotMatching((
+                            input_type.clone(),
+                            output_type.clone(),
+                        )));
+                    }
+                }
+            }
+        }
+
+        let nodes = self
+            .operators
+            .iter()
+            .map(|o| (&o.id, &o.on_deadline_miss, &o.deadline))
+            .chain(
+                self.sources
+                    .iter()
+                    .map(|s| (&s.id, &s.on_deadline_miss, &s.deadline)),
+            )
+            .chain(
+                self.sinks
+                    .iter()
+                    .map(|s| (&s.id, &s.on_deadline_miss, &s.deadline)),
+            );
+
+        for (id, on_deadline_miss, deadline) in nodes {
+            match (on_deadline_miss, deadline) {
+                (Some(_), None) => {
+                    return Err(ZFError::ConfigurationError(format!(
+                        "Node < {} > has a deadline miss policy but no deadline",
+                        id
+                    )))
+                }
+                (Some(DeadlineMissPolicy::Fallback(_)), Some(_)) => {
+                    return Err(ZFError::ConfigurationError(format!(
+                        "Node < {} >: the `fallback` policy is only supported for end-to-end deadlines",
+                        id
+                    )))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the configuration of each node against its schema, if one was provided.
+    ///
+    ///  # Errors
+    /// A variant error is returned if a configuration cannot be merged or does not comply with the
+    /// schema of its node.
+    pub(crate) fn validate_configurations(&self) -> ZFResult<()> {
+        let nodes = self
+            .operators
+            .iter()
+            .map(|o| (&o.id, &o.configuration, &o.schema))
+            .chain(
+                self.sources
+                    .iter()
+                    .map(|s| (&s.id, &s.configuration, &s.schema)),
+            )
+            .chain(
+                self.sinks
+                    .iter()
+                    .map(|s| (&s.id, &s.configuration, &s.schema)),
+            );
+
+        for (id, configuration, schema) in nodes {
+            let configuration = self.get_node_configuration(id, configuration.clone())?;
+            if let Some(schema) = schema {
+                validate_configuration(id, schema, configuration.as_ref())?;
+            }
         }
 
         Ok(())
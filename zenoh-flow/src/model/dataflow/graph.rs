@@ -0,0 +1,560 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::connector::ZFConnectorKind;
+use crate::model::dataflow::descriptor::DataFlowDescriptor;
+use crate::model::dataflow::flag::get_nodes_to_remove;
+use crate::model::dataflow::record::DataFlowRecord;
+use crate::model::link::PortDescriptor;
+use crate::model::{InputDescriptor, OutputDescriptor};
+use crate::{NodeId, RuntimeId};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The kind of a node, as displayed in a diagram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphNodeKind {
+    Source,
+    Operator,
+    Sink,
+    Sender,
+    Receiver,
+}
+
+impl std::fmt::Display for GraphNodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Source => write!(f, "Source"),
+            Self::Operator => write!(f, "Operator"),
+            Self::Sink => write!(f, "Sink"),
+            Self::Sender => write!(f, "ZenohSender"),
+            Self::Receiver => write!(f, "ZenohReceiver"),
+        }
+    }
+}
+
+/// A node of the diagram.
+struct GraphNode {
+    id: NodeId,
+    kind: GraphNodeKind,
+    inputs: Vec<PortDescriptor>,
+    outputs: Vec<PortDescriptor>,
+    runtime: Option<RuntimeId>,
+    resource: Option<String>,
+    disabled: bool,
+}
+
+/// The kind of an edge of the diagram.
+enum GraphEdgeKind {
+    Link,
+    Loop,
+    Deadline(Duration),
+}
+
+/// An edge of the diagram.
+struct GraphEdge {
+    from: OutputDescriptor,
+    to: InputDescriptor,
+    kind: GraphEdgeKind,
+}
+
+/// The intermediate representation shared by the descriptor and the record to produce the DOT and
+/// Mermaid diagrams.
+pub(crate) struct Graph {
+    name: String,
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl From<&DataFlowDescriptor> for Graph {
+    fn from(descriptor: &DataFlowDescriptor) -> Self {
+        let disabled = descriptor
+            .flags
+            .as_ref()
+            .map_or_else(HashSet::new, |flags| get_nodes_to_remove(flags));
+        let runtime = |id: &NodeId| {
+            descriptor
+                .mapping
+                .as_ref()
+                .and_then(|mapping| mapping.get(id).cloned())
+        };
+
+        let mut nodes = Vec::with_capacity(
+            descriptor.sources.len() + descriptor.operators.len() + descriptor.sinks.len(),
+        );
+
+        nodes.extend(descriptor.sources.iter().map(|s| GraphNode {
+            id: s.id.clone(),
+            kind: GraphNodeKind::Source,
+            inputs: vec![],
+            outputs: vec![s.output.clone()],
+            runtime: runtime(&s.id),
+            resource: None,
+            disabled: disabled.contains(&s.id),
+        }));
+
+        nodes.extend(descriptor.operators.iter().map(|o| GraphNode {
+            id: o.id.clone(),
+            kind: GraphNodeKind::Operator,
+            inputs: o.inputs.clone(),
+            outputs: o.outputs.clone(),
+            runtime: runtime(&o.id),
+            resource: None,
+            disabled: disabled.contains(&o.id),
+        }));
+
+        nodes.extend(descriptor.sinks.iter().map(|s| GraphNode {
+            id: s.id.clone(),
+            kind: GraphNodeKind::Sink,
+            inputs: vec![s.input.clone()],
+            outputs: vec![],
+            runtime: runtime(&s.id),
+            resource: None,
+            disabled: disabled.contains(&s.id),
+        }));
+
+        let mut edges: Vec<GraphEdge> = descriptor
+            .links
+            .iter()
+            .map(|l| GraphEdge {
+                from: l.from.clone(),
+                to: l.to.clone(),
+                kind: GraphEdgeKind::Link,
+            })
+            .collect();
+
+        // The feedback ports of the loops are not declared by the user, they are added here as
+        // `DataFlowRecord::try_from` does.
+        for ciclo in descriptor.loops.iter().flatten() {
+            let feedback = PortDescriptor {
+                port_id: ciclo.feedback_port.clone(),
+                port_type: ciclo.port_type.clone(),
+            };
+
+            for node in nodes.iter_mut() {
+                if node.id == ciclo.ingress {
+                    node.inputs.push(feedback.clone());
+                }
+                if node.id == ciclo.egress {
+                    node.outputs.push(feedback.clone());
+                }
+            }
+
+            edges.push(GraphEdge {
+                from: OutputDescriptor {
+                    node: ciclo.egress.clone(),
+                    output: ciclo.feedback_port.clone(),
+                },
+                to: InputDescriptor {
+                    node: ciclo.ingress.clone(),
+                    input: ciclo.feedback_port.clone(),
+                },
+                kind: GraphEdgeKind::Loop,
+            });
+        }
+
+        edges.extend(descriptor.deadlines.iter().flatten().map(|d| GraphEdge {
+            from: d.from.clone(),
+            to: d.to.clone(),
+            kind: GraphEdgeKind::Deadline(d.duration.to_duration()),
+        }));
+
+        Self {
+            name: descriptor.flow.clone(),
+            nodes,
+            edges,
+        }
+    }
+}
+
+impl From<&DataFlowRecord> for Graph {
+    fn from(record: &DataFlowRecord) -> Self {
+        let mut nodes = Vec::with_capacity(
+            record.sources.len()
+                + record.operators.len()
+                + record.sinks.len()
+                + record.connectors.len(),
+        );
+
+        nodes.extend(record.sources.values().map(|s| GraphNode {
+            id: s.id.clone(),
+            kind: GraphNodeKind::Source,
+            inputs: vec![],
+            outputs: vec![s.output.clone()],
+            runtime: Some(s.runtime.clone()),
+            resource: None,
+            disabled: false,
+        }));
+
+        nodes.extend(record.operators.values().map(|o| GraphNode {
+            id: o.id.clone(),
+            kind: GraphNodeKind::Operator,
+            inputs: o.inputs.clone(),
+            outputs: o.outputs.clone(),
+            runtime: Some(o.runtime.clone()),
+            resource: None,
+            disabled: false,
+        }));
+
+        nodes.extend(record.sinks.values().map(|s| GraphNode {
+            id: s.id.clone(),
+            kind: GraphNodeKind::Sink,
+            inputs: vec![s.input.clone()],
+            outputs: vec![],
+            runtime: Some(s.runtime.clone()),
+            resource: None,
+            disabled: false,
+        }));
+
+        nodes.extend(record.connectors.iter().map(|c| {
+            let (kind, inputs, outputs) = match c.kind {
+                ZFConnectorKind::Sender => (GraphNodeKind::Sender, vec![c.link_id.clone()], vec![]),
+                ZFConnectorKind::Receiver => {
+                    (GraphNodeKind::Receiver, vec![], vec![c.link_id.clone()])
+                }
+            };
+
+            GraphNode {
+                id: c.id.clone(),
+                kind,
+                inputs,
+                outputs,
+                runtime: Some(c.runtime.clone()),
+                resource: Some(c.resource.clone()),
+                disabled: false,
+            }
+        }));
+
+        // The records are stored in `HashMap`s, sorting them gives a stable output.
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut edges: Vec<GraphEdge> = record
+            .links
+            .iter()
+            .map(|l| {
+                let is_feedback = record.operators.get(&l.from.node).map_or(false, |o| {
+                    o.cicli.iter().any(|ciclo| {
+                        ciclo.egress == l.from.node && ciclo.feedback_port == l.from.output
+                    })
+                });
+
+                GraphEdge {
+                    from: l.from.clone(),
+                    to: l.to.clone(),
+                    kind: if is_feedback {
+                        GraphEdgeKind::Loop
+                    } else {
+                        GraphEdgeKind::Link
+                    },
+                }
+            })
+            .collect();
+
+        edges.extend(
+            record
+                .end_to_end_deadlines
+                .iter()
+                .flatten()
+                .map(|d| GraphEdge {
+                    from: d.from.clone(),
+                    to: d.to.clone(),
+                    kind: GraphEdgeKind::Deadline(d.duration),
+                }),
+        );
+
+        Self {
+            name: format!("{} ({})", record.flow, record.uuid),
+            nodes,
+            edges,
+        }
+    }
+}
+
+impl Graph {
+    /// Returns the nodes grouped by the runtime they are mapped to. The unmapped nodes are
+    /// associated to `None`.
+    fn nodes_by_runtime(&self) -> BTreeMap<Option<RuntimeId>, Vec<(usize, &GraphNode)>> {
+        let mut runtimes: BTreeMap<Option<RuntimeId>, Vec<(usize, &GraphNode)>> = BTreeMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            runtimes
+                .entry(node.runtime.clone())
+                .or_default()
+                .push((index, node));
+        }
+
+        runtimes
+    }
+
+    /// Returns the index of the node and, if found, the index of the port.
+    fn find_port(&self, node: &NodeId, port: &str, output: bool) -> Option<(usize, Option<usize>)> {
+        self.nodes.iter().position(|n| n.id == *node).map(|index| {
+            let ports = if output {
+                &self.nodes[index].outputs
+            } else {
+                &self.nodes[index].inputs
+            };
+
+            (index, ports.iter().position(|p| p.port_id.as_ref() == port))
+        })
+    }
+
+    /// Renders the graph in the Graphviz DOT language.
+    pub(crate) fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph \"{}\" {{", escape_dot(&self.name));
+        let _ = writeln!(dot, "  rankdir=LR;");
+        let _ = writeln!(dot, "  node [shape=record, fontname=\"Helvetica\"];");
+
+        for (runtime, nodes) in self.nodes_by_runtime() {
+            let indent = match &runtime {
+                Some(runtime) => {
+                    let _ = writeln!(dot, "  subgraph \"cluster_{}\" {{", escape_dot(runtime));
+                    let _ = writeln!(dot, "    label=\"runtime: {}\";", escape_dot(runtime));
+                    let _ = writeln!(dot, "    style=rounded;");
+                    "    "
+                }
+                None => "  ",
+            };
+
+            for (index, node) in nodes {
+                let inputs = node
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| format!("<i{}> {}", i, escape_dot_record(&port_label(p))))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                let outputs = node
+                    .outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| format!("<o{}> {}", i, escape_dot_record(&port_label(p))))
+                    .collect::<Vec<_>>()
+                    .join("|");
+
+                let mut center = format!(
+                    "{}\\n({})",
+                    escape_dot_record(&node.id),
+                    escape_dot_record(&node.kind.to_string())
+                );
+                if let Some(resource) = &node.resource {
+                    let _ = write!(center, "\\n{}", escape_dot_record(resource));
+                }
+
+                let color = match node.kind {
+                    GraphNodeKind::Source => "palegreen",
+                    GraphNodeKind::Operator => "lightblue",
+                    GraphNodeKind::Sink => "lightsalmon",
+                    GraphNodeKind::Sender | GraphNodeKind::Receiver => "lightgrey",
+                };
+                let (style, color) = if node.disabled {
+                    ("rounded,dashed", "white")
+                } else {
+                    ("rounded,filled", color)
+                };
+
+                let _ = writeln!(
+                    dot,
+                    "{}n{} [label=\"{{{{{}}}|{}|{{{}}}}}\", style=\"{}\", fillcolor=\"{}\"{}];",
+                    indent,
+                    index,
+                    inputs,
+                    center,
+                    outputs,
+                    style,
+                    color,
+                    if node.disabled {
+                        ", fontcolor=\"grey\", tooltip=\"disabled by flag\""
+                    } else {
+                        ""
+                    }
+                );
+            }
+
+            if runtime.is_some() {
+                let _ = writeln!(dot, "  }}");
+            }
+        }
+
+        for edge in self.edges.iter() {
+            let from = self.find_port(&edge.from.node, &edge.from.output, true);
+            let to = self.find_port(&edge.to.node, &edge.to.input, false);
+            let (from, to) = match (from, to) {
+                (Some(from), Some(to)) => (from, to),
+                _ => {
+                    log::warn!("Skipping edge {} → {}: node not found", edge.from, edge.to);
+                    continue;
+                }
+            };
+
+            let endpoint = |(node, port): (usize, Option<usize>), prefix: &str| match port {
+                Some(port) => format!("n{}:{}{}", node, prefix, port),
+                None => format!("n{}", node),
+            };
+
+            let attributes = match &edge.kind {
+                GraphEdgeKind::Link => String::new(),
+                GraphEdgeKind::Loop => format!(
+                    " [label=\"loop: {}\", style=bold, color=\"blue\", constraint=false]",
+                    escape_dot(&edge.from.output)
+                ),
+                GraphEdgeKind::Deadline(duration) => format!(
+                    " [label=\"E2E deadline: {:?}\", style=dashed, color=\"red\", fontcolor=\"red\", constraint=false]",
+                    duration
+                ),
+            };
+
+            let _ = writeln!(
+                dot,
+                "  {} -> {}{};",
+                endpoint(from, "o"),
+                endpoint(to, "i"),
+                attributes
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a Mermaid flowchart.
+    pub(crate) fn to_mermaid(&self) -> String {
+        let mut mermaid = String::new();
+        let _ = writeln!(mermaid, "%% {}", self.name);
+        let _ = writeln!(mermaid, "flowchart LR");
+
+        let mut disabled = vec![];
+        for (runtime, nodes) in self.nodes_by_runtime() {
+            let indent = match &runtime {
+                Some(runtime) => {
+                    let _ = writeln!(
+                        mermaid,
+                        "  subgraph rt_{}[\"runtime: {}\"]",
+                        sanitize_mermaid_id(runtime),
+                        escape_mermaid(runtime)
+                    );
+                    "    "
+                }
+                None => "  ",
+            };
+
+            for (index, node) in nodes {
+                let mut label = format!("<b>{}</b><br/>{}", escape_mermaid(&node.id), node.kind);
+                if let Some(resource) = &node.resource {
+                    let _ = write!(label, "<br/>{}", escape_mermaid(resource));
+                }
+                for input in node.inputs.iter() {
+                    let _ = write!(label, "<br/>in: {}", escape_mermaid(&port_label(input)));
+                }
+                for output in node.outputs.iter() {
+                    let _ = write!(label, "<br/>out: {}", escape_mermaid(&port_label(output)));
+                }
+
+                let (open, close) = match node.kind {
+                    GraphNodeKind::Source => ("([", "])"),
+                    GraphNodeKind::Operator => ("[", "]"),
+                    GraphNodeKind::Sink => ("[(", ")]"),
+                    GraphNodeKind::Sender | GraphNodeKind::Receiver => ("{{", "}}"),
+                };
+                let _ = writeln!(
+                    mermaid,
+                    "{}n{}{}\"{}\"{}",
+                    indent, index, open, label, close
+                );
+
+                if node.disabled {
+                    disabled.push(format!("n{}", index));
+                }
+            }
+
+            if runtime.is_some() {
+                let _ = writeln!(mermaid, "  end");
+            }
+        }
+
+        for edge in self.edges.iter() {
+            let from = self.nodes.iter().position(|n| n.id == edge.from.node);
+            let to = self.nodes.iter().position(|n| n.id == edge.to.node);
+            let (from, to) = match (from, to) {
+                (Some(from), Some(to)) => (from, to),
+                _ => {
+                    log::warn!("Skipping edge {} → {}: node not found", edge.from, edge.to);
+                    continue;
+                }
+            };
+
+            let arrow = match &edge.kind {
+                GraphEdgeKind::Link => format!(
+                    "-->|\"{} → {}\"|",
+                    escape_mermaid(&edge.from.output),
+                    escape_mermaid(&edge.to.input)
+                ),
+                GraphEdgeKind::Loop => {
+                    format!("==>|\"loop: {}\"|", escape_mermaid(&edge.from.output))
+                }
+                GraphEdgeKind::Deadline(duration) => {
+                    format!("-.->|\"E2E deadline: {:?}\"|", duration)
+                }
+            };
+
+            let _ = writeln!(mermaid, "  n{} {} n{}", from, arrow, to);
+        }
+
+        if !disabled.is_empty() {
+            let _ = writeln!(
+                mermaid,
+                "  classDef disabled stroke-dasharray: 5 5,fill:#fff,color:#999"
+            );
+            let _ = writeln!(mermaid, "  class {} disabled", disabled.join(","));
+        }
+
+        mermaid
+    }
+}
+
+fn port_label(port: &PortDescriptor) -> String {
+    format!("{}: {}", port.port_id, port.port_type)
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_dot_record(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+fn sanitize_mermaid_id(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -14,5 +14,6 @@
 
 pub mod descriptor;
 pub mod flag;
+pub(crate) mod graph;
 pub mod record;
 pub mod validator;
@@ -14,13 +14,15 @@
 
 use crate::model::connector::{ZFConnectorKind, ZFConnectorRecord};
 use crate::model::dataflow::descriptor::DataFlowDescriptor;
+use crate::model::dataflow::graph::Graph;
 use crate::model::deadline::E2EDeadlineRecord;
 use crate::model::link::{LinkDescriptor, PortDescriptor};
 use crate::model::node::{OperatorRecord, SinkRecord, SourceRecord};
 use crate::model::{InputDescriptor, OutputDescriptor};
+use crate::runtime::liveliness::{RuntimeFailurePolicy, RuntimeRestartPolicy};
 use crate::serde::{Deserialize, Serialize};
 use crate::types::{RuntimeId, ZFError, ZFResult};
-use crate::{merge_configurations, NodeId, PortType};
+use crate::{NodeId, PortType};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
@@ -38,6 +40,10 @@ pub struct DataFlowRecord {
     pub connectors: Vec<ZFConnectorRecord>,
     pub links: Vec<LinkDescriptor>,
     pub end_to_end_deadlines: Option<Vec<E2EDeadlineRecord>>,
+    #[serde(default)]
+    pub on_runtime_failure: Option<RuntimeFailurePolicy>,
+    #[serde(default)]
+    pub on_runtime_restart: Option<RuntimeRestartPolicy>,
 }
 
 impl DataFlowRecord {
@@ -75,6 +81,21 @@ impl DataFlowRecord {
         serde_yaml::to_string(&self).map_err(|_| ZFError::SerializationError)
     }
 
+    /// Returns the representation of the `DataFlowRecord` in the Graphviz DOT language.
+    ///
+    /// The diagram shows the kind and the ports of each node, the links, the loops and the
+    /// end-to-end deadlines; the nodes are grouped by runtime and the connectors are displayed.
+    pub fn to_dot(&self) -> String {
+        Graph::from(self).to_dot()
+    }
+
+    /// Returns the representation of the `DataFlowRecord` as a Mermaid flowchart.
+    ///
+    /// The content is the same as the one of [`to_dot`](`DataFlowRecord::to_dot`).
+    pub fn to_mermaid(&self) -> String {
+        Graph::from(self).to_mermaid()
+    }
+
     /// Returns the runtime mapping for the given node.
     pub fn find_node_runtime(&self, id: &str) -> Option<RuntimeId> {
         match self.operators.get(id) {
@@ -86,6 +107,131 @@ impl DataFlowRecord {
         }
     }
 
+    /// Returns the runtimes running at least a node or a connector of the record.
+    pub fn get_runtimes(&self) -> Vec<RuntimeId> {
+        let mut runtimes: Vec<RuntimeId> = Vec::new();
+        for runtime in self
+            .operators
+            .values()
+            .map(|o| &o.runtime)
+            .chain(self.sources.values().map(|s| &s.runtime))
+            .chain(self.sinks.values().map(|s| &s.runtime))
+            .chain(self.connectors.iter().map(|c| &c.runtime))
+        {
+            if !runtimes.contains(runtime) {
+                runtimes.push(runtime.clone());
+            }
+        }
+        runtimes
+    }
+
+    /// Returns the links between the nodes as they were declared: a link going through a pair
+    /// of connectors is merged back into a single link, with the attributes (size, queueing
+    /// policy and priority) of the declared link.
+    ///
+    ///  # Errors
+    /// A variant error is returned if a receiver has no matching sender.
+    pub fn get_node_links(&self) -> ZFResult<Vec<LinkDescriptor>> {
+        let connectors: HashMap<&NodeId, &ZFConnectorRecord> =
+            self.connectors.iter().map(|c| (&c.id, c)).collect();
+        let mut links = Vec::with_capacity(self.links.len());
+
+        for link in self.links.iter() {
+            if connectors.contains_key(&link.to.node) {
+                // Link between a node and a sender, it is merged with the links of the receivers.
+                continue;
+            }
+
+            let receiver = match connectors.get(&link.from.node) {
+                Some(receiver) => receiver,
+                None => {
+                    links.push(link.clone());
+                    continue;
+                }
+            };
+
+            let from = self
+                .connectors
+                .iter()
+                .filter(|c| c.kind == ZFConnectorKind::Sender && c.resource == receiver.resource)
+                .find_map(|sender| self.links.iter().find(|l| l.to.node == sender.id))
+                .map(|l| l.from.clone())
+                .ok_or_else(|| {
+                    ZFError::Uncompleted(format!("No sender found for < {} >", receiver.id))
+                })?;
+
+            links.push(LinkDescriptor {
+                from,
+                to: link.to.clone(),
+                size: link.size,
+                queueing_policy: link.queueing_policy.clone(),
+                priority: link.priority,
+            });
+        }
+
+        Ok(links)
+    }
+
+    /// Returns a copy of the record where the given node is mapped to `runtime`.
+    ///
+    /// The connectors are generated anew for the new mapping. As the key expressions only depend
+    /// on the upstream node and port, the connectors of the links not involving the node are
+    /// left untouched.
+    ///
+    ///  # Errors
+    /// A variant error is returned if the node is not found or if the links cannot be
+    /// generated.
+    pub fn try_migrate_node(&self, node_id: &NodeId, runtime: RuntimeId) -> ZFResult<Self> {
+        let mut mapping = HashMap::with_capacity(1);
+        mapping.insert(node_id.clone(), runtime);
+        self.try_remap_nodes(&mapping)
+    }
+
+    /// Returns a copy of the record where each node of `mapping` is mapped to the associated
+    /// runtime.
+    ///
+    /// The connectors are generated anew, see
+    /// [`try_migrate_node`](`DataFlowRecord::try_migrate_node`).
+    ///
+    ///  # Errors
+    /// A variant error is returned if a node is not found or if the links cannot be
+    /// generated.
+    pub fn try_remap_nodes(&self, mapping: &HashMap<NodeId, RuntimeId>) -> ZFResult<Self> {
+        let links = self.get_node_links()?;
+        let mut record = self.clone();
+
+        for (node_id, runtime) in mapping {
+            let runtime = runtime.clone();
+            if let Some(operator) = record.operators.get_mut(node_id) {
+                operator.runtime = runtime;
+            } else if let Some(source) = record.sources.get_mut(node_id) {
+                source.runtime = runtime;
+            } else if let Some(sink) = record.sinks.get_mut(node_id) {
+                sink.runtime = runtime;
+            } else {
+                return Err(ZFError::NodeNotFound(node_id.clone()));
+            }
+        }
+
+        record.connectors.clear();
+        record.links.clear();
+        record.add_links(&links, &HashSet::new())?;
+
+        Ok(record)
+    }
+
+    /// Returns the nodes mapped to the given runtime.
+    pub fn get_runtime_nodes(&self, runtime: &str) -> Vec<NodeId> {
+        self.operators
+            .values()
+            .map(|o| (&o.id, &o.runtime))
+            .chain(self.sources.values().map(|s| (&s.id, &s.runtime)))
+            .chain(self.sinks.values().map(|s| (&s.id, &s.runtime)))
+            .filter(|(_, rt)| rt.as_ref() == runtime)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
     /// Returns the output type for the given node and port.
     pub fn find_node_output_type(&self, id: &str, output: &str) -> Option<PortType> {
         log::trace!("find_node_output_type({:?},{:?})", id, output);
@@ -247,16 +393,17 @@ impl DataFlowRecord {
                     runtime: to_runtime,
                 };
 
-                // Creating link between receiver and node
+                // Creating link between receiver and node, it keeps the attributes of the link
+                // so that they are found back when the connectors are merged.
                 let link_receiver = LinkDescriptor {
                     from: OutputDescriptor {
                         node: receiver_id.into(),
                         output: l.to.input.clone(),
                     },
                     to: l.to.clone(),
-                    size: None,
-                    queueing_policy: None,
-                    priority: None,
+                    size: l.size,
+                    queueing_policy: l.queueing_policy.clone(),
+                    priority: l.priority,
                 };
 
                 // storing info in the data flow record
@@ -275,6 +422,27 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
     fn try_from(d: (DataFlowDescriptor, Uuid)) -> Result<Self, Self::Error> {
         let (dataflow, id) = d;
 
+        // The mapping is now known: the runtime configurations must also comply with the schemas.
+        dataflow.validate_configurations()?;
+
+        // The configurations are merged before destructuring the descriptor as the mapping, the
+        // global and the runtime configurations are all needed.
+        let mut configurations = HashMap::with_capacity(
+            dataflow.operators.len() + dataflow.sources.len() + dataflow.sinks.len(),
+        );
+        for (node_id, configuration) in dataflow
+            .operators
+            .iter()
+            .map(|o| (&o.id, &o.configuration))
+            .chain(dataflow.sources.iter().map(|s| (&s.id, &s.configuration)))
+            .chain(dataflow.sinks.iter().map(|s| (&s.id, &s.configuration)))
+        {
+            configurations.insert(
+                node_id.clone(),
+                dataflow.get_node_configuration(node_id, configuration.clone())?,
+            );
+        }
+
         let DataFlowDescriptor {
             flow,
             operators,
@@ -284,8 +452,10 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
             mapping,
             deadlines,
             loops,
-            global_configuration,
             flags,
+            on_runtime_failure,
+            on_runtime_restart,
+            ..
         } = dataflow;
 
         let mapping = mapping.map_or(HashMap::new(), |m| m);
@@ -308,6 +478,8 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
             connectors: Vec::new(),
             links: Vec::new(),
             end_to_end_deadlines: deadlines,
+            on_runtime_failure,
+            on_runtime_restart,
         };
 
         for o in operators
@@ -319,13 +491,15 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
                 inputs: o.inputs,
                 outputs: o.outputs,
                 uri: o.uri,
-                configuration: merge_configurations(global_configuration.clone(), o.configuration),
+                configuration: configurations.remove(&o.id).flatten(),
                 runtime: mapping
                     .get(&o.id)
                     .ok_or(ZFError::MissingConfiguration)
                     .cloned()?,
                 deadline: o.deadline.as_ref().map(|period| period.to_duration()),
-                ciclo: None,
+                on_deadline_miss: o.on_deadline_miss,
+                executor: o.executor,
+                cicli: vec![],
             };
             dfr.operators.insert(o.id, or);
         }
@@ -339,7 +513,9 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
                 period: s.period,
                 output: s.output,
                 uri: s.uri,
-                configuration: merge_configurations(global_configuration.clone(), s.configuration),
+                configuration: configurations.remove(&s.id).flatten(),
+                deadline: s.deadline.as_ref().map(|period| period.to_duration()),
+                on_deadline_miss: s.on_deadline_miss,
                 runtime: mapping
                     .get(&s.id)
                     .ok_or(ZFError::MissingConfiguration)
@@ -356,7 +532,9 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
                 id: s.id.clone(),
                 input: s.input,
                 uri: s.uri,
-                configuration: merge_configurations(global_configuration.clone(), s.configuration),
+                configuration: configurations.remove(&s.id).flatten(),
+                deadline: s.deadline.as_ref().map(|period| period.to_duration()),
+                on_deadline_miss: s.on_deadline_miss,
                 runtime: mapping
                     .get(&s.id)
                     .ok_or(ZFError::MissingConfiguration)
@@ -386,7 +564,7 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
                     port_id: ciclo.feedback_port.clone(),
                     port_type: ciclo.port_type.clone(),
                 });
-                ingress.ciclo = Some(ciclo.clone());
+                ingress.cicli.push(ciclo.clone());
 
                 let egress = dfr
                     .operators
@@ -396,7 +574,7 @@ impl TryFrom<(DataFlowDescriptor, Uuid)> for DataFlowRecord {
                     port_id: ciclo.feedback_port.clone(),
                     port_type: ciclo.port_type.clone(),
                 });
-                egress.ciclo = Some(ciclo.clone());
+                egress.cicli.push(ciclo.clone());
 
                 links.push(LinkDescriptor {
                     from: OutputDescriptor {
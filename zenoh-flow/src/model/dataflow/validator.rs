@@ -15,10 +15,12 @@
 use crate::model::dataflow::descriptor::DataFlowDescriptor;
 use crate::model::dataflow::flag::{get_nodes_to_remove, Flag};
 use crate::model::link::PortDescriptor;
+use crate::model::loops::LoopDescriptor;
 use crate::model::{InputDescriptor, OutputDescriptor};
 use crate::types::{NodeId, ZFError, ZFResult};
 use crate::{PortId, PortType};
 use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::{EdgeFiltered, EdgeRef};
 use petgraph::Graph;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
@@ -51,7 +53,8 @@ use std::convert::TryFrom;
 ///   `node_checker`,
 /// - `map_id_to_type` maps the `(NodeId, PortId, PortKind)` to the type declared in the YAML file,
 /// - `map_id_to_graph_checker_idx` maps the `NodeId` to the indexes in `graph_checker`,
-/// - `loops_node_ids` stores the ids of the nodes involved in loops (ingress and egress).
+/// - `loops_feedback_ports` stores the feedback ports of the loops (input of the ingress and output
+///   of the egress).
 ///
 /// Additional verifications are performed calling:
 /// - `validate_ports`
@@ -66,7 +69,7 @@ pub(crate) struct DataflowValidator {
     map_id_to_node_checker_idx: HashMap<PortUniqueId, NodeIndex>,
     map_id_to_type: HashMap<PortUniqueId, PortType>,
     map_id_to_graph_checker_idx: HashMap<NodeId, (NodeKind, NodeIndex)>,
-    loops_node_ids: HashSet<NodeId>,
+    loops_feedback_ports: HashSet<PortUniqueId>,
 }
 
 /// Type of a Port, either Input or Output.
@@ -157,7 +160,7 @@ impl DataflowValidator {
             map_id_to_node_checker_idx: HashMap::new(),
             map_id_to_type: HashMap::new(),
             map_id_to_graph_checker_idx: HashMap::new(),
-            loops_node_ids: HashSet::new(),
+            loops_feedback_ports: HashSet::new(),
         }
     }
 
@@ -502,24 +505,25 @@ impl DataflowValidator {
 
     /// Validate that the Loop respects the constraints we impose.
     ///
-    /// 1. The Ingress should have only one output.
-    /// 2. The Egress should have only one input.
-    /// 3. The `feedback_port` should not already exist for the Ingress and the Egress.
-    /// 4. The Ingress should be an Operator.
-    /// 5. The Egress should be an Operator.
-    /// 6. No path should exist between the Ingress and Egress — excluding other loops.
-    /// 7. The Ingress should not be involved in another loop as Ingress or Egress.
-    /// 8. The Egress should not be involved in another loop as Ingress or Egress.
+    /// 1. The `feedback_port` should not already exist for the Ingress and the Egress.
+    /// 2. The Ingress should be an Operator.
+    /// 3. The Egress should be an Operator.
+    /// 4. No path should exist between the Egress and the Ingress — excluding other loops.
+    /// 5. An infinite Loop should not have a maximum number of iterations, a finite Loop should
+    ///    allow at least one.
+    ///
+    /// The Ingress (resp. Egress) can have several outputs (resp. inputs) and can take part in
+    /// other loops.
     ///
     ///  # Errors
     /// A variant error is returned if validation fails.
-    pub(crate) fn validate_loop(
-        &mut self,
-        ingress: &NodeId,
-        egress: &NodeId,
-        feedback_port: &PortId,
-    ) -> ZFResult<()> {
-        // Feedback port should not exist for both ingress and egress.
+    pub(crate) fn validate_loop(&mut self, ciclo: &LoopDescriptor) -> ZFResult<()> {
+        let ingress = &ciclo.ingress;
+        let egress = &ciclo.egress;
+        let feedback_port = &ciclo.feedback_port;
+
+        // Feedback port should not exist for both ingress and egress, this includes the feedback
+        // ports of the other loops.
         let ingress_port_id = PortUniqueId {
             node_id: ingress.clone(),
             port_id: feedback_port.clone(),
@@ -529,6 +533,7 @@ impl DataflowValidator {
         if self
             .map_id_to_node_checker_idx
             .contains_key(&ingress_port_id)
+            || self.loops_feedback_ports.contains(&ingress_port_id)
         {
             return Err(ZFError::InvalidData(format!(
                 "Port < {} > already exists for Ingress < {} >",
@@ -545,6 +550,7 @@ impl DataflowValidator {
         if self
             .map_id_to_node_checker_idx
             .contains_key(&egress_port_id)
+            || self.loops_feedback_ports.contains(&egress_port_id)
         {
             return Err(ZFError::InvalidData(format!(
                 "Port < {} > already exists for Egress < {} >",
@@ -552,7 +558,7 @@ impl DataflowValidator {
             )));
         }
 
-        // Ingress must be an Operator and must have a single output.
+        // Ingress must be an Operator.
         let (ingress_kind, ingress_idx) = self
             .map_id_to_graph_checker_idx
             .get(ingress)
@@ -565,19 +571,7 @@ impl DataflowValidator {
             )));
         }
 
-        if self
-            .graph_checker
-            .edges_directed(*ingress_idx, petgraph::Outgoing)
-            .count()
-            > 1
-        {
-            return Err(ZFError::InvalidData(format!(
-                "Ingress < {} > has more than one output",
-                ingress
-            )));
-        }
-
-        // Egress must be an Operator and must have a single input.
+        // Egress must be an Operator.
         let (egress_kind, egress_idx) = self
             .map_id_to_graph_checker_idx
             .get(egress)
@@ -590,43 +584,47 @@ impl DataflowValidator {
             )));
         }
 
-        if self
-            .graph_checker
-            .edges_directed(*egress_idx, petgraph::Incoming)
-            .count()
-            > 1
-        {
-            return Err(ZFError::InvalidData(format!(
-                "Egress < {} > has more than one output",
-                egress
-            )));
-        }
-
-        // No path should exist going from the Egress to the Ingress.
-        if petgraph::algo::has_path_connecting(&self.graph_checker, *egress_idx, *ingress_idx, None)
-        {
+        // No path should exist going from the Egress to the Ingress. The feedback links of the
+        // other loops are, by construction, going backward: they are ignored.
+        let without_feedback_links = EdgeFiltered::from_fn(&self.graph_checker, |edge| {
+            let (node_id, _) = &self.graph_checker[edge.source()];
+            !self.loops_feedback_ports.contains(&PortUniqueId {
+                node_id: node_id.clone(),
+                port_id: edge.weight().0.clone(),
+                kind: PortKind::Output,
+            })
+        });
+
+        if petgraph::algo::has_path_connecting(
+            &without_feedback_links,
+            *egress_idx,
+            *ingress_idx,
+            None,
+        ) {
             return Err(ZFError::InvalidData(format!(
                 "The loop between < {} > and < {} > is not backward, please create a link instead",
                 ingress, egress
             )));
         }
 
-        // We only authorize a single loop per Ingress / Egress.
-        if !self.loops_node_ids.insert(ingress.clone()) {
-            return Err(ZFError::InvalidData(format!(
-                "Ingress < {} > is already used in another loop",
-                ingress
-            )));
+        match (ciclo.is_infinite, ciclo.max_iterations) {
+            (true, Some(_)) => {
+                return Err(ZFError::InvalidData(format!(
+                    "The loop between < {} > and < {} > is infinite, it cannot have a maximum number of iterations",
+                    ingress, egress
+                )))
+            }
+            (false, Some(0)) => {
+                return Err(ZFError::InvalidData(format!(
+                    "The loop between < {} > and < {} > must allow at least one iteration",
+                    ingress, egress
+                )))
+            }
+            _ => (),
         }
 
-        if !self.loops_node_ids.insert(egress.clone()) {
-            // Remove the ingress from the HashSet as we are about to return an Error.
-            self.loops_node_ids.remove(ingress);
-            return Err(ZFError::InvalidData(format!(
-                "Egress < {} > is already used in another loop",
-                egress
-            )));
-        }
+        self.loops_feedback_ports.insert(ingress_port_id);
+        self.loops_feedback_ports.insert(egress_port_id);
 
         Ok(())
     }
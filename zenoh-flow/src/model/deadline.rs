@@ -14,7 +14,7 @@
 
 use crate::model::{InputDescriptor, OutputDescriptor};
 use crate::runtime::deadline::E2EDeadline;
-use crate::DurationDescriptor;
+use crate::{DurationDescriptor, PortId};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -35,13 +35,57 @@ use std::time::Duration;
 /// duration:
 ///    length: 250
 ///     unit: ms
+/// on_miss:
+///    fallback: LateFaces
 /// ```
 ///
+/// `on_miss` is optional, see [`DeadlineMissPolicy`](`DeadlineMissPolicy`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct E2EDeadlineDescriptor {
     pub(crate) from: OutputDescriptor,
     pub(crate) to: InputDescriptor,
     pub(crate) duration: DurationDescriptor,
+    #[serde(default)]
+    pub(crate) on_miss: Option<DeadlineMissPolicy>,
+}
+
+/// The action the runtime takes when a deadline is missed.
+///
+/// Whatever the policy, the miss is logged and counted.
+///
+/// - `log`: the miss is attached to the message that is then processed as usual (default),
+/// - `drop`: the message is discarded, the node does not process it,
+/// - `fallback`: the message is not processed and is directly forwarded to the given output of
+///   the node (end-to-end deadlines only),
+/// - `callback`: the method `on_deadline_miss` of the node is called, then the message is
+///   processed as usual,
+/// - `escalate`: an event is published for the instance, then the message is processed as usual.
+///
+/// Example:
+///
+/// ```yaml
+/// on_miss: drop
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadlineMissPolicy {
+    Log,
+    Drop,
+    Fallback(PortId),
+    Callback,
+    Escalate,
+}
+
+impl std::fmt::Display for DeadlineMissPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeadlineMissPolicy::Log => write!(f, "log"),
+            DeadlineMissPolicy::Drop => write!(f, "drop"),
+            DeadlineMissPolicy::Fallback(output) => write!(f, "fallback({})", output),
+            DeadlineMissPolicy::Callback => write!(f, "callback"),
+            DeadlineMissPolicy::Escalate => write!(f, "escalate"),
+        }
+    }
 }
 
 /// An `E2EDeadlineRecord` is an instance of an [`E2EDeadlineDescriptor`](`E2EDeadlineDescriptor`)
@@ -50,6 +94,8 @@ pub struct E2EDeadlineRecord {
     pub(crate) from: OutputDescriptor,
     pub(crate) to: InputDescriptor,
     pub(crate) duration: Duration,
+    #[serde(default)]
+    pub(crate) on_miss: Option<DeadlineMissPolicy>,
 }
 
 impl From<E2EDeadlineDescriptor> for E2EDeadlineRecord {
@@ -58,6 +104,7 @@ impl From<E2EDeadlineDescriptor> for E2EDeadlineRecord {
             from: desc.from,
             to: desc.to,
             duration: desc.duration.to_duration(),
+            on_miss: desc.on_miss,
         }
     }
 }
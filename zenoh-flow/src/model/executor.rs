@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{NodeId, ZFError, ZFResult};
+use serde::{Deserialize, Serialize};
+
+/// The number of CPUs a CPU affinity can designate, i.e. the size of a `cpu_set_t`.
+#[cfg(target_os = "linux")]
+pub const MAX_CPUS: usize = libc::CPU_SETSIZE as usize;
+/// The number of CPUs a CPU affinity can designate, i.e. the size of a `cpu_set_t`.
+#[cfg(not(target_os = "linux"))]
+pub const MAX_CPUS: usize = 1024;
+
+/// Describes where the `run` of an Operator is executed.
+///
+/// By default, all the nodes of a runtime share the same asynchronous executor. As the `run` of an
+/// Operator is synchronous, an expensive computation blocks one of the workers of this executor
+/// and delays the other nodes. For such Operators, another executor can be chosen:
+///
+/// - `shared` (default): the asynchronous executor shared by all the nodes,
+/// - `blocking`: a pool of threads dedicated to blocking operations,
+/// - `dedicated`: an OS thread reserved for the Operator. On Linux, the thread can optionally be
+///   pinned to a set of CPUs (`cpu_affinity`) and given a real-time (`SCHED_FIFO`) `priority`,
+///   between 1 and 99 — which usually requires the `CAP_SYS_NICE` capability.
+///
+/// Example:
+///
+/// ```yaml
+/// executor: blocking
+/// ```
+///
+/// ```yaml
+/// executor:
+///   dedicated:
+///     cpu_affinity: [2, 3]
+///     priority: 80
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutorDescriptor {
+    Shared,
+    Blocking,
+    Dedicated {
+        cpu_affinity: Option<Vec<usize>>,
+        priority: Option<i32>,
+    },
+}
+
+impl std::fmt::Display for ExecutorDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Shared => write!(f, "shared"),
+            Self::Blocking => write!(f, "blocking"),
+            Self::Dedicated { .. } => write!(f, "dedicated"),
+        }
+    }
+}
+
+impl ExecutorDescriptor {
+    /// Checks that the executor of the Operator `node_id` can be created: the priority must be
+    /// between 1 and 99 and the CPUs of the affinity lower than [`MAX_CPUS`](`MAX_CPUS`).
+    ///
+    /// The CPUs are not checked against the ones of the machine, as the Operator may run on
+    /// another one.
+    ///
+    /// # Errors
+    /// A `ConfigurationError` variant is returned if the priority or a CPU is invalid.
+    pub fn validate(&self, node_id: &NodeId) -> ZFResult<()> {
+        if let Self::Dedicated {
+            cpu_affinity,
+            priority,
+        } = self
+        {
+            if let Some(priority) = priority {
+                if !(1..=99).contains(priority) {
+                    return Err(ZFError::ConfigurationError(format!(
+                        "[Operator: {}] Invalid real-time priority < {} >, expected a value \
+                         between 1 and 99",
+                        node_id, priority
+                    )));
+                }
+            }
+
+            if let Some(cpu) = cpu_affinity.iter().flatten().find(|cpu| **cpu >= MAX_CPUS) {
+                return Err(ZFError::ConfigurationError(format!(
+                    "[Operator: {}] Invalid CPU < {} >, expected a value lower than {}",
+                    node_id, cpu, MAX_CPUS
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
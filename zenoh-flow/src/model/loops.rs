@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::{NodeId, PortId, PortType};
+use crate::{DurationDescriptor, NodeId, PortId, PortType};
 use serde::{Deserialize, Serialize};
 
 /// A LoopDescriptor is the internal representation of a Loop in Zenoh Flow.
@@ -28,13 +28,36 @@ use serde::{Deserialize, Serialize};
 /// raised if an input (resp. output) port with the same name already exists for the Ingress (resp.
 /// Egress).
 ///
+/// A finite Loop can be bounded:
+/// - `max_iterations` is the maximum number of times the body of the Loop is executed,
+/// - `iteration_timeout` is the maximum duration of a single iteration.
+///
+/// Both are enforced by the Egress: once a bound is reached, the output sent on the
+/// `feedback_port` is discarded and the Loop ends. The other outputs of the Egress are not
+/// affected. The `run` of the Egress is raced against the time left for the iteration: if the
+/// `iteration_timeout` expires first, the Loop ends once the `run` returns.
+///
+/// ```yaml
+/// loops:
+///   - ingress: Refine-Start
+///     egress: Refine-End
+///     feedback_port: refine
+///     port_type: image
+///     is_infinite: false
+///     max_iterations: 5
+///     iteration_timeout:
+///       length: 100
+///       unit: ms
+/// ```
+///
 /// The Ingress and Egress must respect the following constraints:
-/// - the Ingress only has a single output,
-/// - the Egress only has a single input,
 /// - they both must be an Operator (not Source nor Sink),
-/// - they must be Ingress or Egress for a single Loop,
 /// - the Ingress must be "before" the Egress (i.e. without the Loop, there should be no path
-///   between both).
+///   between both),
+/// - an infinite Loop cannot have a `max_iterations`.
+///
+/// An Operator can take part in several loops, e.g. when loops are nested, as long as their
+/// `feedback_port` are different.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopDescriptor {
     pub ingress: NodeId,
@@ -42,4 +65,8 @@ pub struct LoopDescriptor {
     pub feedback_port: PortId,
     pub is_infinite: bool,
     pub port_type: PortType,
+    #[serde(default)]
+    pub max_iterations: Option<u64>,
+    #[serde(default)]
+    pub iteration_timeout: Option<DurationDescriptor>,
 }
@@ -15,9 +15,11 @@
 pub mod connector;
 pub mod dataflow;
 pub mod deadline;
+pub mod executor;
 pub mod link;
 pub mod loops;
 pub mod node;
+pub(crate) mod schema;
 
 use crate::model::link::PortDescriptor;
 use crate::serde::{Deserialize, Serialize};
@@ -12,6 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::model::deadline::DeadlineMissPolicy;
+use crate::model::executor::ExecutorDescriptor;
 use crate::model::link::PortDescriptor;
 use crate::model::loops::LoopDescriptor;
 use crate::types::{Configuration, NodeId, RuntimeId};
@@ -31,17 +33,37 @@ use std::time::Duration;
 /// uri: file://./target/release/libgeneric_sink.so
 /// configuration:
 ///   file: /tmp/generic-sink.txt
+/// schema:
+///   type: object
+///   properties:
+///     file:
+///       type: string
+///   additionalProperties: false
 /// input:
 ///   id: Data
 ///   type: usize
+/// deadline:
+///   length: 50
+///   unit: ms
+/// on_deadline_miss: escalate
 /// ```
 ///
+/// The optional `schema` is checked against the configuration of the node (after it was merged
+/// with the global and runtime configurations) when the descriptor is validated.
+///
+/// The optional `deadline` applies to each execution of `run`, the optional `on_deadline_miss`
+/// is the policy applied when it is missed, see [`DeadlineMissPolicy`](`DeadlineMissPolicy`).
+/// As the data was already consumed, `drop` has the same effect as `log` and `fallback` is not
+/// supported.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SinkDescriptor {
     pub id: NodeId,
     pub input: PortDescriptor,
     pub uri: Option<String>,
     pub configuration: Option<Configuration>,
+    pub schema: Option<Configuration>,
+    pub deadline: Option<DurationDescriptor>,
+    pub on_deadline_miss: Option<DeadlineMissPolicy>,
     pub runtime: Option<RuntimeId>, // to be removed
 }
 
@@ -61,11 +83,26 @@ impl std::fmt::Display for SinkDescriptor {
 /// uri: file://./target/release/libcounter_source.so
 /// configuration:
 ///   start: 10
+/// schema:
+///   type: object
+///   properties:
+///     start:
+///       type: integer
 /// output:
 ///   id: Counter
 ///   type: usize
+/// deadline:
+///   length: 5
+///   unit: ms
+/// on_deadline_miss: drop
 /// ```
 ///
+/// The optional `schema` is checked against the configuration of the node (after it was merged
+/// with the global and runtime configurations) when the descriptor is validated.
+///
+/// The optional `deadline` applies to each execution of `run`, the optional `on_deadline_miss`
+/// is the policy applied when it is missed, see [`DeadlineMissPolicy`](`DeadlineMissPolicy`).
+/// With `drop`, the late output is not sent; `fallback` is not supported.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SourceDescriptor {
     pub id: NodeId,
@@ -73,6 +110,9 @@ pub struct SourceDescriptor {
     pub period: Option<DurationDescriptor>,
     pub uri: Option<String>,
     pub configuration: Option<Configuration>,
+    pub schema: Option<Configuration>,
+    pub deadline: Option<DurationDescriptor>,
+    pub on_deadline_miss: Option<DeadlineMissPolicy>,
     pub runtime: Option<RuntimeId>, // to be removed
 }
 
@@ -92,14 +132,34 @@ impl std::fmt::Display for SourceDescriptor {
 /// uri: file://./target/release/libmy_op.so
 /// configuration:
 ///   by: 10
+/// schema:
+///   type: object
+///   properties:
+///     by:
+///       type: number
+///   required: [by]
 /// inputs:
 ///     - id: Number
 ///       type: usize
 /// outputs:
 ///   - id: Multiplied
 ///     type: usize
+/// deadline:
+///   length: 10
+///   unit: ms
+/// on_deadline_miss: drop
+/// executor: blocking
 /// ```
 ///
+/// The optional `on_deadline_miss` is the policy applied when the local `deadline` is missed, see
+/// [`DeadlineMissPolicy`](`DeadlineMissPolicy`). The `fallback` policy is not supported for local
+/// deadlines.
+///
+/// The optional `executor` indicates where the `run` is executed, see
+/// [`ExecutorDescriptor`](`ExecutorDescriptor`).
+///
+/// The optional `schema` is checked against the configuration of the node (after it was merged
+/// with the global and runtime configurations) when the descriptor is validated.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OperatorDescriptor {
     pub id: NodeId,
@@ -107,7 +167,10 @@ pub struct OperatorDescriptor {
     pub outputs: Vec<PortDescriptor>,
     pub uri: Option<String>,
     pub configuration: Option<Configuration>,
+    pub schema: Option<Configuration>,
     pub deadline: Option<DurationDescriptor>,
+    pub on_deadline_miss: Option<DeadlineMissPolicy>,
+    pub executor: Option<ExecutorDescriptor>,
     pub runtime: Option<RuntimeId>, // to be removed
 }
 
@@ -126,6 +189,8 @@ pub struct SinkRecord {
     pub input: PortDescriptor,
     pub uri: Option<String>,
     pub configuration: Option<Configuration>,
+    pub deadline: Option<Duration>,
+    pub on_deadline_miss: Option<DeadlineMissPolicy>,
     pub runtime: RuntimeId,
 }
 
@@ -154,6 +219,8 @@ pub struct SourceRecord {
     pub period: Option<DurationDescriptor>,
     pub uri: Option<String>,
     pub configuration: Option<Configuration>,
+    pub deadline: Option<Duration>,
+    pub on_deadline_miss: Option<DeadlineMissPolicy>,
     pub runtime: RuntimeId,
 }
 
@@ -183,9 +250,39 @@ pub struct OperatorRecord {
     pub(crate) uri: Option<String>,
     pub(crate) configuration: Option<Configuration>,
     pub(crate) deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
+    pub(crate) executor: Option<ExecutorDescriptor>,
     pub(crate) runtime: RuntimeId,
-    // Ciclo is the italian word for "loop" — we cannot use "loop" as it’s a reserved keyword.
-    pub(crate) ciclo: Option<LoopDescriptor>,
+    // Cicli is the italian word for "loops" — we cannot use "loop" as it’s a reserved keyword.
+    // The records stored by previous versions have a single, optional, `ciclo`.
+    #[serde(alias = "ciclo", default, deserialize_with = "deserialize_cicli")]
+    pub(crate) cicli: Vec<LoopDescriptor>,
+}
+
+/// The loops of an operator: a list or, as stored by previous versions, at most one loop.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Cicli {
+    Many(Vec<LoopDescriptor>),
+    One(Option<LoopDescriptor>),
+}
+
+/// Deserializes the loops of an operator, accepting the single `ciclo` of previous versions.
+///
+/// Only the self-describing formats (e.g. JSON or YAML) can tell both apart: the other formats,
+/// such as bincode, always read a list.
+fn deserialize_cicli<'de, D>(deserializer: D) -> Result<Vec<LoopDescriptor>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Vec::deserialize(deserializer);
+    }
+
+    Ok(match Cicli::deserialize(deserializer)? {
+        Cicli::Many(cicli) => cicli,
+        Cicli::One(ciclo) => ciclo.into_iter().collect(),
+    })
 }
 
 impl std::fmt::Display for OperatorRecord {
@@ -0,0 +1,205 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::types::{Configuration, NodeId, ZFError, ZFResult};
+
+/// Checks that the `configuration` of the node `node_id` complies with the provided `schema`.
+///
+/// The schema follows the [JSON Schema](https://json-schema.org) syntax, of which the following
+/// keywords are supported:
+/// - `type` (a single type or an array of types),
+/// - `enum`,
+/// - `minimum` and `maximum`,
+/// - `properties`, `required` and `additionalProperties` (boolean or schema),
+/// - `items`.
+///
+/// Any other keyword is ignored.
+///
+/// Example:
+///
+/// ```yaml
+/// schema:
+///   type: object
+///   properties:
+///     file:
+///       type: string
+///   required: [file]
+///   additionalProperties: false
+/// ```
+///
+/// Setting `additionalProperties` to `false` is the way to detect misspelled keys.
+///
+/// A missing configuration is checked as an empty object.
+///
+///  # Errors
+/// A `ConfigurationError` is returned, indicating the path of the first offending value.
+pub(crate) fn validate_configuration(
+    node_id: &NodeId,
+    schema: &Configuration,
+    configuration: Option<&Configuration>,
+) -> ZFResult<()> {
+    let empty = Configuration::Object(serde_json::Map::new());
+    let configuration = configuration.unwrap_or(&empty);
+
+    validate_value(schema, configuration, "configuration")
+        .map_err(|e| ZFError::ConfigurationError(format!("Node < {} >: {}", node_id, e)))
+}
+
+fn validate_value(schema: &Configuration, value: &Configuration, path: &str) -> Result<(), String> {
+    let schema = match schema {
+        Configuration::Bool(true) => return Ok(()),
+        Configuration::Bool(false) => return Err(format!("{}: no value is allowed", path)),
+        Configuration::Object(schema) => schema,
+        _ => return Err(format!("{}: invalid schema {}", path, schema)),
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Configuration::String(expected) => is_of_type(expected, value),
+            Configuration::Array(expected) => expected
+                .iter()
+                .filter_map(|t| t.as_str())
+                .any(|t| is_of_type(t, value)),
+            _ => return Err(format!("{}: invalid `type` in schema {}", path, expected)),
+        };
+
+        if !matches {
+            return Err(format!(
+                "{}: expected type {}, found {}",
+                path, expected, value
+            ));
+        }
+    }
+
+    if let Some(Configuration::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{}: {} is not one of {}",
+                path,
+                value,
+                Configuration::Array(allowed.clone())
+            ));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(|m| m.as_f64()) {
+            if number < minimum {
+                return Err(format!("{}: {} is lower than {}", path, number, minimum));
+            }
+        }
+
+        if let Some(maximum) = schema.get("maximum").and_then(|m| m.as_f64()) {
+            if number > maximum {
+                return Err(format!("{}: {} is greater than {}", path, number, maximum));
+            }
+        }
+    }
+
+    if let Configuration::Object(object) = value {
+        if let Some(Configuration::Array(required)) = schema.get("required") {
+            if let Some(missing) = required
+                .iter()
+                .filter_map(|key| key.as_str())
+                .find(|key| !object.contains_key(*key))
+            {
+                return Err(format!("{}: missing required key `{}`", path, missing));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (key, inner) in object {
+            let inner_path = format!("{}.{}", path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(inner_schema) => validate_value(inner_schema, inner, &inner_path)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Configuration::Bool(false)) => {
+                        return Err(format!("{}: unknown key `{}`", path, key))
+                    }
+                    Some(additional) => validate_value(additional, inner, &inner_path)?,
+                    None => (),
+                },
+            }
+        }
+    }
+
+    if let (Configuration::Array(array), Some(items)) = (value, schema.get("items")) {
+        for (index, inner) in array.iter().enumerate() {
+            validate_value(items, inner, &format!("{}[{}]", path, index))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_of_type(expected: &str, value: &Configuration) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "string" => value.is_string(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_configuration;
+    use crate::ZFError;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_configuration() {
+        let node_id = "node".into();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "file": { "type": "string" },
+                "rate": { "type": "integer", "minimum": 1, "maximum": 1000 },
+                "mode": { "enum": ["fast", "slow"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+            },
+            "required": ["file"],
+            "additionalProperties": false,
+        });
+
+        assert!(validate_configuration(
+            &node_id,
+            &schema,
+            Some(&json!({ "file": "/tmp/out.txt", "rate": 10, "mode": "fast", "tags": ["a"] }))
+        )
+        .is_ok());
+
+        let ko = [
+            json!({ "rate": 10 }),
+            json!({ "file": 1 }),
+            json!({ "file": "/tmp/out.txt", "rate": 0 }),
+            json!({ "file": "/tmp/out.txt", "mode": "medium" }),
+            json!({ "file": "/tmp/out.txt", "tags": [1] }),
+            json!({ "file": "/tmp/out.txt", "rtae": 10 }),
+        ];
+
+        for configuration in ko.iter() {
+            assert!(matches!(
+                validate_configuration(&node_id, &schema, Some(configuration)),
+                Err(ZFError::ConfigurationError(_))
+            ));
+        }
+
+        assert!(validate_configuration(&node_id, &schema, None).is_err());
+    }
+}
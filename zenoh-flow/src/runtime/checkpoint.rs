@@ -0,0 +1,86 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{DurationDescriptor, FlowId, NodeId, PortId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use uhlc::Timestamp;
+use uuid::Uuid;
+
+/// For how long taking or restoring a checkpoint waits for a node to release its state.
+///
+/// A node holds its state while it executes a callback: a source waiting for data inside its
+/// `run` is only checkpointed once it produces it.
+pub const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The configuration of the checkpoints of a runtime.
+///
+/// Every `period`, if set, the states of the nodes of all the instances that support
+/// checkpointing are saved. When `restore` is set, the nodes of an instance are restored from the
+/// latest checkpoints of the instance when it is created on a runtime, e.g. when its nodes are
+/// re-mapped after a runtime failure, and a node restarted by the watchdog is restored from its
+/// latest checkpoint.
+///
+/// Example:
+///
+/// ```yaml
+/// checkpoint:
+///   period:
+///     length: 30
+///     unit: s
+///   restore: true
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckpointConfig {
+    #[serde(default)]
+    pub period: Option<DurationDescriptor>,
+    #[serde(default)]
+    pub restore: bool,
+}
+
+/// The serialized state of a node, as kept in the
+/// [`DataStore`](`crate::runtime::resources::DataStore`).
+///
+/// Checkpoints are stored per instance and node, only the latest checkpoint
+/// of a node is kept. An instance keeps its identifier when its nodes are
+/// migrated or re-mapped to other runtimes: they find their checkpoints there,
+/// while the other instances of the same flow do not interfere.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeCheckpoint {
+    pub flow_id: FlowId,
+    pub instance_id: Uuid,
+    pub node_id: NodeId,
+    pub runtime_id: Uuid,
+    pub timestamp: Timestamp,
+    pub state: Vec<u8>,
+}
+
+/// The timestamps of the last messages a node received on each of its inputs before it was
+/// removed from a runtime, one per sender, as kept in the
+/// [`DataStore`](`crate::runtime::resources::DataStore`).
+///
+/// The senders are told apart by the ID of the timestamps of their messages: the timestamps of
+/// different senders are not compared.
+///
+/// When a node is migrated, its receivers on the new runtime subscribe before the old ones are
+/// removed: the messages that the node already received on the old runtime are skipped on the
+/// new one, and only once.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputWatermarks {
+    pub flow_id: FlowId,
+    pub instance_id: Uuid,
+    pub node_id: NodeId,
+    pub inputs: HashMap<PortId, Vec<Timestamp>>,
+}
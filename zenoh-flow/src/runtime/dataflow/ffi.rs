@@ -0,0 +1,1665 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Stable C ABI of the nodes.
+//!
+//! The declarations exported with [`export_operator`](`crate::export_operator`),
+//! [`export_source`](`crate::export_source`) and [`export_sink`](`crate::export_sink`) hold Rust
+//! trait objects. As the Rust ABI is not stable, such a library is only loaded if it was built
+//! with the same versions of the Rust compiler and of Zenoh Flow as the runtime.
+//!
+//! A library can instead export a declaration made of `extern "C"` functions, for instance with
+//! [`export_c_operator`](`crate::export_c_operator`), [`export_c_source`](`crate::export_c_source`)
+//! or [`export_c_sink`](`crate::export_c_sink`). Such a node keeps working with a runtime built
+//! with another toolchain, as long as both implement compatible versions of the interface.
+//!
+//! # Declarations
+//!
+//! The library exports `zfoperator_c_declaration`, `zfsource_c_declaration` or
+//! `zfsink_c_declaration`: respectively a [`FfiOperatorDeclaration`], a [`FfiSourceDeclaration`]
+//! or a [`FfiSinkDeclaration`]. Every declaration starts with a [`FfiDeclarationHeader`]: the
+//! [`AbiVersion`] it implements, which is checked before anything else is read, and the size of
+//! the declaration. The node is loaded if its major version is the one of the runtime,
+//! [`ABI_VERSION`], and if its minor version is not greater.
+//!
+//! A new minor version can only append fields to the declarations, and these fields must be
+//! nullable. The runtime only reads the `size` bytes declared by the library: the fields the
+//! library does not know are null.
+//!
+//! The `checkpoints` of a declaration are optional: a node that does not provide them cannot be
+//! checkpointed.
+//!
+//! # Calling convention
+//!
+//! - The functions return `0` on success. Otherwise, they can describe the error, in UTF-8, in
+//!   the `error` buffer.
+//! - The buffers produced by the library ([`FfiBuffer`]) are freed by the runtime through the
+//!   `free_buffer` function of the library. The bytes lent to the library ([`FfiSlice`]) are only
+//!   valid for the duration of the call.
+//! - `initialize` receives the configuration of the node serialized as JSON (empty if the node
+//!   has no configuration) and creates the state of the node: an opaque pointer, passed to the
+//!   other functions, that `finalize` releases. The functions are never called concurrently for
+//!   the same state, but they can be called from different threads.
+//! - `mode` points to the mode of the [`Context`](`Context`), which the node can update.
+//! - The `run` functions of the sources and of the sinks are called from a thread that can block.
+//!   They must nevertheless return periodically: the node cannot be stopped, checkpointed or
+//!   finalized while they are running.
+//!
+//! # Encodings
+//!
+//! The integers are little-endian. A list of ports is encoded as its number of entries (`u32`)
+//! followed by the entries, each entry being the identifier of the port (UTF-8) and its bytes,
+//! both preceded by their length (`u32`):
+//!
+//! ```text
+//! count | id_len | id | data_len | data | id_len | id | data_len | data | ...
+//! ```
+//!
+//! A message is its timestamp, i.e. its time (`u64`, in the NTP64 format) and the identifier of
+//! its clock preceded by its length (`u32`), followed by its data:
+//!
+//! ```text
+//! time | clock_len | clock | data
+//! ```
+//!
+//! The inputs of an operator and the input of a sink are messages. An input token is either `0`,
+//! if it is pending, or `1` followed by its action and its message. An action is a single byte:
+//! `0` to consume, `1` to drop and `2` to keep the data. The outputs are lists of data.
+//!
+//! The end-to-end deadlines and the loop contexts of the messages do not cross the interface, and
+//! an operator cannot send control messages.
+
+use crate::runtime::dataflow::loader::{OperatorRegisterFn, SinkRegisterFn, SourceRegisterFn};
+use crate::runtime::message::DataMessage;
+use crate::runtime::token::{DataToken, TokenAction};
+use crate::{
+    Configuration, Context, Data, InputToken, LocalDeadlineMiss, Node, NodeOutput, Operator,
+    PortId, Sink, Source, State, ZFError, ZFResult, ZFState,
+};
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::os::raw::c_void;
+use std::time::{Duration, Instant};
+use uhlc::{Timestamp, ID, NTP64};
+
+/// The version of the C ABI implemented by the runtime.
+pub static ABI_VERSION: AbiVersion = AbiVersion { major: 1, minor: 0 };
+
+const TOKEN_PENDING: u8 = 0;
+const TOKEN_READY: u8 = 1;
+
+const ACTION_CONSUME: u8 = 0;
+const ACTION_DROP: u8 = 1;
+const ACTION_KEEP: u8 = 2;
+
+/// A version of the C ABI.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl AbiVersion {
+    /// Returns `true` if a library implementing this version can be loaded by a runtime
+    /// implementing `runtime`.
+    pub fn is_compatible_with(&self, runtime: &AbiVersion) -> bool {
+        self.major == runtime.major && self.minor <= runtime.minor
+    }
+
+    /// Checks that a library implementing this version can be loaded by the runtime.
+    ///
+    /// # Errors
+    /// `VersionMismatch` is returned if the versions are not compatible.
+    pub(crate) fn negotiate(&self) -> ZFResult<()> {
+        if !self.is_compatible_with(&ABI_VERSION) {
+            log::error!(
+                "The node implements the version {} of the C ABI, the runtime implements the \
+                 version {}",
+                self,
+                ABI_VERSION
+            );
+            return Err(ZFError::VersionMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for AbiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Bytes lent to the library for the duration of a call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiSlice {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+impl FfiSlice {
+    fn new(bytes: &[u8]) -> Self {
+        Self {
+            ptr: bytes.as_ptr(),
+            len: bytes.len(),
+        }
+    }
+
+    /// # Safety
+    /// The slice must designate `len` readable bytes, or have a null `ptr`.
+    unsafe fn as_slice<'a>(&self) -> &'a [u8] {
+        if self.ptr.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.ptr, self.len)
+        }
+    }
+}
+
+/// Bytes produced by the library, freed through its `free_buffer` function.
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl FfiBuffer {
+    fn empty() -> Self {
+        Self {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = std::mem::ManuallyDrop::new(bytes);
+        Self {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            capacity: bytes.capacity(),
+        }
+    }
+
+    /// # Safety
+    /// The buffer must be empty or come from `from_vec`, in the same library.
+    unsafe fn into_vec(self) -> Vec<u8> {
+        if self.ptr.is_null() {
+            Vec::new()
+        } else {
+            Vec::from_raw_parts(self.ptr, self.len, self.capacity)
+        }
+    }
+
+    /// # Safety
+    /// The buffer must designate `len` readable bytes, or have a null `ptr`.
+    unsafe fn as_slice(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.ptr, self.len)
+        }
+    }
+}
+
+/// A [`LocalDeadlineMiss`](`LocalDeadlineMiss`), as provided to the output rule of an operator.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiDeadlineMiss {
+    pub deadline_ns: u64,
+    pub elapsed_ns: u64,
+}
+
+impl From<&LocalDeadlineMiss> for FfiDeadlineMiss {
+    fn from(miss: &LocalDeadlineMiss) -> Self {
+        let nanos = |duration: Duration| u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        Self {
+            deadline_ns: nanos(miss.deadline),
+            elapsed_ns: nanos(miss.elapsed),
+        }
+    }
+}
+
+impl From<&FfiDeadlineMiss> for LocalDeadlineMiss {
+    fn from(miss: &FfiDeadlineMiss) -> Self {
+        let now = Instant::now();
+        let elapsed = Duration::from_nanos(miss.elapsed_ns);
+        Self {
+            start: now.checked_sub(elapsed).unwrap_or(now),
+            deadline: Duration::from_nanos(miss.deadline_ns),
+            elapsed,
+        }
+    }
+}
+
+/// `initialize(configuration, state, error)` creates the state of the node.
+pub type FfiInitializeFn = unsafe extern "C" fn(FfiSlice, *mut *mut c_void, *mut FfiBuffer) -> i32;
+
+/// `finalize(state, error)` releases the state of the node, even if it fails.
+pub type FfiFinalizeFn = unsafe extern "C" fn(*mut c_void, *mut FfiBuffer) -> i32;
+
+/// `free_buffer(buffer)` frees a buffer produced by the library.
+pub type FfiFreeBufferFn = unsafe extern "C" fn(FfiBuffer);
+
+/// `input_rule(state, mode, tokens, ready, actions, error)` receives the list of input tokens,
+/// tells if the operator is ready and produces the list of the actions of the ready tokens.
+pub type FfiInputRuleFn = unsafe extern "C" fn(
+    *mut c_void,
+    *mut usize,
+    FfiSlice,
+    *mut bool,
+    *mut FfiBuffer,
+    *mut FfiBuffer,
+) -> i32;
+
+/// `run(state, mode, inputs, outputs, error)` receives the list of input messages and produces
+/// the list of outputs.
+pub type FfiOperatorRunFn =
+    unsafe extern "C" fn(*mut c_void, *mut usize, FfiSlice, *mut FfiBuffer, *mut FfiBuffer) -> i32;
+
+/// `output_rule(state, mode, outputs, deadline_miss, results, error)` receives the list of outputs
+/// and the deadline miss, null if the deadline was not missed, and produces the list of outputs
+/// to send.
+pub type FfiOutputRuleFn = unsafe extern "C" fn(
+    *mut c_void,
+    *mut usize,
+    FfiSlice,
+    *const FfiDeadlineMiss,
+    *mut FfiBuffer,
+    *mut FfiBuffer,
+) -> i32;
+
+/// `run(state, mode, data, error)` produces the bytes of the data.
+pub type FfiSourceRunFn =
+    unsafe extern "C" fn(*mut c_void, *mut usize, *mut FfiBuffer, *mut FfiBuffer) -> i32;
+
+/// `run(state, mode, message, error)` receives the input message.
+pub type FfiSinkRunFn =
+    unsafe extern "C" fn(*mut c_void, *mut usize, FfiSlice, *mut FfiBuffer) -> i32;
+
+/// `checkpoint(state, supported, checkpoint, error)` serializes the state of the node, or sets
+/// `supported` to `false` if the state cannot be checkpointed.
+pub type FfiCheckpointFn =
+    unsafe extern "C" fn(*mut c_void, *mut bool, *mut FfiBuffer, *mut FfiBuffer) -> i32;
+
+/// `restore(state, checkpoint, error)` restores the state of the node from a checkpoint.
+pub type FfiRestoreFn = unsafe extern "C" fn(*mut c_void, FfiSlice, *mut FfiBuffer) -> i32;
+
+/// The header of every declaration.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FfiDeclarationHeader {
+    pub abi_version: AbiVersion,
+    /// The size of the whole declaration, in bytes.
+    pub size: usize,
+}
+
+impl FfiDeclarationHeader {
+    /// The header of a declaration `D` implementing the version of the runtime.
+    pub const fn new<D>() -> Self {
+        Self {
+            abi_version: ABI_VERSION,
+            size: std::mem::size_of::<D>(),
+        }
+    }
+}
+
+/// The functions shared by all the kinds of nodes.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiNodeVTable {
+    pub initialize: FfiInitializeFn,
+    pub finalize: FfiFinalizeFn,
+    pub free_buffer: FfiFreeBufferFn,
+}
+
+/// The optional functions checkpointing the state of a node.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiCheckpointVTable {
+    pub checkpoint: Option<FfiCheckpointFn>,
+    pub restore: Option<FfiRestoreFn>,
+}
+
+/// Operator declaration exported, as `zfoperator_c_declaration`, by a library implementing the
+/// C ABI.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiOperatorDeclaration {
+    pub header: FfiDeclarationHeader,
+    pub node: FfiNodeVTable,
+    pub input_rule: FfiInputRuleFn,
+    pub run: FfiOperatorRunFn,
+    pub output_rule: FfiOutputRuleFn,
+    pub checkpoints: FfiCheckpointVTable,
+}
+
+/// Source declaration exported, as `zfsource_c_declaration`, by a library implementing the C ABI.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiSourceDeclaration {
+    pub header: FfiDeclarationHeader,
+    pub node: FfiNodeVTable,
+    pub run: FfiSourceRunFn,
+    pub checkpoints: FfiCheckpointVTable,
+}
+
+/// Sink declaration exported, as `zfsink_c_declaration`, by a library implementing the C ABI.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiSinkDeclaration {
+    pub header: FfiDeclarationHeader,
+    pub node: FfiNodeVTable,
+    pub run: FfiSinkRunFn,
+    pub checkpoints: FfiCheckpointVTable,
+}
+
+/// A declaration of the C ABI, read by the loader.
+///
+/// # Safety
+/// The type must start with a [`FfiDeclarationHeader`], and the bytes following its first
+/// `required_size()` bytes must be valid when they are all zeroes.
+pub(crate) unsafe trait FfiDeclaration: Copy {
+    /// The number of bytes that every compatible library declares.
+    fn required_size() -> usize;
+}
+
+// SAFETY: the header has no optional field.
+unsafe impl FfiDeclaration for FfiDeclarationHeader {
+    fn required_size() -> usize {
+        std::mem::size_of::<Self>()
+    }
+}
+
+/// Implements `FfiDeclaration` for a declaration whose fields are all required up to `$optional`.
+macro_rules! ffi_declaration {
+    ($declaration:ty, $optional:ident) => {
+        // SAFETY: the fields from `$optional` onwards are nullable.
+        unsafe impl FfiDeclaration for $declaration {
+            fn required_size() -> usize {
+                let declaration = std::mem::MaybeUninit::<Self>::uninit();
+                let base = declaration.as_ptr();
+                // SAFETY: only the address of the field is computed.
+                let field = unsafe { std::ptr::addr_of!((*base).$optional) };
+                field as usize - base as usize
+            }
+        }
+    };
+}
+
+ffi_declaration!(FfiOperatorDeclaration, checkpoints);
+ffi_declaration!(FfiSourceDeclaration, checkpoints);
+ffi_declaration!(FfiSinkDeclaration, checkpoints);
+
+/// The pointer created by the library.
+struct StatePtr(*mut c_void);
+
+// SAFETY: the C ABI requires the state to be usable from any thread, and the runtime only uses it
+// behind a lock.
+unsafe impl Send for StatePtr {}
+
+/// The pointer created by the library, shared with the blocking calls of the sources and sinks.
+///
+/// The pointer is locked for the duration of every call, so that it is never finalized during a
+/// call.
+#[derive(Clone)]
+struct StateHandle(Arc<std::sync::Mutex<StatePtr>>);
+
+impl StateHandle {
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatePtr> {
+        // A panic of the runtime while calling the library does not invalidate the pointer.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Calls `f` with the pointer created by the library.
+    ///
+    /// # Errors
+    /// `InvalidState` is returned if the state was finalized, otherwise the error of `f` is
+    /// returned.
+    fn with<T>(&self, f: impl FnOnce(*mut c_void) -> ZFResult<T>) -> ZFResult<T> {
+        let state = self.lock();
+        if state.0.is_null() {
+            return Err(ZFError::InvalidState);
+        }
+        f(state.0)
+    }
+}
+
+/// The state of a node loaded through the C ABI: the pointer created by the library, and the
+/// functions checkpointing it.
+pub struct FfiState {
+    state: StateHandle,
+    node: FfiNodeVTable,
+    checkpoints: FfiCheckpointVTable,
+}
+
+impl std::fmt::Debug for FfiState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FfiState")
+            .field("state", &self.state.lock().0)
+            .finish()
+    }
+}
+
+impl ZFState for FfiState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+        let checkpoint = match self.checkpoints.checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+
+        self.state.with(|state| {
+            let mut supported = false;
+            let mut bytes = FfiBuffer::empty();
+            // SAFETY: the arguments are valid for the duration of the call.
+            unsafe {
+                let result = self.node.call("checkpoint", |error| {
+                    checkpoint(state, &mut supported, &mut bytes, error)
+                });
+                let bytes = self.node.take(bytes);
+                result?;
+                Ok(if supported { Some(bytes) } else { None })
+            }
+        })
+    }
+
+    fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+        let restore = self.checkpoints.restore.ok_or(ZFError::Unsupported)?;
+
+        self.state.with(|state| {
+            // SAFETY: the arguments are valid for the duration of the call.
+            unsafe {
+                self.node.call("restore", |error| {
+                    restore(state, FfiSlice::new(bytes), error)
+                })
+            }
+        })
+    }
+}
+
+impl FfiNodeVTable {
+    fn initialize(
+        &self,
+        configuration: &Option<Configuration>,
+        checkpoints: FfiCheckpointVTable,
+    ) -> ZFResult<State> {
+        let configuration = match configuration {
+            Some(configuration) => serde_json::to_vec(configuration)?,
+            None => Vec::new(),
+        };
+
+        let mut state = std::ptr::null_mut();
+        // SAFETY: the arguments are valid for the duration of the call.
+        unsafe {
+            self.call("initialize", |error| {
+                (self.initialize)(FfiSlice::new(&configuration), &mut state, error)
+            })?;
+        }
+
+        Ok(State::from(FfiState {
+            state: StateHandle(Arc::new(std::sync::Mutex::new(StatePtr(state)))),
+            node: *self,
+            checkpoints,
+        }))
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        let handle = state.try_get::<FfiState>()?.state.clone();
+        // The lock waits for the blocking call of a source or a sink to return.
+        let mut state = handle.lock();
+        if state.0.is_null() {
+            return Err(ZFError::InvalidState);
+        }
+        // The library releases the state even if it fails to finalize it.
+        let ptr = std::mem::replace(&mut state.0, std::ptr::null_mut());
+
+        // SAFETY: the state is valid, and no longer used once the call returns.
+        unsafe { self.call("finalize", |error| (self.finalize)(ptr, error)) }
+    }
+
+    /// Calls the function `name` of the library with an error buffer and converts its status
+    /// into a `ZFResult`.
+    ///
+    /// # Safety
+    /// The call must respect the C ABI.
+    unsafe fn call(&self, name: &str, call: impl FnOnce(*mut FfiBuffer) -> i32) -> ZFResult<()> {
+        let mut error = FfiBuffer::empty();
+        let status = call(&mut error);
+        let message = self.take(error);
+
+        match status {
+            0 => Ok(()),
+            _ if message.is_empty() => Err(ZFError::FfiError(format!(
+                "< {} > returned the status {}",
+                name, status
+            ))),
+            _ => Err(ZFError::FfiError(format!(
+                "< {} > failed: {}",
+                name,
+                String::from_utf8_lossy(&message)
+            ))),
+        }
+    }
+
+    /// Copies the bytes of a buffer produced by the library, and frees it.
+    ///
+    /// # Safety
+    /// The buffer must be empty or produced by the library.
+    unsafe fn take(&self, buffer: FfiBuffer) -> Vec<u8> {
+        if buffer.ptr.is_null() {
+            return Vec::new();
+        }
+        let bytes = buffer.as_slice().to_vec();
+        (self.free_buffer)(buffer);
+        bytes
+    }
+}
+
+/// An Operator loaded through the C ABI.
+pub struct FfiOperator {
+    declaration: FfiOperatorDeclaration,
+}
+
+impl FfiOperator {
+    /// # Safety
+    /// The functions of the declaration must implement the C ABI, and the library exporting them
+    /// must outlive the operator.
+    pub(crate) unsafe fn new(declaration: FfiOperatorDeclaration) -> Self {
+        Self { declaration }
+    }
+}
+
+impl Node for FfiOperator {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.declaration
+            .node
+            .initialize(configuration, self.declaration.checkpoints)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        self.declaration.node.finalize(state)
+    }
+}
+
+impl Operator for FfiOperator {
+    fn input_rule(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        let mut entries = Vec::with_capacity(tokens.len());
+        for (port_id, token) in tokens.iter_mut() {
+            let entry = match token {
+                InputToken::Pending => vec![TOKEN_PENDING],
+                InputToken::Ready(token) => {
+                    let mut entry = vec![TOKEN_READY, encode_action(&token.action)];
+                    encode_message(&mut entry, &mut token.data)?;
+                    entry
+                }
+            };
+            entries.push((port_id.clone(), entry));
+        }
+        let tokens_bytes = encode_ports(&entries);
+
+        let node = &self.declaration.node;
+        let mut ready = false;
+        let actions = state.try_get::<FfiState>()?.state.with(|state| {
+            let mut actions = FfiBuffer::empty();
+            // SAFETY: the arguments are valid for the duration of the call.
+            unsafe {
+                let result = node.call("input_rule", |error| {
+                    (self.declaration.input_rule)(
+                        state,
+                        &mut context.mode,
+                        FfiSlice::new(&tokens_bytes),
+                        &mut ready,
+                        &mut actions,
+                        error,
+                    )
+                });
+                let actions = node.take(actions);
+                result?;
+                Ok(actions)
+            }
+        })?;
+
+        for (port_id, action) in decode_ports(&actions)? {
+            let action = match action.as_slice() {
+                [action] => decode_action(*action)?,
+                _ => return Err(ZFError::DeseralizationError),
+            };
+            if let Some(InputToken::Ready(token)) = tokens.get_mut(&port_id) {
+                token.action = action;
+            }
+        }
+
+        Ok(ready)
+    }
+
+    fn run(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let mut entries = Vec::with_capacity(inputs.len());
+        for (port_id, message) in inputs.iter_mut() {
+            let mut entry = Vec::new();
+            encode_message(&mut entry, message)?;
+            entries.push((port_id.clone(), entry));
+        }
+        let inputs_bytes = encode_ports(&entries);
+
+        let node = &self.declaration.node;
+        let outputs = state.try_get::<FfiState>()?.state.with(|state| {
+            let mut outputs = FfiBuffer::empty();
+            // SAFETY: the arguments are valid for the duration of the call.
+            unsafe {
+                let result = node.call("run", |error| {
+                    (self.declaration.run)(
+                        state,
+                        &mut context.mode,
+                        FfiSlice::new(&inputs_bytes),
+                        &mut outputs,
+                        error,
+                    )
+                });
+                let outputs = node.take(outputs);
+                result?;
+                Ok(outputs)
+            }
+        })?;
+
+        decode_data(&outputs)
+    }
+
+    fn output_rule(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        let outputs_bytes = encode_data(outputs)?;
+        let deadline_miss = deadline_miss.as_ref().map(FfiDeadlineMiss::from);
+        let deadline_miss_ptr = deadline_miss
+            .as_ref()
+            .map_or(std::ptr::null(), |miss| miss as *const FfiDeadlineMiss);
+
+        let node = &self.declaration.node;
+        let results = state.try_get::<FfiState>()?.state.with(|state| {
+            let mut results = FfiBuffer::empty();
+            // SAFETY: the arguments are valid for the duration of the call.
+            unsafe {
+                let result = node.call("output_rule", |error| {
+                    (self.declaration.output_rule)(
+                        state,
+                        &mut context.mode,
+                        FfiSlice::new(&outputs_bytes),
+                        deadline_miss_ptr,
+                        &mut results,
+                        error,
+                    )
+                });
+                let results = node.take(results);
+                result?;
+                Ok(results)
+            }
+        })?;
+
+        Ok(decode_data(&results)?
+            .into_iter()
+            .map(|(port_id, data)| (port_id, NodeOutput::Data(data)))
+            .collect())
+    }
+}
+
+/// A Source loaded through the C ABI.
+///
+/// Its `run` function is called on a thread that can block, and it must return periodically: the
+/// source cannot be stopped or finalized while it is running.
+pub struct FfiSource {
+    declaration: FfiSourceDeclaration,
+}
+
+impl FfiSource {
+    /// # Safety
+    /// The functions of the declaration must implement the C ABI, and the library exporting them
+    /// must outlive the source.
+    pub(crate) unsafe fn new(declaration: FfiSourceDeclaration) -> Self {
+        Self { declaration }
+    }
+}
+
+impl Node for FfiSource {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.declaration
+            .node
+            .initialize(configuration, self.declaration.checkpoints)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        self.declaration.node.finalize(state)
+    }
+}
+
+#[async_trait]
+impl Source for FfiSource {
+    async fn run(&self, context: &mut Context, state: &mut State) -> ZFResult<Data> {
+        let handle = state.try_get::<FfiState>()?.state.clone();
+        let declaration = self.declaration;
+        let mut mode = context.mode;
+
+        // The library can block: it is not called from the asynchronous threads.
+        let (mode, data) = async_std::task::spawn_blocking(move || {
+            let node = &declaration.node;
+            let data = handle.with(|state| {
+                let mut data = FfiBuffer::empty();
+                // SAFETY: the arguments are valid for the duration of the call, and the state is
+                // locked until it returns.
+                unsafe {
+                    let result = node.call("run", |error| {
+                        (declaration.run)(state, &mut mode, &mut data, error)
+                    });
+                    let data = node.take(data);
+                    result?;
+                    Ok(data)
+                }
+            })?;
+            Ok::<_, ZFError>((mode, data))
+        })
+        .await?;
+
+        context.mode = mode;
+        Ok(Data::from_bytes(data))
+    }
+}
+
+/// A Sink loaded through the C ABI.
+///
+/// Its `run` function is called on a thread that can block, and it must return periodically: the
+/// sink cannot be stopped or finalized while it is running.
+pub struct FfiSink {
+    declaration: FfiSinkDeclaration,
+}
+
+impl FfiSink {
+    /// # Safety
+    /// The functions of the declaration must implement the C ABI, and the library exporting them
+    /// must outlive the sink.
+    pub(crate) unsafe fn new(declaration: FfiSinkDeclaration) -> Self {
+        Self { declaration }
+    }
+}
+
+impl Node for FfiSink {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.declaration
+            .node
+            .initialize(configuration, self.declaration.checkpoints)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        self.declaration.node.finalize(state)
+    }
+}
+
+#[async_trait]
+impl Sink for FfiSink {
+    async fn run(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        mut input: DataMessage,
+    ) -> ZFResult<()> {
+        let mut message = Vec::new();
+        encode_message(&mut message, &mut input)?;
+
+        let handle = state.try_get::<FfiState>()?.state.clone();
+        let declaration = self.declaration;
+        let mut mode = context.mode;
+
+        // The library can block: it is not called from the asynchronous threads.
+        let mode = async_std::task::spawn_blocking(move || {
+            handle.with(|state| {
+                // SAFETY: the arguments are valid for the duration of the call, and the state is
+                // locked until it returns.
+                unsafe {
+                    declaration.node.call("run", |error| {
+                        (declaration.run)(state, &mut mode, FfiSlice::new(&message), error)
+                    })
+                }
+            })?;
+            Ok::<_, ZFError>(mode)
+        })
+        .await?;
+
+        context.mode = mode;
+        Ok(())
+    }
+}
+
+/// The implementation of the C ABI on top of the node traits, used by the declarations that
+/// [`export_c_operator`](`crate::export_c_operator`), [`export_c_source`](`crate::export_c_source`)
+/// and [`export_c_sink`](`crate::export_c_sink`) generate.
+#[doc(hidden)]
+pub mod plugin {
+    use super::*;
+
+    enum PluginKind {
+        Operator(Arc<dyn Operator>),
+        Source(Arc<dyn Source>),
+        Sink(Arc<dyn Sink>),
+    }
+
+    /// A node and its state, behind the pointer given to the runtime.
+    struct PluginNode {
+        kind: PluginKind,
+        state: State,
+    }
+
+    impl PluginNode {
+        /// # Safety
+        /// The pointer must be null or come from `initialize`, and not be finalized.
+        unsafe fn from_ptr<'a>(state: *mut c_void) -> ZFResult<&'a mut Self> {
+            (state as *mut Self).as_mut().ok_or(ZFError::InvalidState)
+        }
+
+        fn as_operator(&mut self) -> ZFResult<(&dyn Operator, &mut State)> {
+            match &self.kind {
+                PluginKind::Operator(operator) => Ok((operator.as_ref(), &mut self.state)),
+                _ => Err(ZFError::InvalidState),
+            }
+        }
+
+        fn as_source(&mut self) -> ZFResult<(&dyn Source, &mut State)> {
+            match &self.kind {
+                PluginKind::Source(source) => Ok((source.as_ref(), &mut self.state)),
+                _ => Err(ZFError::InvalidState),
+            }
+        }
+
+        fn as_sink(&mut self) -> ZFResult<(&dyn Sink, &mut State)> {
+            match &self.kind {
+                PluginKind::Sink(sink) => Ok((sink.as_ref(), &mut self.state)),
+                _ => Err(ZFError::InvalidState),
+            }
+        }
+    }
+
+    /// Runs `f`, reporting its error or its panic, which must not unwind across the C ABI,
+    /// through `error`.
+    ///
+    /// # Safety
+    /// `error` must be null or valid for writes.
+    unsafe fn guard(error: *mut FfiBuffer, f: impl FnOnce() -> ZFResult<()>) -> i32 {
+        let (status, message) = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+            Ok(Ok(())) => return 0,
+            Ok(Err(e)) => (1, e.to_string()),
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                (2, format!("The node panicked: {}", message))
+            }
+        };
+
+        if !error.is_null() {
+            error.write(FfiBuffer::from_vec(message.into_bytes()));
+        }
+        status
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    unsafe fn initialize(
+        register: impl FnOnce() -> ZFResult<PluginKind>,
+        configuration: FfiSlice,
+        state: *mut *mut c_void,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let configuration = match configuration.as_slice() {
+                [] => None,
+                bytes => Some(serde_json::from_slice::<Configuration>(bytes)?),
+            };
+
+            let kind = register()?;
+            let node_state = match &kind {
+                PluginKind::Operator(operator) => operator.initialize(&configuration)?,
+                PluginKind::Source(source) => source.initialize(&configuration)?,
+                PluginKind::Sink(sink) => sink.initialize(&configuration)?,
+            };
+
+            let node = Box::new(PluginNode {
+                kind,
+                state: node_state,
+            });
+            state.write(Box::into_raw(node) as *mut c_void);
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe fn initialize_operator(
+        register: OperatorRegisterFn,
+        configuration: FfiSlice,
+        state: *mut *mut c_void,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        initialize(
+            || Ok(PluginKind::Operator(register()?)),
+            configuration,
+            state,
+            error,
+        )
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe fn initialize_source(
+        register: SourceRegisterFn,
+        configuration: FfiSlice,
+        state: *mut *mut c_void,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        initialize(
+            || Ok(PluginKind::Source(register()?)),
+            configuration,
+            state,
+            error,
+        )
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe fn initialize_sink(
+        register: SinkRegisterFn,
+        configuration: FfiSlice,
+        state: *mut *mut c_void,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        initialize(
+            || Ok(PluginKind::Sink(register()?)),
+            configuration,
+            state,
+            error,
+        )
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn finalize(state: *mut c_void, error: *mut FfiBuffer) -> i32 {
+        guard(error, || {
+            if state.is_null() {
+                return Err(ZFError::InvalidState);
+            }
+            let PluginNode { kind, mut state } = *Box::from_raw(state as *mut PluginNode);
+            match kind {
+                PluginKind::Operator(operator) => operator.finalize(&mut state),
+                PluginKind::Source(source) => source.finalize(&mut state),
+                PluginKind::Sink(sink) => sink.finalize(&mut state),
+            }
+        })
+    }
+
+    /// # Safety
+    /// The buffer must be empty or produced by this library.
+    pub unsafe extern "C" fn free_buffer(buffer: FfiBuffer) {
+        drop(buffer.into_vec());
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn checkpoint(
+        state: *mut c_void,
+        supported: *mut bool,
+        checkpoint: *mut FfiBuffer,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let node = PluginNode::from_ptr(state)?;
+            match node.state.try_serialize()? {
+                Some(bytes) => {
+                    supported.write(true);
+                    checkpoint.write(FfiBuffer::from_vec(bytes));
+                }
+                None => supported.write(false),
+            }
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn restore(
+        state: *mut c_void,
+        checkpoint: FfiSlice,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            PluginNode::from_ptr(state)?
+                .state
+                .try_restore(checkpoint.as_slice())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn input_rule(
+        state: *mut c_void,
+        mode: *mut usize,
+        tokens: FfiSlice,
+        ready: *mut bool,
+        actions: *mut FfiBuffer,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let (operator, state) = PluginNode::from_ptr(state)?.as_operator()?;
+            let mut tokens = decode_ports(tokens.as_slice())?
+                .into_iter()
+                .map(|(port_id, entry)| Ok((port_id, decode_token(&entry)?)))
+                .collect::<ZFResult<HashMap<PortId, InputToken>>>()?;
+
+            let mut context = Context { mode: *mode };
+            let is_ready = operator.input_rule(&mut context, state, &mut tokens)?;
+            *mode = context.mode;
+
+            let entries: Vec<(PortId, [u8; 1])> = tokens
+                .into_iter()
+                .filter_map(|(port_id, token)| match token {
+                    InputToken::Ready(token) => Some((port_id, [encode_action(&token.action)])),
+                    InputToken::Pending => None,
+                })
+                .collect();
+            ready.write(is_ready);
+            actions.write(FfiBuffer::from_vec(encode_ports(&entries)));
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn run_operator(
+        state: *mut c_void,
+        mode: *mut usize,
+        inputs: FfiSlice,
+        outputs: *mut FfiBuffer,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let (operator, state) = PluginNode::from_ptr(state)?.as_operator()?;
+            let mut inputs = decode_ports(inputs.as_slice())?
+                .into_iter()
+                .map(|(port_id, message)| Ok((port_id, decode_message(&message)?)))
+                .collect::<ZFResult<HashMap<PortId, DataMessage>>>()?;
+
+            let mut context = Context { mode: *mode };
+            let results = operator.run(&mut context, state, &mut inputs)?;
+            *mode = context.mode;
+
+            outputs.write(FfiBuffer::from_vec(encode_data(results)?));
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn output_rule(
+        state: *mut c_void,
+        mode: *mut usize,
+        outputs: FfiSlice,
+        deadline_miss: *const FfiDeadlineMiss,
+        results: *mut FfiBuffer,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let (operator, state) = PluginNode::from_ptr(state)?.as_operator()?;
+            let outputs = decode_data(outputs.as_slice())?;
+            let deadline_miss = deadline_miss.as_ref().map(LocalDeadlineMiss::from);
+
+            let mut context = Context { mode: *mode };
+            let node_outputs = operator.output_rule(&mut context, state, outputs, deadline_miss)?;
+            *mode = context.mode;
+
+            // Only data can cross the C ABI.
+            let data = node_outputs
+                .into_iter()
+                .map(|(port_id, output)| match output {
+                    NodeOutput::Data(data) => Ok((port_id, data)),
+                    NodeOutput::Control(_) => Err(ZFError::Unsupported),
+                })
+                .collect::<ZFResult<HashMap<PortId, Data>>>()?;
+            results.write(FfiBuffer::from_vec(encode_data(data)?));
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn run_source(
+        state: *mut c_void,
+        mode: *mut usize,
+        data: *mut FfiBuffer,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let (source, state) = PluginNode::from_ptr(state)?.as_source()?;
+
+            let mut context = Context { mode: *mode };
+            let output = async_std::task::block_on(source.run(&mut context, state))?;
+            *mode = context.mode;
+
+            data.write(FfiBuffer::from_vec(output.try_as_bytes()?.to_vec()));
+            Ok(())
+        })
+    }
+
+    /// # Safety
+    /// The arguments must respect the C ABI.
+    pub unsafe extern "C" fn run_sink(
+        state: *mut c_void,
+        mode: *mut usize,
+        message: FfiSlice,
+        error: *mut FfiBuffer,
+    ) -> i32 {
+        guard(error, || {
+            let (sink, state) = PluginNode::from_ptr(state)?.as_sink()?;
+            let input = decode_message(message.as_slice())?;
+
+            let mut context = Context { mode: *mode };
+            async_std::task::block_on(sink.run(&mut context, state, input))?;
+            *mode = context.mode;
+            Ok(())
+        })
+    }
+}
+
+fn encode_action(action: &TokenAction) -> u8 {
+    match action {
+        TokenAction::Consume => ACTION_CONSUME,
+        TokenAction::Drop => ACTION_DROP,
+        TokenAction::Keep => ACTION_KEEP,
+    }
+}
+
+fn decode_action(action: u8) -> ZFResult<TokenAction> {
+    match action {
+        ACTION_CONSUME => Ok(TokenAction::Consume),
+        ACTION_DROP => Ok(TokenAction::Drop),
+        ACTION_KEEP => Ok(TokenAction::Keep),
+        _ => Err(ZFError::DeseralizationError),
+    }
+}
+
+/// Decodes an input token.
+///
+/// # Errors
+/// An error variant is returned if the token is invalid.
+fn decode_token(entry: &[u8]) -> ZFResult<InputToken> {
+    match entry {
+        [TOKEN_PENDING] => Ok(InputToken::Pending),
+        [TOKEN_READY, action, message @ ..] => Ok(InputToken::Ready(DataToken::new(
+            decode_action(*action)?,
+            decode_message(message)?,
+        ))),
+        _ => Err(ZFError::DeseralizationError),
+    }
+}
+
+/// Appends the encoded message to `bytes`. The data is serialized in place, so that it is not
+/// serialized again by a later call.
+///
+/// # Errors
+/// An error variant is returned if the data cannot be serialized.
+fn encode_message(bytes: &mut Vec<u8>, message: &mut DataMessage) -> ZFResult<()> {
+    if let Data::Typed(_) = message.data {
+        message.data = Data::Bytes(message.data.try_as_bytes()?);
+    }
+    let data = message.data.try_as_bytes()?;
+    let clock = message.timestamp.get_id().as_slice();
+
+    bytes.reserve(12 + clock.len() + data.len());
+    bytes.extend_from_slice(&message.timestamp.get_time().0.to_le_bytes());
+    bytes.extend_from_slice(&(clock.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(clock);
+    bytes.extend_from_slice(&data);
+    Ok(())
+}
+
+/// Decodes a message.
+///
+/// # Errors
+/// An error variant is returned if the message is truncated or if the identifier of the clock is
+/// invalid.
+fn decode_message(bytes: &[u8]) -> ZFResult<DataMessage> {
+    let mut cursor = bytes;
+    let mut time = [0u8; 8];
+    time.copy_from_slice(take(&mut cursor, 8)?);
+    let clock_len = take_len(&mut cursor)?;
+    let clock = take(&mut cursor, clock_len)?;
+    if clock.len() > ID::MAX_SIZE {
+        return Err(ZFError::DeseralizationError);
+    }
+    let mut id = [0u8; ID::MAX_SIZE];
+    id[..clock.len()].copy_from_slice(clock);
+
+    let timestamp = Timestamp::new(NTP64(u64::from_le_bytes(time)), ID::new(clock.len(), id));
+    Ok(DataMessage::new(
+        Data::from_bytes(cursor.to_vec()),
+        timestamp,
+        vec![],
+    ))
+}
+
+/// Encodes a list of data.
+///
+/// # Errors
+/// An error variant is returned if some data cannot be serialized.
+fn encode_data(outputs: HashMap<PortId, Data>) -> ZFResult<Vec<u8>> {
+    let mut data = Vec::with_capacity(outputs.len());
+    for (port_id, output) in outputs {
+        data.push((port_id, output.try_as_bytes()?));
+    }
+    let ports: Vec<(PortId, &[u8])> = data
+        .iter()
+        .map(|(port_id, bytes)| (port_id.clone(), bytes.as_slice()))
+        .collect();
+    Ok(encode_ports(&ports))
+}
+
+/// Decodes a list of data.
+///
+/// # Errors
+/// An error variant is returned if the list is invalid.
+fn decode_data(bytes: &[u8]) -> ZFResult<HashMap<PortId, Data>> {
+    Ok(decode_ports(bytes)?
+        .into_iter()
+        .map(|(port_id, bytes)| (port_id, Data::from_bytes(bytes)))
+        .collect())
+}
+
+/// Encodes a list of ports.
+pub(crate) fn encode_ports<B: AsRef<[u8]>>(ports: &[(PortId, B)]) -> Vec<u8> {
+    let len = ports.iter().fold(4, |len, (id, data)| {
+        len + 8 + id.len() + data.as_ref().len()
+    });
+    let mut bytes = Vec::with_capacity(len);
+    bytes.extend_from_slice(&(ports.len() as u32).to_le_bytes());
+    for (id, data) in ports {
+        bytes.extend_from_slice(&(id.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(id.as_bytes());
+        let data = data.as_ref();
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+    }
+    bytes
+}
+
+/// Decodes a list of ports.
+///
+/// # Errors
+/// An error variant is returned if the list is truncated or if an identifier is not valid UTF-8.
+pub(crate) fn decode_ports(bytes: &[u8]) -> ZFResult<Vec<(PortId, Vec<u8>)>> {
+    let mut cursor = bytes;
+    let count = take_len(&mut cursor)?;
+    // The count is not trusted to reserve the memory: each entry takes at least 8 bytes.
+    let mut ports = Vec::with_capacity(count.min(cursor.len() / 8));
+    for _ in 0..count {
+        let id_len = take_len(&mut cursor)?;
+        let id = std::str::from_utf8(take(&mut cursor, id_len)?)
+            .map_err(|_| ZFError::DeseralizationError)?;
+        let data_len = take_len(&mut cursor)?;
+        ports.push((id.into(), take(&mut cursor, data_len)?.to_vec()));
+    }
+
+    Ok(ports)
+}
+
+/// Takes `len` bytes from the beginning of `cursor`.
+fn take<'a>(cursor: &mut &'a [u8], len: usize) -> ZFResult<&'a [u8]> {
+    if cursor.len() < len {
+        return Err(ZFError::DeseralizationError);
+    }
+    let (head, tail) = cursor.split_at(len);
+    *cursor = tail;
+    Ok(head)
+}
+
+/// Takes a little-endian `u32` from the beginning of `cursor`.
+fn take_len(cursor: &mut &[u8]) -> ZFResult<usize> {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(take(cursor, 4)?);
+    Ok(u32::from_le_bytes(word) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{default_input_rule, default_output_rule, EmptyState};
+    use uhlc::HLC;
+
+    // An operator copying its inputs to its outputs, it keeps its tokens and panics on `panic`.
+    struct Echo;
+
+    impl Node for Echo {
+        fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+            match configuration {
+                Some(Configuration::Object(_)) | None => Ok(State::from(EmptyState {})),
+                Some(_) => Err(ZFError::ConfigurationError("Not an object".into())),
+            }
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Operator for Echo {
+        fn input_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            tokens: &mut HashMap<PortId, InputToken>,
+        ) -> ZFResult<bool> {
+            for token in tokens.values_mut() {
+                token.set_action_keep();
+            }
+            default_input_rule(state, tokens)
+        }
+
+        fn run(
+            &self,
+            context: &mut Context,
+            _state: &mut State,
+            inputs: &mut HashMap<PortId, DataMessage>,
+        ) -> ZFResult<HashMap<PortId, Data>> {
+            context.mode += 1;
+            let mut outputs = HashMap::with_capacity(inputs.len());
+            for (port_id, message) in inputs.iter_mut() {
+                if message.get_inner_data().try_as_bytes()?.as_slice() == b"panic" {
+                    panic!("Invalid input");
+                }
+                outputs.insert(port_id.clone(), message.get_inner_data().clone());
+            }
+            Ok(outputs)
+        }
+
+        fn output_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            outputs: HashMap<PortId, Data>,
+            _deadline_miss: Option<LocalDeadlineMiss>,
+        ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+            default_output_rule(state, outputs)
+        }
+    }
+
+    fn register() -> ZFResult<Arc<dyn Operator>> {
+        Ok(Arc::new(Echo) as Arc<dyn Operator>)
+    }
+
+    crate::export_c_operator!(register);
+
+    // A source counting its runs, its state can be checkpointed.
+    #[derive(Debug)]
+    struct CounterState(u64);
+
+    impl ZFState for CounterState {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+            Ok(Some(self.0.to_le_bytes().to_vec()))
+        }
+
+        fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+            let mut counter = [0u8; 8];
+            counter.copy_from_slice(bytes.get(..8).ok_or(ZFError::DeseralizationError)?);
+            self.0 = u64::from_le_bytes(counter);
+            Ok(())
+        }
+    }
+
+    struct Counter;
+
+    impl Node for Counter {
+        fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+            Ok(State::from(CounterState(0)))
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Source for Counter {
+        async fn run(&self, _context: &mut Context, state: &mut State) -> ZFResult<Data> {
+            let state = state.try_get::<CounterState>()?;
+            state.0 += 1;
+            Ok(Data::from_bytes(state.0.to_le_bytes().to_vec()))
+        }
+    }
+
+    fn register_source() -> ZFResult<Arc<dyn Source>> {
+        Ok(Arc::new(Counter) as Arc<dyn Source>)
+    }
+
+    crate::export_c_source!(register_source);
+
+    fn bytes(data: &Data) -> Vec<u8> {
+        data.try_as_bytes().unwrap().to_vec()
+    }
+
+    #[test]
+    fn version_negotiation() {
+        let version = |major, minor| AbiVersion { major, minor };
+        assert!(version(1, 0).is_compatible_with(&version(1, 0)));
+        assert!(version(1, 0).is_compatible_with(&version(1, 2)));
+        assert!(!version(1, 3).is_compatible_with(&version(1, 2)));
+        assert!(!version(2, 0).is_compatible_with(&version(1, 0)));
+        assert!(!version(0, 9).is_compatible_with(&version(1, 0)));
+
+        assert!(ABI_VERSION.negotiate().is_ok());
+        assert_eq!(
+            version(ABI_VERSION.major + 1, 0).negotiate(),
+            Err(ZFError::VersionMismatch)
+        );
+    }
+
+    #[test]
+    fn declaration_size() {
+        let header = zfoperator_c_declaration.header;
+        assert_eq!(header.abi_version, ABI_VERSION);
+        assert_eq!(header.size, std::mem::size_of::<FfiOperatorDeclaration>());
+        assert_eq!(
+            FfiOperatorDeclaration::required_size() + std::mem::size_of::<FfiCheckpointVTable>(),
+            std::mem::size_of::<FfiOperatorDeclaration>()
+        );
+        assert_eq!(
+            FfiSourceDeclaration::required_size() + std::mem::size_of::<FfiCheckpointVTable>(),
+            std::mem::size_of::<FfiSourceDeclaration>()
+        );
+        assert_eq!(
+            FfiSinkDeclaration::required_size() + std::mem::size_of::<FfiCheckpointVTable>(),
+            std::mem::size_of::<FfiSinkDeclaration>()
+        );
+    }
+
+    #[test]
+    fn run_source() {
+        // SAFETY: the declaration is generated by `export_c_source`, in this library.
+        let source = unsafe { FfiSource::new(zfsource_c_declaration) };
+        let mut state = source.initialize(&None).unwrap();
+        let mut context = Context::default();
+
+        let run = |state: &mut State, context: &mut Context| {
+            let data = async_std::task::block_on(Source::run(&source, context, state)).unwrap();
+            bytes(&data)
+        };
+        assert_eq!(run(&mut state, &mut context), 1u64.to_le_bytes());
+
+        let checkpoint = state.try_serialize().unwrap().unwrap();
+        assert_eq!(run(&mut state, &mut context), 2u64.to_le_bytes());
+        state.try_restore(&checkpoint).unwrap();
+        assert_eq!(run(&mut state, &mut context), 2u64.to_le_bytes());
+
+        assert!(source.finalize(&mut state).is_ok());
+        assert_eq!(
+            async_std::task::block_on(Source::run(&source, &mut context, &mut state)).err(),
+            Some(ZFError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn message_roundtrip() {
+        let timestamp = HLC::default().new_timestamp();
+        let mut message = DataMessage::new(Data::from_bytes(b"data".to_vec()), timestamp, vec![]);
+        let mut encoded = Vec::new();
+        encode_message(&mut encoded, &mut message).unwrap();
+
+        let mut decoded = decode_message(&encoded).unwrap();
+        assert_eq!(decoded.get_timestamp(), &timestamp);
+        assert_eq!(bytes(decoded.get_inner_data()), b"data");
+
+        assert_eq!(
+            decode_message(&encoded[..10]).err(),
+            Some(ZFError::DeseralizationError)
+        );
+        assert_eq!(
+            decode_token(&[TOKEN_READY, 3]).err(),
+            Some(ZFError::DeseralizationError)
+        );
+    }
+
+    #[test]
+    fn run_operator() {
+        // SAFETY: the declaration is generated by `export_c_operator`, in this library.
+        let operator = unsafe { FfiOperator::new(zfoperator_c_declaration) };
+
+        match operator.initialize(&Some(serde_json::json!("invalid"))) {
+            Err(ZFError::FfiError(e)) => assert!(e.contains("Not an object")),
+            _ => panic!("The initialization should fail"),
+        }
+
+        let mut state = operator
+            .initialize(&Some(serde_json::json!({"key": "value"})))
+            .unwrap();
+        // The state of the operator does not support checkpointing.
+        assert_eq!(state.try_serialize(), Ok(None));
+        let mut context = Context::default();
+        let message = DataMessage::new(
+            Data::from_bytes(b"hello".to_vec()),
+            HLC::default().new_timestamp(),
+            vec![],
+        );
+
+        let mut tokens = HashMap::new();
+        tokens.insert(PortId::from("in"), InputToken::Pending);
+        assert!(!operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+            .unwrap());
+        tokens.insert(PortId::from("in"), InputToken::from(message.clone()));
+        assert!(operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+            .unwrap());
+        match tokens.get(&PortId::from("in")) {
+            Some(InputToken::Ready(token)) => assert_eq!(token.get_action(), &TokenAction::Keep),
+            _ => panic!("The token should be ready"),
+        }
+
+        let mut inputs = HashMap::new();
+        inputs.insert(PortId::from("in"), message);
+        let outputs = Operator::run(&operator, &mut context, &mut state, &mut inputs).unwrap();
+        assert_eq!(context.mode, 1);
+        assert_eq!(bytes(&outputs[&PortId::from("in")]), b"hello");
+
+        let deadline_miss =
+            crate::testing::local_deadline_miss(Duration::from_millis(1), Duration::from_millis(2));
+        let results = operator
+            .output_rule(&mut context, &mut state, outputs, Some(deadline_miss))
+            .unwrap();
+        match &results[&PortId::from("in")] {
+            NodeOutput::Data(data) => assert_eq!(bytes(data), b"hello"),
+            _ => panic!("The output should be data"),
+        }
+
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            PortId::from("in"),
+            DataMessage::new(
+                Data::from_bytes(b"panic".to_vec()),
+                HLC::default().new_timestamp(),
+                vec![],
+            ),
+        );
+        match Operator::run(&operator, &mut context, &mut state, &mut inputs) {
+            Err(ZFError::FfiError(e)) => assert!(e.contains("panicked: Invalid input")),
+            _ => panic!("The panic should be reported"),
+        }
+
+        assert!(operator.finalize(&mut state).is_ok());
+        assert_eq!(operator.finalize(&mut state), Err(ZFError::InvalidState));
+    }
+
+    #[test]
+    fn ports_roundtrip() {
+        let ports: Vec<(PortId, &[u8])> =
+            vec![("in".into(), &b"data"[..]), ("empty".into(), &b""[..])];
+        let bytes = encode_ports(&ports);
+        let decoded = decode_ports(&bytes).unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                (PortId::from("in"), b"data".to_vec()),
+                (PortId::from("empty"), Vec::new())
+            ]
+        );
+
+        assert_eq!(decode_ports(&[]), Err(ZFError::DeseralizationError));
+        assert_eq!(
+            decode_ports(&bytes[..bytes.len() - 1]),
+            Err(ZFError::DeseralizationError)
+        );
+        assert!(decode_ports(&encode_ports::<&[u8]>(&[]))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -12,8 +12,12 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::runtime::message::Message;
 use crate::{PortId, ZFResult};
 use async_std::sync::Arc;
+use std::any::Any;
+use std::collections::HashMap;
+use uhlc::ID;
 
 /// The Zenoh Flow link sender.
 /// A wrapper over a flume Sender, that sends `Arc<T>` and is associated
@@ -27,10 +31,15 @@ pub struct LinkSender<T> {
 /// The Zenoh Flow link receiver.
 /// A wrapper over a flume Receiver, that receives `Arc<T>` and the associated
 /// `PortId`
+///
+/// The last message received from each sender is kept, it is shared with the clones of the
+/// receiver. The senders of [`Message`](`Message`)s are told apart by the ID of their
+/// timestamps, any other message is considered to come from a single sender.
 #[derive(Clone, Debug)]
 pub struct LinkReceiver<T> {
     pub id: PortId,
     pub receiver: flume::Receiver<Arc<T>>,
+    pub(crate) last_received: Arc<std::sync::Mutex<HashMap<Option<ID>, Arc<T>>>>,
 }
 
 /// The output of the [`LinkReceiver<T>`](`LinkReceiver<T>`), a tuple
@@ -41,7 +50,7 @@ pub struct LinkReceiver<T> {
 ///
 pub type ZFLinkOutput<T> = ZFResult<(PortId, Arc<T>)>;
 
-impl<T: std::marker::Send + std::marker::Sync> LinkReceiver<T> {
+impl<T: std::marker::Send + std::marker::Sync + 'static> LinkReceiver<T> {
     /// Wrapper over flume::Receiver::recv_async(),
     /// it returns [`ZFLinkOutput<T>`](`ZFLinkOutput<T>`)
     ///
@@ -51,8 +60,15 @@ impl<T: std::marker::Send + std::marker::Sync> LinkReceiver<T> {
         &self,
     ) -> ::core::pin::Pin<Box<dyn std::future::Future<Output = ZFLinkOutput<T>> + '_ + Send + Sync>>
     {
-        async fn __recv<T>(_self: &LinkReceiver<T>) -> ZFResult<(PortId, Arc<T>)> {
-            Ok((_self.id.clone(), _self.receiver.recv_async().await?))
+        async fn __recv<T: 'static>(_self: &LinkReceiver<T>) -> ZFResult<(PortId, Arc<T>)> {
+            let message = _self.receiver.recv_async().await?;
+            if let Ok(mut last_received) = _self.last_received.lock() {
+                let sender = (&*message as &dyn Any)
+                    .downcast_ref::<Message>()
+                    .map(|message| *message.get_timestamp().get_id());
+                last_received.insert(sender, message.clone());
+            }
+            Ok((_self.id.clone(), message))
         }
 
         Box::pin(__recv(self))
@@ -73,10 +89,28 @@ impl<T: std::marker::Send + std::marker::Sync> LinkReceiver<T> {
         self.id.clone()
     }
 
+    /// Returns the number of messages waiting in the receiver.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// Checks if no message is waiting in the receiver.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
     /// Checks if the receiver is disconnected.
     pub fn is_disconnected(&self) -> bool {
         self.receiver.is_disconnected()
     }
+
+    /// Returns the last message received from each sender, none if no message was received yet.
+    pub fn last_received(&self) -> Vec<Arc<T>> {
+        self.last_received
+            .lock()
+            .map(|last_received| last_received.values().cloned().collect())
+            .unwrap_or_default()
+    }
 }
 
 impl<T> LinkSender<T> {
@@ -134,6 +168,7 @@ pub fn link<T>(
         LinkReceiver {
             id: recv_id,
             receiver,
+            last_received: Arc::new(std::sync::Mutex::new(HashMap::new())),
         },
     )
 }
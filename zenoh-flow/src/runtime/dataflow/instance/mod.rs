@@ -16,23 +16,72 @@ pub mod link;
 pub mod runners;
 
 use crate::model::connector::ZFConnectorKind;
+use crate::model::dataflow::record::DataFlowRecord;
 use crate::model::link::LinkDescriptor;
-use crate::runtime::dataflow::instance::link::link;
+use crate::runtime::checkpoint::{InputWatermarks, NodeCheckpoint, CHECKPOINT_TIMEOUT};
+use crate::runtime::dataflow::instance::link::{link, LinkReceiver, LinkSender};
 use crate::runtime::dataflow::instance::runners::connector::{ZenohReceiver, ZenohSender};
 use crate::runtime::dataflow::instance::runners::operator::{OperatorIO, OperatorRunner};
-use crate::runtime::dataflow::instance::runners::replay::ZenohReplay;
+use crate::runtime::dataflow::instance::runners::replay::{ReplayOptions, ZenohReplay};
 use crate::runtime::dataflow::instance::runners::sink::SinkRunner;
 use crate::runtime::dataflow::instance::runners::source::SourceRunner;
 use crate::runtime::dataflow::instance::runners::{NodeRunner, RunnerKind};
 use crate::runtime::dataflow::Dataflow;
+use crate::runtime::deadline::DeadlineMissStats;
+use crate::runtime::watchdog::NodeHealth;
 use crate::runtime::InstanceContext;
 use crate::{Message, NodeId, PortId, PortType, ZFError, ZFResult};
 use async_std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 use self::runners::RunnerManager;
 
+/// How often the instance checks if it is idle while draining.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How many consecutive checks must find the instance idle for it to be
+/// drained: a message just taken out of a link may not be in a `run` yet.
+const DRAIN_IDLE_CHECKS: usize = 3;
+
+/// Waits for `is_idle` to report that everything it checks is idle, for at most `timeout`.
+///
+/// This is how the parts of an instance spanning several runtimes are drained together: `is_idle`
+/// checks all of them, their nodes are only stopped once they are all idle. Otherwise a runtime
+/// could stop while another one still sends it messages, which would be discarded.
+///
+/// It returns `true` if everything is idle, `false` if the timeout expired.
+///
+/// # Errors
+/// The errors of `is_idle` are returned as is.
+pub async fn wait_all_idle<F, Fut>(timeout: Duration, mut is_idle: F) -> ZFResult<bool>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ZFResult<bool>>,
+{
+    let start = Instant::now();
+    let mut idle_checks = 0;
+
+    loop {
+        if is_idle().await? {
+            idle_checks += 1;
+            if idle_checks >= DRAIN_IDLE_CHECKS {
+                return Ok(true);
+            }
+        } else {
+            idle_checks = 0;
+        }
+
+        if start.elapsed() >= timeout {
+            return Ok(false);
+        }
+
+        async_std::task::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
 /// The instance of a data flow graph.
 /// It contains runtime information for the instance
 /// and the [`InstanceContext`](`InstanceContext`)
@@ -40,6 +89,105 @@ pub struct DataflowInstance {
     pub(crate) context: InstanceContext,
     pub(crate) runners: HashMap<NodeId, NodeRunner>,
     pub(crate) managers: HashMap<NodeId, RunnerManager>,
+    pub(crate) links: Vec<InstanceLink>,
+    pub(crate) stop_order: Vec<NodeId>,
+}
+
+/// Takes, and restores, the checkpoints of a node of an instance, without holding the instance.
+///
+/// A node can take up to [`CHECKPOINT_TIMEOUT`](`CHECKPOINT_TIMEOUT`) to release its state: the
+/// instance, and whatever guards it, can be released in the meantime.
+#[derive(Clone)]
+pub struct NodeCheckpointer {
+    context: InstanceContext,
+    runner: NodeRunner,
+}
+
+impl NodeCheckpointer {
+    /// Returns the identifier of the node.
+    pub fn get_id(&self) -> NodeId {
+        self.runner.get_id()
+    }
+
+    /// Returns the identifier of the instance of the node.
+    pub fn get_instance_id(&self) -> Uuid {
+        self.context.instance_id
+    }
+
+    /// Takes a checkpoint of the state of the node, as
+    /// [`DataflowInstance::checkpoint_node`](`DataflowInstance::checkpoint_node`) does.
+    ///
+    /// # Errors
+    /// If fails if the node does not release its state in time or if the serialization fails.
+    pub async fn checkpoint(&self) -> ZFResult<Option<NodeCheckpoint>> {
+        let node_id = self.runner.get_id();
+        let state = async_std::future::timeout(CHECKPOINT_TIMEOUT, self.runner.checkpoint())
+            .await
+            .map_err(|_| {
+                ZFError::Uncompleted(format!(
+                    "Node < {} > did not release its state within {:?}",
+                    node_id, CHECKPOINT_TIMEOUT
+                ))
+            })??;
+
+        Ok(state.map(|state| NodeCheckpoint {
+            flow_id: self.context.flow_id.clone(),
+            instance_id: self.context.instance_id,
+            node_id,
+            runtime_id: self.context.runtime.runtime_uuid,
+            timestamp: self.context.runtime.hlc.new_timestamp(),
+            state,
+        }))
+    }
+
+    /// Restores the state of the node from the given checkpoint, as
+    /// [`DataflowInstance::restore_node`](`DataflowInstance::restore_node`) does.
+    ///
+    /// # Errors
+    /// If fails if the node does not release its state in time, if its state does not support
+    /// checkpointing or if the deserialization fails.
+    pub async fn restore(&self, checkpoint: &NodeCheckpoint) -> ZFResult<()> {
+        async_std::future::timeout(CHECKPOINT_TIMEOUT, self.runner.restore(&checkpoint.state))
+            .await
+            .map_err(|_| {
+                ZFError::Uncompleted(format!(
+                    "Node < {} > did not release its state within {:?}",
+                    checkpoint.node_id, CHECKPOINT_TIMEOUT
+                ))
+            })?
+    }
+}
+
+/// A link between two nodes of an instance.
+///
+/// Both ends are kept to check if messages are in flight and to relink the nodes when the
+/// instance is reconfigured.
+pub(crate) struct InstanceLink {
+    pub(crate) descriptor: LinkDescriptor,
+    pub(crate) sender: LinkSender<Message>,
+    pub(crate) receiver: LinkReceiver<Message>,
+}
+
+impl InstanceLink {
+    /// Checks if this link connects the same ports as the given descriptor.
+    fn connects(&self, descriptor: &LinkDescriptor) -> bool {
+        self.descriptor.from == descriptor.from && self.descriptor.to == descriptor.to
+    }
+}
+
+/// The changes made to an instance by
+/// [`try_apply_record`](`DataflowInstance::try_apply_record`).
+///
+/// The added nodes are not started, the removed nodes were checkpointed, if their state supports
+/// it, before being finalized. The input watermarks of the removed nodes are to be applied to
+/// their copies on the other runtimes, see
+/// [`skip_received`](`DataflowInstance::skip_received`).
+#[derive(Debug, Default)]
+pub struct RecordChanges {
+    pub added: Vec<NodeId>,
+    pub removed: Vec<NodeId>,
+    pub checkpoints: Vec<NodeCheckpoint>,
+    pub watermarks: Vec<InputWatermarks>,
 }
 
 /// Creates the [`Link`](`Link`) between the `nodes` using `links`.
@@ -50,8 +198,9 @@ pub struct DataflowInstance {
 fn create_links(
     nodes: &[NodeId],
     links: &[LinkDescriptor],
-) -> ZFResult<HashMap<NodeId, OperatorIO>> {
+) -> ZFResult<(HashMap<NodeId, OperatorIO>, Vec<InstanceLink>)> {
     let mut io: HashMap<NodeId, OperatorIO> = HashMap::with_capacity(nodes.len());
+    let mut instance_links = Vec::with_capacity(links.len());
 
     for link_desc in links {
         let upstream_node = link_desc.from.node.clone();
@@ -68,6 +217,11 @@ fn create_links(
             link_desc.from.output.clone(),
             link_desc.to.input.clone(),
         );
+        instance_links.push(InstanceLink {
+            descriptor: link_desc.clone(),
+            sender: tx.clone(),
+            receiver: rx.clone(),
+        });
 
         match io.get_mut(&upstream_node) {
             Some(operator_io) => operator_io.add_output(tx),
@@ -88,7 +242,179 @@ fn create_links(
         }
     }
 
-    Ok(io)
+    Ok((io, instance_links))
+}
+
+/// Orders the `nodes` so that every node comes after the nodes upstream of it, following `links`.
+///
+/// Loops have no such order: when only nodes within or after loops remain, a node fed by an
+/// already ordered node goes first, the one with the fewest upstream nodes left if several are.
+fn topological_order(nodes: &[NodeId], links: &[LinkDescriptor]) -> Vec<NodeId> {
+    let mut in_degrees: HashMap<NodeId, usize> = nodes.iter().map(|id| (id.clone(), 0)).collect();
+    let mut downstream: HashMap<NodeId, Vec<NodeId>> = HashMap::with_capacity(nodes.len());
+    let mut upstream: HashMap<NodeId, Vec<NodeId>> = HashMap::with_capacity(nodes.len());
+
+    for link_desc in links {
+        if !in_degrees.contains_key(&link_desc.from.node)
+            || !in_degrees.contains_key(&link_desc.to.node)
+        {
+            continue;
+        }
+
+        downstream
+            .entry(link_desc.from.node.clone())
+            .or_insert_with(Vec::new)
+            .push(link_desc.to.node.clone());
+        upstream
+            .entry(link_desc.to.node.clone())
+            .or_insert_with(Vec::new)
+            .push(link_desc.from.node.clone());
+        if let Some(degree) = in_degrees.get_mut(&link_desc.to.node) {
+            *degree += 1;
+        }
+    }
+
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut ready: VecDeque<NodeId> = nodes
+        .iter()
+        .filter(|id| in_degrees.get(*id) == Some(&0))
+        .cloned()
+        .collect();
+
+    while order.len() < nodes.len() {
+        let node = match ready.pop_front() {
+            Some(node) => node,
+            None => match nodes
+                .iter()
+                .filter(|id| !order.contains(*id))
+                .min_by_key(|id| {
+                    let fed = upstream
+                        .get(*id)
+                        .map_or(false, |up| up.iter().any(|node| order.contains(node)));
+                    (!fed, in_degrees.get(*id).copied().unwrap_or(0))
+                }) {
+                Some(node) => node.clone(),
+                None => break,
+            },
+        };
+
+        if order.contains(&node) {
+            continue;
+        }
+
+        if let Some(next) = downstream.get(&node) {
+            for id in next {
+                if let Some(degree) = in_degrees.get_mut(id) {
+                    if *degree > 0 {
+                        *degree -= 1;
+                        if *degree == 0 && !order.contains(id) {
+                            ready.push_back(id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        order.push(node);
+    }
+
+    order
+}
+
+/// Creates the runners of the nodes and connectors of the `dataflow`, taking their links from
+/// `io`.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// -  the links of a node were not created
+/// -  a runner cannot be created
+fn try_create_runners(
+    context: &InstanceContext,
+    dataflow: Dataflow,
+    io: &mut HashMap<NodeId, OperatorIO>,
+) -> ZFResult<HashMap<NodeId, NodeRunner>> {
+    let mut runners: HashMap<NodeId, NodeRunner> = HashMap::with_capacity(
+        dataflow.sources.len()
+            + dataflow.operators.len()
+            + dataflow.sinks.len()
+            + dataflow.connectors.len(),
+    );
+
+    for (id, source) in dataflow.sources.into_iter() {
+        let node_io = io.remove(&id).ok_or_else(|| {
+            ZFError::IOError(format!(
+                "Links for Source < {} > were not created.",
+                &source.id
+            ))
+        })?;
+        runners.insert(
+            id,
+            NodeRunner::new(
+                Arc::new(SourceRunner::try_new(context.clone(), source, node_io)?),
+                context.clone(),
+            ),
+        );
+    }
+
+    for (id, operator) in dataflow.operators.into_iter() {
+        let node_io = io.remove(&operator.id).ok_or_else(|| {
+            ZFError::IOError(format!(
+                "Links for Operator < {} > were not created.",
+                &operator.id
+            ))
+        })?;
+        runners.insert(
+            id,
+            NodeRunner::new(
+                Arc::new(OperatorRunner::try_new(context.clone(), operator, node_io)?),
+                context.clone(),
+            ),
+        );
+    }
+
+    for (id, sink) in dataflow.sinks.into_iter() {
+        let node_io = io.remove(&id).ok_or_else(|| {
+            ZFError::IOError(format!("Links for Sink < {} > were not created.", &sink.id))
+        })?;
+        runners.insert(
+            id,
+            NodeRunner::new(
+                Arc::new(SinkRunner::try_new(context.clone(), sink, node_io)?),
+                context.clone(),
+            ),
+        );
+    }
+
+    for (id, connector) in dataflow.connectors.into_iter() {
+        let node_io = io.remove(&id).ok_or_else(|| {
+            ZFError::IOError(format!(
+                "Links for Connector < {} > were not created.",
+                &connector.id
+            ))
+        })?;
+        match connector.kind {
+            ZFConnectorKind::Sender => {
+                runners.insert(
+                    id,
+                    NodeRunner::new(
+                        Arc::new(ZenohSender::try_new(context.clone(), connector, node_io)?),
+                        context.clone(),
+                    ),
+                );
+            }
+            ZFConnectorKind::Receiver => {
+                runners.insert(
+                    id,
+                    NodeRunner::new(
+                        Arc::new(ZenohReceiver::try_new(context.clone(), connector, node_io)?),
+                        context.clone(),
+                    ),
+                );
+            }
+        }
+    }
+
+    Ok(runners)
 }
 
 impl DataflowInstance {
@@ -116,95 +442,24 @@ impl DataflowInstance {
         node_ids.append(&mut dataflow.sinks.keys().cloned().collect::<Vec<_>>());
         node_ids.append(&mut dataflow.connectors.keys().cloned().collect::<Vec<_>>());
 
-        let mut links = create_links(&node_ids, &dataflow.links)?;
+        let (mut io, links) = create_links(&node_ids, &dataflow.links)?;
+        let stop_order = topological_order(&node_ids, &dataflow.links);
 
         let context = InstanceContext {
-            flow_id: dataflow.flow_id,
+            flow_id: dataflow.flow_id.clone(),
             instance_id: dataflow.uuid,
-            runtime: dataflow.context,
+            runtime: dataflow.context.clone(),
         };
 
         // The links were created, we can generate the Runners.
-        let mut runners: HashMap<NodeId, NodeRunner> = HashMap::with_capacity(node_ids.len());
-
-        for (id, source) in dataflow.sources.into_iter() {
-            let io = links.remove(&id).ok_or_else(|| {
-                ZFError::IOError(format!(
-                    "Links for Source < {} > were not created.",
-                    &source.id
-                ))
-            })?;
-            runners.insert(
-                id,
-                NodeRunner::new(
-                    Arc::new(SourceRunner::try_new(context.clone(), source, io)?),
-                    context.clone(),
-                ),
-            );
-        }
-
-        for (id, operator) in dataflow.operators.into_iter() {
-            let io = links.remove(&operator.id).ok_or_else(|| {
-                ZFError::IOError(format!(
-                    "Links for Operator < {} > were not created.",
-                    &operator.id
-                ))
-            })?;
-            runners.insert(
-                id,
-                NodeRunner::new(
-                    Arc::new(OperatorRunner::try_new(context.clone(), operator, io)?),
-                    context.clone(),
-                ),
-            );
-        }
-
-        for (id, sink) in dataflow.sinks.into_iter() {
-            let io = links.remove(&id).ok_or_else(|| {
-                ZFError::IOError(format!("Links for Sink < {} > were not created.", &sink.id))
-            })?;
-            runners.insert(
-                id,
-                NodeRunner::new(
-                    Arc::new(SinkRunner::try_new(context.clone(), sink, io)?),
-                    context.clone(),
-                ),
-            );
-        }
-
-        for (id, connector) in dataflow.connectors.into_iter() {
-            let io = links.remove(&id).ok_or_else(|| {
-                ZFError::IOError(format!(
-                    "Links for Connector < {} > were not created.",
-                    &connector.id
-                ))
-            })?;
-            match connector.kind {
-                ZFConnectorKind::Sender => {
-                    runners.insert(
-                        id,
-                        NodeRunner::new(
-                            Arc::new(ZenohSender::try_new(context.clone(), connector, io)?),
-                            context.clone(),
-                        ),
-                    );
-                }
-                ZFConnectorKind::Receiver => {
-                    runners.insert(
-                        id,
-                        NodeRunner::new(
-                            Arc::new(ZenohReceiver::try_new(context.clone(), connector, io)?),
-                            context.clone(),
-                        ),
-                    );
-                }
-            }
-        }
+        let runners = try_create_runners(&context, dataflow, &mut io)?;
 
         Ok(Self {
             context,
             runners,
             managers: HashMap::with_capacity(node_ids.len()),
+            links,
+            stop_order,
         })
     }
 
@@ -267,49 +522,183 @@ impl DataflowInstance {
             .collect()
     }
 
-    /// Starts all the sources in this instance.
+    /// Returns the error of the last run of the given node that failed, if any, and forgets it.
+    ///
+    /// A node whose run fails is restarted, see
+    /// [`NodeRunner::take_failure`](`runners::NodeRunner::take_failure`).
     ///
+    /// # Errors
+    /// If fails if the node is not found.
+    pub async fn take_node_failure(&self, node_id: &NodeId) -> ZFResult<Option<ZFError>> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        Ok(runner.take_failure().await)
+    }
+
+    /// Returns the nodes of this instance that were started and not stopped since, with their
+    /// kind.
+    pub fn get_started_nodes(&self) -> Vec<(NodeId, RunnerKind)> {
+        self.managers
+            .keys()
+            .filter_map(|id| {
+                self.runners
+                    .get(id)
+                    .map(|runner| (id.clone(), runner.get_kind()))
+            })
+            .collect()
+    }
+
+    /// Starts all the sources in this instance.
     ///
-    /// **Note:** Not implemented.
+    /// The other nodes should be started first, see
+    /// [`start_nodes`](`DataflowInstance::start_nodes`), so that no data is lost.
     ///
     /// # Errors
     /// An error variant is returned in case of:
-    /// -  sources already started.
+    /// -  a source cannot be started.
     pub async fn start_sources(&mut self) -> ZFResult<()> {
-        Err(ZFError::Unimplemented)
+        for id in self.get_sources() {
+            self.start_node(&id).await?;
+        }
+        Ok(())
     }
 
-    /// Starts all the nodes in this instance.
+    /// Starts all the nodes in this instance, except the sources.
     ///
-    /// **Note:** Not implemented.
+    /// The sinks are started first, then the operators and the connectors.
     ///
     /// # Errors
     /// An error variant is returned in case of:
-    /// -  nodes already started.
+    /// -  a node cannot be started.
     pub async fn start_nodes(&mut self) -> ZFResult<()> {
-        Err(ZFError::Unimplemented)
+        let mut nodes = self.get_sinks();
+        nodes.extend(self.get_operators());
+        nodes.extend(self.get_connectors());
+        for id in nodes {
+            self.start_node(&id).await?;
+        }
+        Ok(())
     }
 
     /// Stops all the sources in this instance.
     ///
-    /// **Note:** Not implemented.
-    ///
     /// # Errors
     /// An error variant is returned in case of:
-    /// -  sources already stopped.
+    /// -  a source is not running.
     pub async fn stop_sources(&mut self) -> ZFResult<()> {
-        Err(ZFError::Unimplemented)
+        for id in self.get_sources() {
+            self.stop_node(&id).await?;
+        }
+        Ok(())
     }
 
-    /// Stops all the sources in this instance.
-    ///
-    /// **Note:** Not implemented.
+    /// Stops all the nodes in this instance, except the sources.
     ///
     /// # Errors
     /// An error variant is returned in case of:
-    /// -  nodes already stopped.
+    /// -  a node is not running.
     pub async fn stop_nodes(&mut self) -> ZFResult<()> {
-        Err(ZFError::Unimplemented)
+        let mut nodes = self.get_sinks();
+        nodes.extend(self.get_operators());
+        nodes.extend(self.get_connectors());
+        for id in nodes {
+            self.stop_node(&id).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of messages waiting in the links of this instance.
+    pub fn pending_messages(&self) -> usize {
+        self.links.iter().map(|link| link.receiver.len()).sum()
+    }
+
+    /// Checks if no message is waiting in the links of this instance and no running node is
+    /// executing its `run` or holding inputs its input rule did not accept yet.
+    pub async fn is_idle(&self) -> bool {
+        if self.pending_messages() > 0 {
+            return false;
+        }
+
+        for manager in self.managers.values() {
+            if manager.get_run_elapsed().await.is_some() || manager.get_buffered_tokens().await > 0
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Waits for this instance to be idle, for at most `timeout`.
+    ///
+    /// It returns `true` if the instance is idle, `false` if the timeout expired.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let instance = self;
+        // Checking the instance cannot fail.
+        wait_all_idle(timeout, move || async move { Ok(instance.is_idle().await) })
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Stops the sources of this instance that are running.
+    ///
+    /// # Errors
+    /// An error variant is returned if a source cannot be stopped.
+    pub async fn stop_running_sources(&mut self) -> ZFResult<()> {
+        for id in self.get_sources() {
+            if self.managers.contains_key(&id) {
+                self.stop_node(&id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the nodes of this instance that are running, upstream nodes first.
+    ///
+    /// # Errors
+    /// An error variant is returned if a node cannot be stopped.
+    pub async fn stop_running_nodes(&mut self) -> ZFResult<()> {
+        let mut nodes = self.stop_order.clone();
+        nodes.extend(self.managers.keys().cloned().collect::<Vec<_>>());
+        for id in nodes {
+            if self.managers.contains_key(&id) {
+                self.stop_node(&id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops this instance without discarding the messages in flight.
+    ///
+    /// The sources are stopped first. Then, for at most `timeout`, the other nodes process the
+    /// messages waiting in the links. Finally they are stopped, upstream nodes first.
+    ///
+    /// It returns `true` if all the messages were processed, `false` if the timeout expired and
+    /// the remaining ones were discarded.
+    ///
+    /// An instance spanning several runtimes must be drained on all of them at once, see
+    /// [`wait_all_idle`](`wait_all_idle`).
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  a node cannot be stopped.
+    pub async fn drain(&mut self, timeout: Duration) -> ZFResult<bool> {
+        self.stop_running_sources().await?;
+
+        let drained = self.wait_idle(timeout).await;
+        if !drained {
+            log::warn!(
+                "[Instance: {}] Not drained within {:?}, discarding {} message(s)",
+                self.context.instance_id,
+                timeout,
+                self.pending_messages()
+            );
+        }
+
+        self.stop_running_nodes().await?;
+        Ok(drained)
     }
 
     /// Checks if the given node is running.
@@ -327,6 +716,94 @@ impl DataflowInstance {
         }
     }
 
+    /// Returns the counters of the deadline misses of the given node.
+    ///
+    /// # Errors
+    /// If fails if the node is not found.
+    pub async fn get_deadline_miss_stats(&self, node_id: &NodeId) -> ZFResult<DeadlineMissStats> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+
+        Ok(runner.get_deadline_miss_stats().await)
+    }
+
+    /// Returns the health of the given node.
+    ///
+    /// Without `hang_threshold`, a running node is never reported as hung.
+    ///
+    /// # Errors
+    /// If fails if the node is not found.
+    pub async fn get_node_health(
+        &self,
+        node_id: &NodeId,
+        hang_threshold: Option<Duration>,
+    ) -> ZFResult<NodeHealth> {
+        if !self.is_node_running(node_id).await? {
+            return Ok(NodeHealth::Stopped);
+        }
+
+        let elapsed = match self.managers.get(node_id) {
+            Some(manager) => manager.get_run_elapsed().await,
+            None => None,
+        };
+
+        match (elapsed, hang_threshold) {
+            (Some(elapsed), Some(threshold)) if elapsed > threshold => {
+                Ok(NodeHealth::Hung { elapsed })
+            }
+            _ if self.is_node_paused(node_id).await? => Ok(NodeHealth::Paused),
+            _ => Ok(NodeHealth::Running),
+        }
+    }
+
+    /// Returns the running nodes whose current execution of `run` lasts for more than
+    /// `hang_threshold`, along with the duration of that execution.
+    pub async fn get_hung_nodes(&self, hang_threshold: Duration) -> Vec<(NodeId, Duration)> {
+        let mut hung_nodes = vec![];
+        for (node_id, manager) in self.managers.iter() {
+            if !manager.is_running().await {
+                continue;
+            }
+
+            if let Some(elapsed) = manager.get_run_elapsed().await {
+                if elapsed > hang_threshold {
+                    hung_nodes.push((node_id.clone(), elapsed));
+                }
+            }
+        }
+
+        hung_nodes
+    }
+
+    /// Restarts the given node: it is stopped then started again.
+    ///
+    /// A hung node may never acknowledge the stop: after `stop_timeout` the previous execution
+    /// is abandoned and the node is started anyway.
+    ///
+    /// # Errors
+    /// If fails if the node is not found or it is not running.
+    pub async fn restart_node(&mut self, node_id: &NodeId, stop_timeout: Duration) -> ZFResult<()> {
+        self.detach_node(node_id)?.stop_within(stop_timeout).await?;
+        self.start_node(node_id).await
+    }
+
+    /// Detaches the given node from the instance, returning the `RunnerManager` to stop it.
+    ///
+    /// The node is no longer considered as running by the instance. This allows stopping it
+    /// without holding the instance, e.g. with
+    /// [`RunnerManager::stop_within`](`RunnerManager::stop_within`), before starting it again with
+    /// [`start_node`](`DataflowInstance::start_node`).
+    ///
+    /// # Errors
+    /// If fails if the node is not found or it is not running.
+    pub fn detach_node(&mut self, node_id: &NodeId) -> ZFResult<RunnerManager> {
+        self.managers
+            .remove(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))
+    }
+
     /// Starts the given node.
     ///
     /// # Errors
@@ -354,6 +831,457 @@ impl DataflowInstance {
         manager.await
     }
 
+    /// Pauses the given node.
+    ///
+    /// Once done with its current execution of `run`, the node waits until it is resumed. Its
+    /// state is kept and the messages sent to it wait in its links.
+    ///
+    /// # Errors
+    /// If fails if the node is not found or if it cannot be paused (i.e. a connector).
+    pub async fn pause_node(&self, node_id: &NodeId) -> ZFResult<()> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        runner.pause().await
+    }
+
+    /// Resumes the given node where it was paused.
+    ///
+    /// # Errors
+    /// If fails if the node is not found or if it cannot be paused (i.e. a connector).
+    pub async fn resume_node(&self, node_id: &NodeId) -> ZFResult<()> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        runner.resume().await
+    }
+
+    /// Checks if the given node is paused.
+    ///
+    /// # Errors
+    /// If fails if the node is not found.
+    pub async fn is_node_paused(&self, node_id: &NodeId) -> ZFResult<bool> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        Ok(runner.is_paused().await)
+    }
+
+    /// Pauses this instance: the sources first, then the other nodes.
+    ///
+    /// The connectors are not paused, what they receive waits in the links of the paused nodes.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  a node cannot be paused.
+    pub async fn pause(&self) -> ZFResult<()> {
+        let mut nodes = self.get_sources();
+        nodes.extend(self.get_operators());
+        nodes.extend(self.get_sinks());
+        for id in nodes {
+            self.pause_node(&id).await?;
+        }
+        Ok(())
+    }
+
+    /// Resumes this instance: the other nodes first, then the sources.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  a node cannot be resumed.
+    pub async fn resume(&self) -> ZFResult<()> {
+        let mut nodes = self.get_sinks();
+        nodes.extend(self.get_operators());
+        nodes.extend(self.get_sources());
+        for id in nodes {
+            self.resume_node(&id).await?;
+        }
+        Ok(())
+    }
+
+    /// Takes a checkpoint of the state of the given node.
+    ///
+    /// `None` is returned if the state of the node does not support checkpointing. The state is
+    /// serialized between two iterations of the node, waiting at most
+    /// [`CHECKPOINT_TIMEOUT`](`CHECKPOINT_TIMEOUT`) for the node to release it.
+    ///
+    /// # Errors
+    /// If fails if the node is not found, if it does not release its state in time or if the
+    /// serialization fails.
+    pub async fn checkpoint_node(&self, node_id: &NodeId) -> ZFResult<Option<NodeCheckpoint>> {
+        self.get_checkpointer(node_id)?.checkpoint().await
+    }
+
+    /// Returns a [`NodeCheckpointer`](`NodeCheckpointer`) of the given node, to checkpoint or
+    /// restore it without holding this instance.
+    ///
+    /// # Errors
+    /// If fails if the node is not found.
+    pub fn get_checkpointer(&self, node_id: &NodeId) -> ZFResult<NodeCheckpointer> {
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        Ok(NodeCheckpointer {
+            context: self.context.clone(),
+            runner: runner.clone(),
+        })
+    }
+
+    /// Returns a [`NodeCheckpointer`](`NodeCheckpointer`) of each node of this instance.
+    pub fn get_checkpointers(&self) -> Vec<NodeCheckpointer> {
+        self.runners
+            .values()
+            .map(|runner| NodeCheckpointer {
+                context: self.context.clone(),
+                runner: runner.clone(),
+            })
+            .collect()
+    }
+
+    /// Takes a checkpoint of the state of all the nodes of this instance that support it.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  a node does not release its state in time
+    /// -  the serialization of a state fails
+    pub async fn checkpoint(&self) -> ZFResult<Vec<NodeCheckpoint>> {
+        let mut checkpoints = vec![];
+        for id in self.get_nodes() {
+            if let Some(checkpoint) = self.checkpoint_node(&id).await? {
+                checkpoints.push(checkpoint);
+            }
+        }
+        Ok(checkpoints)
+    }
+
+    /// Restores the state of a node from the given checkpoint.
+    ///
+    /// The checkpoint can come from another instance of the same flow. As for
+    /// [`checkpoint_node`](`DataflowInstance::checkpoint_node`), this waits at most
+    /// [`CHECKPOINT_TIMEOUT`](`CHECKPOINT_TIMEOUT`) for the node to release its state.
+    ///
+    /// # Errors
+    /// If fails if the node is not found, if it does not release its state in time, if its state
+    /// does not support checkpointing or if the deserialization fails.
+    pub async fn restore_node(&self, checkpoint: &NodeCheckpoint) -> ZFResult<()> {
+        self.get_checkpointer(&checkpoint.node_id)?
+            .restore(checkpoint)
+            .await
+    }
+
+    /// Reconfigures this instance to match the given record, e.g. after a node was migrated to
+    /// another runtime.
+    ///
+    /// Only the differences are applied: the nodes and connectors no longer mapped to this
+    /// runtime are checkpointed, if their state supports it, then finalized and removed; the new
+    /// ones are loaded; the links between the remaining nodes are kept, with the messages waiting
+    /// in them. The running nodes whose links change are stopped and started again once relinked,
+    /// the added nodes are not started.
+    ///
+    /// The messages waiting in the links of a removed node are forwarded to the links replacing
+    /// them: the ones sent to a remaining node go to its new input link, the ones sent by a
+    /// remaining node go to the new link of the same output, i.e. to the sender connector
+    /// towards the new runtime of the removed node. The messages that cannot be forwarded
+    /// without being duplicated are discarded.
+    ///
+    /// The timestamp of the last message a removed node received on each of its inputs is
+    /// returned, for its copy on another runtime to skip the messages it already received.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  a node cannot be stopped or started
+    /// -  a new node cannot be loaded
+    /// -  a link cannot be created
+    pub async fn try_apply_record(&mut self, record: &DataFlowRecord) -> ZFResult<RecordChanges> {
+        let runtime = &self.context.runtime.runtime_name;
+        let nodes: Vec<NodeId> = record
+            .sources
+            .values()
+            .filter(|source| &source.runtime == runtime)
+            .map(|source| source.id.clone())
+            .chain(
+                record
+                    .operators
+                    .values()
+                    .filter(|operator| &operator.runtime == runtime)
+                    .map(|operator| operator.id.clone()),
+            )
+            .chain(
+                record
+                    .sinks
+                    .values()
+                    .filter(|sink| &sink.runtime == runtime)
+                    .map(|sink| sink.id.clone()),
+            )
+            .chain(
+                record
+                    .connectors
+                    .iter()
+                    .filter(|connector| &connector.runtime == runtime)
+                    .map(|connector| connector.id.clone()),
+            )
+            .collect();
+
+        let removed: Vec<NodeId> = self
+            .runners
+            .keys()
+            .filter(|id| !nodes.contains(id))
+            .cloned()
+            .collect();
+        let added: Vec<NodeId> = nodes
+            .iter()
+            .filter(|id| !self.runners.contains_key(*id))
+            .cloned()
+            .collect();
+
+        let local_links: Vec<LinkDescriptor> = record
+            .links
+            .iter()
+            .filter(|link| nodes.contains(&link.from.node) && nodes.contains(&link.to.node))
+            .cloned()
+            .collect();
+        let created: Vec<LinkDescriptor> = local_links
+            .iter()
+            .filter(|desc| !self.links.iter().any(|link| link.connects(desc)))
+            .cloned()
+            .collect();
+        let (kept, dropped): (Vec<InstanceLink>, Vec<InstanceLink>) = self
+            .links
+            .drain(..)
+            .partition(|link| local_links.iter().any(|desc| link.connects(desc)));
+        self.links = kept;
+
+        // The running nodes being removed or relinked are stopped first.
+        let mut to_stop = removed.clone();
+        for desc in dropped
+            .iter()
+            .map(|link| &link.descriptor)
+            .chain(created.iter())
+        {
+            for id in [&desc.from.node, &desc.to.node] {
+                if self.runners.contains_key(id) && !to_stop.contains(id) {
+                    to_stop.push(id.clone());
+                }
+            }
+        }
+
+        let mut to_restart = vec![];
+        for id in to_stop.iter() {
+            if self.managers.contains_key(id) {
+                self.stop_node(id).await?;
+                if !removed.contains(id) {
+                    to_restart.push(id.clone());
+                }
+            }
+        }
+
+        let mut checkpoints = vec![];
+        for id in removed.iter() {
+            match self.checkpoint_node(id).await {
+                Ok(Some(checkpoint)) => checkpoints.push(checkpoint),
+                Ok(None) => (),
+                Err(e) => log::warn!("[Node: {}] Unable to checkpoint: {:?}", id, e),
+            }
+        }
+
+        let mut watermarks: HashMap<NodeId, InputWatermarks> = HashMap::new();
+        for link in dropped.iter() {
+            let node_id = &link.descriptor.to.node;
+            if !removed.contains(node_id) || record.find_node_runtime(node_id).is_none() {
+                continue;
+            }
+
+            let last_received = link.receiver.last_received();
+            if !last_received.is_empty() {
+                watermarks
+                    .entry(node_id.clone())
+                    .or_insert_with(|| InputWatermarks {
+                        flow_id: self.context.flow_id.clone(),
+                        instance_id: self.context.instance_id,
+                        node_id: node_id.clone(),
+                        inputs: HashMap::new(),
+                    })
+                    .inputs
+                    .insert(
+                        link.descriptor.to.input.clone(),
+                        last_received
+                            .iter()
+                            .map(|message| message.get_timestamp())
+                            .collect(),
+                    );
+            }
+        }
+
+        // The messages waiting in the dropped links are taken before their ends are removed.
+        let mut waiting = Vec::with_capacity(dropped.len());
+        for link in dropped.iter() {
+            let messages: Vec<Arc<Message>> = link.receiver.receiver.try_iter().collect();
+            if !messages.is_empty() {
+                waiting.push((link.descriptor.clone(), messages));
+            }
+
+            if removed.contains(&link.descriptor.from.node) {
+                continue;
+            }
+
+            if let Some(runner) = self.runners.get(&link.descriptor.from.node) {
+                runner.remove_output(&link.sender).await?;
+            }
+        }
+
+        for id in removed.iter() {
+            if let Some(runner) = self.runners.remove(id) {
+                if let Err(e) = runner.clean().await {
+                    log::warn!("[Node: {}] Unable to finalize: {:?}", id, e);
+                }
+            }
+        }
+
+        let mut io: HashMap<NodeId, OperatorIO> = HashMap::with_capacity(added.len());
+        for desc in created.into_iter() {
+            let (tx, rx) = link::<Message>(None, desc.from.output.clone(), desc.to.input.clone());
+
+            match self.runners.get(&desc.from.node) {
+                Some(runner) => runner.add_output(tx.clone()).await?,
+                None => io
+                    .entry(desc.from.node.clone())
+                    .or_insert_with(OperatorIO::default)
+                    .add_output(tx.clone()),
+            }
+
+            match self.runners.get(&desc.to.node) {
+                Some(runner) => runner.add_input(rx.clone()).await?,
+                None => io
+                    .entry(desc.to.node.clone())
+                    .or_insert_with(OperatorIO::default)
+                    .try_add_input(rx.clone())?,
+            }
+
+            self.links.push(InstanceLink {
+                descriptor: desc,
+                sender: tx,
+                receiver: rx,
+            });
+        }
+
+        if !added.is_empty() {
+            let mut partial = record.clone();
+            partial.sources.retain(|id, _| added.contains(id));
+            partial.operators.retain(|id, _| added.contains(id));
+            partial.sinks.retain(|id, _| added.contains(id));
+            partial
+                .connectors
+                .retain(|connector| added.contains(&connector.id));
+
+            // Loading the nodes can block, e.g. to start their node hosts.
+            let runtime = self.context.runtime.clone();
+            let dataflow =
+                async_std::task::spawn_blocking(move || Dataflow::try_new(runtime, partial))
+                    .await?;
+            let runners = try_create_runners(&self.context, dataflow, &mut io)?;
+            self.runners.extend(runners);
+        }
+
+        for (descriptor, messages) in waiting.into_iter() {
+            let forward_to = if !removed.contains(&descriptor.to.node) {
+                self.links
+                    .iter()
+                    .find(|link| link.descriptor.to == descriptor.to)
+            } else if !removed.contains(&descriptor.from.node) {
+                // Only a new link of the same output: the messages sent to an existing sender
+                // connector would also reach its other receivers.
+                self.links.iter().find(|link| {
+                    link.descriptor.from == descriptor.from
+                        && added.contains(&link.descriptor.to.node)
+                        && record.connectors.iter().any(|connector| {
+                            connector.id == link.descriptor.to.node
+                                && connector.kind == ZFConnectorKind::Sender
+                        })
+                })
+            } else {
+                None
+            };
+
+            match forward_to {
+                Some(link) => {
+                    log::debug!(
+                        "[Instance: {}] Forwarding {} message(s) sent from < {} > to < {} > to the link from < {} > to < {} >",
+                        self.context.instance_id,
+                        messages.len(),
+                        descriptor.from.node,
+                        descriptor.to.node,
+                        link.descriptor.from.node,
+                        link.descriptor.to.node
+                    );
+                    for message in messages.into_iter() {
+                        link.sender.send(message).await?;
+                    }
+                }
+                None => log::warn!(
+                    "[Instance: {}] Discarding {} message(s) sent from < {} > to < {} >",
+                    self.context.instance_id,
+                    messages.len(),
+                    descriptor.from.node,
+                    descriptor.to.node
+                ),
+            }
+        }
+
+        self.stop_order = topological_order(&nodes, &local_links);
+
+        // The sources are started last.
+        let sources = self.get_sources();
+        to_restart.sort_by_key(|id| sources.contains(id));
+        for id in to_restart.iter() {
+            self.start_node(id).await?;
+        }
+
+        Ok(RecordChanges {
+            added,
+            removed,
+            checkpoints,
+            watermarks: watermarks.into_values().collect(),
+        })
+    }
+
+    /// Skips the messages that a node already received on another runtime before it was
+    /// migrated, given the [`InputWatermarks`](`InputWatermarks`) returned when it was removed
+    /// there.
+    ///
+    /// The receivers of the node on this runtime subscribed before the ones on the other runtime
+    /// were removed: the messages both received are only delivered once. The inputs of the node
+    /// that are not fed by a receiver are left untouched.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// -  the upstream node of an input is not found
+    /// -  the messages waiting in a link cannot be sent back
+    pub async fn skip_received(&self, watermarks: &InputWatermarks) -> ZFResult<()> {
+        for (input, timestamps) in watermarks.inputs.iter() {
+            let link = match self.links.iter().find(|link| {
+                link.descriptor.to.node == watermarks.node_id && &link.descriptor.to.input == input
+            }) {
+                Some(link) => link,
+                None => continue,
+            };
+
+            let runner = self
+                .runners
+                .get(&link.descriptor.from.node)
+                .ok_or_else(|| ZFError::NodeNotFound(link.descriptor.from.node.clone()))?;
+            match runner.skip_received(&link.receiver, timestamps).await {
+                Ok(()) | Err(ZFError::Unsupported) => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
     /// Finalized the given node.
     /// Finalizing a node means cleaning up its state.
     ///
@@ -367,32 +1295,79 @@ impl DataflowInstance {
         runner.clean().await
     }
 
-    /// Starts the recording for the given source.
+    /// Starts the recording of the given output port of the given node.
+    ///
+    /// If no port is provided, the node must have a single output (e.g. a Source), this output is
+    /// recorded.
     ///
     /// It returns the key expression where the recording is stored.
     ///
     /// # Errors
-    /// If fails if the node is not found.
-    pub async fn start_recording(&self, node_id: &NodeId) -> ZFResult<String> {
+    /// If fails if:
+    /// - the node is not found,
+    /// - the port is not provided and the node does not have exactly one output,
+    /// - the port is already recorded.
+    pub async fn start_recording(
+        &self,
+        node_id: &NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String> {
         let manager = self
             .managers
             .get(node_id)
             .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
-        manager.start_recording().await
+        let port_id = self.get_recorded_port(node_id, port_id)?;
+        manager.start_recording(&port_id).await
     }
 
-    /// Stops the recording for the given source.
+    /// Stops the recording of the given output port of the given node.
+    ///
+    /// If no port is provided, the node must have a single output (e.g. a Source), the recording
+    /// of this output is stopped.
     ///
     /// It returns the key expression where the recording is stored.
     ///
     /// # Errors
-    /// If fails if the node is not found.
-    pub async fn stop_recording(&self, node_id: &NodeId) -> ZFResult<String> {
+    /// If fails if:
+    /// - the node is not found,
+    /// - the port is not provided and the node does not have exactly one output,
+    /// - the port is not recorded.
+    pub async fn stop_recording(
+        &self,
+        node_id: &NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String> {
         let manager = self
             .managers
             .get(node_id)
             .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
-        manager.stop_recording().await
+        let port_id = self.get_recorded_port(node_id, port_id)?;
+        manager.stop_recording(&port_id).await
+    }
+
+    /// Returns the output port to record: either the provided one or, if the node has a single
+    /// output, that one.
+    ///
+    /// # Errors
+    /// It fails if the node is not found or if no port is provided and the node does not have
+    /// exactly one output.
+    fn get_recorded_port(&self, node_id: &NodeId, port_id: Option<PortId>) -> ZFResult<PortId> {
+        if let Some(port_id) = port_id {
+            return Ok(port_id);
+        }
+
+        let runner = self
+            .runners
+            .get(node_id)
+            .ok_or_else(|| ZFError::NodeNotFound(node_id.clone()))?;
+        let mut outputs = runner.get_outputs().into_keys();
+        match (outputs.next(), outputs.next()) {
+            (Some(port_id), None) => Ok(port_id),
+            _ => Err(ZFError::InvalidData(format!(
+                "Node < {} > does not have a single output, the port to record must be provided",
+                node_id
+            ))),
+        }
     }
 
     /// Assumes the source is already stopped before calling the start replay!
@@ -406,7 +1381,13 @@ impl DataflowInstance {
     /// - the source is not stopped
     /// - the node is not a source
     /// - the key expression is not point to a recording
-    pub async fn start_replay(&mut self, source_id: &NodeId, resource: String) -> ZFResult<NodeId> {
+    /// - the replay options are not valid
+    pub async fn start_replay(
+        &mut self,
+        source_id: &NodeId,
+        resource: String,
+        options: ReplayOptions,
+    ) -> ZFResult<NodeId> {
         let runner = self
             .runners
             .get(source_id)
@@ -442,6 +1423,7 @@ impl DataflowInstance {
             _output_type,
             output_links,
             resource,
+            options,
         )?;
 
         let replay_runner = NodeRunner::new(Arc::new(replay_node), self.context.clone());
@@ -462,3 +1444,70 @@ impl DataflowInstance {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::topological_order;
+    use crate::model::link::LinkDescriptor;
+    use crate::model::{InputDescriptor, OutputDescriptor};
+    use crate::NodeId;
+
+    fn link(from: &str, to: &str) -> LinkDescriptor {
+        LinkDescriptor {
+            from: OutputDescriptor {
+                node: from.into(),
+                output: "out".into(),
+            },
+            to: InputDescriptor {
+                node: to.into(),
+                input: from.into(),
+            },
+            size: None,
+            queueing_policy: None,
+            priority: None,
+        }
+    }
+
+    fn position(order: &[NodeId], node: &str) -> usize {
+        order.iter().position(|id| id.as_ref() == node).unwrap()
+    }
+
+    #[test]
+    fn topological_order_upstream_first() {
+        let nodes: Vec<NodeId> = vec!["sink".into(), "op-2".into(), "op-1".into(), "src".into()];
+        let links = vec![
+            link("src", "op-1"),
+            link("src", "op-2"),
+            link("op-1", "op-2"),
+            link("op-2", "sink"),
+            link("op-1", "remote"),
+        ];
+
+        let order = topological_order(&nodes, &links);
+        assert_eq!(order.len(), 4);
+        assert!(position(&order, "src") < position(&order, "op-1"));
+        assert!(position(&order, "op-1") < position(&order, "op-2"));
+        assert!(position(&order, "op-2") < position(&order, "sink"));
+    }
+
+    #[test]
+    fn topological_order_with_loop() {
+        let nodes: Vec<NodeId> = vec![
+            "sink".into(),
+            "loop-end".into(),
+            "loop-start".into(),
+            "src".into(),
+        ];
+        let links = vec![
+            link("src", "loop-start"),
+            link("loop-start", "loop-end"),
+            link("loop-end", "loop-start"),
+            link("loop-end", "sink"),
+        ];
+
+        let order = topological_order(&nodes, &links);
+        assert_eq!(order.len(), 4);
+        assert_eq!(order[0].as_ref(), "src");
+        assert_eq!(order[3].as_ref(), "sink");
+    }
+}
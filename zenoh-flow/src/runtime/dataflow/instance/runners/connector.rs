@@ -18,23 +18,28 @@ use crate::async_std::sync::{Arc, Mutex};
 use crate::model::connector::ZFConnectorRecord;
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
 use crate::runtime::dataflow::instance::runners::operator::OperatorIO;
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
 use crate::runtime::dataflow::instance::runners::{Runner, RunnerKind};
 use crate::runtime::message::Message;
 use crate::runtime::InstanceContext;
 use crate::{NodeId, PortId, PortType, ZFError, ZFResult};
 use async_trait::async_trait;
 use futures::prelude::*;
+use uhlc::{Timestamp, ID};
 use zenoh::net::protocol::io::SplitBuffer;
 use zenoh::publication::CongestionControl;
 
 /// The `ZenohSender` is the connector that sends the data to Zenoh
 /// when nodes are running on different runtimes.
+///
+/// The messages it sends can be recorded.
 #[derive(Clone)]
 pub struct ZenohSender {
     pub(crate) id: NodeId,
     pub(crate) context: InstanceContext,
     pub(crate) record: ZFConnectorRecord,
     pub(crate) is_running: Arc<Mutex<bool>>,
+    pub(crate) recorders: Recorders,
     pub(crate) link: Arc<Mutex<Option<LinkReceiver<Message>>>>,
 }
 
@@ -58,11 +63,18 @@ impl ZenohSender {
             ))
         })?;
 
+        let recorders = Recorders::new(
+            &context,
+            &record.id,
+            std::iter::once((&port_id, &record.link_id.port_type)),
+        );
+
         Ok(Self {
             id: record.id.clone(),
             context,
             record,
             is_running: Arc::new(Mutex::new(false)),
+            recorders,
             link: Arc::new(Mutex::new(Some(link))),
         })
     }
@@ -82,17 +94,20 @@ impl ZenohSender {
     async fn iteration(&self) -> ZFResult<()> {
         log::debug!("ZenohSender - {} - Started", self.record.resource);
         if let Some(link) = &*self.link.lock().await {
+            let session = self.context.runtime.session.get().await?;
             while let Ok((_, message)) = link.recv().await {
                 log::trace!("ZenohSender IN <= {:?} ", message);
 
                 let serialized = message.serialize_bincode()?;
                 log::trace!("ZenohSender - {}=>{:?} ", self.record.resource, serialized);
-                self.context
-                    .runtime
-                    .session
+                session
                     .put(&self.record.resource, serialized)
                     .congestion_control(CongestionControl::Block)
                     .await?;
+
+                self.recorders
+                    .record(&self.record.link_id.port_id, message)
+                    .await?;
             }
         } else {
             return Err(ZFError::Disconnected);
@@ -171,16 +186,16 @@ impl Runner for ZenohSender {
         HashMap::with_capacity(0)
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn start_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.start(port_id).await
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.stop(port_id).await
     }
 
-    async fn is_recording(&self) -> bool {
-        false
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        self.recorders.recording_ports().await
     }
 
     async fn is_running(&self) -> bool {
@@ -198,13 +213,21 @@ impl Runner for ZenohSender {
 
 /// A `ZenohReceiver` receives the messages from Zenoh when nodes are running
 /// on different runtimes.
+///
+/// The messages it receives can be recorded.
+///
+/// The messages that its node already received from another receiver, while the node was
+/// migrated, are skipped. The messages of each sender, told apart by the ID of their timestamps,
+/// are skipped up to the last one the node received from it.
 #[derive(Clone)]
 pub struct ZenohReceiver {
     pub(crate) id: NodeId,
     pub(crate) context: InstanceContext,
     pub(crate) record: ZFConnectorRecord,
     pub(crate) is_running: Arc<Mutex<bool>>,
+    pub(crate) recorders: Recorders,
     pub(crate) link: Arc<Mutex<Option<LinkSender<Message>>>>,
+    pub(crate) skip_until: Arc<Mutex<HashMap<ID, Timestamp>>>,
 }
 
 impl ZenohReceiver {
@@ -237,13 +260,20 @@ impl ZenohReceiver {
         }
 
         let link = Some(links.remove(0));
+        let recorders = Recorders::new(
+            &context,
+            &record.id,
+            std::iter::once((&port_id, &record.link_id.port_type)),
+        );
 
         Ok(Self {
             id: record.id.clone(),
             context,
             record,
             is_running: Arc::new(Mutex::new(false)),
+            recorders,
             link: Arc::new(Mutex::new(link)),
+            skip_until: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -251,6 +281,14 @@ impl ZenohReceiver {
     async fn start(&self) {
         *self.is_running.lock().await = true;
     }
+
+    /// Checks if the message with the given `timestamp` was already received by the node, given
+    /// the last timestamp it received from each sender.
+    fn is_received(skip_until: &HashMap<ID, Timestamp>, timestamp: &Timestamp) -> bool {
+        skip_until
+            .get(timestamp.get_id())
+            .map_or(false, |until| timestamp <= until)
+    }
 }
 
 #[async_trait]
@@ -267,20 +305,36 @@ impl Runner for ZenohReceiver {
 
         let res = {
             log::debug!("ZenohReceiver - {} - Started", self.record.resource);
-            if let Some(link) = &*self.link.lock().await {
-                let mut subscriber = self
-                    .context
-                    .runtime
-                    .session
-                    .subscribe(&self.record.resource)
-                    .await?;
+            if self.link.lock().await.is_some() {
+                let session = self.context.runtime.session.get().await?;
+                let mut subscriber = session.subscribe(&self.record.resource).await?;
 
                 while let Some(msg) = subscriber.receiver().next().await {
                     log::trace!("ZenohSender - {}<={:?} ", self.record.resource, msg);
                     let de: Message = bincode::deserialize(&msg.value.payload.contiguous())
                         .map_err(|_| ZFError::DeseralizationError)?;
                     log::trace!("ZenohSender - OUT =>{:?} ", de);
-                    link.send(Arc::new(de)).await?;
+
+                    // The link is held while the message is sent: the messages are skipped
+                    // atomically, see `skip_received`.
+                    let link_guard = self.link.lock().await;
+                    let link = match &*link_guard {
+                        Some(link) => link,
+                        None => break,
+                    };
+                    if Self::is_received(&*self.skip_until.lock().await, &de.get_timestamp()) {
+                        log::trace!(
+                            "ZenohReceiver - {} - Skipping a message already received",
+                            self.record.resource
+                        );
+                        continue;
+                    }
+
+                    let message = Arc::new(de);
+                    link.send(message.clone()).await?;
+                    self.recorders
+                        .record(&self.record.link_id.port_id, message)
+                        .await?;
                 }
             }
 
@@ -308,6 +362,16 @@ impl Runner for ZenohReceiver {
         Ok(())
     }
 
+    async fn remove_output(&self, output: &LinkSender<Message>) -> ZFResult<()> {
+        let mut link_guard = self.link.lock().await;
+        if let Some(link) = &*link_guard {
+            if link.sender.same_channel(&output.sender) {
+                *link_guard = None;
+            }
+        }
+        Ok(())
+    }
+
     async fn add_input(&self, _input: LinkReceiver<Message>) -> ZFResult<()> {
         Err(ZFError::ReceiverDoNotHaveInputs)
     }
@@ -326,20 +390,54 @@ impl Runner for ZenohReceiver {
         HashMap::with_capacity(0)
     }
 
+    async fn skip_received(
+        &self,
+        link: &LinkReceiver<Message>,
+        timestamps: &[Timestamp],
+    ) -> ZFResult<()> {
+        // No message is sent while the link is held: the messages waiting in it are sent back in
+        // the same order.
+        let link_guard = self.link.lock().await;
+        let mut skip_until = self.skip_until.lock().await;
+        for timestamp in timestamps {
+            skip_until.insert(*timestamp.get_id(), *timestamp);
+        }
+
+        let (skipped, waiting): (Vec<Arc<Message>>, Vec<Arc<Message>>) = link
+            .receiver
+            .try_iter()
+            .partition(|message| Self::is_received(&skip_until, &message.get_timestamp()));
+        if !skipped.is_empty() {
+            log::debug!(
+                "ZenohReceiver - {} - Skipping {} message(s) already received",
+                self.record.resource,
+                skipped.len()
+            );
+        }
+
+        if let Some(sender) = &*link_guard {
+            for message in waiting.into_iter() {
+                sender.send(message).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn clean(&self) -> ZFResult<()> {
         Ok(())
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn start_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.start(port_id).await
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.stop(port_id).await
     }
 
-    async fn is_recording(&self) -> bool {
-        false
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        self.recorders.recording_ports().await
     }
 
     async fn is_running(&self) -> bool {
@@ -350,3 +448,100 @@ impl Runner for ZenohReceiver {
         *self.is_running.lock().await = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::connector::ZFConnectorKind;
+    use crate::model::link::PortDescriptor;
+    use crate::runtime::dataflow::instance::link::link;
+    use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
+    use crate::runtime::message::DataMessage;
+    use crate::runtime::session::LazySession;
+    use crate::runtime::RuntimeContext;
+    use uhlc::HLC;
+    use uuid::Uuid;
+
+    fn receiver() -> (ZenohReceiver, LinkSender<Message>, LinkReceiver<Message>) {
+        let context = InstanceContext {
+            flow_id: "flow".into(),
+            instance_id: Uuid::new_v4(),
+            runtime: RuntimeContext {
+                session: LazySession::disabled(),
+                loader: Arc::new(Loader::new(LoaderConfig::new())),
+                hlc: Arc::new(HLC::default()),
+                runtime_name: "connector-runtime".into(),
+                runtime_uuid: Uuid::new_v4(),
+                recordings_dir: None,
+            },
+        };
+        let record = ZFConnectorRecord {
+            kind: ZFConnectorKind::Receiver,
+            id: "receiver".into(),
+            resource: "flow/receiver".into(),
+            link_id: PortDescriptor {
+                port_id: "out".into(),
+                port_type: "int".into(),
+            },
+            runtime: "connector-runtime".into(),
+        };
+        let (tx, rx) = link::<Message>(None, "out".into(), "in".into());
+        let mut io = OperatorIO::default();
+        io.add_output(tx.clone());
+
+        (ZenohReceiver::try_new(context, record, io).unwrap(), tx, rx)
+    }
+
+    fn message(timestamp: Timestamp) -> Arc<Message> {
+        Arc::new(Message::Data(DataMessage::new_serialized(
+            Arc::new(vec![]),
+            timestamp,
+            vec![],
+            vec![],
+        )))
+    }
+
+    #[test]
+    fn skip_received_per_sender() {
+        let (receiver, tx, rx) = receiver();
+        let (first, second, other) = (HLC::default(), HLC::default(), HLC::default());
+
+        // The timestamps of the two senders interleave, the third one only sent older messages.
+        let other_timestamp = other.new_timestamp();
+        let mut timestamps = vec![];
+        for _ in 0..3 {
+            timestamps.push(first.new_timestamp());
+            timestamps.push(second.new_timestamp());
+        }
+
+        async_std::task::block_on(async move {
+            tx.send(message(other_timestamp)).await.unwrap();
+            for timestamp in timestamps.iter() {
+                tx.send(message(*timestamp)).await.unwrap();
+            }
+
+            // The node received the first two messages of the first sender and the first one of
+            // the second sender.
+            receiver
+                .skip_received(&rx, &[timestamps[2], timestamps[1]])
+                .await
+                .unwrap();
+
+            let kept: Vec<Timestamp> = rx
+                .receiver
+                .try_iter()
+                .map(|message| message.get_timestamp())
+                .collect();
+            assert_eq!(
+                kept,
+                vec![other_timestamp, timestamps[3], timestamps[4], timestamps[5]]
+            );
+
+            // The messages received afterwards are skipped the same way.
+            let skip_until = receiver.skip_until.lock().await;
+            assert!(ZenohReceiver::is_received(&skip_until, &timestamps[0]));
+            assert!(!ZenohReceiver::is_received(&skip_until, &timestamps[3]));
+            assert!(!ZenohReceiver::is_received(&skip_until, &other_timestamp));
+        });
+    }
+}
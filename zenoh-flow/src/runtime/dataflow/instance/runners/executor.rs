@@ -0,0 +1,413 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::executor::ExecutorDescriptor;
+use crate::{Context, Data, DataMessage, EmptyState, NodeId, Operator, PortId, State};
+use crate::{ZFError, ZFResult};
+use async_std::sync::Arc;
+use std::collections::HashMap;
+
+/// A job sent to a dedicated thread.
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// The executor on which the `run` of an Operator is executed.
+///
+/// See [`ExecutorDescriptor`](`ExecutorDescriptor`) for a description of the different kinds.
+pub(crate) enum NodeExecutor {
+    Shared,
+    Blocking,
+    Dedicated(flume::Sender<Job>),
+}
+
+impl NodeExecutor {
+    /// Creates the executor described by `descriptor`, `Shared` if none is provided.
+    ///
+    /// For a `Dedicated` executor, the thread is spawned and configured before this function
+    /// returns. The thread exits once the executor is dropped.
+    ///
+    /// # Errors
+    /// An error variant is returned if the descriptor is invalid, if a CPU of the affinity is not
+    /// available to the process, or if the thread could not be spawned or configured (e.g. the process is not
+    /// allowed to set the priority).
+    pub(crate) fn try_new(
+        node_id: &NodeId,
+        descriptor: Option<&ExecutorDescriptor>,
+    ) -> ZFResult<Self> {
+        let (cpu_affinity, priority) = match descriptor {
+            None | Some(ExecutorDescriptor::Shared) => return Ok(Self::Shared),
+            Some(ExecutorDescriptor::Blocking) => return Ok(Self::Blocking),
+            Some(ExecutorDescriptor::Dedicated {
+                cpu_affinity,
+                priority,
+            }) => (cpu_affinity.clone(), *priority),
+        };
+
+        if let Some(descriptor) = descriptor {
+            descriptor.validate(node_id)?;
+        }
+        check_available_cpus(node_id, cpu_affinity.as_deref())?;
+
+        let (tx_jobs, rx_jobs) = flume::unbounded::<Job>();
+        let (tx_ready, rx_ready) = flume::bounded::<ZFResult<()>>(1);
+
+        let thread_node_id = node_id.clone();
+        std::thread::Builder::new()
+            .name(format!("zf-{}", node_id))
+            .spawn(move || {
+                let configured =
+                    configure_current_thread(&thread_node_id, cpu_affinity.as_deref(), priority);
+                let is_configured = configured.is_ok();
+                let _ = tx_ready.send(configured);
+                if !is_configured {
+                    return;
+                }
+
+                while let Ok(job) = rx_jobs.recv() {
+                    job();
+                }
+
+                log::trace!("[Operator: {}] Dedicated thread exiting", thread_node_id);
+            })?;
+
+        rx_ready.recv()??;
+
+        Ok(Self::Dedicated(tx_jobs))
+    }
+
+    /// Executes the `run` of the `operator` on this executor.
+    ///
+    /// When the executor is not `Shared`, the `context`, `state` and `inputs` are moved to the
+    /// thread executing the `run` and moved back afterwards.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `run` failed or, for a `Dedicated` executor, if the
+    /// thread is no longer running (e.g. the `run` panicked). In the latter case the state of the
+    /// operator is lost.
+    pub(crate) async fn run_operator(
+        &self,
+        operator: &Arc<dyn Operator>,
+        context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        if let Self::Shared = self {
+            return operator.run(context, state, inputs);
+        }
+
+        let operator = operator.clone();
+        let mut owned_context = std::mem::take(context);
+        let mut owned_state = std::mem::replace(state, State::from(EmptyState));
+        let mut owned_inputs = std::mem::take(inputs);
+
+        let job = move || {
+            let result = operator.run(&mut owned_context, &mut owned_state, &mut owned_inputs);
+            (owned_context, owned_state, owned_inputs, result)
+        };
+
+        let (owned_context, owned_state, owned_inputs, result) = match self {
+            Self::Blocking => async_std::task::spawn_blocking(job).await,
+            Self::Dedicated(jobs) => {
+                let (tx, rx) = flume::bounded(1);
+                jobs.send_async(Box::new(move || {
+                    let _ = tx.send(job());
+                }))
+                .await?;
+                rx.recv_async().await?
+            }
+            Self::Shared => unreachable!(),
+        };
+
+        *context = owned_context;
+        *state = owned_state;
+        *inputs = owned_inputs;
+
+        result
+    }
+}
+
+/// Checks that the CPUs of the affinity are available to the process: online and part of its
+/// own affinity, e.g. not excluded by a cgroup or by `taskset`.
+///
+/// The CPUs were checked to be lower than [`MAX_CPUS`](`crate::model::executor::MAX_CPUS`) by
+/// [`ExecutorDescriptor::validate`](`ExecutorDescriptor::validate`).
+///
+/// # Errors
+/// A `ConfigurationError` variant is returned if a CPU is not available, an `IOError` if the
+/// affinity of the process cannot be retrieved.
+#[cfg(target_os = "linux")]
+fn check_available_cpus(node_id: &NodeId, cpu_affinity: Option<&[usize]>) -> ZFResult<()> {
+    let cpus = match cpu_affinity {
+        Some(cpus) => cpus,
+        None => return Ok(()),
+    };
+
+    // SAFETY: `cpu_set_t` is a plain bit mask for which all zeros is a valid (empty) value.
+    let mut available: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let res = unsafe {
+        libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut available)
+    };
+    if res != 0 {
+        return Err(ZFError::IOError(format!(
+            "[Operator: {}] Could not get the available CPUs: {}",
+            node_id,
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    match cpus
+        .iter()
+        .find(|cpu| !unsafe { libc::CPU_ISSET(**cpu, &available) })
+    {
+        Some(cpu) => Err(ZFError::ConfigurationError(format!(
+            "[Operator: {}] Invalid CPU < {} >, it is not available to the runtime",
+            node_id, cpu
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the CPUs of the affinity are available to the process.
+///
+/// Only supported on Linux: on other platforms the affinity is ignored.
+#[cfg(not(target_os = "linux"))]
+fn check_available_cpus(_node_id: &NodeId, _cpu_affinity: Option<&[usize]>) -> ZFResult<()> {
+    Ok(())
+}
+
+/// Applies the CPU affinity and the real-time priority to the calling thread.
+#[cfg(target_os = "linux")]
+fn configure_current_thread(
+    node_id: &NodeId,
+    cpu_affinity: Option<&[usize]>,
+    priority: Option<i32>,
+) -> ZFResult<()> {
+    if let Some(cpus) = cpu_affinity {
+        // SAFETY: `cpu_set_t` is a plain bit mask for which all zeros is a valid (empty) value.
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in cpus {
+            unsafe { libc::CPU_SET(*cpu, &mut set) };
+        }
+
+        let res =
+            unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+        if res != 0 {
+            return Err(ZFError::IOError(format!(
+                "[Operator: {}] Could not set the CPU affinity to {:?}: {}",
+                node_id,
+                cpus,
+                std::io::Error::last_os_error()
+            )));
+        }
+    }
+
+    if let Some(priority) = priority {
+        let param = libc::sched_param {
+            sched_priority: priority,
+        };
+        let res =
+            unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+        if res != 0 {
+            return Err(ZFError::IOError(format!(
+                "[Operator: {}] Could not set the real-time priority to {}: {}",
+                node_id,
+                priority,
+                std::io::Error::from_raw_os_error(res)
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the CPU affinity and the real-time priority to the calling thread.
+///
+/// Only supported on Linux: on other platforms they are ignored.
+#[cfg(not(target_os = "linux"))]
+fn configure_current_thread(
+    node_id: &NodeId,
+    cpu_affinity: Option<&[usize]>,
+    priority: Option<i32>,
+) -> ZFResult<()> {
+    if cpu_affinity.is_some() || priority.is_some() {
+        log::warn!(
+            "[Operator: {}] CPU affinity and priority are only supported on Linux, ignoring them.",
+            node_id
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeExecutor;
+    use crate::model::executor::ExecutorDescriptor;
+    use crate::{
+        default_input_rule, default_output_rule, Configuration, Context, Data, DataMessage,
+        InputToken, LocalDeadlineMiss, Node, NodeOutput, Operator, PortId, State, ZFError,
+        ZFResult, ZFState,
+    };
+    use async_std::sync::Arc;
+    use std::collections::HashMap;
+
+    #[derive(Debug)]
+    struct ThreadState {
+        thread_name: Option<String>,
+    }
+
+    impl ZFState for ThreadState {
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    struct ThreadOperator;
+
+    impl Node for ThreadOperator {
+        fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+            Ok(State::from(ThreadState { thread_name: None }))
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Operator for ThreadOperator {
+        fn input_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            tokens: &mut HashMap<PortId, InputToken>,
+        ) -> ZFResult<bool> {
+            default_input_rule(state, tokens)
+        }
+
+        fn run(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            _inputs: &mut HashMap<PortId, DataMessage>,
+        ) -> ZFResult<HashMap<PortId, Data>> {
+            let state = state.try_get::<ThreadState>()?;
+            state.thread_name = std::thread::current().name().map(|name| name.to_string());
+            Ok(HashMap::new())
+        }
+
+        fn output_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            outputs: HashMap<PortId, Data>,
+            _deadline_miss: Option<LocalDeadlineMiss>,
+        ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+            default_output_rule(state, outputs)
+        }
+    }
+
+    #[test]
+    fn test_dedicated_executor() {
+        let node_id = "op".into();
+        let executor = NodeExecutor::try_new(
+            &node_id,
+            Some(&ExecutorDescriptor::Dedicated {
+                cpu_affinity: None,
+                priority: None,
+            }),
+        )
+        .unwrap();
+
+        let operator: Arc<dyn Operator> = Arc::new(ThreadOperator);
+        let mut context = Context::default();
+        let mut state = operator.initialize(&None).unwrap();
+        let mut inputs = HashMap::new();
+
+        async_std::task::block_on(async {
+            executor
+                .run_operator(&operator, &mut context, &mut state, &mut inputs)
+                .await
+                .unwrap();
+        });
+
+        // The state was moved back after the execution.
+        let state = state.try_get::<ThreadState>().unwrap();
+        assert_eq!(state.thread_name.as_deref(), Some("zf-op"));
+    }
+
+    #[test]
+    fn test_invalid_priority() {
+        let node_id = "op".into();
+        assert!(matches!(
+            NodeExecutor::try_new(
+                &node_id,
+                Some(&ExecutorDescriptor::Dedicated {
+                    cpu_affinity: None,
+                    priority: Some(100),
+                }),
+            ),
+            Err(ZFError::ConfigurationError(_))
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unavailable_cpu() {
+        let mut available: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        let res = unsafe {
+            libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut available)
+        };
+        assert_eq!(res, 0);
+
+        // A CPU index lower than the number of online CPUs can still be excluded from the
+        // affinity of the process.
+        let node_id = "op".into();
+        let mut cpus = 0..crate::model::executor::MAX_CPUS;
+        let is_available = |cpu: &usize| unsafe { libc::CPU_ISSET(*cpu, &available) };
+        let try_new = |cpu: usize| {
+            NodeExecutor::try_new(
+                &node_id,
+                Some(&ExecutorDescriptor::Dedicated {
+                    cpu_affinity: Some(vec![cpu]),
+                    priority: None,
+                }),
+            )
+        };
+
+        let cpu = cpus.clone().find(is_available).unwrap();
+        assert!(try_new(cpu).is_ok());
+        let cpu = cpus.find(|cpu| !is_available(cpu)).unwrap();
+        assert!(matches!(try_new(cpu), Err(ZFError::ConfigurationError(_))));
+    }
+
+    #[test]
+    fn test_invalid_cpu() {
+        let node_id = "op".into();
+        for cpu in [crate::model::executor::MAX_CPUS, usize::MAX] {
+            assert!(matches!(
+                NodeExecutor::try_new(
+                    &node_id,
+                    Some(&ExecutorDescriptor::Dedicated {
+                        cpu_affinity: Some(vec![cpu]),
+                        priority: None,
+                    }),
+                ),
+                Err(ZFError::ConfigurationError(_))
+            ));
+        }
+    }
+}
@@ -13,16 +13,19 @@
 //
 
 pub mod connector;
+pub(crate) mod executor;
 pub mod operator;
+pub(crate) mod recorder;
 pub mod replay;
 pub mod sink;
 pub mod source;
 
 use crate::async_std::prelude::*;
-use crate::async_std::sync::Arc;
+use crate::async_std::sync::{Arc, Mutex};
 use crate::async_std::task::JoinHandle;
 
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
+use crate::runtime::deadline::DeadlineMissStats;
 use crate::runtime::message::Message;
 use crate::runtime::InstanceContext;
 use crate::types::{NodeId, ZFResult};
@@ -32,7 +35,10 @@ use futures_lite::future::FutureExt;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::task::{Context, Poll};
+use std::time::Duration;
+use uhlc::Timestamp;
 
 /// Type of the Runner.
 ///
@@ -78,8 +84,8 @@ impl RunnerManager {
     /// # Errors
     /// An error variant is returned in case the runner is already stopped.
     pub async fn kill(&self) -> ZFResult<()> {
-        if self.runner.is_recording().await {
-            self.runner.stop_recording().await?;
+        for port_id in self.runner.get_recording_ports().await {
+            self.runner.stop_recording(&port_id).await?;
         }
         log::info!("RunnerManager triggering stop to {}", self.get_id());
 
@@ -89,6 +95,29 @@ impl RunnerManager {
         })
     }
 
+    /// Stops the associated runner and waits, at most `timeout`, for it to finish.
+    ///
+    /// A runner that does not acknowledge the stop within `timeout` (e.g. a hung node) is
+    /// abandoned.
+    ///
+    /// # Errors
+    /// An error variant is returned in case the runner is already stopped.
+    pub async fn stop_within(self, timeout: Duration) -> ZFResult<()> {
+        self.kill().await?;
+
+        let id = self.get_id();
+        match async_std::future::timeout(timeout, self).await {
+            Ok(Err(e)) => log::warn!("[Node: {}] Stopped with error: {:?}", id, e),
+            Err(_) => log::error!(
+                "[Node: {}] Did not stop within {:?}, abandoning it",
+                id,
+                timeout
+            ),
+            Ok(Ok(())) => (),
+        }
+        Ok(())
+    }
+
     /// Returns a reference to the handler.
     ///
     /// The handler can be used to verify the exit value
@@ -100,20 +129,20 @@ impl RunnerManager {
         &self.handler
     }
 
-    /// Starts the recording of the associated `Runner`.
+    /// Starts the recording of the given port of the associated `Runner`.
     ///
     /// # Errors
-    /// Fails if the `Runner` is not a source.
-    pub async fn start_recording(&self) -> ZFResult<String> {
-        self.runner.start_recording().await
+    /// Fails if the `Runner` cannot record this port.
+    pub async fn start_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.runner.start_recording(port_id).await
     }
 
-    /// Stops the recording for the associated `Runner`.
+    /// Stops the recording of the given port of the associated `Runner`.
     ///
     /// # Errors
-    /// Fails if the `Runner` is not a source.
-    pub async fn stop_recording(&self) -> ZFResult<String> {
-        self.runner.stop_recording().await
+    /// Fails if the `Runner` is not recording this port.
+    pub async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.runner.stop_recording(port_id).await
     }
 
     /// Returns a reference to the instance context.
@@ -171,6 +200,14 @@ pub trait Runner: Send + Sync {
     /// if it does not expect any outputs (e.g.  Sink)
     async fn add_output(&self, output: LinkSender<Message>) -> ZFResult<()>;
 
+    /// Removes the given output link from the runner.
+    ///
+    /// # Errors
+    /// It fails if the runner does not have outputs (e.g. Sink).
+    async fn remove_output(&self, _output: &LinkSender<Message>) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
+
     /// Finalizes the node
     ///
     /// # Errors
@@ -195,29 +232,160 @@ pub trait Runner: Send + Sync {
     /// Returns the input link of the `Runner`.
     async fn take_input_links(&self) -> HashMap<PortId, LinkReceiver<Message>>;
 
-    /// Starts the recording of the `Runner`
+    /// Starts the recording of the given output port of the `Runner`.
     ///
     /// # Errors
-    /// Fails if the `Runner` is not a source
-    async fn start_recording(&self) -> ZFResult<String>;
+    /// Fails if the `Runner` does not have this output (e.g. a Sink) or if
+    /// the port is already recorded.
+    async fn start_recording(&self, port_id: &PortId) -> ZFResult<String>;
 
-    /// Stops the recording of the `Runner`.
+    /// Stops the recording of the given output port of the `Runner`.
     ///
     /// # Errors
-    /// Fails it the `Runner` is not a source.
-    async fn stop_recording(&self) -> ZFResult<String>;
+    /// Fails it the `Runner` does not have this output or if the port is
+    /// not recorded.
+    async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String>;
 
-    /// Checks if the `Runner` is recording.
+    /// Checks if the `Runner` is recording at least one of its ports.
     ///
-    /// # Errors
-    /// Always `false` if the runner is not a source.
-    async fn is_recording(&self) -> bool;
+    /// Always `false` if the runner does not have outputs.
+    async fn is_recording(&self) -> bool {
+        !self.get_recording_ports().await.is_empty()
+    }
+
+    /// Returns the ports of the `Runner` that are being recorded.
+    ///
+    /// Always empty if the runner does not have outputs.
+    async fn get_recording_ports(&self) -> Vec<PortId>;
 
     /// Checks if the `Runner` is running.
     async fn is_running(&self) -> bool;
 
     /// Stops the runner.
     async fn stop(&self);
+
+    /// Returns the counters of the deadline misses of the `Runner`.
+    ///
+    /// Always empty if the runner does not check deadlines.
+    async fn get_deadline_miss_stats(&self) -> DeadlineMissStats {
+        DeadlineMissStats::default()
+    }
+
+    /// Returns for how long the `Runner` has been executing the `run` of its node, if it is
+    /// currently executing it.
+    ///
+    /// Always `None` if the runner does not execute a node.
+    async fn get_run_elapsed(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Returns the number of inputs the node received that wait for its input rule to accept
+    /// them, i.e. that were not yet given to its `run`.
+    ///
+    /// Always `0` if the runner does not have an input rule.
+    async fn get_buffered_tokens(&self) -> usize {
+        0
+    }
+
+    /// Pauses the `Runner`: once its node is done with its current execution of `run`, the
+    /// `Runner` waits until it is resumed. Its state and its links are left untouched.
+    ///
+    /// # Errors
+    /// Fails if the `Runner` cannot be paused (e.g. a connector).
+    async fn pause(&self) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
+
+    /// Resumes the `Runner` where it was paused.
+    ///
+    /// # Errors
+    /// Fails if the `Runner` cannot be paused (e.g. a connector).
+    async fn resume(&self) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
+
+    /// Checks if the `Runner` is paused.
+    ///
+    /// Always `false` if the runner cannot be paused.
+    async fn is_paused(&self) -> bool {
+        false
+    }
+
+    /// Serializes the state of the node, `None` is returned if the state does not support
+    /// checkpointing.
+    ///
+    /// The state is serialized between two iterations: this waits until the node releases it.
+    ///
+    /// # Errors
+    /// Fails if the serialization fails.
+    async fn checkpoint(&self) -> ZFResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Restores the state of the node from a checkpoint.
+    ///
+    /// # Errors
+    /// Fails if the `Runner` has no state (e.g. a connector), if the state does not support
+    /// checkpointing or if the deserialization fails.
+    async fn restore(&self, _bytes: &[u8]) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
+
+    /// Skips the messages whose timestamps are not after the one of `timestamps` with the same
+    /// ID, i.e. from the same sender: the ones waiting in `link`, the output link of the
+    /// `Runner`, and the ones it receives afterwards.
+    ///
+    /// # Errors
+    /// Fails if the `Runner` does not receive its messages from another runtime (anything but a
+    /// receiver connector).
+    async fn skip_received(
+        &self,
+        _link: &LinkReceiver<Message>,
+        _timestamps: &[Timestamp],
+    ) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
+}
+
+/// Holds a `Runner` while it is paused.
+///
+/// The `Runner` passes through the gate before each iteration and before handing over data to,
+/// or taking data from, its node. A paused `Runner` that was waiting for an input when paused thus
+/// holds the next message it receives until it is resumed: the message is not lost.
+#[derive(Clone, Default)]
+pub(crate) struct PauseGate {
+    closed: Arc<Mutex<Option<(flume::Sender<()>, flume::Receiver<()>)>>>,
+}
+
+impl PauseGate {
+    /// Closes the gate, the `Runner` stops at the next pass.
+    pub(crate) async fn close(&self) {
+        let mut closed = self.closed.lock().await;
+        if closed.is_none() {
+            *closed = Some(flume::bounded(1));
+        }
+    }
+
+    /// Opens the gate, releasing the `Runner` if it is waiting.
+    pub(crate) async fn open(&self) {
+        // Dropping the sender disconnects the receivers, which wakes up `pass`.
+        self.closed.lock().await.take();
+    }
+
+    /// Checks if the gate is closed.
+    pub(crate) async fn is_closed(&self) -> bool {
+        self.closed.lock().await.is_some()
+    }
+
+    /// Passes through the gate, waiting for it to be opened if it is closed.
+    pub(crate) async fn pass(&self) {
+        let receiver = match &*self.closed.lock().await {
+            Some((_, receiver)) => receiver.clone(),
+            None => return,
+        };
+        // Nothing is ever sent: this returns when the gate is opened.
+        let _ = receiver.recv_async().await;
+    }
 }
 
 /// A `NodeRunner` wraps the `Runner and associates it
@@ -226,19 +394,36 @@ pub trait Runner: Send + Sync {
 pub struct NodeRunner {
     inner: Arc<dyn Runner>,
     ctx: InstanceContext,
+    // Incremented at each start: an execution abandoned by a restart must not mark the runner as
+    // stopped once it finally acknowledges its stop.
+    generation: Arc<AtomicU64>,
+    // The error of the last run that failed: the run is restarted, the error would be lost.
+    failure: Arc<Mutex<Option<ZFError>>>,
 }
 
 impl NodeRunner {
     /// Creates a new `NodeRunner`.
     pub fn new(inner: Arc<dyn Runner>, ctx: InstanceContext) -> Self {
-        Self { inner, ctx }
+        Self {
+            inner,
+            ctx,
+            generation: Arc::new(AtomicU64::new(0)),
+            failure: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the error of the last run of the node that failed, if any, and forgets it.
+    ///
+    /// A failed run is restarted: this error is the only trace that the node failed.
+    pub async fn take_failure(&self) -> Option<ZFError> {
+        self.failure.lock().await.take()
     }
 
     /// Run the node in a stoppable fashion.
     ///
     ///  # Errors
     /// An error variant is returned in case the run returns an error.
-    async fn run_stoppable(&self, rx: flume::Receiver<bool>) -> ZFResult<()> {
+    async fn run_stoppable(&self, rx: flume::Receiver<bool>, generation: u64) -> ZFResult<()> {
         loop {
             log::info!("NodeRunner {} starting run!", self.get_id());
 
@@ -266,13 +451,18 @@ impl NodeRunner {
                         self.get_id(),
                         e
                     );
+                    if e.is_some() {
+                        *self.failure.lock().await = e;
+                    }
                 }
                 RunAction::Stop => {
                     log::trace!(
                         "[Node: {}] Received kill command, killing runner",
                         self.get_id()
                     );
-                    self.stop().await;
+                    if self.generation.load(Ordering::SeqCst) == generation {
+                        self.stop().await;
+                    }
                     return Ok(());
                 }
             }
@@ -291,8 +481,10 @@ impl NodeRunner {
         // This is why this is a Barrier(2).
         let (tx, rx) = flume::bounded::<bool>(1);
 
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
         let cloned_self = self.clone();
-        let h = async_std::task::spawn(async move { cloned_self.run_stoppable(rx).await });
+        let h =
+            async_std::task::spawn(async move { cloned_self.run_stoppable(rx, generation).await });
         RunnerManager::new(tx, h, self.inner.clone(), self.ctx.clone())
     }
 }
@@ -13,15 +13,20 @@
 //
 
 use crate::async_std::sync::{Arc, Mutex};
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
 use crate::model::loops::LoopDescriptor;
 use crate::model::node::OperatorRecord;
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
-use crate::runtime::dataflow::instance::runners::{Runner, RunnerKind};
+use crate::runtime::dataflow::instance::runners::executor::NodeExecutor;
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
+use crate::runtime::dataflow::instance::runners::{PauseGate, Runner, RunnerKind};
 use crate::runtime::dataflow::node::OperatorLoaded;
-use crate::runtime::deadline::E2EDeadline;
+use crate::runtime::deadline::{
+    report_deadline_miss, DeadlineMiss, DeadlineMissStats, E2EDeadline,
+};
 use crate::runtime::loops::LoopContext;
 use crate::runtime::message::Message;
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::InstanceContext;
 use crate::{
     Context, DataMessage, InputToken, LocalDeadlineMiss, NodeId, Operator, PortId, PortType, State,
@@ -29,7 +34,8 @@ use crate::{
 };
 use async_trait::async_trait;
 use futures::{future, Future};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 #[cfg(target_family = "unix")]
@@ -131,10 +137,17 @@ pub struct OperatorRunner {
     pub(crate) inputs: HashMap<PortId, PortType>,
     pub(crate) outputs: HashMap<PortId, PortType>,
     pub(crate) local_deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
     pub(crate) end_to_end_deadlines: Vec<E2EDeadlineRecord>,
+    pub(crate) deadline_miss_stats: Arc<Mutex<DeadlineMissStats>>,
     pub(crate) is_running: Arc<Mutex<bool>>,
-    // Ciclo is the italian word for "loop" — we cannot use "loop" as it’s a reserved keyword.
-    pub(crate) ciclo: Option<LoopDescriptor>,
+    // Cicli is the italian word for "loops" — we cannot use "loop" as it’s a reserved keyword.
+    pub(crate) cicli: Vec<LoopDescriptor>,
+    pub(crate) executor: Arc<NodeExecutor>,
+    pub(crate) run_tracker: RunTracker,
+    pub(crate) pause_gate: PauseGate,
+    pub(crate) buffered_tokens: Arc<AtomicUsize>,
+    pub(crate) recorders: Recorders,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) operator: Arc<dyn Operator>,
     pub(crate) _library: Option<Arc<Library>>,
@@ -146,13 +159,16 @@ impl OperatorRunner {
     /// and [`OperatorIO`](`OperatorIO`).
     ///
     /// # Errors
-    /// If fails if the output is not connected.
+    /// If fails if the output is not connected or if its executor could not be created.
     pub fn try_new(
         context: InstanceContext,
         operator: OperatorLoaded,
         operator_io: OperatorIO,
     ) -> ZFResult<Self> {
         // TODO Check that all ports are used.
+        let executor = NodeExecutor::try_new(&operator.id, operator.executor.as_ref())?;
+        let recorders = Recorders::new(&context, &operator.id, operator.outputs.iter());
+
         Ok(Self {
             id: operator.id,
             context,
@@ -164,14 +180,24 @@ impl OperatorRunner {
             operator: operator.operator,
             _library: operator.library,
             local_deadline: operator.local_deadline,
+            on_deadline_miss: operator.on_deadline_miss,
             end_to_end_deadlines: operator.end_to_end_deadlines,
-            ciclo: operator.ciclo,
+            deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+            cicli: operator.cicli,
+            executor: Arc::new(executor),
+            run_tracker: RunTracker::default(),
+            pause_gate: PauseGate::default(),
+            buffered_tokens: Arc::new(AtomicUsize::new(0)),
+            recorders,
         })
     }
 
     /// Starts the operator.
     async fn start(&self) {
         *self.is_running.lock().await = true;
+        self.run_tracker.reset();
+        // The tokens of a previous run are lost when it ends.
+        self.buffered_tokens.store(0, Ordering::SeqCst);
     }
 
     /// A single iteration of the run loop.
@@ -207,9 +233,18 @@ impl OperatorRunner {
             }
         }
 
+        // The ports whose data was received during this iteration and not yet given to `run`.
+        let mut received: HashSet<PortId> = HashSet::with_capacity(tokens.len());
+
         'input_rule: loop {
             if !links.is_empty() {
-                match future::select_all(links).await {
+                // The state is released while waiting for the inputs so that it can be
+                // checkpointed.
+                drop(state);
+                let selected = future::select_all(links).await;
+                state = self.state.lock().await;
+
+                match selected {
                     (Ok((port_id, message)), _index, remaining) => {
                         match message.as_ref() {
                             Message::Data(data_message) => {
@@ -257,16 +292,64 @@ impl OperatorRunner {
                                 let mut data_msg = data_message.clone();
 
                                 let now = self.context.runtime.hlc.new_timestamp();
-                                data_message
-                                    .end_to_end_deadlines
-                                    .iter()
-                                    .for_each(|deadline| {
-                                        if let Some(miss) = deadline.check(&self.id, &port_id, &now)
-                                        {
-                                            data_msg.missed_end_to_end_deadlines.push(miss)
+                                let mut discard = false;
+                                let mut fallback = None;
+
+                                for deadline in data_message.end_to_end_deadlines.iter() {
+                                    if let Some(miss) = deadline.check(&self.id, &port_id, &now) {
+                                        let policy = deadline
+                                            .on_miss
+                                            .clone()
+                                            .unwrap_or(DeadlineMissPolicy::Log);
+                                        let deadline_miss = DeadlineMiss::EndToEnd(miss.clone());
+
+                                        if policy == DeadlineMissPolicy::Callback {
+                                            self.operator
+                                                .on_deadline_miss(&mut state, &deadline_miss)?;
                                         }
-                                    });
 
+                                        report_deadline_miss(
+                                            &self.context,
+                                            &self.id,
+                                            &self.deadline_miss_stats,
+                                            &policy,
+                                            &deadline_miss,
+                                        )
+                                        .await;
+
+                                        match policy {
+                                            DeadlineMissPolicy::Drop => discard = true,
+                                            DeadlineMissPolicy::Fallback(output) => {
+                                                fallback = Some(output)
+                                            }
+                                            _ => (),
+                                        }
+
+                                        data_msg.missed_end_to_end_deadlines.push(miss);
+                                    }
+                                }
+
+                                // The message is not given to the operator: the `input_rule` is
+                                // not called again as the tokens did not change, we only poll
+                                // the port anew.
+                                if discard || fallback.is_some() {
+                                    if let Some(output) = fallback {
+                                        self.forward(&io, &output, message.clone()).await;
+                                    } else {
+                                        log::debug!(
+                                            "[Operator: {}] Dropping message received on < {} > \
+                                             (deadline miss).",
+                                            self.id,
+                                            port_id
+                                        );
+                                    }
+
+                                    links = remaining;
+                                    links.push(io.poll_input(&self.id, &port_id)?);
+                                    continue 'input_rule;
+                                }
+
+                                received.insert(port_id.clone());
                                 tokens.insert(port_id, InputToken::from(data_msg));
                             }
 
@@ -293,6 +376,7 @@ impl OperatorRunner {
             {
                 Ok(true) => {
                     log::trace!("[Operator: {}] Input Rule returned < true >.", self.id);
+                    self.buffered_tokens.store(0, Ordering::SeqCst);
                     break 'input_rule;
                 }
                 Ok(false) => {
@@ -315,11 +399,14 @@ impl OperatorRunner {
                     links.push(io.poll_input(&self.id, port_id)?);
                 }
             }
+
+            received.retain(|port_id| matches!(tokens.get(port_id), Some(InputToken::Ready(_))));
+            self.buffered_tokens.store(received.len(), Ordering::SeqCst);
         } // end < 'input_rule: loop >
 
         let mut earliest_source_timestamp = None;
         let mut e2e_deadlines_to_propagate: Vec<E2EDeadline> = vec![];
-        let mut loop_feedback = false;
+        let mut loop_feedbacks: Vec<PortId> = vec![];
         let mut loop_contexts_to_propagate: Vec<LoopContext> = vec![];
 
         for (port_id, token) in tokens.iter_mut() {
@@ -359,15 +446,22 @@ impl OperatorRunner {
                                     .cloned(),
                             );
 
-                            loop_contexts_to_propagate
-                                .extend(data_token.data.loop_contexts.iter().cloned());
-
-                            if let Some(ciclo) = &self.ciclo {
-                                if ciclo.ingress == self.id && *port_id == ciclo.feedback_port {
-                                    loop_feedback = true;
+                            // The same LoopContext can be received on several inputs.
+                            for loop_ctx in data_token.data.loop_contexts.iter() {
+                                if !loop_contexts_to_propagate.iter().any(|ctx| {
+                                    ctx.ingress == loop_ctx.ingress
+                                        && ctx.feedback_port == loop_ctx.feedback_port
+                                }) {
+                                    loop_contexts_to_propagate.push(loop_ctx.clone());
                                 }
                             }
 
+                            if self.cicli.iter().any(|ciclo| {
+                                ciclo.ingress == self.id && *port_id == ciclo.feedback_port
+                            }) {
+                                loop_feedbacks.push(port_id.clone());
+                            }
+
                             data.insert(port_id.clone(), data_token.data.clone());
                             if data_token.action == TokenAction::Consume {
                                 *token = InputToken::Pending;
@@ -385,25 +479,35 @@ impl OperatorRunner {
 
         // Loop management: Ingress.
         //
-        // Two, mutually exclusive, cases can happen:
+        // For each Loop for which the node is the Ingress, two, mutually exclusive, cases can
+        // happen:
         // 1. No message coming from the feedback link was processed. This means a new Loop is
         //    starting and we need to associate a context to this message.
         // 2. A message from the feedback link was processed: a LoopContext is already
         //    associated. We need to update it.
-        if let Some(ciclo) = &self.ciclo {
-            if ciclo.ingress == self.id {
-                let now = self.context.runtime.hlc.new_timestamp();
-                if !loop_feedback {
-                    log::trace!("[Operator: {}] Ingress, new loop detected", self.id);
-                    loop_contexts_to_propagate.push(LoopContext::new(ciclo, now));
-                } else {
-                    log::trace!("[Operator: {}] Ingress, updating LoopContext", self.id);
-                    let loop_ctx = loop_contexts_to_propagate
-                        .iter_mut()
-                        .find(|loop_ctx| loop_ctx.ingress == self.id);
-                    if let Some(ctx) = loop_ctx {
-                        ctx.update_ingress(now);
-                    }
+        for ciclo in self.cicli.iter().filter(|ciclo| ciclo.ingress == self.id) {
+            let now = self.context.runtime.hlc.new_timestamp();
+            if !loop_feedbacks.contains(&ciclo.feedback_port) {
+                log::trace!(
+                    "[Operator: {}] Ingress, new loop detected < {} >",
+                    self.id,
+                    ciclo.feedback_port
+                );
+                // A previous run of the same Loop (e.g. an inner loop within an outer loop)
+                // has ended, its context is replaced.
+                loop_contexts_to_propagate.retain(|loop_ctx| !loop_ctx.is_context_of(ciclo));
+                loop_contexts_to_propagate.push(LoopContext::new(ciclo, now));
+            } else {
+                log::trace!(
+                    "[Operator: {}] Ingress, updating LoopContext < {} >",
+                    self.id,
+                    ciclo.feedback_port
+                );
+                let loop_ctx = loop_contexts_to_propagate
+                    .iter_mut()
+                    .find(|loop_ctx| loop_ctx.is_context_of(ciclo));
+                if let Some(ctx) = loop_ctx {
+                    ctx.update_ingress(now);
                 }
             }
         }
@@ -415,11 +519,69 @@ impl OperatorRunner {
             }
         };
 
+        drop(state);
+        self.pause_gate.pass().await;
+        let mut state = self.state.lock().await;
+
+        // Loop management: Egress. The iterations that end with this `run` are raced against
+        // their `iteration_timeout`: the time left for the earliest one arms a timer.
+        let now = self.context.runtime.hlc.new_timestamp();
+        let iteration_timers: Vec<(PortId, Duration)> = self
+            .cicli
+            .iter()
+            .filter(|ciclo| ciclo.egress == self.id)
+            .filter_map(|ciclo| {
+                loop_contexts_to_propagate
+                    .iter()
+                    .find(|loop_ctx| loop_ctx.is_context_of(ciclo))
+                    .and_then(|loop_ctx| loop_ctx.remaining_iteration_time(ciclo, now))
+                    .map(|remaining| (ciclo.feedback_port.clone(), remaining))
+            })
+            .collect();
+        let iteration_timer = iteration_timers
+            .iter()
+            .map(|(_, remaining)| *remaining)
+            .min();
+
         // Running
         let start = Instant::now();
-        let run_outputs = self.operator.run(&mut context, &mut state, &mut data)?;
+        let run_guard = self.run_tracker.enter();
+        let run = self
+            .executor
+            .run_operator(&self.operator, &mut context, &mut state, &mut data);
+        let (run_outputs, expired_timer) = match iteration_timer {
+            None => (run.await, None),
+            Some(remaining) => {
+                futures::pin_mut!(run);
+                match future::select(run, Box::pin(async_std::task::sleep(remaining))).await {
+                    future::Either::Left((run_outputs, _)) => (run_outputs, None),
+                    // The `run` cannot be interrupted without losing the state: the Loops whose
+                    // time is up end, and the `run` is awaited for the other outputs.
+                    future::Either::Right((_, run)) => {
+                        log::warn!(
+                            "[Operator: {}] Loop iteration timeout expired during `run` ({} µs)",
+                            self.id,
+                            remaining.as_micros()
+                        );
+                        (run.await, Some(remaining))
+                    }
+                }
+            }
+        };
+        drop(run_guard);
+        let run_outputs = run_outputs?;
         let elapsed = start.elapsed();
 
+        // The feedback ports of the Loops whose iteration timed out during `run`.
+        let timed_out_loops: Vec<PortId> = match expired_timer {
+            Some(expired) => iteration_timers
+                .into_iter()
+                .filter(|(_, remaining)| *remaining <= expired)
+                .map(|(feedback_port, _)| feedback_port)
+                .collect(),
+            None => vec![],
+        };
+
         log::trace!(
             "[Operator: {}] `run` executed in {} ms",
             self.id,
@@ -436,11 +598,40 @@ impl OperatorRunner {
                     elapsed.as_micros(),
                     deadline.as_micros()
                 );
-                deadline_miss = Some(LocalDeadlineMiss {
+                let miss = LocalDeadlineMiss {
                     start,
                     deadline,
                     elapsed,
-                });
+                };
+                let policy = match self.on_deadline_miss.clone() {
+                    // Rejected by the validation, there is no output to fall back to.
+                    None | Some(DeadlineMissPolicy::Fallback(_)) => DeadlineMissPolicy::Log,
+                    Some(policy) => policy,
+                };
+                let local_miss = DeadlineMiss::Local(miss.clone());
+
+                if policy == DeadlineMissPolicy::Callback {
+                    self.operator.on_deadline_miss(&mut state, &local_miss)?;
+                }
+
+                report_deadline_miss(
+                    &self.context,
+                    &self.id,
+                    &self.deadline_miss_stats,
+                    &policy,
+                    &local_miss,
+                )
+                .await;
+
+                if policy == DeadlineMissPolicy::Drop {
+                    log::debug!(
+                        "[Operator: {}] Dropping the outputs of `run` (deadline miss).",
+                        self.id
+                    );
+                    return Ok((context, tokens, data));
+                }
+
+                deadline_miss = Some(miss);
             }
         }
 
@@ -474,28 +665,35 @@ impl OperatorRunner {
                 // CAVEAT: both options are possible at the same time! A message can be sent on
                 // the feedback link and on another output. We thus need to clone.
                 let mut loop_contexts = loop_contexts_to_propagate.clone();
-
-                if let Some(ciclo) = &self.ciclo {
-                    if ciclo.egress == self.id {
-                        if *port_id != ciclo.feedback_port {
-                            // Output is not sent on the feedback link, remove the LoopContext.
-                            loop_contexts = loop_contexts
-                                .into_iter()
-                                .filter(|loop_ctx| loop_ctx.egress != self.id)
-                                .collect::<Vec<LoopContext>>();
-                        } else {
-                            // Output is sent on the feedback link, we updade the context before
-                            // sending it.
-                            let loop_context = loop_contexts
-                                .iter_mut()
-                                .find(|loop_ctx| loop_ctx.egress == self.id);
-                            if let Some(loop_ctx) = loop_context {
-                                loop_ctx.update_egress(now);
-                            }
+                let mut is_loop_over = false;
+
+                for ciclo in self.cicli.iter().filter(|ciclo| ciclo.egress == self.id) {
+                    if *port_id != ciclo.feedback_port {
+                        // Output is not sent on the feedback link, remove the LoopContext.
+                        loop_contexts.retain(|loop_ctx| !loop_ctx.is_context_of(ciclo));
+                    } else {
+                        // Output is sent on the feedback link, we updade the context before
+                        // sending it and check that the Loop can go on.
+                        let loop_context = loop_contexts
+                            .iter_mut()
+                            .find(|loop_ctx| loop_ctx.is_context_of(ciclo));
+                        if let Some(loop_ctx) = loop_context {
+                            loop_ctx.update_egress(now);
+                            is_loop_over = timed_out_loops.contains(&ciclo.feedback_port)
+                                || loop_ctx.is_bound_reached(ciclo);
                         }
                     }
                 }
 
+                if is_loop_over {
+                    log::debug!(
+                        "[Operator: {}] Loop bound reached, discarding output < {} >.",
+                        self.id,
+                        port_id
+                    );
+                    continue;
+                }
+
                 let zf_message = Arc::new(Message::from_node_output(
                     output,
                     timestamp,
@@ -517,10 +715,51 @@ impl OperatorRunner {
                         );
                     }
                 }
+
+                self.record(port_id, zf_message).await;
             }
         }
         Ok((context, tokens, data))
     }
+
+    /// Forwards, untouched, the `message` on all the links of the given `output`.
+    ///
+    /// This is how the `fallback` deadline miss policy is applied.
+    async fn forward(&self, io: &OperatorIO, output: &PortId, message: Arc<Message>) {
+        log::debug!(
+            "[Operator: {}] Forwarding message to fallback output < {} > (deadline miss).",
+            self.id,
+            output
+        );
+
+        if let Some(link_senders) = io.outputs.get(output) {
+            for link_sender in link_senders {
+                if let Err(e) = link_sender.send(message.clone()).await {
+                    log::error!(
+                        "[Operator: {}] Could not send output < {} > on link < {} >: {:?}",
+                        self.id,
+                        output,
+                        link_sender.id,
+                        e
+                    );
+                }
+            }
+
+            self.record(output, message).await;
+        }
+    }
+
+    /// Records the `message` sent on `output`, if this output is being recorded.
+    async fn record(&self, output: &PortId, message: Arc<Message>) {
+        if let Err(e) = self.recorders.record(output, message).await {
+            log::error!(
+                "[Operator: {}] Could not record output < {} >: {:?}",
+                self.id,
+                output,
+                e
+            );
+        }
+    }
 }
 
 #[async_trait]
@@ -550,6 +789,14 @@ impl Runner for OperatorRunner {
         Ok(())
     }
 
+    async fn remove_output(&self, output: &LinkSender<Message>) -> ZFResult<()> {
+        let mut guard = self.io.lock().await;
+        if let Some(links) = guard.outputs.get_mut(output.id.as_ref()) {
+            links.retain(|link| !link.sender.same_channel(&output.sender));
+        }
+        Ok(())
+    }
+
     fn get_inputs(&self) -> HashMap<PortId, PortType> {
         self.inputs.clone()
     }
@@ -570,22 +817,56 @@ impl Runner for OperatorRunner {
         current_inputs
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn start_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.start(port_id).await
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
-        Err(ZFError::Unsupported)
+    async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.stop(port_id).await
     }
 
-    async fn is_recording(&self) -> bool {
-        false
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        self.recorders.recording_ports().await
     }
 
     async fn stop(&self) {
         *self.is_running.lock().await = false;
     }
 
+    async fn get_deadline_miss_stats(&self) -> DeadlineMissStats {
+        self.deadline_miss_stats.lock().await.clone()
+    }
+
+    async fn get_run_elapsed(&self) -> Option<Duration> {
+        self.run_tracker.elapsed()
+    }
+
+    async fn get_buffered_tokens(&self) -> usize {
+        self.buffered_tokens.load(Ordering::SeqCst)
+    }
+
+    async fn pause(&self) -> ZFResult<()> {
+        self.pause_gate.close().await;
+        Ok(())
+    }
+
+    async fn resume(&self) -> ZFResult<()> {
+        self.pause_gate.open().await;
+        Ok(())
+    }
+
+    async fn is_paused(&self) -> bool {
+        self.pause_gate.is_closed().await
+    }
+
+    async fn checkpoint(&self) -> ZFResult<Option<Vec<u8>>> {
+        self.state.lock().await.try_serialize()
+    }
+
+    async fn restore(&self, bytes: &[u8]) -> ZFResult<()> {
+        self.state.lock().await.try_restore(bytes)
+    }
+
     async fn is_running(&self) -> bool {
         *self.is_running.lock().await
     }
@@ -610,6 +891,7 @@ impl Runner for OperatorRunner {
         // run of the source, as a run can fail in case of error it
         // stops and returns the error to the caller (the RunnerManager)
         loop {
+            self.pause_gate.pass().await;
             match self.iteration(context, tokens, data).await {
                 Ok((ctx, tkn, d)) => {
                     log::trace!(
@@ -646,3 +928,7 @@ mod tests;
 #[cfg(test)]
 #[path = "./tests/operator_e2e_deadline_tests.rs"]
 mod e2e_deadline_tests;
+
+#[cfg(test)]
+#[path = "./tests/operator_deadline_policy_tests.rs"]
+mod deadline_policy_tests;
@@ -0,0 +1,365 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::async_std::sync::{Arc, Mutex};
+use crate::runtime::message::Message;
+use crate::runtime::recording::{
+    RecordingHeader, RecordingInfo, RecordingWriter, RECORDING_FILE_EXTENSION,
+};
+use crate::runtime::resources::DataStore;
+use crate::runtime::InstanceContext;
+use crate::{
+    ControlMessage, NodeId, PortId, PortType, RecordingMetadata, ZFError, ZFResult,
+    RECORDING_METADATA_VERSION,
+};
+use async_std::task;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zenoh::publication::CongestionControl;
+
+/// A recording in progress.
+struct Recording {
+    info: RecordingInfo,
+    file: Option<RecordingWriter>,
+}
+
+impl Recording {
+    /// Puts the `message` on Zenoh and, if any, writes it in the file.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - serialization fails
+    /// - unable to put on zenoh
+    /// - unable to write the file
+    async fn record(&mut self, context: &InstanceContext, message: &Message) -> ZFResult<()> {
+        let serialized = message.serialize_bincode()?;
+        log::trace!("ZenohLogger - {} => {:?} ", self.info.key_expr, serialized);
+        self.info.size += serialized.len() as u64;
+        if let Message::Data(_) = message {
+            self.info.messages += 1;
+        }
+
+        context
+            .runtime
+            .session
+            .get()
+            .await?
+            .put(&self.info.key_expr, serialized.clone())
+            .congestion_control(CongestionControl::Block)
+            .await?;
+
+        // The file is written on a blocking thread, it is given back once written.
+        if let Some(mut file) = self.file.take() {
+            let timestamp = message.get_timestamp();
+            let (file, result) = task::spawn_blocking(move || {
+                let result = file.write_serialized(&serialized, timestamp);
+                (file, result)
+            })
+            .await;
+            self.file = Some(file);
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A `PortRecorder` records, on Zenoh, the messages sent on a port of a node.
+///
+/// A recording is stored under
+/// `/zf/record/{flow_id}/{instance_id}/{node_id}/{port_id}/{start_time}`. It starts with a
+/// `RecordingStart` control message, followed by the recorded messages, and ends with a
+/// `RecordingStop` control message.
+///
+/// If the runtime has a `recordings_dir`, the recording is also written in the file
+/// `{recordings_dir}/{flow_id}/{instance_id}/{node_id}/{port_id}/{start_time}.zfrec`.
+///
+/// Each recording is referenced, as a [`RecordingInfo`](`RecordingInfo`), in the catalog of
+/// the `DataStore`: when it starts and, with its final statistics, when it stops.
+#[derive(Clone)]
+pub(crate) struct PortRecorder {
+    context: InstanceContext,
+    node_id: NodeId,
+    port_id: PortId,
+    port_type: PortType,
+    current_recording: Arc<Mutex<Option<Recording>>>,
+}
+
+impl PortRecorder {
+    /// Creates a new `PortRecorder` for the port `port_id` of the node `node_id`.
+    pub(crate) fn new(
+        context: InstanceContext,
+        node_id: NodeId,
+        port_id: PortId,
+        port_type: PortType,
+    ) -> Self {
+        Self {
+            context,
+            node_id,
+            port_id,
+            port_type,
+            current_recording: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the path of the file of the recording described by `metadata`, in the
+    /// `recordings_dir`.
+    fn file_path(recordings_dir: &Path, metadata: &RecordingMetadata) -> PathBuf {
+        let mut path = recordings_dir.to_path_buf();
+        path.push(metadata.get_flow_id().to_string());
+        path.push(metadata.get_instance_id().to_string());
+        path.push(metadata.get_node_id().to_string());
+        path.push(metadata.get_port_id().to_string());
+        path.push(format!("{}", metadata.get_timestamp().get_time()));
+        path.set_extension(RECORDING_FILE_EXTENSION);
+        path
+    }
+
+    /// Creates the file, at `path`, of the recording described by `metadata`, on a blocking
+    /// thread.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be created.
+    async fn create_file(path: PathBuf, metadata: RecordingMetadata) -> ZFResult<RecordingWriter> {
+        log::debug!("ZenohLogger - Recording in file {}", path.display());
+
+        task::spawn_blocking(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            RecordingWriter::create(&path, &RecordingHeader { metadata })
+        })
+        .await
+    }
+
+    /// Starts a recording, returns the key expression where it is stored.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - already recording
+    /// - serialization fails
+    /// - unable to put on zenoh
+    /// - unable to create the file
+    /// - unable to add the recording to the catalog
+    pub(crate) async fn start(&self) -> ZFResult<String> {
+        let mut recording_guard = self.current_recording.lock().await;
+        if recording_guard.is_some() {
+            return Err(ZFError::AlreadyRecording);
+        }
+
+        let ts_recording_start = self.context.runtime.hlc.new_timestamp();
+        let recording_metadata = RecordingMetadata {
+            version: RECORDING_METADATA_VERSION,
+            timestamp: ts_recording_start,
+            port_id: self.port_id.clone(),
+            port_type: self.port_type.clone(),
+            node_id: self.node_id.clone(),
+            flow_id: self.context.flow_id.clone(),
+            instance_id: self.context.instance_id,
+        };
+
+        let file_path = self
+            .context
+            .runtime
+            .recordings_dir
+            .as_ref()
+            .map(|recordings_dir| Self::file_path(recordings_dir, &recording_metadata));
+        let file = match &file_path {
+            Some(path) => Some(Self::create_file(path.clone(), recording_metadata.clone()).await?),
+            None => None,
+        };
+
+        let mut recording = Recording {
+            info: RecordingInfo {
+                id: Uuid::new_v4(),
+                key_expr: recording_metadata.get_resource_name(),
+                flow_id: self.context.flow_id.clone(),
+                instance_id: self.context.instance_id,
+                node_id: self.node_id.clone(),
+                port_id: self.port_id.clone(),
+                port_type: self.port_type.clone(),
+                runtime_id: self.context.runtime.runtime_uuid,
+                start: ts_recording_start,
+                stop: None,
+                messages: 0,
+                size: 0,
+                file: file_path.map(|path| path.to_string_lossy().into_owned()),
+            },
+            file,
+        };
+        log::trace!(
+            "ZenohLogger - {} - Started recording at {:?}",
+            recording.info.key_expr,
+            ts_recording_start
+        );
+
+        let message = Message::Control(ControlMessage::RecordingStart(recording_metadata));
+        recording.record(&self.context, &message).await?;
+
+        DataStore::new(self.context.runtime.session.get().await?)
+            .add_recording(&recording.info)
+            .await?;
+
+        let resource_name = recording.info.key_expr.clone();
+        *recording_guard = Some(recording);
+        Ok(resource_name)
+    }
+
+    /// Stops the recording, returns the key expression where it is stored.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - not recording
+    /// - serialization fails
+    /// - unable to put on zenoh
+    /// - unable to write the file
+    /// - unable to update the recording in the catalog
+    pub(crate) async fn stop(&self) -> ZFResult<String> {
+        let mut recording = self
+            .current_recording
+            .lock()
+            .await
+            .take()
+            .ok_or(ZFError::NotRecording)?;
+
+        let ts_recording_stop = self.context.runtime.hlc.new_timestamp();
+        let message = Message::Control(ControlMessage::RecordingStop(ts_recording_stop));
+        log::debug!(
+            "ZenohLogger - {} - Stop recording at {:?}",
+            recording.info.key_expr,
+            ts_recording_stop
+        );
+        recording.record(&self.context, &message).await?;
+
+        if let Some(file) = recording.file {
+            task::spawn_blocking(move || file.finish()).await?;
+        }
+
+        recording.info.stop = Some(ts_recording_stop);
+        DataStore::new(self.context.runtime.session.get().await?)
+            .add_recording(&recording.info)
+            .await?;
+
+        Ok(recording.info.key_expr)
+    }
+
+    /// Checks if the port is being recorded.
+    pub(crate) async fn is_recording(&self) -> bool {
+        self.current_recording.lock().await.is_some()
+    }
+
+    /// Records the given `message`, if the port is being recorded.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - unable to put on zenoh
+    /// - unable to write the file
+    /// - serialization fails
+    pub(crate) async fn record(&self, message: Arc<Message>) -> ZFResult<()> {
+        log::trace!("ZenohLogger IN <= {:?} ", message);
+        let mut recording_guard = self.current_recording.lock().await;
+
+        match recording_guard.as_mut() {
+            Some(recording) => recording.record(&self.context, &message).await,
+            None => {
+                log::trace!("ZenohLogger Dropping!");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The [`PortRecorder`](`PortRecorder`)s of all the ports of a node that can be recorded.
+#[derive(Clone)]
+pub(crate) struct Recorders {
+    node_id: NodeId,
+    recorders: HashMap<PortId, PortRecorder>,
+}
+
+impl Recorders {
+    /// Creates a `PortRecorder` for each of the provided ports of the node `node_id`.
+    pub(crate) fn new<'a>(
+        context: &InstanceContext,
+        node_id: &NodeId,
+        ports: impl Iterator<Item = (&'a PortId, &'a PortType)>,
+    ) -> Self {
+        Self {
+            node_id: node_id.clone(),
+            recorders: ports
+                .map(|(port_id, port_type)| {
+                    (
+                        port_id.clone(),
+                        PortRecorder::new(
+                            context.clone(),
+                            node_id.clone(),
+                            port_id.clone(),
+                            port_type.clone(),
+                        ),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the `PortRecorder` of the given port.
+    ///
+    /// # Errors
+    /// An error variant is returned if the port cannot be recorded.
+    fn get(&self, port_id: &PortId) -> ZFResult<&PortRecorder> {
+        self.recorders
+            .get(port_id)
+            .ok_or_else(|| ZFError::PortNotFound((self.node_id.clone(), port_id.clone())))
+    }
+
+    /// Starts recording the given port, returns the key expression where it is stored.
+    ///
+    /// # Errors
+    /// An error variant is returned if the port cannot be recorded or if
+    /// [`PortRecorder::start`](`PortRecorder::start`) fails.
+    pub(crate) async fn start(&self, port_id: &PortId) -> ZFResult<String> {
+        self.get(port_id)?.start().await
+    }
+
+    /// Stops recording the given port, returns the key expression where it is stored.
+    ///
+    /// # Errors
+    /// An error variant is returned if the port cannot be recorded or if
+    /// [`PortRecorder::stop`](`PortRecorder::stop`) fails.
+    pub(crate) async fn stop(&self, port_id: &PortId) -> ZFResult<String> {
+        self.get(port_id)?.stop().await
+    }
+
+    /// Returns the ports that are being recorded.
+    pub(crate) async fn recording_ports(&self) -> Vec<PortId> {
+        let mut ports = vec![];
+        for (port_id, recorder) in self.recorders.iter() {
+            if recorder.is_recording().await {
+                ports.push(port_id.clone());
+            }
+        }
+        ports
+    }
+
+    /// Records the given `message` sent on `port_id`, if this port is being recorded.
+    ///
+    /// # Errors
+    /// An error variant is returned if [`PortRecorder::record`](`PortRecorder::record`) fails.
+    pub(crate) async fn record(&self, port_id: &PortId, message: Arc<Message>) -> ZFResult<()> {
+        match self.recorders.get(port_id) {
+            Some(recorder) => recorder.record(message).await,
+            None => Ok(()),
+        }
+    }
+}
@@ -12,20 +12,151 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use super::{Runner, RunnerKind};
+use super::{PauseGate, Runner, RunnerKind};
 use crate::async_std::sync::{Arc, Mutex};
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
 use crate::runtime::message::Message;
+use crate::runtime::recording::{read_recording, RECORDING_FILE_SCHEME};
 use crate::runtime::InstanceContext;
 use crate::{ControlMessage, NodeId, PortId, PortType, ZFError, ZFResult};
 use async_std::task;
 use async_trait::async_trait;
 use futures::prelude::*;
-use std::collections::HashMap;
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+use uhlc::{Timestamp, NTP64};
 use zenoh::net::protocol::io::SplitBuffer;
 use zenoh::query::*;
 use zenoh::*;
 
+/// In streaming mode, the number of messages kept in memory to reorder them before replaying.
+const STREAMING_REORDER_WINDOW: usize = 1024;
+
+/// The pace at which a recording is replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaySpeed {
+    /// The pace of the recording.
+    RealTime,
+    /// The pace of the recording multiplied by the factor: `2.0` replays twice as fast, `0.5`
+    /// twice as slow.
+    Factor(f64),
+    /// The messages are replayed without waiting.
+    AsFastAsPossible,
+}
+
+impl Default for ReplaySpeed {
+    fn default() -> Self {
+        Self::RealTime
+    }
+}
+
+/// The options of a replay.
+///
+/// - `speed` is the pace of the replay,
+/// - `start` and `end` select a time window, relative to the start of the recording,
+/// - `looping` replays the recording again, once finished, until the replay is stopped: the
+///   timestamps of each pass are moved later by the duration of the recording, a recording
+///   without any message to replay stops the loop,
+/// - `streaming` replays the messages as they are retrieved instead of first retrieving the
+///   whole recording: only a small window of messages is kept in memory to reorder them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayOptions {
+    #[serde(default)]
+    pub speed: ReplaySpeed,
+    #[serde(default)]
+    pub start: Option<Duration>,
+    #[serde(default)]
+    pub end: Option<Duration>,
+    #[serde(default)]
+    pub looping: bool,
+    #[serde(default)]
+    pub streaming: bool,
+}
+
+impl ReplayOptions {
+    /// Checks that the options are consistent.
+    ///
+    /// # Errors
+    /// An error variant is returned if the speed factor is not strictly positive or if the time
+    /// window is empty.
+    pub fn validate(&self) -> ZFResult<()> {
+        if let ReplaySpeed::Factor(factor) = self.speed {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(ZFError::InvalidData(format!(
+                    "Replay speed factor must be strictly positive, found: {}",
+                    factor
+                )));
+            }
+        }
+
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            if start > end {
+                return Err(ZFError::InvalidData(format!(
+                    "Replay window is empty: start ({:?}) is after end ({:?})",
+                    start, end
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns how long to wait, given the time elapsed between two messages in the recording.
+    fn scale(&self, elapsed: Duration) -> Duration {
+        match self.speed {
+            ReplaySpeed::RealTime => elapsed,
+            ReplaySpeed::Factor(factor) => elapsed.div_f64(factor),
+            ReplaySpeed::AsFastAsPossible => Duration::ZERO,
+        }
+    }
+}
+
+/// Where a replay is in the recording.
+#[derive(Default)]
+struct Playhead {
+    // The timestamp of the `RecordingStart`, or of the first message if it is missing.
+    origin: Option<Timestamp>,
+    // The offset, relative to the `origin`, of the last message replayed.
+    offset: Duration,
+    // The offset, relative to the `origin`, of the `RecordingStop` or of the last message.
+    end: Duration,
+    // How much later than recorded the messages are replayed, when looping.
+    shift: Duration,
+    // The number of messages replayed.
+    played: usize,
+}
+
+/// Returns the given timestamp moved `shift` later.
+fn shift_timestamp(timestamp: &Timestamp, shift: Duration) -> Timestamp {
+    // NTP64: the seconds in the upper 32 bits, the fraction of second in the lower 32 bits.
+    let fraction = ((shift.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    let shift = (shift.as_secs() << 32) + fraction;
+    Timestamp::new(
+        NTP64(timestamp.get_time().0.saturating_add(shift)),
+        *timestamp.get_id(),
+    )
+}
+
+/// Returns the given message moved `shift` later: its timestamp and the start of its end to end
+/// deadlines.
+fn shift_message(message: Message, shift: Duration) -> Message {
+    match message {
+        Message::Data(mut data_msg) => {
+            data_msg.timestamp = shift_timestamp(&data_msg.timestamp, shift);
+            for deadline in data_msg.end_to_end_deadlines.iter_mut() {
+                deadline.start = shift_timestamp(&deadline.start, shift);
+            }
+            Message::Data(data_msg)
+        }
+        control => control,
+    }
+}
+
 /// The Replay node.
 #[derive(Clone)]
 pub struct ZenohReplay {
@@ -35,16 +166,21 @@ pub struct ZenohReplay {
     pub(crate) port_id: PortId,
     pub(crate) port_type: PortType,
     pub(crate) resource_name: String,
+    pub(crate) options: ReplayOptions,
     pub(crate) is_running: Arc<Mutex<bool>>,
+    pub(crate) pause_gate: PauseGate,
     pub(crate) links: Arc<Mutex<Vec<LinkSender<Message>>>>,
 }
 
 impl ZenohReplay {
     /// Tries to create a replay node, that will replay from the given
-    /// `resource_name`.
+    /// `resource_name` according to the `options`.
+    /// If `resource_name` starts with `file://` the replay reads the
+    /// recording file that follows.
     ///
     /// # Errors
-    /// It fails if the ports are not connected correctly.
+    /// It fails if the ports are not connected correctly or if the options
+    /// are not valid.
     pub fn try_new(
         id: NodeId,
         context: InstanceContext,
@@ -53,7 +189,10 @@ impl ZenohReplay {
         port_type: PortType,
         links: Vec<LinkSender<Message>>,
         resource_name: String,
+        options: ReplayOptions,
     ) -> ZFResult<Self> {
+        options.validate()?;
+
         if links.len() != 1 {
             return Err(ZFError::IOError(format!(
                 "Expected exactly one link for port < {} > for Replay < {} >, found: {}",
@@ -70,7 +209,9 @@ impl ZenohReplay {
             port_id,
             port_type,
             resource_name,
+            options,
             is_running: Arc::new(Mutex::new(false)),
+            pause_gate: PauseGate::default(),
             links: Arc::new(Mutex::new(links)),
         })
     }
@@ -97,6 +238,173 @@ impl ZenohReplay {
     async fn start(&self) {
         *self.is_running.lock().await = true;
     }
+
+    /// Retrieves the messages of the recording, either from Zenoh or, if the
+    /// resource name starts with `file://`, from a recording file.
+    ///
+    /// A message that cannot be retrieved, e.g. the start of a recording made
+    /// with another version of the metadata, is an error of the stream.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - zenoh query fails
+    /// - the file cannot be read
+    /// - the recorded port type does not match the port type of the source
+    async fn messages(&self) -> ZFResult<BoxStream<'static, ZFResult<Message>>> {
+        if let Some(path) = self.resource_name.strip_prefix(RECORDING_FILE_SCHEME) {
+            let (header, messages) =
+                read_recording(PathBuf::from(path), STREAMING_REORDER_WINDOW).await?;
+            let recorded_type = header.metadata.get_port_type();
+            if *recorded_type != self.port_type {
+                return Err(ZFError::PortTypeNotMatching((
+                    self.port_type.clone(),
+                    recorded_type.clone(),
+                )));
+            }
+
+            return Ok(messages);
+        }
+
+        let query_target = QueryTarget {
+            kind: queryable::STORAGE,
+            target: Target::default(),
+        };
+        let res_name = format!("{}?(starttime=0)", self.resource_name);
+        let session = self.context.runtime.session.get().await?;
+        let replies = session
+            .get(&res_name)
+            .target(query_target)
+            .consolidation(QueryConsolidation::none())
+            .await?;
+
+        Ok(replies
+            .map(|reply| Message::deserialize_bincode(&reply.sample.value.payload.contiguous()))
+            .boxed())
+    }
+
+    /// Replays the recording once, `shift` later than recorded, and returns where the replay
+    /// ended.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - zenoh query fails
+    /// - the file cannot be read
+    /// - a message cannot be deserialized
+    /// - link send fails
+    async fn replay(&self, shift: Duration) -> ZFResult<Playhead> {
+        log::debug!("ZenohReplay - {} - Started", self.resource_name);
+        let mut messages = self.messages().await?;
+        let mut playhead = Playhead {
+            shift,
+            ..Default::default()
+        };
+
+        if !self.options.streaming {
+            // Here we need to get all the data and then order it.
+            let mut zf_data: Vec<Message> = messages.try_collect().await?;
+            zf_data.sort();
+            log::trace!("ZenohReplay - Total samples {} ", zf_data.len());
+
+            for de in zf_data {
+                if !self.play(de, &mut playhead).await? {
+                    break;
+                }
+            }
+
+            return Ok(playhead);
+        }
+
+        // Streaming: the messages are reordered within a window, a message arriving after a
+        // more recent one left the window is replayed late.
+        let mut window = BinaryHeap::with_capacity(STREAMING_REORDER_WINDOW + 1);
+        while let Some(de) = messages.next().await {
+            window.push(Reverse(de?));
+            if window.len() > STREAMING_REORDER_WINDOW {
+                if let Some(Reverse(de)) = window.pop() {
+                    if !self.play(de, &mut playhead).await? {
+                        return Ok(playhead);
+                    }
+                }
+            }
+        }
+
+        while let Some(Reverse(de)) = window.pop() {
+            if !self.play(de, &mut playhead).await? {
+                break;
+            }
+        }
+
+        Ok(playhead)
+    }
+
+    /// Replays a single message: waits, depending on the speed, and sends it if it is within the
+    /// time window.
+    ///
+    /// Returns `false` if the end of the time window is reached.
+    ///
+    /// # Errors
+    /// An error variant is returned if link send fails.
+    async fn play(&self, de: Message, playhead: &mut Playhead) -> ZFResult<bool> {
+        log::trace!("ZenohReplay - {}<={:?} ", self.resource_name, de);
+        let data_ts = match &de {
+            Message::Control(ControlMessage::RecordingStart(ref ts)) => {
+                log::trace!("ZenohReplay - Recording start {:?} ", ts);
+                playhead.origin.get_or_insert(ts.timestamp);
+                return Ok(true);
+            }
+            Message::Control(ControlMessage::RecordingStop(ref rs)) => {
+                log::trace!("ZenohReplay - Recording Stop {:?} ", rs);
+                if let Some(origin) = playhead.origin {
+                    if *rs > origin {
+                        playhead.end = std::cmp::max(playhead.end, rs.get_diff_duration(&origin));
+                    }
+                }
+                return Ok(true);
+            }
+            Message::Data(ref data_msg) => data_msg.timestamp,
+        };
+
+        let origin = *playhead.origin.get_or_insert(data_ts);
+        let offset = if data_ts > origin {
+            data_ts.get_diff_duration(&origin)
+        } else {
+            Duration::ZERO
+        };
+        playhead.end = std::cmp::max(playhead.end, offset);
+
+        if let Some(start) = self.options.start {
+            if offset < start {
+                return Ok(true);
+            }
+        }
+
+        if let Some(end) = self.options.end {
+            if offset > end {
+                log::debug!("ZenohReplay - {} - End of window", self.resource_name);
+                return Ok(false);
+            }
+        }
+
+        let last_offset = std::cmp::max(playhead.offset, self.options.start.unwrap_or_default());
+        let wait_time = self.options.scale(offset.saturating_sub(last_offset));
+        if !wait_time.is_zero() {
+            log::trace!("ZenohReplay - Wait for {:?} ", wait_time);
+            task::sleep(wait_time).await;
+        }
+
+        // A paused replay holds the message, the time spent paused does not shorten the next waits.
+        self.pause_gate.pass().await;
+        if playhead.shift.is_zero() {
+            self.send_data(de).await?;
+        } else {
+            self.send_data(shift_message(de, playhead.shift)).await?;
+        }
+
+        // Updating last sent offset
+        playhead.offset = offset;
+        playhead.played += 1;
+        Ok(true)
+    }
 }
 
 #[async_trait]
@@ -140,16 +448,16 @@ impl Runner for ZenohReplay {
         HashMap::with_capacity(0)
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
+    async fn start_recording(&self, _port_id: &PortId) -> ZFResult<String> {
         Err(ZFError::Unsupported)
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
+    async fn stop_recording(&self, _port_id: &PortId) -> ZFResult<String> {
         Err(ZFError::Unsupported)
     }
 
-    async fn is_recording(&self) -> bool {
-        false
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        vec![]
     }
 
     async fn is_running(&self) -> bool {
@@ -160,72 +468,229 @@ impl Runner for ZenohReplay {
         *self.is_running.lock().await = false;
     }
 
+    async fn pause(&self) -> ZFResult<()> {
+        self.pause_gate.close().await;
+        Ok(())
+    }
+
+    async fn resume(&self) -> ZFResult<()> {
+        self.pause_gate.open().await;
+        Ok(())
+    }
+
+    async fn is_paused(&self) -> bool {
+        self.pause_gate.is_closed().await
+    }
+
     async fn run(&self) -> ZFResult<()> {
         self.start().await;
 
-        let res = {
-            log::debug!("ZenohReplay - {} - Started", self.resource_name);
-            let query_target = QueryTarget {
-                kind: queryable::STORAGE,
-                target: Target::default(),
+        let mut shift = Duration::ZERO;
+        let res = loop {
+            let playhead = match self.replay(shift).await {
+                Ok(playhead) => playhead,
+                Err(e) => break Err(e),
             };
-            let res_name = format!("{}?(starttime=0)", self.resource_name);
-            let replies = self
-                .context
-                .runtime
-                .session
-                .get(&res_name)
-                .target(query_target)
-                .consolidation(QueryConsolidation::none())
-                .await?;
-
-            // Placeholder
-            let mut last_ts = self.context.runtime.hlc.new_timestamp();
 
-            // Here we need to get all the data and then order it.
-            let data = replies.collect::<Vec<Reply>>().await;
-            let mut zf_data: Vec<Message> = data
-                .iter()
-                .filter_map(|msg| {
-                    bincode::deserialize::<Message>(&msg.sample.value.payload.contiguous()).ok()
-                })
-                .collect();
-            zf_data.sort();
-            log::trace!("ZenohReplay - Total samples {} ", zf_data.len());
+            if !self.options.looping {
+                break Ok(());
+            }
 
-            for de in zf_data {
-                log::trace!("ZenohReplay - {}<={:?} ", self.resource_name, de);
-                match &de {
-                    Message::Control(ref ctrl_msg) => match &ctrl_msg {
-                        ControlMessage::RecordingStart(ref ts) => {
-                            log::trace!("ZenohReplay - Recording start {:?} ", ts);
-                            last_ts = ts.timestamp;
-                        }
-                        ControlMessage::RecordingStop(ref rs) => {
-                            log::trace!("ZenohReplay - Recording Stop {:?} ", rs);
-                        } // Commented because Control messages are not yet defined.
-                          // _ => {
-                          //     self.send_data(de).await?;
-                          // }
-                    },
-                    Message::Data(ref data_msg) => {
-                        let data_ts = data_msg.timestamp;
-                        let wait_time = data_ts.get_diff_duration(&last_ts);
-
-                        log::trace!("ZenohReplay - Wait for {:?} ", wait_time);
-                        task::sleep(wait_time).await;
-
-                        self.send_data(de).await?;
-
-                        // Updating last sent timestamp
-                        last_ts = data_ts;
-                    }
-                }
+            // Otherwise an empty, or missing, recording would be queried again and again.
+            if playhead.played == 0 {
+                log::error!(
+                    "ZenohReplay - {} - No message to replay, stopping the loop",
+                    self.resource_name
+                );
+                break Err(ZFError::Empty);
             }
+
+            // The next pass is moved later by the duration of the recording: its timestamps keep
+            // increasing. The extra nanosecond separates the last message of a pass from the first
+            // one of the next pass when the recording has neither a start nor a stop.
+            shift += playhead.end + Duration::from_nanos(1);
+            log::debug!("ZenohReplay - {} - Looping", self.resource_name);
         };
 
         self.stop().await;
 
-        Ok(res)
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::dataflow::instance::link::link;
+    use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
+    use crate::runtime::message::{DataMessage, RecordingMetadata};
+    use crate::runtime::recording::{RecordingHeader, RecordingWriter, RECORDING_FILE_EXTENSION};
+    use crate::runtime::session::LazySession;
+    use crate::runtime::RuntimeContext;
+    use crate::RECORDING_METADATA_VERSION;
+    use uhlc::HLC;
+    use uuid::Uuid;
+
+    const MESSAGES: usize = 5;
+
+    fn write_recording(messages: usize) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "zf-replay-{}.{}",
+            Uuid::new_v4(),
+            RECORDING_FILE_EXTENSION
+        ));
+        let hlc = HLC::default();
+        let metadata = RecordingMetadata {
+            version: RECORDING_METADATA_VERSION,
+            timestamp: hlc.new_timestamp(),
+            port_id: "out".into(),
+            port_type: "int".into(),
+            node_id: "source".into(),
+            flow_id: "flow".into(),
+            instance_id: Uuid::new_v4(),
+        };
+
+        let mut writer = RecordingWriter::create(
+            &path,
+            &RecordingHeader {
+                metadata: metadata.clone(),
+            },
+        )
+        .unwrap();
+        writer
+            .write(&Message::Control(ControlMessage::RecordingStart(metadata)))
+            .unwrap();
+        for i in 0..messages {
+            writer
+                .write(&Message::Data(DataMessage::new_serialized(
+                    Arc::new(vec![i as u8]),
+                    hlc.new_timestamp(),
+                    vec![],
+                    vec![],
+                )))
+                .unwrap();
+        }
+        writer
+            .write(&Message::Control(ControlMessage::RecordingStop(
+                hlc.new_timestamp(),
+            )))
+            .unwrap();
+        writer.finish().unwrap();
+
+        path
+    }
+
+    fn looping_replay(path: &std::path::Path) -> (ZenohReplay, LinkReceiver<Message>) {
+        let runtime_uuid = Uuid::new_v4();
+        let context = InstanceContext {
+            flow_id: "flow".into(),
+            instance_id: Uuid::new_v4(),
+            runtime: RuntimeContext {
+                session: LazySession::disabled(),
+                loader: Arc::new(Loader::new(LoaderConfig::new())),
+                hlc: Arc::new(HLC::default()),
+                runtime_name: "replay-runtime".into(),
+                runtime_uuid,
+                recordings_dir: None,
+            },
+        };
+        let (tx, rx) = link::<Message>(None, "out".into(), "in".into());
+        let replay = ZenohReplay::try_new(
+            "replay".into(),
+            context,
+            "source".into(),
+            "out".into(),
+            "int".into(),
+            vec![tx],
+            format!("{}{}", RECORDING_FILE_SCHEME, path.display()),
+            ReplayOptions {
+                speed: ReplaySpeed::AsFastAsPossible,
+                looping: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        (replay, rx)
+    }
+
+    #[test]
+    fn replay_looping_moves_timestamps() {
+        let path = write_recording(MESSAGES);
+        let (replay, rx) = looping_replay(&path);
+
+        async_std::task::block_on(async move {
+            let handle = task::spawn(async move { replay.run().await });
+
+            let mut timestamps = Vec::with_capacity(3 * MESSAGES);
+            for _ in 0..3 * MESSAGES {
+                let (_, message) = rx.recv().await.unwrap();
+                timestamps.push(message.get_timestamp());
+            }
+            handle.cancel().await;
+
+            // The timestamps keep increasing across the passes, each pass is moved by the same
+            // duration.
+            assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+            let shift = timestamps[MESSAGES].get_diff_duration(&timestamps[0]);
+            for (first, next) in timestamps[..MESSAGES]
+                .iter()
+                .zip(timestamps[MESSAGES..].iter())
+            {
+                assert_eq!(next.get_diff_duration(first), shift);
+            }
+        });
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn replay_looping_empty_recording() {
+        let path = write_recording(0);
+        let (replay, _rx) = looping_replay(&path);
+
+        let res = async_std::task::block_on(async move {
+            async_std::future::timeout(Duration::from_secs(5), replay.run()).await
+        });
+        assert!(matches!(res, Ok(Err(ZFError::Empty))));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn replay_options_validate() {
+        assert!(ReplayOptions::default().validate().is_ok());
+
+        let options = ReplayOptions {
+            speed: ReplaySpeed::Factor(0.0),
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+
+        let options = ReplayOptions {
+            start: Some(Duration::from_millis(20)),
+            end: Some(Duration::from_millis(10)),
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn replay_options_scale() {
+        let elapsed = Duration::from_millis(100);
+
+        assert_eq!(ReplayOptions::default().scale(elapsed), elapsed);
+
+        let options = ReplayOptions {
+            speed: ReplaySpeed::Factor(2.0),
+            ..Default::default()
+        };
+        assert_eq!(options.scale(elapsed), Duration::from_millis(50));
+
+        let options = ReplayOptions {
+            speed: ReplaySpeed::AsFastAsPossible,
+            ..Default::default()
+        };
+        assert_eq!(options.scale(elapsed), Duration::ZERO);
     }
 }
@@ -15,17 +15,20 @@
 use std::collections::HashMap;
 
 use crate::async_std::sync::{Arc, Mutex};
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
 use crate::model::link::PortDescriptor;
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
 use crate::runtime::dataflow::instance::runners::operator::OperatorIO;
-use crate::runtime::dataflow::instance::runners::{Runner, RunnerKind};
+use crate::runtime::dataflow::instance::runners::{PauseGate, Runner, RunnerKind};
 use crate::runtime::dataflow::node::SinkLoaded;
+use crate::runtime::deadline::{report_deadline_miss, DeadlineMiss, DeadlineMissStats};
 use crate::runtime::message::Message;
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::InstanceContext;
 use crate::types::ZFResult;
-use crate::{Context, NodeId, PortId, PortType, Sink, State, ZFError};
+use crate::{Context, LocalDeadlineMiss, NodeId, PortId, PortType, Sink, State, ZFError};
 use async_trait::async_trait;
+use std::time::{Duration, Instant};
 
 #[cfg(target_family = "unix")]
 use libloading::os::unix::Library;
@@ -47,6 +50,11 @@ pub struct SinkRunner {
     pub(crate) input: PortDescriptor,
     pub(crate) link: Arc<Mutex<Option<LinkReceiver<Message>>>>,
     pub(crate) _end_to_end_deadlines: Vec<E2EDeadlineRecord>, //FIXME
+    pub(crate) local_deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
+    pub(crate) deadline_miss_stats: Arc<Mutex<DeadlineMissStats>>,
+    pub(crate) run_tracker: RunTracker,
+    pub(crate) pause_gate: PauseGate,
     pub(crate) is_running: Arc<Mutex<bool>>,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) sink: Arc<dyn Sink>,
@@ -76,6 +84,11 @@ impl SinkRunner {
             input: sink.input,
             link: Arc::new(Mutex::new(Some(link))),
             _end_to_end_deadlines: sink.end_to_end_deadlines,
+            local_deadline: sink.local_deadline,
+            on_deadline_miss: sink.on_deadline_miss,
+            deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+            run_tracker: RunTracker::default(),
+            pause_gate: PauseGate::default(),
             is_running: Arc::new(Mutex::new(false)),
             state: sink.state,
             sink: sink.sink,
@@ -86,6 +99,7 @@ impl SinkRunner {
     /// Starts the sink.
     async fn start(&self) {
         *self.is_running.lock().await = true;
+        self.run_tracker.reset();
     }
 
     /// A single iteration of the run loop.
@@ -98,9 +112,12 @@ impl SinkRunner {
     async fn iteration(&self, mut context: Context) -> ZFResult<Context> {
         // Guards are taken at the beginning of each iteration to allow interleaving.
         if let Some(link) = &*self.link.lock().await {
+            let (port_id, message) = link.recv().await?;
+            // The state is only locked once a message is received (and the node is not paused)
+            // so that it can be checkpointed while the sink waits.
+            self.pause_gate.pass().await;
             let mut state = self.state.lock().await;
 
-            let (port_id, message) = link.recv().await?;
             let input = match message.as_ref() {
                 Message::Data(data_message) => {
                     if let Err(error) = self
@@ -118,15 +135,45 @@ impl SinkRunner {
                     }
                     let now = self.context.runtime.hlc.new_timestamp();
                     let mut input = data_message.clone();
+                    let mut discard = false;
+
+                    for e2e_deadline in data_message.end_to_end_deadlines.iter() {
+                        if let Some(miss) = e2e_deadline.check(&self.id, &port_id, &now) {
+                            let policy = match e2e_deadline.on_miss.clone() {
+                                // Rejected by the validation, a sink has no output.
+                                None | Some(DeadlineMissPolicy::Fallback(_)) => {
+                                    DeadlineMissPolicy::Log
+                                }
+                                Some(policy) => policy,
+                            };
+                            let deadline_miss = DeadlineMiss::EndToEnd(miss.clone());
 
-                    data_message
-                        .end_to_end_deadlines
-                        .iter()
-                        .for_each(|e2e_deadline| {
-                            if let Some(miss) = e2e_deadline.check(&self.id, &port_id, &now) {
-                                input.missed_end_to_end_deadlines.push(miss);
+                            if policy == DeadlineMissPolicy::Callback {
+                                self.sink.on_deadline_miss(&mut state, &deadline_miss)?;
                             }
-                        });
+
+                            report_deadline_miss(
+                                &self.context,
+                                &self.id,
+                                &self.deadline_miss_stats,
+                                &policy,
+                                &deadline_miss,
+                            )
+                            .await;
+
+                            discard |= policy == DeadlineMissPolicy::Drop;
+                            input.missed_end_to_end_deadlines.push(miss);
+                        }
+                    }
+
+                    if discard {
+                        log::debug!(
+                            "[Sink: {}] Dropping message received on < {} > (deadline miss).",
+                            self.id,
+                            port_id
+                        );
+                        return Ok(context);
+                    }
 
                     input
                 }
@@ -134,7 +181,46 @@ impl SinkRunner {
                 Message::Control(_) => return Err(ZFError::Unimplemented),
             };
 
-            self.sink.run(&mut context, &mut state, input).await?;
+            let start = Instant::now();
+            let run_guard = self.run_tracker.enter();
+            let result = self.sink.run(&mut context, &mut state, input).await;
+            drop(run_guard);
+            result?;
+            let elapsed = start.elapsed();
+
+            if let Some(deadline) = self.local_deadline {
+                if elapsed > deadline {
+                    log::warn!(
+                        "[Sink: {}] Deadline miss detected for `run`: {} ms (expected < {} ms)",
+                        self.id,
+                        elapsed.as_micros(),
+                        deadline.as_micros()
+                    );
+                    // The data was consumed: `drop` has the same effect as `log`.
+                    let policy = match self.on_deadline_miss.clone() {
+                        None | Some(DeadlineMissPolicy::Fallback(_)) => DeadlineMissPolicy::Log,
+                        Some(policy) => policy,
+                    };
+                    let miss = DeadlineMiss::Local(LocalDeadlineMiss {
+                        start,
+                        deadline,
+                        elapsed,
+                    });
+
+                    if policy == DeadlineMissPolicy::Callback {
+                        self.sink.on_deadline_miss(&mut state, &miss)?;
+                    }
+
+                    report_deadline_miss(
+                        &self.context,
+                        &self.id,
+                        &self.deadline_miss_stats,
+                        &policy,
+                        &miss,
+                    )
+                    .await;
+                }
+            }
         }
         Ok(context)
     }
@@ -187,16 +273,16 @@ impl Runner for SinkRunner {
         HashMap::with_capacity(0)
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
+    async fn start_recording(&self, _port_id: &PortId) -> ZFResult<String> {
         Err(ZFError::Unsupported)
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
+    async fn stop_recording(&self, _port_id: &PortId) -> ZFResult<String> {
         Err(ZFError::Unsupported)
     }
 
-    async fn is_recording(&self) -> bool {
-        false
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        vec![]
     }
 
     async fn is_running(&self) -> bool {
@@ -207,6 +293,36 @@ impl Runner for SinkRunner {
         *self.is_running.lock().await = false;
     }
 
+    async fn get_deadline_miss_stats(&self) -> DeadlineMissStats {
+        self.deadline_miss_stats.lock().await.clone()
+    }
+
+    async fn get_run_elapsed(&self) -> Option<Duration> {
+        self.run_tracker.elapsed()
+    }
+
+    async fn pause(&self) -> ZFResult<()> {
+        self.pause_gate.close().await;
+        Ok(())
+    }
+
+    async fn resume(&self) -> ZFResult<()> {
+        self.pause_gate.open().await;
+        Ok(())
+    }
+
+    async fn is_paused(&self) -> bool {
+        self.pause_gate.is_closed().await
+    }
+
+    async fn checkpoint(&self) -> ZFResult<Option<Vec<u8>>> {
+        self.state.lock().await.try_serialize()
+    }
+
+    async fn restore(&self, bytes: &[u8]) -> ZFResult<()> {
+        self.state.lock().await.try_restore(bytes)
+    }
+
     async fn run(&self) -> ZFResult<()> {
         self.start().await;
 
@@ -216,6 +332,7 @@ impl Runner for SinkRunner {
         // stops and returns the error to the caller (the RunnerManager)
 
         loop {
+            self.pause_gate.pass().await;
             match self.iteration(context).await {
                 Ok(ctx) => {
                     log::trace!(
@@ -14,22 +14,23 @@
 
 use super::operator::OperatorIO;
 use crate::async_std::sync::{Arc, Mutex};
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
 use crate::model::link::PortDescriptor;
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
-use crate::runtime::dataflow::instance::runners::{Runner, RunnerKind};
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
+use crate::runtime::dataflow::instance::runners::{PauseGate, Runner, RunnerKind};
 use crate::runtime::dataflow::node::SourceLoaded;
-use crate::runtime::deadline::E2EDeadline;
+use crate::runtime::deadline::{
+    report_deadline_miss, DeadlineMiss, DeadlineMissStats, E2EDeadline,
+};
 use crate::runtime::message::Message;
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::InstanceContext;
 use crate::types::ZFResult;
-use crate::{
-    Context, ControlMessage, NodeId, PortId, PortType, RecordingMetadata, Source, State, ZFError,
-};
+use crate::{Context, LocalDeadlineMiss, NodeId, PortId, PortType, Source, State, ZFError};
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::time::Duration;
-use zenoh::publication::CongestionControl;
+use std::time::{Duration, Instant};
 
 #[cfg(target_family = "unix")]
 use libloading::os::unix::Library;
@@ -53,9 +54,12 @@ pub struct SourceRunner {
     pub(crate) links: Arc<Mutex<Vec<LinkSender<Message>>>>,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) end_to_end_deadlines: Vec<E2EDeadlineRecord>,
-    pub(crate) base_resource_name: String,
-    pub(crate) current_recording_resource: Arc<Mutex<Option<String>>>,
-    pub(crate) is_recording: Arc<Mutex<bool>>,
+    pub(crate) local_deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
+    pub(crate) deadline_miss_stats: Arc<Mutex<DeadlineMissStats>>,
+    pub(crate) run_tracker: RunTracker,
+    pub(crate) pause_gate: PauseGate,
+    pub(crate) recorders: Recorders,
     pub(crate) is_running: Arc<Mutex<bool>>,
     pub(crate) source: Arc<dyn Source>,
     pub(crate) _library: Option<Arc<Library>>,
@@ -82,9 +86,10 @@ impl SourceRunner {
             ))
         })?;
 
-        let base_resource_name = format!(
-            "/zf/record/{}/{}/{}/{}",
-            &context.flow_id, &context.instance_id, source.id, port_id
+        let recorders = Recorders::new(
+            &context,
+            &source.id,
+            std::iter::once((&port_id, &source.output.port_type)),
         );
 
         Ok(Self {
@@ -95,48 +100,18 @@ impl SourceRunner {
             output: source.output,
             links: Arc::new(Mutex::new(links)),
             end_to_end_deadlines: source.end_to_end_deadlines,
+            local_deadline: source.local_deadline,
+            on_deadline_miss: source.on_deadline_miss,
+            deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+            run_tracker: RunTracker::default(),
+            pause_gate: PauseGate::default(),
             source: source.source,
             _library: source.library,
-            base_resource_name,
-            is_recording: Arc::new(Mutex::new(false)),
+            recorders,
             is_running: Arc::new(Mutex::new(false)),
-            current_recording_resource: Arc::new(Mutex::new(None)),
         })
     }
 
-    /// Records the given `message`.
-    ///
-    /// # Errors
-    /// An error variant is returned in case of:
-    /// - unable to put on zenoh
-    /// - serialization fails
-    async fn record(&self, message: Arc<Message>) -> ZFResult<()> {
-        log::trace!("ZenohLogger IN <= {:?} ", message);
-        let recording = self.is_recording.lock().await;
-
-        if !(*recording) {
-            log::trace!("ZenohLogger Dropping!");
-            return Ok(());
-        }
-
-        let resource_name_guard = self.current_recording_resource.lock().await;
-        let resource_name = resource_name_guard
-            .as_ref()
-            .ok_or(ZFError::Unimplemented)?
-            .clone();
-
-        let serialized = message.serialize_bincode()?;
-        log::trace!("ZenohLogger - {} => {:?} ", resource_name, serialized);
-        self.context
-            .runtime
-            .session
-            .put(&resource_name, serialized)
-            .congestion_control(CongestionControl::Block)
-            .await?;
-
-        Ok(())
-    }
-
     /// A single iteration of the run loop.
     ///
     /// # Errors
@@ -149,7 +124,60 @@ impl SourceRunner {
         let mut state = self.state.lock().await;
 
         // Running
-        let output = self.source.run(&mut context, &mut state).await?;
+        let start = Instant::now();
+        let run_guard = self.run_tracker.enter();
+        let output = self.source.run(&mut context, &mut state).await;
+        drop(run_guard);
+        // The time spent paused is not part of the execution of `run`.
+        let elapsed = start.elapsed();
+        // A source paused while in `run` holds its output until it is resumed. Its state is
+        // released in the meantime so that it can be checkpointed.
+        drop(state);
+        self.pause_gate.pass().await;
+        let mut state = self.state.lock().await;
+        let output = output?;
+
+        if let Some(deadline) = self.local_deadline {
+            if elapsed > deadline {
+                log::warn!(
+                    "[Source: {}] Deadline miss detected for `run`: {} ms (expected < {} ms)",
+                    self.id,
+                    elapsed.as_micros(),
+                    deadline.as_micros()
+                );
+                let policy = match self.on_deadline_miss.clone() {
+                    // Rejected by the validation, there is no output to fall back to.
+                    None | Some(DeadlineMissPolicy::Fallback(_)) => DeadlineMissPolicy::Log,
+                    Some(policy) => policy,
+                };
+                let miss = DeadlineMiss::Local(LocalDeadlineMiss {
+                    start,
+                    deadline,
+                    elapsed,
+                });
+
+                if policy == DeadlineMissPolicy::Callback {
+                    self.source.on_deadline_miss(&mut state, &miss)?;
+                }
+
+                report_deadline_miss(
+                    &self.context,
+                    &self.id,
+                    &self.deadline_miss_stats,
+                    &policy,
+                    &miss,
+                )
+                .await;
+
+                if policy == DeadlineMissPolicy::Drop {
+                    log::debug!(
+                        "[Source: {}] Dropping the output of `run` (deadline miss).",
+                        self.id
+                    );
+                    return Ok(context);
+                }
+            }
+        }
 
         let timestamp = self.context.runtime.hlc.new_timestamp();
 
@@ -172,13 +200,16 @@ impl SourceRunner {
             log::trace!("\tSending on: {:?}", link);
             link.send(zf_message.clone()).await?;
         }
-        self.record(zf_message).await?;
+        self.recorders
+            .record(&self.output.port_id, zf_message)
+            .await?;
         Ok(context)
     }
 
     /// Starts the source.
     async fn start(&self) {
         *self.is_running.lock().await = true;
+        self.run_tracker.reset();
     }
 }
 
@@ -195,6 +226,11 @@ impl Runner for SourceRunner {
         Ok(())
     }
 
+    async fn remove_output(&self, output: &LinkSender<Message>) -> ZFResult<()> {
+        (*self.links.lock().await).retain(|link| !link.sender.same_channel(&output.sender));
+        Ok(())
+    }
+
     async fn add_input(&self, _input: LinkReceiver<Message>) -> ZFResult<()> {
         Err(ZFError::SourceDoNotHaveInputs)
     }
@@ -224,77 +260,16 @@ impl Runner for SourceRunner {
         HashMap::with_capacity(0)
     }
 
-    async fn start_recording(&self) -> ZFResult<String> {
-        let mut is_recording_guard = self.is_recording.lock().await;
-        if !(*is_recording_guard) {
-            let ts_recording_start = self.context.runtime.hlc.new_timestamp();
-            let resource_name = format!(
-                "{}/{}",
-                self.base_resource_name,
-                ts_recording_start.get_time()
-            );
-
-            *(self.current_recording_resource.lock().await) = Some(resource_name.clone());
-
-            let recording_metadata = RecordingMetadata {
-                timestamp: ts_recording_start,
-                port_id: self.output.port_id.clone(),
-                node_id: self.id.clone(),
-                flow_id: self.context.flow_id.clone(),
-                instance_id: self.context.instance_id,
-            };
-
-            let message = Message::Control(ControlMessage::RecordingStart(recording_metadata));
-            let serialized = message.serialize_bincode()?;
-            log::trace!(
-                "ZenohLogger - {} - Started recording at {:?}",
-                resource_name,
-                ts_recording_start
-            );
-            self.context
-                .runtime
-                .session
-                .put(&resource_name, serialized)
-                .await?;
-            *is_recording_guard = true;
-            return Ok(resource_name);
-        }
-        return Err(ZFError::AlreadyRecording);
+    async fn start_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.start(port_id).await
     }
 
-    async fn stop_recording(&self) -> ZFResult<String> {
-        let mut is_recording_guard = self.is_recording.lock().await;
-        if *is_recording_guard {
-            let mut resource_name_guard = self.current_recording_resource.lock().await;
-
-            let resource_name = resource_name_guard
-                .as_ref()
-                .ok_or(ZFError::Unimplemented)?
-                .clone();
-
-            let ts_recording_stop = self.context.runtime.hlc.new_timestamp();
-            let message = Message::Control(ControlMessage::RecordingStop(ts_recording_stop));
-            let serialized = message.serialize_bincode()?;
-            log::debug!(
-                "ZenohLogger - {} - Stop recording at {:?}",
-                resource_name,
-                ts_recording_stop
-            );
-            self.context
-                .runtime
-                .session
-                .put(&resource_name, serialized)
-                .await?;
-
-            *is_recording_guard = false;
-            *resource_name_guard = None;
-            return Ok(resource_name);
-        }
-        return Err(ZFError::NotRecording);
+    async fn stop_recording(&self, port_id: &PortId) -> ZFResult<String> {
+        self.recorders.stop(port_id).await
     }
 
-    async fn is_recording(&self) -> bool {
-        *self.is_recording.lock().await
+    async fn get_recording_ports(&self) -> Vec<PortId> {
+        self.recorders.recording_ports().await
     }
 
     async fn is_running(&self) -> bool {
@@ -305,6 +280,36 @@ impl Runner for SourceRunner {
         *self.is_running.lock().await = false;
     }
 
+    async fn get_deadline_miss_stats(&self) -> DeadlineMissStats {
+        self.deadline_miss_stats.lock().await.clone()
+    }
+
+    async fn get_run_elapsed(&self) -> Option<Duration> {
+        self.run_tracker.elapsed()
+    }
+
+    async fn pause(&self) -> ZFResult<()> {
+        self.pause_gate.close().await;
+        Ok(())
+    }
+
+    async fn resume(&self) -> ZFResult<()> {
+        self.pause_gate.open().await;
+        Ok(())
+    }
+
+    async fn is_paused(&self) -> bool {
+        self.pause_gate.is_closed().await
+    }
+
+    async fn checkpoint(&self) -> ZFResult<Option<Vec<u8>>> {
+        self.state.lock().await.try_serialize()
+    }
+
+    async fn restore(&self, bytes: &[u8]) -> ZFResult<()> {
+        self.state.lock().await.try_restore(bytes)
+    }
+
     async fn run(&self) -> ZFResult<()> {
         self.start().await;
 
@@ -313,6 +318,7 @@ impl Runner for SourceRunner {
         // run of the source, as a run can fail in case of error it
         // stops and returns the error to the caller (the RunnerManager)
         loop {
+            self.pause_gate.pass().await;
             match self.iteration(context).await {
                 Ok(ctx) => {
                     log::trace!(
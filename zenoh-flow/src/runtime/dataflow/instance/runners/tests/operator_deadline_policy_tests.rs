@@ -0,0 +1,414 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::model::deadline::DeadlineMissPolicy;
+use crate::model::{InputDescriptor, OutputDescriptor};
+use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
+use crate::runtime::dataflow::instance::runners::executor::NodeExecutor;
+use crate::runtime::dataflow::instance::runners::operator::{OperatorIO, OperatorRunner};
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
+use crate::runtime::dataflow::instance::runners::{NodeRunner, PauseGate};
+use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
+use crate::runtime::deadline::{DeadlineMissStats, E2EDeadline};
+use crate::runtime::watchdog::RunTracker;
+use crate::runtime::{InstanceContext, RuntimeContext};
+use crate::{
+    default_input_rule, default_output_rule, Configuration, Context, Data, DataMessage,
+    DeadlineMiss, EmptyState, InputToken, LocalDeadlineMiss, Message, Node, NodeId, NodeOutput,
+    Operator, PortId, PortType, State, ZFResult,
+};
+use async_std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use uhlc::HLC;
+use zenoh::prelude::*;
+
+// -------------------------------------------------------------------------------------------------
+// Scenarios tested, the counters of the deadline misses are checked for each of them:
+//
+// 1) `drop` on an end-to-end deadline: the late message never reaches the Operator;
+//
+// 2) `fallback` on an end-to-end deadline: the late message is forwarded, untouched, on the
+//    fallback output without calling `run`;
+//
+// 3) `callback` on an end-to-end deadline: `on_deadline_miss` is called and the message is
+//    processed;
+//
+// 4) `drop` on a local deadline: the outputs of the late `run` are discarded.
+// -------------------------------------------------------------------------------------------------
+static INPUT: &str = "IN";
+static OUTPUT: &str = "OUT";
+static FALLBACK: &str = "FALLBACK";
+static OPERATOR: &str = "PolicyOperator";
+
+struct PolicyOperator {
+    run_delay: Duration,
+    runs: Arc<AtomicUsize>,
+    callbacks: Arc<AtomicUsize>,
+}
+
+impl Node for PolicyOperator {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        Ok(State::from::<EmptyState>(EmptyState {}))
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+
+    fn on_deadline_miss(&self, _state: &mut State, _miss: &DeadlineMiss) -> ZFResult<()> {
+        self.callbacks.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl Operator for PolicyOperator {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(self.run_delay);
+
+        let mut results = HashMap::new();
+        if let Some(mut message) = inputs.remove(INPUT) {
+            results.insert(OUTPUT.into(), message.get_inner_data().clone());
+        }
+        Ok(results)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+struct Harness {
+    hlc: Arc<HLC>,
+    runner: NodeRunner,
+    input: LinkSender<Message>,
+    output: LinkReceiver<Message>,
+    fallback: LinkReceiver<Message>,
+    stats: Arc<Mutex<DeadlineMissStats>>,
+    runs: Arc<AtomicUsize>,
+    callbacks: Arc<AtomicUsize>,
+}
+
+fn link(id: &str) -> (LinkSender<Message>, LinkReceiver<Message>) {
+    let (sender, receiver) = flume::unbounded::<Arc<Message>>();
+    (
+        LinkSender {
+            id: id.into(),
+            sender,
+        },
+        LinkReceiver {
+            id: id.into(),
+            receiver,
+            last_received: std::sync::Arc::new(std::sync::Mutex::new(
+                std::collections::HashMap::new(),
+            )),
+        },
+    )
+}
+
+fn harness(
+    local_deadline: Option<Duration>,
+    on_deadline_miss: Option<DeadlineMissPolicy>,
+    run_delay: Duration,
+) -> Harness {
+    let session = zenoh::open(zenoh::config::Config::default())
+        .wait()
+        .unwrap();
+    let hlc = Arc::new(HLC::default());
+    let runtime_context = RuntimeContext {
+        session: Arc::new(session).into(),
+        hlc: hlc.clone(),
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: "test-runtime-deadline-policy".into(),
+        runtime_uuid: uuid::Uuid::new_v4(),
+        recordings_dir: None,
+    };
+    let instance_context = InstanceContext {
+        flow_id: "test-deadline-policy-flow".into(),
+        instance_id: uuid::Uuid::new_v4(),
+        runtime: runtime_context,
+    };
+
+    let (input, input_receiver) = link(INPUT);
+    let (output_sender, output) = link(OUTPUT);
+    let (fallback_sender, fallback) = link(FALLBACK);
+
+    let mut io_inputs = HashMap::new();
+    io_inputs.insert(PortId::from(INPUT), input_receiver);
+    let mut io_outputs = HashMap::new();
+    io_outputs.insert(PortId::from(OUTPUT), vec![output_sender]);
+    io_outputs.insert(PortId::from(FALLBACK), vec![fallback_sender]);
+
+    let mut inputs: HashMap<PortId, PortType> = HashMap::new();
+    inputs.insert(INPUT.into(), "usize".into());
+    let mut outputs: HashMap<PortId, PortType> = HashMap::new();
+    outputs.insert(OUTPUT.into(), "usize".into());
+    outputs.insert(FALLBACK.into(), "usize".into());
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let callbacks = Arc::new(AtomicUsize::new(0));
+    let operator = PolicyOperator {
+        run_delay,
+        runs: runs.clone(),
+        callbacks: callbacks.clone(),
+    };
+    let stats = Arc::new(Mutex::new(DeadlineMissStats::default()));
+
+    let operator_runner = OperatorRunner {
+        id: OPERATOR.into(),
+        context: instance_context.clone(),
+        io: Arc::new(Mutex::new(OperatorIO {
+            inputs: io_inputs,
+            outputs: io_outputs,
+        })),
+        inputs,
+        outputs,
+        local_deadline,
+        is_running: Arc::new(Mutex::new(false)),
+        state: Arc::new(Mutex::new(operator.initialize(&None).unwrap())),
+        operator: Arc::new(operator),
+        _library: None,
+        end_to_end_deadlines: vec![],
+        cicli: vec![],
+        executor: Arc::new(NodeExecutor::Shared),
+        on_deadline_miss,
+        deadline_miss_stats: stats.clone(),
+        run_tracker: RunTracker::default(),
+        pause_gate: PauseGate::default(),
+        buffered_tokens: Arc::new(AtomicUsize::new(0)),
+        recorders: Recorders::new(&instance_context, &OPERATOR.into(), std::iter::empty()),
+    };
+
+    Harness {
+        hlc,
+        runner: NodeRunner::new(Arc::new(operator_runner), instance_context),
+        input,
+        output,
+        fallback,
+        stats,
+        runs,
+        callbacks,
+    }
+}
+
+impl Harness {
+    /// Sends `number` with an end-to-end deadline ending on the Operator, missed if `late`.
+    async fn send(&self, number: usize, policy: Option<DeadlineMissPolicy>, late: bool) {
+        let now = self.hlc.new_timestamp();
+        let deadline = E2EDeadline {
+            duration: if late {
+                Duration::from_nanos(1)
+            } else {
+                Duration::from_secs(60)
+            },
+            from: OutputDescriptor {
+                node: "past".into(),
+                output: "out".into(),
+            },
+            to: InputDescriptor {
+                node: NodeId::from(OPERATOR),
+                input: INPUT.into(),
+            },
+            start: now,
+            on_miss: policy,
+        };
+        if late {
+            async_std::task::sleep(Duration::from_millis(10)).await;
+        }
+
+        self.input
+            .send(Arc::new(Message::Data(DataMessage::new(
+                Data::from_bytes(number.to_le_bytes().to_vec()),
+                self.hlc.new_timestamp(),
+                vec![deadline],
+            ))))
+            .await
+            .unwrap();
+    }
+
+    async fn stats(&self) -> DeadlineMissStats {
+        self.stats.lock().await.clone()
+    }
+}
+
+/// Receives the number sent on the link, `None` if nothing is received within 500 ms.
+async fn receive(receiver: &LinkReceiver<Message>) -> Option<usize> {
+    let (_, message) = async_std::future::timeout(Duration::from_millis(500), receiver.recv())
+        .await
+        .ok()?
+        .unwrap();
+    match message.as_ref() {
+        Message::Data(data_message) => {
+            let bytes = data_message.data.try_as_bytes().unwrap();
+            let mut number = [0u8; std::mem::size_of::<usize>()];
+            number.copy_from_slice(&bytes);
+            Some(usize::from_le_bytes(number))
+        }
+        Message::Control(_) => panic!("Unexpected control message"),
+    }
+}
+
+fn run_scenario<F>(harness: Harness, scenario: impl FnOnce(Harness) -> F)
+where
+    F: std::future::Future<Output = ()>,
+{
+    // We use a timeout as, if an `assert` fails, the `task::block_on` would never return.
+    assert!(
+        async_std::task::block_on(async_std::future::timeout(
+            Duration::from_secs(10),
+            scenario(harness)
+        ))
+        .is_ok(),
+        "Deadlock detected"
+    );
+}
+
+#[test]
+fn e2e_deadline_drop() {
+    run_scenario(
+        harness(None, None, Duration::from_millis(0)),
+        |h| async move {
+            let runner_manager = h.runner.start();
+
+            h.send(1, Some(DeadlineMissPolicy::Drop), true).await;
+            h.send(2, Some(DeadlineMissPolicy::Drop), false).await;
+
+            assert_eq!(receive(&h.output).await, Some(2));
+            assert_eq!(receive(&h.fallback).await, None);
+            assert_eq!(h.runs.load(Ordering::SeqCst), 1);
+            assert_eq!(
+                h.stats().await,
+                DeadlineMissStats {
+                    missed: 1,
+                    dropped: 1,
+                    ..Default::default()
+                }
+            );
+
+            runner_manager.kill().await.unwrap();
+            runner_manager.await.unwrap();
+        },
+    );
+}
+
+#[test]
+fn e2e_deadline_fallback() {
+    run_scenario(
+        harness(None, None, Duration::from_millis(0)),
+        |h| async move {
+            let runner_manager = h.runner.start();
+
+            let policy = Some(DeadlineMissPolicy::Fallback(FALLBACK.into()));
+            h.send(1, policy.clone(), true).await;
+            assert_eq!(receive(&h.fallback).await, Some(1));
+            assert_eq!(h.runs.load(Ordering::SeqCst), 0);
+
+            h.send(2, policy, false).await;
+            assert_eq!(receive(&h.output).await, Some(2));
+            assert_eq!(h.runs.load(Ordering::SeqCst), 1);
+            assert_eq!(
+                h.stats().await,
+                DeadlineMissStats {
+                    missed: 1,
+                    fallback: 1,
+                    ..Default::default()
+                }
+            );
+
+            runner_manager.kill().await.unwrap();
+            runner_manager.await.unwrap();
+        },
+    );
+}
+
+#[test]
+fn e2e_deadline_callback() {
+    run_scenario(
+        harness(None, None, Duration::from_millis(0)),
+        |h| async move {
+            let runner_manager = h.runner.start();
+
+            h.send(1, Some(DeadlineMissPolicy::Callback), true).await;
+            assert_eq!(receive(&h.output).await, Some(1));
+            assert_eq!(h.callbacks.load(Ordering::SeqCst), 1);
+
+            h.send(2, None, true).await;
+            assert_eq!(receive(&h.output).await, Some(2));
+            assert_eq!(h.callbacks.load(Ordering::SeqCst), 1);
+            assert_eq!(
+                h.stats().await,
+                DeadlineMissStats {
+                    missed: 2,
+                    callback: 1,
+                    ..Default::default()
+                }
+            );
+
+            runner_manager.kill().await.unwrap();
+            runner_manager.await.unwrap();
+        },
+    );
+}
+
+#[test]
+fn local_deadline_drop() {
+    run_scenario(
+        harness(
+            Some(Duration::from_millis(5)),
+            Some(DeadlineMissPolicy::Drop),
+            Duration::from_millis(50),
+        ),
+        |h| async move {
+            let runner_manager = h.runner.start();
+
+            h.send(1, None, false).await;
+            h.send(2, None, false).await;
+            assert_eq!(receive(&h.output).await, None);
+            assert_eq!(h.runs.load(Ordering::SeqCst), 2);
+            assert_eq!(
+                h.stats().await,
+                DeadlineMissStats {
+                    missed: 2,
+                    dropped: 2,
+                    ..Default::default()
+                }
+            );
+
+            runner_manager.kill().await.unwrap();
+            runner_manager.await.unwrap();
+        },
+    );
+}
@@ -15,10 +15,13 @@
 use crate::model::deadline::E2EDeadlineRecord;
 use crate::model::{InputDescriptor, OutputDescriptor};
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
+use crate::runtime::dataflow::instance::runners::executor::NodeExecutor;
 use crate::runtime::dataflow::instance::runners::operator::{OperatorIO, OperatorRunner};
-use crate::runtime::dataflow::instance::runners::NodeRunner;
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
+use crate::runtime::dataflow::instance::runners::{NodeRunner, PauseGate};
 use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
-use crate::runtime::deadline::E2EDeadline;
+use crate::runtime::deadline::{DeadlineMissStats, E2EDeadline};
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::{InstanceContext, RuntimeContext};
 use crate::{
     default_output_rule, Configuration, Context, Data, DataMessage, Deserializable, DowncastAny,
@@ -26,6 +29,7 @@ use crate::{
     PortType, State, ZFData, ZFError, ZFResult,
 };
 use async_std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicUsize;
 use std::time::Duration;
 use std::{collections::HashMap, convert::TryInto};
 use uhlc::HLC;
@@ -166,11 +170,12 @@ fn e2e_deadline() {
     let hlc = Arc::new(uhlc::HLC::default());
     let uuid = uuid::Uuid::new_v4();
     let runtime_context = RuntimeContext {
-        session: Arc::new(session),
+        session: Arc::new(session).into(),
         hlc: hlc.clone(),
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: "test-runtime-input-rule-keep".into(),
         runtime_uuid: uuid,
+        recordings_dir: None,
     };
     let instance_context = InstanceContext {
         flow_id: "test-input-rule-keep-flow".into(),
@@ -185,6 +190,7 @@ fn e2e_deadline() {
     let receiver_input1: LinkReceiver<Message> = LinkReceiver {
         id: input1.clone(),
         receiver: rx_input1,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_input1: LinkSender<Message> = LinkSender {
         id: input1.clone(),
@@ -197,6 +203,7 @@ fn e2e_deadline() {
     let receiver_input2: LinkReceiver<Message> = LinkReceiver {
         id: input2.clone(),
         receiver: rx_input2,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
 
     let mut io_inputs: HashMap<PortId, LinkReceiver<Message>> = HashMap::with_capacity(2);
@@ -212,6 +219,7 @@ fn e2e_deadline() {
     let receiver_output: LinkReceiver<Message> = LinkReceiver {
         id: output.clone(),
         receiver: rx_output,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_output: LinkSender<Message> = LinkSender {
         id: output.clone(),
@@ -242,6 +250,7 @@ fn e2e_deadline() {
             input: input1.clone(),
         },
         duration: Duration::from_secs(5),
+        on_miss: None,
     };
 
     let operator_runner = OperatorRunner {
@@ -256,7 +265,14 @@ fn e2e_deadline() {
         operator: Arc::new(operator),
         _library: None,
         end_to_end_deadlines: vec![operator_deadline.clone()],
-        ciclo: None,
+        cicli: vec![],
+        executor: Arc::new(NodeExecutor::Shared),
+        on_deadline_miss: None,
+        deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+        run_tracker: RunTracker::default(),
+        pause_gate: PauseGate::default(),
+        buffered_tokens: Arc::new(AtomicUsize::new(0)),
+        recorders: Recorders::new(&instance_context, &"operator".into(), std::iter::empty()),
     };
 
     let runner = NodeRunner::new(Arc::new(operator_runner), instance_context);
@@ -282,6 +298,7 @@ fn e2e_deadline() {
                     input: input1.clone(),
                 },
                 start,
+                on_miss: None,
             };
 
             // We sleep for 500 ms.
@@ -310,6 +327,7 @@ fn e2e_deadline() {
                             input: input1.clone(),
                         },
                         start,
+                        on_miss: None,
                     },
                     E2EDeadline {
                         duration: Duration::from_millis(100),
@@ -322,6 +340,7 @@ fn e2e_deadline() {
                             input: input2.clone(),
                         },
                         start,
+                        on_miss: None,
                     },
                     deadline_violated_to_propagate.clone(),
                 ],
@@ -13,6 +13,7 @@
 //
 
 use async_std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicUsize;
 use std::{collections::HashMap, convert::TryInto};
 use uhlc::HLC;
 use zenoh::prelude::*;
@@ -24,17 +25,20 @@ use crate::{
             instance::{
                 link::{LinkReceiver, LinkSender},
                 runners::{
+                    executor::NodeExecutor,
                     operator::{OperatorIO, OperatorRunner},
-                    NodeRunner,
+                    recorder::Recorders,
+                    NodeRunner, PauseGate,
                 },
             },
             loader::{Loader, LoaderConfig},
         },
+        watchdog::RunTracker,
         InstanceContext, RuntimeContext,
     },
-    Configuration, Context, Data, DataMessage, Deserializable, DowncastAny, EmptyState, InputToken,
-    LocalDeadlineMiss, Message, Node, NodeOutput, Operator, PortId, PortType, State, TokenAction,
-    ZFData, ZFError, ZFResult,
+    Configuration, Context, Data, DataMessage, DeadlineMissStats, Deserializable, DowncastAny,
+    EmptyState, InputToken, LocalDeadlineMiss, Message, Node, NodeOutput, Operator, PortId,
+    PortType, State, TokenAction, ZFData, ZFError, ZFResult,
 };
 
 // ZFUsize implements Data.
@@ -187,11 +191,12 @@ fn input_rule_keep() {
     let hlc = Arc::new(uhlc::HLC::default());
     let uuid = uuid::Uuid::new_v4();
     let runtime_context = RuntimeContext {
-        session: Arc::new(session),
+        session: Arc::new(session).into(),
         hlc: hlc.clone(),
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: "test-runtime-input-rule-keep".into(),
         runtime_uuid: uuid,
+        recordings_dir: None,
     };
     let instance_context = InstanceContext {
         flow_id: "test-input-rule-keep-flow".into(),
@@ -205,6 +210,7 @@ fn input_rule_keep() {
     let receiver_input_1: LinkReceiver<Message> = LinkReceiver {
         id: input_1.clone(),
         receiver: rx_input_1,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_input_1: LinkSender<Message> = LinkSender {
         id: input_1.clone(),
@@ -216,6 +222,7 @@ fn input_rule_keep() {
     let receiver_input_2: LinkReceiver<Message> = LinkReceiver {
         id: input_2.clone(),
         receiver: rx_input_2,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_input_2: LinkSender<Message> = LinkSender {
         id: input_2.clone(),
@@ -234,6 +241,7 @@ fn input_rule_keep() {
     let receiver_output: LinkReceiver<Message> = LinkReceiver {
         id: output.clone(),
         receiver: rx_output,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_output: LinkSender<Message> = LinkSender {
         id: output.clone(),
@@ -267,7 +275,14 @@ fn input_rule_keep() {
         operator: Arc::new(operator),
         _library: None,
         end_to_end_deadlines: vec![],
-        ciclo: None,
+        cicli: vec![],
+        executor: Arc::new(NodeExecutor::Shared),
+        on_deadline_miss: None,
+        deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+        run_tracker: RunTracker::default(),
+        pause_gate: PauseGate::default(),
+        buffered_tokens: Arc::new(AtomicUsize::new(0)),
+        recorders: Recorders::new(&instance_context, &"operator".into(), std::iter::empty()),
     };
 
     let runner = NodeRunner::new(Arc::new(operator_runner), instance_context);
@@ -17,9 +17,10 @@ use crate::model::link::PortDescriptor;
 use crate::model::{InputDescriptor, OutputDescriptor};
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
 use crate::runtime::dataflow::instance::runners::sink::SinkRunner;
-use crate::runtime::dataflow::instance::runners::NodeRunner;
+use crate::runtime::dataflow::instance::runners::{NodeRunner, PauseGate};
 use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
-use crate::runtime::deadline::E2EDeadline;
+use crate::runtime::deadline::{DeadlineMissStats, E2EDeadline};
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::{InstanceContext, RuntimeContext};
 use crate::{
     Configuration, Context, Data, DataMessage, Deserializable, DowncastAny, EmptyState, Message,
@@ -104,11 +105,12 @@ fn sink_e2e_deadline() {
     let hlc = Arc::new(uhlc::HLC::default());
     let uuid = uuid::Uuid::new_v4();
     let runtime_context = RuntimeContext {
-        session: Arc::new(session),
+        session: Arc::new(session).into(),
         hlc: hlc.clone(),
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: "runtime--SINK-e2e-deadline-tests".into(),
         runtime_uuid: uuid,
+        recordings_dir: None,
     };
     let instance_context = InstanceContext {
         flow_id: "flow--SINK-e2e-deadline-tests".into(),
@@ -121,6 +123,7 @@ fn sink_e2e_deadline() {
     let receiver_input: LinkReceiver<Message> = LinkReceiver {
         id: input.clone(),
         receiver: rx_input,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_input: LinkSender<Message> = LinkSender {
         id: input.clone(),
@@ -140,6 +143,7 @@ fn sink_e2e_deadline() {
             input: input.clone(),
         },
         duration: Duration::from_millis(100),
+        on_miss: None,
     };
 
     let e2e_deadline_ok = E2EDeadlineRecord {
@@ -152,6 +156,7 @@ fn sink_e2e_deadline() {
             input: input.clone(),
         },
         duration: Duration::from_secs(1),
+        on_miss: None,
     };
 
     let sink_runner = SinkRunner {
@@ -167,6 +172,11 @@ fn sink_e2e_deadline() {
         sink: Arc::new(sink),
         _library: None,
         _end_to_end_deadlines: vec![e2e_deadline_miss.clone(), e2e_deadline_ok.clone()],
+        local_deadline: None,
+        on_deadline_miss: None,
+        deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+        run_tracker: RunTracker::default(),
+        pause_gate: PauseGate::default(),
     };
 
     let runner = NodeRunner::new(Arc::new(sink_runner), instance_context);
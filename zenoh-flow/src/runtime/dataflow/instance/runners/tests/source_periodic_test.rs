@@ -16,9 +16,12 @@ use more_asserts::assert_le;
 
 use crate::model::link::PortDescriptor;
 use crate::runtime::dataflow::instance::link::{LinkReceiver, LinkSender};
+use crate::runtime::dataflow::instance::runners::recorder::Recorders;
 use crate::runtime::dataflow::instance::runners::source::SourceRunner;
-use crate::runtime::dataflow::instance::runners::NodeRunner;
+use crate::runtime::dataflow::instance::runners::{NodeRunner, PauseGate};
 use crate::runtime::dataflow::loader::{Loader, LoaderConfig};
+use crate::runtime::deadline::DeadlineMissStats;
+use crate::runtime::watchdog::RunTracker;
 use crate::runtime::{InstanceContext, RuntimeContext};
 use crate::{
     Configuration, Context, Data, Deserializable, DowncastAny, EmptyState, Message, Node, NodeId,
@@ -97,11 +100,12 @@ fn source_periodic() {
     let hlc = Arc::new(uhlc::HLC::default());
     let uuid = uuid::Uuid::new_v4();
     let runtime_context = RuntimeContext {
-        session: Arc::new(session),
+        session: Arc::new(session).into(),
         hlc,
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: "runtime--source-periodic-tests".into(),
         runtime_uuid: uuid,
+        recordings_dir: None,
     };
     let instance_context = InstanceContext {
         flow_id: "flow--source-periodic-tests".into(),
@@ -114,6 +118,7 @@ fn source_periodic() {
     let receiver_output: LinkReceiver<Message> = LinkReceiver {
         id: output.clone(),
         receiver: rx_output,
+        last_received: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
     };
     let sender_output: LinkSender<Message> = LinkSender {
         id: output.clone(),
@@ -135,9 +140,12 @@ fn source_periodic() {
         is_running: Arc::new(Mutex::new(false)),
         state: Arc::new(Mutex::new(source.initialize(&None).unwrap())),
         end_to_end_deadlines: vec![],
-        base_resource_name: "test".into(),
-        current_recording_resource: Arc::new(Mutex::new(None)),
-        is_recording: Arc::new(Mutex::new(false)),
+        local_deadline: None,
+        on_deadline_miss: None,
+        deadline_miss_stats: Arc::new(Mutex::new(DeadlineMissStats::default())),
+        run_tracker: RunTracker::default(),
+        pause_gate: PauseGate::default(),
+        recorders: Recorders::new(&instance_context, &"source".into(), std::iter::empty()),
         source: Arc::new(source),
         _library: None,
     };
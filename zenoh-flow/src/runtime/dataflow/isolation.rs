@@ -0,0 +1,1585 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Out-of-process execution of the nodes.
+//!
+//! When the [`Loader`](`Loader`) is given a [`NodeHostCommand`](`NodeHostCommand`), each
+//! dynamically loaded node is executed by its own child process, a *node host*, instead of within
+//! the runtime. A node that panics or crashes only takes down its host: the request in progress
+//! fails, which makes the runner of the node restart its run loop — the inputs of the iteration of
+//! an operator are lost. The host is restarted in the background, after a delay growing
+//! exponentially with the consecutive failures, and the new host restores the latest checkpoint of
+//! the node, if any. In the meantime, the requests of an operator fail while the ones of a source
+//! or of a sink wait for the new host.
+//!
+//! The runtime and a host communicate through a Unix socket, created in a private directory, with
+//! length-prefixed `bincode` frames of at most [`MAX_FRAME_SIZE`](`MAX_FRAME_SIZE`) bytes. The data
+//! crossing the socket is serialized: an isolated node always receives and produces
+//! `Data::Bytes`.
+//!
+//! An iteration of an isolated operator is a single request: the host executes the input rule
+//! and, if it returns `true`, `run` and the output rule without waiting for the runtime. The output
+//! rule is thus given the local deadline miss measured by the host, and it is executed before the
+//! runtime applies its deadline miss policy.
+
+use super::loader::{Loader, LoaderConfig, NodeHostCommand};
+use super::node::{OperatorLoaded, SinkLoaded, SourceLoaded};
+use crate::async_std::sync::Mutex as AsyncMutex;
+use crate::model::node::{OperatorRecord, SinkRecord, SourceRecord};
+use crate::runtime::deadline::{E2EDeadlineMiss, LocalDeadlineMiss};
+use crate::runtime::token::{DataToken, TokenAction};
+use crate::serde::de::DeserializeOwned;
+use crate::serde::{Deserialize, Serialize};
+use crate::{
+    Configuration, Context, Data, DataMessage, DeadlineMiss, InputToken, Node, NodeId, NodeOutput,
+    Operator, PortId, Sink, Source, State, ZFError, ZFResult, ZFState,
+};
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{ErrorKind, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// The maximum size, in bytes, of a frame exchanged between the runtime and a node host.
+pub const MAX_FRAME_SIZE: usize = 256 * 1024 * 1024;
+
+/// How long a node host has to connect to the runtime once started.
+static HOST_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a node host has to answer a request that does not execute `run`, and to read any
+/// request. A host exceeding it is considered as failed.
+static HOST_IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The delay before restarting a failed host, doubled at each consecutive failure up to
+/// `HOST_MAX_RESTART_DELAY`. A host running for longer than `HOST_MAX_RESTART_DELAY` resets it.
+static HOST_RESTART_DELAY: Duration = Duration::from_secs(1);
+static HOST_MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+/// How often a source or a sink checks whether the host of its node was restarted.
+static HOST_RESTART_POLL: Duration = Duration::from_millis(100);
+
+/// The record of the node executed by a host.
+#[derive(Clone, Serialize, Deserialize)]
+enum HostedRecord {
+    Source(SourceRecord),
+    Operator(OperatorRecord),
+    Sink(SinkRecord),
+}
+
+impl HostedRecord {
+    fn id(&self) -> &NodeId {
+        match self {
+            Self::Source(record) => &record.id,
+            Self::Operator(record) => &record.id,
+            Self::Sink(record) => &record.id,
+        }
+    }
+
+    fn set_configuration(&mut self, configuration: Option<Configuration>) {
+        match self {
+            Self::Source(record) => record.configuration = configuration,
+            Self::Operator(record) => record.configuration = configuration,
+            Self::Sink(record) => record.configuration = configuration,
+        }
+    }
+}
+
+/// A [`LocalDeadlineMiss`](`LocalDeadlineMiss`), as sent to a host.
+#[derive(Serialize, Deserialize)]
+struct WireLocalMiss {
+    deadline: Duration,
+    elapsed: Duration,
+}
+
+impl From<&LocalDeadlineMiss> for WireLocalMiss {
+    fn from(miss: &LocalDeadlineMiss) -> Self {
+        Self {
+            deadline: miss.deadline,
+            elapsed: miss.elapsed,
+        }
+    }
+}
+
+impl From<WireLocalMiss> for LocalDeadlineMiss {
+    fn from(miss: WireLocalMiss) -> Self {
+        let now = Instant::now();
+        Self {
+            start: now.checked_sub(miss.elapsed).unwrap_or(now),
+            deadline: miss.deadline,
+            elapsed: miss.elapsed,
+        }
+    }
+}
+
+/// A [`DeadlineMiss`](`DeadlineMiss`), as sent to a host.
+#[derive(Serialize, Deserialize)]
+enum WireDeadlineMiss {
+    Local(WireLocalMiss),
+    EndToEnd(E2EDeadlineMiss),
+}
+
+impl From<&DeadlineMiss> for WireDeadlineMiss {
+    fn from(miss: &DeadlineMiss) -> Self {
+        match miss {
+            DeadlineMiss::Local(miss) => Self::Local(miss.into()),
+            DeadlineMiss::EndToEnd(miss) => Self::EndToEnd(miss.clone()),
+        }
+    }
+}
+
+impl From<WireDeadlineMiss> for DeadlineMiss {
+    fn from(miss: WireDeadlineMiss) -> Self {
+        match miss {
+            WireDeadlineMiss::Local(miss) => Self::Local(miss.into()),
+            WireDeadlineMiss::EndToEnd(miss) => Self::EndToEnd(miss),
+        }
+    }
+}
+
+/// The requests sent by the runtime to a host, each one is answered by a `Response`.
+#[derive(Serialize, Deserialize)]
+enum Request {
+    /// Loads and initializes the node, the record and the loader configuration are serialized
+    /// as JSON as they contain configurations.
+    Load {
+        record: String,
+        loader: String,
+    },
+    /// An iteration of an operator, answered by `Response::InputRule` and, if the input rule
+    /// returned `true`, by `Response::Iteration` once `run` and the output rule are executed.
+    Iteration {
+        mode: usize,
+        tokens: HashMap<PortId, Option<(DataMessage, TokenAction)>>,
+    },
+    RunSource {
+        mode: usize,
+    },
+    RunSink {
+        mode: usize,
+        input: DataMessage,
+    },
+    OnDeadlineMiss(WireDeadlineMiss),
+    Checkpoint,
+    Restore(Vec<u8>),
+    /// Finalizes the node, the host then exits.
+    Finalize,
+}
+
+/// The responses of a host.
+#[derive(Serialize, Deserialize)]
+enum Response {
+    Done,
+    InputRule(bool, HashMap<PortId, TokenAction>),
+    /// The outputs of `run` and the ones of the output rule.
+    Iteration(HashMap<PortId, Data>, HashMap<PortId, NodeOutput>),
+    Data(Data),
+    Checkpoint(Option<Vec<u8>>),
+    Error(ZFError),
+}
+
+impl Response {
+    fn unexpected() -> ZFError {
+        ZFError::InvalidData("Unexpected response from the node host".to_string())
+    }
+
+    fn done(self) -> ZFResult<()> {
+        match self {
+            Self::Done => Ok(()),
+            Self::Error(e) => Err(e),
+            _ => Err(Self::unexpected()),
+        }
+    }
+}
+
+/// Converts the data to `Data::Bytes`, so that it can cross the socket.
+fn to_bytes(data: Data) -> ZFResult<Data> {
+    match data {
+        Data::Bytes(_) => Ok(data),
+        Data::Typed(_) => Ok(Data::Bytes(data.try_as_bytes()?)),
+    }
+}
+
+/// Converts, in place, the data of the message to `Data::Bytes`.
+fn make_bytes(message: &mut DataMessage) -> ZFResult<()> {
+    if let Data::Typed(_) = message.data {
+        message.data = Data::Bytes(message.data.try_as_bytes()?);
+    }
+    Ok(())
+}
+
+/// Writes a frame: the length of the serialized message, as a little-endian `u32`, followed by
+/// the message serialized with `bincode`.
+///
+/// # Errors
+/// An `InvalidData` variant is returned, before writing anything, if the frame exceeds
+/// `MAX_FRAME_SIZE`.
+fn write_frame<T: Serialize>(stream: &mut UnixStream, message: &T) -> ZFResult<()> {
+    let bytes = bincode::serialize(message).map_err(|_| ZFError::SerializationError)?;
+    if bytes.len() > MAX_FRAME_SIZE {
+        return Err(ZFError::InvalidData(format!(
+            "Frame of {} bytes exceeds {} bytes",
+            bytes.len(),
+            MAX_FRAME_SIZE
+        )));
+    }
+    let len = u32::try_from(bytes.len()).map_err(|_| ZFError::SerializationError)?;
+    stream.write_all(&len.to_le_bytes())?;
+    stream.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads a frame written by `write_frame`.
+///
+/// # Errors
+/// An `InvalidData` variant is returned, before reading the message, if the frame exceeds
+/// `MAX_FRAME_SIZE`: the stream cannot be used anymore.
+fn read_frame<T: DeserializeOwned>(stream: &mut UnixStream) -> ZFResult<T> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(ZFError::InvalidData(format!(
+            "Frame of {} bytes exceeds {} bytes",
+            len, MAX_FRAME_SIZE
+        )));
+    }
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes).map_err(|_| ZFError::DeseralizationError)
+}
+
+/// Locks the mutex, ignoring its poisoning: the data it protects stays consistent.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A running node host, seen from the runtime.
+struct NodeHost {
+    child: Option<Child>,
+    stream: UnixStream,
+}
+
+impl NodeHost {
+    /// Starts a host with the `command` and waits for it to connect.
+    ///
+    /// This blocks the calling thread for up to `HOST_CONNECT_TIMEOUT`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the host cannot be started or if it does not connect
+    /// within `HOST_CONNECT_TIMEOUT`.
+    fn spawn(command: &NodeHostCommand) -> ZFResult<Self> {
+        // Only the user of the runtime can access the socket.
+        let dir = std::env::temp_dir().join(format!("zenoh-flow-{}", Uuid::new_v4()));
+        std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let host = Self::spawn_in(command, &dir);
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            log::warn!("Unable to remove {}: {}", dir.display(), e);
+        }
+        host
+    }
+
+    fn spawn_in(command: &NodeHostCommand, dir: &Path) -> ZFResult<Self> {
+        let socket = dir.join("host.sock");
+        let listener = UnixListener::bind(&socket)?;
+        listener.set_nonblocking(true)?;
+
+        let mut child = Command::new(&command.program)
+            .args(&command.args)
+            .arg(&socket)
+            .stdin(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                ZFError::IOError(format!(
+                    "Unable to start the node host < {} >: {}",
+                    command.program.display(),
+                    e
+                ))
+            })?;
+
+        match Self::accept(&listener, &mut child) {
+            Ok(stream) => Ok(Self {
+                child: Some(child),
+                stream,
+            }),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(e)
+            }
+        }
+    }
+
+    fn accept(listener: &UnixListener, child: &mut Child) -> ZFResult<UnixStream> {
+        let started = Instant::now();
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(false)?;
+                    stream.set_write_timeout(Some(HOST_IO_TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    if let Some(status) = child.try_wait()? {
+                        return Err(ZFError::IOError(format!(
+                            "The node host exited before connecting: {}",
+                            status
+                        )));
+                    }
+                    if started.elapsed() > HOST_CONNECT_TIMEOUT {
+                        return Err(ZFError::IOError(format!(
+                            "The node host did not connect within {:?}",
+                            HOST_CONNECT_TIMEOUT
+                        )));
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Waits for a response, for at most `timeout` if any.
+    ///
+    /// # Errors
+    /// An error variant is returned if the host cannot be reached or does not answer in time: it
+    /// cannot be used anymore.
+    fn receive(&mut self, timeout: Option<Duration>) -> ZFResult<Response> {
+        self.stream.set_read_timeout(timeout)?;
+        read_frame(&mut self.stream)
+    }
+
+    /// Sends the `request` and waits for the response, for at most `timeout` if any.
+    ///
+    /// # Errors
+    /// An error variant is returned if the host cannot be reached or does not answer in time: it
+    /// cannot be used anymore.
+    fn call(&mut self, request: &Request, timeout: Option<Duration>) -> ZFResult<Response> {
+        write_frame(&mut self.stream, request)?;
+        self.receive(timeout)
+    }
+
+    /// Stops the host, if it is still running, and returns how it exited.
+    fn terminate(&mut self) -> String {
+        match self.child.take() {
+            Some(mut child) => {
+                let _ = child.kill();
+                match child.wait() {
+                    Ok(status) => status.to_string(),
+                    Err(e) => e.to_string(),
+                }
+            }
+            None => "no process".to_string(),
+        }
+    }
+}
+
+impl Drop for NodeHost {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+/// Starts a host and loads the node, restoring the `checkpoint` if any.
+///
+/// This blocks the calling thread until the node is loaded.
+///
+/// # Errors
+/// An error variant is returned if the host cannot be started or if it fails to load the node.
+fn start_host(
+    command: &NodeHostCommand,
+    record: &str,
+    loader: &str,
+    checkpoint: Option<&[u8]>,
+) -> ZFResult<NodeHost> {
+    let mut host = NodeHost::spawn(command)?;
+    host.call(
+        &Request::Load {
+            record: record.to_string(),
+            loader: loader.to_string(),
+        },
+        Some(HOST_CONNECT_TIMEOUT),
+    )?
+    .done()?;
+
+    if let Some(checkpoint) = checkpoint {
+        if let Err(e) = host
+            .call(
+                &Request::Restore(checkpoint.to_vec()),
+                Some(HOST_IO_TIMEOUT),
+            )
+            .and_then(Response::done)
+        {
+            log::warn!("The node host could not restore the checkpoint: {:?}", e);
+        }
+    }
+
+    Ok(host)
+}
+
+/// The delay before restarting a host after `failures` consecutive failures.
+fn restart_delay(failures: u32) -> Duration {
+    2u32.checked_pow(failures.saturating_sub(1))
+        .and_then(|factor| HOST_RESTART_DELAY.checked_mul(factor))
+        .map_or(HOST_MAX_RESTART_DELAY, |delay| {
+            delay.min(HOST_MAX_RESTART_DELAY)
+        })
+}
+
+/// What can be done on the host of a node without waiting for the request in progress.
+#[derive(Default)]
+struct HostControl {
+    /// A handle on the socket of the current host.
+    socket: Mutex<Option<UnixStream>>,
+    /// Set once the node is finalized: the host is not restarted anymore.
+    stopped: AtomicBool,
+}
+
+impl HostControl {
+    /// Interrupts the request in progress, if any, by closing the socket of the current host. The
+    /// host is then stopped by the session.
+    fn abort(&self) {
+        if let Some(socket) = lock(&self.socket).take() {
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+}
+
+/// The result of an iteration of an operator: the outputs of `run` and of the output rule.
+type IterationOutputs = (HashMap<PortId, Data>, HashMap<PortId, NodeOutput>);
+
+/// The connection of an isolated node to its host, which is restarted when it fails.
+struct HostSession {
+    node: NodeId,
+    command: Option<NodeHostCommand>,
+    record: String,
+    loader: String,
+    host: Option<NodeHost>,
+    /// When the current host was started.
+    started: Option<Instant>,
+    /// The number of consecutive failures of the host, it sets the delay before the restart.
+    failures: u32,
+    /// Whether a thread is restarting the host.
+    restarting: bool,
+    checkpoint: Option<Vec<u8>>,
+    /// Whether the host is executing `run` and the output rule of an operator.
+    pending_iteration: bool,
+    outputs: Option<ZFResult<IterationOutputs>>,
+    node_outputs: Option<HashMap<PortId, NodeOutput>>,
+    control: Arc<HostControl>,
+}
+
+impl HostSession {
+    fn install(&mut self, host: NodeHost) {
+        *lock(&self.control.socket) = host.stream.try_clone().ok();
+        self.host = Some(host);
+        self.started = Some(Instant::now());
+        self.pending_iteration = false;
+        self.outputs = None;
+        self.node_outputs = None;
+    }
+
+    /// The error returned while there is no host.
+    fn unavailable(&self) -> ZFError {
+        if self.command.is_none() || self.control.is_stopped() {
+            ZFError::Disconnected
+        } else {
+            ZFError::IOError(format!("The node host of < {} > is restarting", self.node))
+        }
+    }
+
+    /// Stops the host after it failed with `error`.
+    fn fail(&mut self, error: ZFError) -> ZFError {
+        lock(&self.control.socket).take();
+        let status = match self.host.take() {
+            Some(mut host) => host.terminate(),
+            None => "no process".to_string(),
+        };
+        self.pending_iteration = false;
+
+        // A host that ran for a while is not failing repeatedly.
+        match self.started {
+            Some(started) if started.elapsed() > HOST_MAX_RESTART_DELAY => self.failures = 1,
+            _ => self.failures = self.failures.saturating_add(1),
+        }
+
+        log::error!(
+            "The node host of < {} > failed ({}): {:?}",
+            self.node,
+            status,
+            error
+        );
+        ZFError::IOError(format!("The node host failed ({})", status))
+    }
+
+    /// Waits for the end of the iteration in progress, if any, and keeps its outputs.
+    fn collect_outputs(&mut self) {
+        if !self.pending_iteration {
+            return;
+        }
+        self.pending_iteration = false;
+
+        let outputs = match self.host.as_mut() {
+            None => Err(self.unavailable()),
+            Some(host) => match host.receive(None) {
+                Ok(Response::Iteration(outputs, node_outputs)) => Ok((outputs, node_outputs)),
+                Ok(Response::Error(e)) => Err(e),
+                Ok(_) => Err(self.fail(Response::unexpected())),
+                Err(e) => Err(self.fail(e)),
+            },
+        };
+        self.outputs = Some(outputs);
+    }
+
+    /// Sends the `request` to the host and waits for the response, for at most `timeout` if any.
+    ///
+    /// # Errors
+    /// The error of the node is returned as is. If the host cannot be reached it is stopped, and
+    /// an `IOError` variant is returned: the host is restarted in the background. While there is
+    /// no host, an `IOError` variant is returned, or a `Disconnected` variant if the host cannot
+    /// be restarted.
+    fn call(&mut self, request: &Request, timeout: Option<Duration>) -> ZFResult<Response> {
+        self.collect_outputs();
+        let host = match self.host.as_mut() {
+            Some(host) => host,
+            None => return Err(self.unavailable()),
+        };
+
+        match host.call(request, timeout) {
+            Ok(Response::Error(e)) => Err(e),
+            Ok(response) => Ok(response),
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+}
+
+/// Restarts, in the background, the host of the `session` if it failed.
+fn restart_if_failed(session: &Arc<Mutex<HostSession>>) {
+    let node = {
+        let mut guard = lock(session);
+        if guard.host.is_some()
+            || guard.restarting
+            || guard.command.is_none()
+            || guard.control.is_stopped()
+        {
+            return;
+        }
+        guard.restarting = true;
+        guard.node.clone()
+    };
+
+    let restarted = session.clone();
+    if let Err(e) = std::thread::Builder::new()
+        .name(format!("node-host-{}", node))
+        .spawn(move || restart(restarted))
+    {
+        log::error!("Unable to restart the node host of < {} >: {}", node, e);
+        lock(session).restarting = false;
+    }
+}
+
+/// Restarts the host of the `session`, waiting for a delay growing with its consecutive failures.
+fn restart(session: Arc<Mutex<HostSession>>) {
+    loop {
+        let (command, record, loader, delay, control) = {
+            let guard = lock(&session);
+            match &guard.command {
+                Some(command) => (
+                    command.clone(),
+                    guard.record.clone(),
+                    guard.loader.clone(),
+                    restart_delay(guard.failures),
+                    guard.control.clone(),
+                ),
+                None => return,
+            }
+        };
+
+        std::thread::sleep(delay);
+        if control.is_stopped() {
+            lock(&session).restarting = false;
+            return;
+        }
+
+        let checkpoint = lock(&session).checkpoint.clone();
+        let result = start_host(&command, &record, &loader, checkpoint.as_deref());
+
+        let mut guard = lock(&session);
+        match result {
+            Ok(mut host) => {
+                guard.restarting = false;
+                if control.is_stopped() {
+                    return;
+                }
+                // The node was checkpointed or restored while the host was starting.
+                if guard.checkpoint != checkpoint {
+                    if let Some(checkpoint) = &guard.checkpoint {
+                        if let Err(e) = host
+                            .call(&Request::Restore(checkpoint.clone()), Some(HOST_IO_TIMEOUT))
+                            .and_then(Response::done)
+                        {
+                            log::warn!("The node host could not restore the checkpoint: {:?}", e);
+                        }
+                    }
+                }
+                log::info!("Restarted the node host of < {} >", guard.node);
+                guard.install(host);
+                return;
+            }
+            Err(e) => {
+                guard.failures = guard.failures.saturating_add(1);
+                log::error!(
+                    "Unable to restart the node host of < {} >: {:?}",
+                    guard.node,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Interrupts a request of an asynchronous node if it is dropped before the end of the request.
+struct CallGuard {
+    control: Arc<HostControl>,
+    cancelled: Arc<AtomicBool>,
+    in_flight: Arc<AtomicBool>,
+    done: bool,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        self.cancelled.store(true, Ordering::SeqCst);
+        // Otherwise the request would hold the session until the node returns.
+        if self.in_flight.load(Ordering::SeqCst) {
+            self.control.abort();
+        }
+    }
+}
+
+/// The state of an isolated node: the connection to its host, the actual state of the node lives
+/// in the host.
+pub struct IsolatedState {
+    node: NodeId,
+    session: Arc<Mutex<HostSession>>,
+    control: Arc<HostControl>,
+}
+
+impl IsolatedState {
+    fn new(session: HostSession) -> Self {
+        Self {
+            node: session.node.clone(),
+            control: session.control.clone(),
+            session: Arc::new(Mutex::new(session)),
+        }
+    }
+
+    /// Calls `f` on the session, the host is restarted before and after if it failed.
+    fn with_session<T>(&self, f: impl FnOnce(&mut HostSession) -> ZFResult<T>) -> ZFResult<T> {
+        restart_if_failed(&self.session);
+        let result = f(&mut lock(&self.session));
+        restart_if_failed(&self.session);
+        result
+    }
+
+    fn call(&self, request: &Request) -> ZFResult<Response> {
+        self.with_session(|session| session.call(request, Some(HOST_IO_TIMEOUT)))
+    }
+
+    /// Sends the `request` from a thread that can block, for the asynchronous nodes. The request
+    /// waits for the host to be restarted, and has no timeout.
+    ///
+    /// If the returned future is dropped while the host executes the request, the host is
+    /// stopped, and restarted.
+    async fn call_blocking(&self, request: Request) -> ZFResult<Response> {
+        let mut guard = CallGuard {
+            control: self.control.clone(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            in_flight: Arc::new(AtomicBool::new(false)),
+            done: false,
+        };
+        let cancelled = guard.cancelled.clone();
+        let in_flight = guard.in_flight.clone();
+        let session = self.session.clone();
+
+        let result = async_std::task::spawn_blocking(move || loop {
+            restart_if_failed(&session);
+            {
+                let mut session = lock(&session);
+                if session.host.is_some()
+                    || session.command.is_none()
+                    || session.control.is_stopped()
+                {
+                    in_flight.store(true, Ordering::SeqCst);
+                    if cancelled.load(Ordering::SeqCst) {
+                        return Err(ZFError::Disconnected);
+                    }
+                    let result = session.call(&request, None);
+                    in_flight.store(false, Ordering::SeqCst);
+                    return result;
+                }
+            }
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(ZFError::Disconnected);
+            }
+            std::thread::sleep(HOST_RESTART_POLL);
+        })
+        .await;
+
+        guard.done = true;
+        result
+    }
+}
+
+impl std::fmt::Debug for IsolatedState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IsolatedState")
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+impl ZFState for IsolatedState {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+        self.with_session(|session| {
+            // The host restores the latest checkpoint once restarted.
+            if session.host.is_none() && session.restarting {
+                return Ok(session.checkpoint.clone());
+            }
+            match session.call(&Request::Checkpoint, Some(HOST_IO_TIMEOUT))? {
+                Response::Checkpoint(checkpoint) => {
+                    // Kept to be restored if the host has to be restarted.
+                    session.checkpoint = checkpoint.clone();
+                    Ok(checkpoint)
+                }
+                _ => Err(Response::unexpected()),
+            }
+        })
+    }
+
+    fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+        self.with_session(|session| {
+            if !(session.host.is_none() && session.restarting) {
+                session
+                    .call(&Request::Restore(bytes.to_vec()), Some(HOST_IO_TIMEOUT))?
+                    .done()?;
+            }
+            session.checkpoint = Some(bytes.to_vec());
+            Ok(())
+        })
+    }
+}
+
+/// A node executed by a node host, it forwards every call to its host.
+///
+/// It implements the traits of all the kinds of nodes, the record it is created with decides
+/// which kind of node the host loads.
+pub struct IsolatedNode {
+    command: NodeHostCommand,
+    record: HostedRecord,
+    loader: LoaderConfig,
+}
+
+impl IsolatedNode {
+    /// Creates an isolated source, its host loads the `record` with a loader using `loader`.
+    pub fn source(command: NodeHostCommand, record: SourceRecord, loader: LoaderConfig) -> Self {
+        Self {
+            command,
+            record: HostedRecord::Source(record),
+            loader,
+        }
+    }
+
+    /// Creates an isolated operator, its host loads the `record` with a loader using `loader`.
+    pub fn operator(
+        command: NodeHostCommand,
+        record: OperatorRecord,
+        loader: LoaderConfig,
+    ) -> Self {
+        Self {
+            command,
+            record: HostedRecord::Operator(record),
+            loader,
+        }
+    }
+
+    /// Creates an isolated sink, its host loads the `record` with a loader using `loader`.
+    pub fn sink(command: NodeHostCommand, record: SinkRecord, loader: LoaderConfig) -> Self {
+        Self {
+            command,
+            record: HostedRecord::Sink(record),
+            loader,
+        }
+    }
+}
+
+impl Node for IsolatedNode {
+    /// Starts the host and loads the node, this blocks the calling thread until the node is
+    /// loaded.
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        let mut record = self.record.clone();
+        record.set_configuration(configuration.clone());
+        let record = serde_json::to_string(&record).map_err(|_| ZFError::SerializationError)?;
+        let loader =
+            serde_json::to_string(&self.loader).map_err(|_| ZFError::SerializationError)?;
+
+        let host = start_host(&self.command, &record, &loader, None)?;
+        let mut session = HostSession {
+            node: self.record.id().clone(),
+            command: Some(self.command.clone()),
+            record,
+            loader,
+            host: None,
+            started: None,
+            failures: 0,
+            restarting: false,
+            checkpoint: None,
+            pending_iteration: false,
+            outputs: None,
+            node_outputs: None,
+            control: Arc::new(HostControl::default()),
+        };
+        session.install(host);
+
+        Ok(State::from(IsolatedState::new(session)))
+    }
+
+    /// Finalizes the node in its host, which then exits. A request in progress is interrupted
+    /// and the host stopped: the node is then not finalized.
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        let state = state.try_get::<IsolatedState>()?;
+        state.control.stopped.store(true, Ordering::SeqCst);
+        let interrupted = matches!(state.session.try_lock(), Err(TryLockError::WouldBlock));
+        if interrupted {
+            state.control.abort();
+        }
+
+        let mut session = lock(&state.session);
+        let result = if session.host.is_some() {
+            session
+                .call(&Request::Finalize, Some(HOST_IO_TIMEOUT))
+                .and_then(Response::done)
+        } else {
+            if interrupted {
+                log::warn!(
+                    "The node host of < {} > was stopped during a request, the node is not finalized",
+                    session.node
+                );
+            }
+            Ok(())
+        };
+        // The host exits once the node is finalized.
+        session.host = None;
+        result
+    }
+
+    fn on_deadline_miss(&self, state: &mut State, miss: &DeadlineMiss) -> ZFResult<()> {
+        state
+            .try_get::<IsolatedState>()?
+            .call(&Request::OnDeadlineMiss(miss.into()))?
+            .done()
+    }
+}
+
+/// The host executes a whole iteration upon `input_rule`: `run` returns the outputs the host
+/// computed, ignoring its inputs, and `output_rule` returns the outputs of the output rule
+/// executed by the host, ignoring its arguments.
+impl Operator for IsolatedNode {
+    fn input_rule(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        let mut wire_tokens = HashMap::with_capacity(tokens.len());
+        for (port_id, token) in tokens.iter_mut() {
+            let wire_token = match token {
+                InputToken::Pending => None,
+                InputToken::Ready(token) => {
+                    // Converted in place, so that the data is not serialized again.
+                    make_bytes(&mut token.data)?;
+                    Some((token.data.clone(), token.action.clone()))
+                }
+            };
+            wire_tokens.insert(port_id.clone(), wire_token);
+        }
+
+        let request = Request::Iteration {
+            mode: context.mode,
+            tokens: wire_tokens,
+        };
+        let (ready, actions) = state.try_get::<IsolatedState>()?.with_session(|session| {
+            // The outputs of an iteration that was not collected are discarded.
+            session.collect_outputs();
+            session.outputs = None;
+            session.node_outputs = None;
+
+            match session.call(&request, Some(HOST_IO_TIMEOUT))? {
+                Response::InputRule(ready, actions) => {
+                    session.pending_iteration = ready;
+                    Ok((ready, actions))
+                }
+                _ => Err(session.fail(Response::unexpected())),
+            }
+        })?;
+
+        for (port_id, action) in actions {
+            if let Some(InputToken::Ready(token)) = tokens.get_mut(&port_id) {
+                token.action = action;
+            }
+        }
+        Ok(ready)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        _inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        state.try_get::<IsolatedState>()?.with_session(|session| {
+            session.collect_outputs();
+            match session.outputs.take() {
+                Some(Ok((outputs, node_outputs))) => {
+                    session.node_outputs = Some(node_outputs);
+                    Ok(outputs)
+                }
+                Some(Err(e)) => Err(e),
+                None => Err(ZFError::InvalidState),
+            }
+        })
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        _outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        state
+            .try_get::<IsolatedState>()?
+            .with_session(|session| session.node_outputs.take().ok_or(ZFError::InvalidState))
+    }
+}
+
+/// The asynchronous nodes are executed from a thread that can block: a source isolated in a host
+/// should still return periodically, so that it can be stopped without stopping its host.
+#[async_trait]
+impl Source for IsolatedNode {
+    async fn run(&self, context: &mut Context, state: &mut State) -> ZFResult<Data> {
+        let request = Request::RunSource { mode: context.mode };
+        match state
+            .try_get::<IsolatedState>()?
+            .call_blocking(request)
+            .await?
+        {
+            Response::Data(data) => Ok(data),
+            _ => Err(Response::unexpected()),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for IsolatedNode {
+    async fn run(
+        &self,
+        context: &mut Context,
+        state: &mut State,
+        mut input: DataMessage,
+    ) -> ZFResult<()> {
+        make_bytes(&mut input)?;
+        let request = Request::RunSink {
+            mode: context.mode,
+            input,
+        };
+        state
+            .try_get::<IsolatedState>()?
+            .call_blocking(request)
+            .await?
+            .done()
+    }
+}
+
+/// The node loaded by a host.
+enum HostedNode {
+    Source(SourceLoaded),
+    Operator(OperatorLoaded),
+    Sink(SinkLoaded),
+}
+
+impl HostedNode {
+    fn state(&self) -> &Arc<AsyncMutex<State>> {
+        match self {
+            Self::Source(source) => &source.state,
+            Self::Operator(operator) => &operator.state,
+            Self::Sink(sink) => &sink.state,
+        }
+    }
+
+    async fn finalize(&self) -> ZFResult<()> {
+        let mut state = self.state().lock().await;
+        match self {
+            Self::Source(source) => source.source.finalize(&mut state),
+            Self::Operator(operator) => operator.operator.finalize(&mut state),
+            Self::Sink(sink) => sink.sink.finalize(&mut state),
+        }
+    }
+
+    async fn on_deadline_miss(&self, miss: &DeadlineMiss) -> ZFResult<()> {
+        let mut state = self.state().lock().await;
+        match self {
+            Self::Source(source) => source.source.on_deadline_miss(&mut state, miss),
+            Self::Operator(operator) => operator.operator.on_deadline_miss(&mut state, miss),
+            Self::Sink(sink) => sink.sink.on_deadline_miss(&mut state, miss),
+        }
+    }
+
+    /// Executes an iteration of the operator: the response of the input rule is written right
+    /// away, then, if it returned `true`, the one of `run` and of the output rule.
+    async fn iteration(
+        &self,
+        stream: &mut UnixStream,
+        mode: usize,
+        tokens: HashMap<PortId, Option<(DataMessage, TokenAction)>>,
+    ) -> ZFResult<()> {
+        let operator = match self {
+            Self::Operator(operator) => operator,
+            _ => return write_response(stream, &Response::Error(ZFError::Unsupported)),
+        };
+
+        let mut tokens: HashMap<PortId, InputToken> = tokens
+            .into_iter()
+            .map(|(port_id, token)| {
+                let token = match token {
+                    Some((message, action)) => InputToken::Ready(DataToken::new(action, message)),
+                    None => InputToken::Pending,
+                };
+                (port_id, token)
+            })
+            .collect();
+
+        let mut context = Context { mode };
+        let mut state = operator.state.lock().await;
+        let ready = match operator
+            .operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+        {
+            Ok(ready) => ready,
+            Err(e) => return write_response(stream, &Response::Error(e)),
+        };
+        let actions = tokens
+            .iter()
+            .filter_map(|(port_id, token)| match token {
+                InputToken::Ready(token) => Some((port_id.clone(), token.action.clone())),
+                InputToken::Pending => None,
+            })
+            .collect();
+        write_response(stream, &Response::InputRule(ready, actions))?;
+        if !ready {
+            return Ok(());
+        }
+
+        // As the runner does: the dropped tokens are not given to `run`.
+        let mut inputs: HashMap<PortId, DataMessage> = tokens
+            .into_iter()
+            .filter_map(|(port_id, token)| match token {
+                InputToken::Ready(token) if token.action != TokenAction::Drop => {
+                    Some((port_id, token.data))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let response = Self::run_operator(operator, &mut context, &mut state, &mut inputs)
+            .unwrap_or_else(Response::Error);
+        write_response(stream, &response)
+    }
+
+    /// Executes `run` and the output rule of the operator, measuring the local deadline miss.
+    fn run_operator(
+        operator: &OperatorLoaded,
+        context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<Response> {
+        let start = Instant::now();
+        let outputs = operator.operator.run(context, state, inputs)?;
+        let elapsed = start.elapsed();
+
+        let deadline_miss = operator
+            .local_deadline
+            .filter(|deadline| elapsed > *deadline)
+            .map(|deadline| LocalDeadlineMiss {
+                start,
+                deadline,
+                elapsed,
+            });
+
+        let outputs = outputs
+            .into_iter()
+            .map(|(port_id, data)| Ok((port_id, to_bytes(data)?)))
+            .collect::<ZFResult<HashMap<_, _>>>()?;
+        let node_outputs = operator
+            .operator
+            .output_rule(context, state, outputs.clone(), deadline_miss)?
+            .into_iter()
+            .map(|(port_id, output)| {
+                let output = match output {
+                    NodeOutput::Data(data) => NodeOutput::Data(to_bytes(data)?),
+                    control => control,
+                };
+                Ok((port_id, output))
+            })
+            .collect::<ZFResult<_>>()?;
+
+        Ok(Response::Iteration(outputs, node_outputs))
+    }
+
+    /// Executes the `request` on the node.
+    async fn handle(&self, request: Request) -> ZFResult<Response> {
+        match (self, request) {
+            (Self::Source(source), Request::RunSource { mode }) => {
+                let data = source
+                    .source
+                    .run(&mut Context { mode }, &mut *source.state.lock().await)
+                    .await?;
+                Ok(Response::Data(to_bytes(data)?))
+            }
+            (Self::Sink(sink), Request::RunSink { mode, input }) => {
+                sink.sink
+                    .run(&mut Context { mode }, &mut *sink.state.lock().await, input)
+                    .await?;
+                Ok(Response::Done)
+            }
+            (node, Request::OnDeadlineMiss(miss)) => {
+                node.on_deadline_miss(&miss.into()).await?;
+                Ok(Response::Done)
+            }
+            (node, Request::Checkpoint) => Ok(Response::Checkpoint(
+                node.state().lock().await.try_serialize()?,
+            )),
+            (node, Request::Restore(bytes)) => {
+                node.state().lock().await.try_restore(&bytes)?;
+                Ok(Response::Done)
+            }
+            (node, Request::Finalize) => {
+                node.finalize().await?;
+                Ok(Response::Done)
+            }
+            _ => Err(ZFError::Unsupported),
+        }
+    }
+}
+
+/// Writes the `response`, replacing it with its error if it cannot be written: it is either too
+/// large or cannot be serialized.
+fn write_response(stream: &mut UnixStream, response: &Response) -> ZFResult<()> {
+    match write_frame(stream, response) {
+        Err(e @ ZFError::IOError(_)) => Err(e),
+        Err(e) => write_frame(stream, &Response::Error(e)),
+        result => result,
+    }
+}
+
+/// Serves the requests of the runtime until the node is finalized or the runtime disconnects.
+async fn serve(mut stream: UnixStream, node: HostedNode) -> ZFResult<()> {
+    loop {
+        let request = match read_frame::<Request>(&mut stream) {
+            Ok(request) => request,
+            // The runtime is gone.
+            Err(ZFError::IOError(_)) => return node.finalize().await,
+            Err(e) => return Err(e),
+        };
+
+        match request {
+            Request::Iteration { mode, tokens } => {
+                node.iteration(&mut stream, mode, tokens).await?
+            }
+            Request::Finalize => {
+                let response = node
+                    .handle(Request::Finalize)
+                    .await
+                    .unwrap_or_else(Response::Error);
+                return write_response(&mut stream, &response);
+            }
+            request => {
+                let response = node.handle(request).await.unwrap_or_else(Response::Error);
+                write_response(&mut stream, &response)?;
+            }
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(json: &str) -> ZFResult<T> {
+    serde_json::from_str(json).map_err(|e| ZFError::ParsingError(format!("{}", e)))
+}
+
+/// Connects to the runtime through the `socket`, loads the node with `load` and serves the
+/// requests of the runtime.
+async fn host(
+    socket: &Path,
+    load: impl FnOnce(HostedRecord, LoaderConfig) -> ZFResult<HostedNode>,
+) -> ZFResult<()> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_write_timeout(Some(HOST_IO_TIMEOUT))?;
+
+    let node = match read_frame::<Request>(&mut stream)? {
+        Request::Load { record, loader } => {
+            parse(&record).and_then(|record| load(record, parse(&loader)?))
+        }
+        _ => Err(ZFError::InvalidState),
+    };
+
+    match node {
+        Ok(node) => {
+            write_response(&mut stream, &Response::Done)?;
+            serve(stream, node).await
+        }
+        Err(e) => {
+            write_response(&mut stream, &Response::Error(e.clone()))?;
+            Err(e)
+        }
+    }
+}
+
+/// Runs a node host: it connects to the runtime through the `socket`, loads the node the runtime
+/// sends, and executes it until the runtime finalizes it or disconnects.
+///
+/// This is meant to be the whole life of the process started by a
+/// [`NodeHostCommand`](`NodeHostCommand`), with the `socket` as its last argument.
+///
+/// # Errors
+/// An error variant is returned if the runtime cannot be reached or if the node cannot be
+/// loaded.
+pub async fn run_node_host(socket: &Path) -> ZFResult<()> {
+    host(socket, |record, loader| {
+        let loader = Loader::new(loader);
+        match record {
+            HostedRecord::Source(record) => Ok(HostedNode::Source(loader.load_source(record)?)),
+            HostedRecord::Operator(record) => {
+                Ok(HostedNode::Operator(loader.load_operator(record)?))
+            }
+            HostedRecord::Sink(record) => Ok(HostedNode::Sink(loader.load_sink(record)?)),
+        }
+    })
+    .await
+}
+
+/// Runs a node host executing the `operator`, instead of the one the runtime sends: only its
+/// record, e.g. its configuration, is used.
+///
+/// This is meant for programs embedding an operator, they are then given as the program of the
+/// [`NodeHostCommand`](`NodeHostCommand`).
+///
+/// # Errors
+/// An error variant is returned if the runtime cannot be reached, if it does not send an
+/// operator or if the operator cannot be initialized.
+pub async fn run_static_node_host(socket: &Path, operator: Arc<dyn Operator>) -> ZFResult<()> {
+    host(socket, |record, _| match record {
+        HostedRecord::Operator(record) => Ok(HostedNode::Operator(OperatorLoaded::try_new(
+            record, None, operator,
+        )?)),
+        _ => Err(ZFError::Unsupported),
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::link::PortDescriptor;
+    use crate::{default_input_rule, default_output_rule};
+
+    #[derive(Debug)]
+    struct Counter(u64);
+
+    impl ZFState for Counter {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+            Ok(Some(self.0.to_le_bytes().to_vec()))
+        }
+
+        fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+            let mut count = [0u8; 8];
+            count.copy_from_slice(bytes);
+            self.0 = u64::from_le_bytes(count);
+            Ok(())
+        }
+    }
+
+    // Counts the inputs it received.
+    struct CountOperator;
+
+    impl Node for CountOperator {
+        fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+            Ok(State::from(Counter(0)))
+        }
+
+        fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Operator for CountOperator {
+        fn input_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            tokens: &mut HashMap<PortId, InputToken>,
+        ) -> ZFResult<bool> {
+            default_input_rule(state, tokens)
+        }
+
+        fn run(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            inputs: &mut HashMap<PortId, DataMessage>,
+        ) -> ZFResult<HashMap<PortId, Data>> {
+            let counter = state.try_get::<Counter>()?;
+            counter.0 += inputs.len() as u64;
+
+            let mut outputs = HashMap::new();
+            outputs.insert(
+                "out".into(),
+                Data::from_bytes(counter.0.to_le_bytes().to_vec()),
+            );
+            Ok(outputs)
+        }
+
+        fn output_rule(
+            &self,
+            _context: &mut Context,
+            state: &mut State,
+            outputs: HashMap<PortId, Data>,
+            _deadline_miss: Option<LocalDeadlineMiss>,
+        ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+            default_output_rule(state, outputs)
+        }
+    }
+
+    fn operator_record() -> OperatorRecord {
+        OperatorRecord {
+            id: "count".into(),
+            inputs: vec![PortDescriptor {
+                port_id: "in".into(),
+                port_type: "u64".into(),
+            }],
+            outputs: vec![PortDescriptor {
+                port_id: "out".into(),
+                port_type: "u64".into(),
+            }],
+            uri: None,
+            configuration: None,
+            deadline: None,
+            on_deadline_miss: None,
+            executor: None,
+            runtime: "runtime".into(),
+            cicli: vec![],
+        }
+    }
+
+    fn isolated_operator() -> IsolatedNode {
+        IsolatedNode::operator(
+            NodeHostCommand {
+                program: "/nonexistent/node-host".into(),
+                args: vec![],
+            },
+            operator_record(),
+            LoaderConfig::new(),
+        )
+    }
+
+    // The state of an isolated node whose host is connected through `stream`, the host cannot be
+    // restarted.
+    fn isolated_state(stream: UnixStream) -> State {
+        let mut session = HostSession {
+            node: "count".into(),
+            command: None,
+            record: String::new(),
+            loader: String::new(),
+            host: None,
+            started: None,
+            failures: 0,
+            restarting: false,
+            checkpoint: None,
+            pending_iteration: false,
+            outputs: None,
+            node_outputs: None,
+            control: Arc::new(HostControl::default()),
+        };
+        session.install(NodeHost {
+            child: None,
+            stream,
+        });
+        State::from(IsolatedState::new(session))
+    }
+
+    #[test]
+    fn run_isolated_operator() {
+        let (runtime, host) = UnixStream::pair().unwrap();
+        let node = HostedNode::Operator(
+            OperatorLoaded::try_new(operator_record(), None, Arc::new(CountOperator)).unwrap(),
+        );
+        let handle = std::thread::spawn(move || async_std::task::block_on(serve(host, node)));
+
+        let operator = isolated_operator();
+        let mut state = isolated_state(runtime);
+        let mut context = Context::default();
+        let message = DataMessage::new(
+            Data::from_bytes(vec![1]),
+            uhlc::HLC::default().new_timestamp(),
+            vec![],
+        );
+
+        let mut tokens = HashMap::new();
+        tokens.insert(PortId::from("in"), InputToken::Pending);
+        assert!(!operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+            .unwrap());
+        // There is no iteration to collect.
+        assert!(matches!(
+            Operator::run(&operator, &mut context, &mut state, &mut HashMap::new()),
+            Err(ZFError::InvalidState)
+        ));
+
+        // The host executes the whole iteration.
+        tokens.insert(PortId::from("in"), InputToken::from(message));
+        assert!(operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+            .unwrap());
+        let outputs =
+            Operator::run(&operator, &mut context, &mut state, &mut HashMap::new()).unwrap();
+        assert_eq!(
+            outputs[&PortId::from("out")]
+                .try_as_bytes()
+                .unwrap()
+                .as_slice(),
+            &1u64.to_le_bytes()
+        );
+        let outputs = operator
+            .output_rule(&mut context, &mut state, HashMap::new(), None)
+            .unwrap();
+        assert!(matches!(
+            outputs.get(&PortId::from("out")),
+            Some(NodeOutput::Data(_))
+        ));
+
+        // The outputs of an iteration are collected before any other request.
+        assert!(operator
+            .input_rule(&mut context, &mut state, &mut tokens)
+            .unwrap());
+        assert_eq!(
+            state.try_serialize().unwrap(),
+            Some(2u64.to_le_bytes().to_vec())
+        );
+        let outputs =
+            Operator::run(&operator, &mut context, &mut state, &mut HashMap::new()).unwrap();
+        assert_eq!(
+            outputs[&PortId::from("out")]
+                .try_as_bytes()
+                .unwrap()
+                .as_slice(),
+            &2u64.to_le_bytes()
+        );
+
+        // The state of the node lives in the host.
+        state.try_restore(&5u64.to_le_bytes()).unwrap();
+        assert_eq!(
+            state.try_serialize().unwrap(),
+            Some(5u64.to_le_bytes().to_vec())
+        );
+
+        assert!(operator.finalize(&mut state).is_ok());
+        assert!(handle.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn host_failure() {
+        let (runtime, host) = UnixStream::pair().unwrap();
+        // The host exits right away.
+        drop(host);
+
+        let operator = isolated_operator();
+        let mut state = isolated_state(runtime);
+        let mut context = Context::default();
+
+        let mut tokens = HashMap::new();
+
+        assert!(matches!(
+            operator.input_rule(&mut context, &mut state, &mut tokens),
+            Err(ZFError::IOError(_))
+        ));
+        // The host is not reachable anymore and cannot be restarted.
+        assert!(matches!(
+            operator.input_rule(&mut context, &mut state, &mut tokens),
+            Err(ZFError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn frame_size() {
+        let (mut runtime, mut host) = UnixStream::pair().unwrap();
+        runtime
+            .write_all(&(MAX_FRAME_SIZE as u32 + 1).to_le_bytes())
+            .unwrap();
+        assert!(matches!(
+            read_frame::<Request>(&mut host),
+            Err(ZFError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn restart_backoff() {
+        assert_eq!(restart_delay(0), HOST_RESTART_DELAY);
+        assert_eq!(restart_delay(1), HOST_RESTART_DELAY);
+        assert_eq!(restart_delay(2), HOST_RESTART_DELAY * 2);
+        assert_eq!(restart_delay(3), HOST_RESTART_DELAY * 4);
+        assert_eq!(restart_delay(10), HOST_MAX_RESTART_DELAY);
+        assert_eq!(restart_delay(u32::MAX), HOST_MAX_RESTART_DELAY);
+    }
+}
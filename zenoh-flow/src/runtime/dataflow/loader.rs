@@ -12,7 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use super::ffi::{
+    AbiVersion, FfiDeclaration, FfiDeclarationHeader, FfiOperator, FfiOperatorDeclaration, FfiSink,
+    FfiSinkDeclaration, FfiSource, FfiSourceDeclaration,
+};
+#[cfg(unix)]
+use super::isolation::IsolatedNode;
 use super::node::{OperatorLoaded, SinkLoaded, SourceLoaded};
+#[cfg(feature = "wasm")]
+use super::wasm::{WasmOperator, WasmSink, WasmSource};
 use crate::model::node::{OperatorRecord, SinkRecord, SourceRecord};
 use crate::serde::{Deserialize, Serialize};
 use crate::{Configuration, Operator, Sink, Source, ZFError, ZFResult};
@@ -24,6 +32,7 @@ use libloading::os::unix::Library;
 use libloading::Library;
 
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use url::Url;
 
 #[cfg(target_family = "unix")]
@@ -39,10 +48,15 @@ pub static CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// the Rust compiler than the currently running Zenoh Flow daemon.
 /// As Rust is not ABI stable,
 /// this is to prevent (possibly cryptic) runtime errors.
+/// The nodes implementing the C ABI, see [`ffi`](`super::ffi`), are not concerned.
 pub static RUSTC_VERSION: &str = env!("RUSTC_VERSION");
 
 pub static EXT_FILE_EXTENSION: &str = "zfext";
 
+/// The file extension of the nodes executed by the embedded WebAssembly runtime, available with
+/// the `wasm` feature.
+pub static WASM_FILE_EXTENSION: &str = "wasm";
+
 // OPERATOR
 /// Operator register function signature
 ///
@@ -104,14 +118,117 @@ pub struct SinkDeclaration {
 /// operator_lib: ./target/release/libpy_op.so
 /// config_lib_key: python-script
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExtensibleImplementation {
-    pub(crate) name: String,
-    pub(crate) file_extension: String,
-    pub(crate) source_lib: String,
-    pub(crate) sink_lib: String,
-    pub(crate) operator_lib: String,
-    pub(crate) config_lib_key: String,
+    pub name: String,
+    pub file_extension: String,
+    pub source_lib: String,
+    pub sink_lib: String,
+    pub operator_lib: String,
+    pub config_lib_key: String,
+}
+
+impl ExtensibleImplementation {
+    /// Validates the extension: its libraries must exist and export the declaration of their
+    /// kind of node, either implementing a compatible version of the C ABI or built with the same
+    /// versions of Zenoh Flow and of the Rust compiler as the runtime.
+    ///
+    /// The libraries are loaded, and then unloaded, to look up their declaration.
+    ///
+    /// # Errors
+    /// A `LoadingError` variant describing the first problem found is returned.
+    pub fn validate(&self) -> ZFResult<()> {
+        if self.name.is_empty() || self.file_extension.is_empty() {
+            return Err(ZFError::LoadingError(format!(
+                "Extension < {} > must have a name and a file extension",
+                self.name
+            )));
+        }
+
+        // The name is also the name of the file of the extension.
+        if self.name.contains(std::path::is_separator) || self.name.starts_with('.') {
+            return Err(ZFError::LoadingError(format!(
+                "Invalid extension name < {} >",
+                self.name
+            )));
+        }
+
+        if Loader::is_lib(&self.file_extension)
+            || Loader::is_wasm(&self.file_extension)
+            || self.file_extension == EXT_FILE_EXTENSION
+        {
+            return Err(ZFError::LoadingError(format!(
+                "Extension < {} > cannot handle the < .{} > files",
+                self.name, self.file_extension
+            )));
+        }
+
+        let check = |kind: &str, lib: &str, result: ZFResult<()>| {
+            result.map_err(|e| {
+                ZFError::LoadingError(format!(
+                    "Extension < {} >: invalid {} library < {} >: {:?}",
+                    self.name, kind, lib, e
+                ))
+            })
+        };
+
+        // SAFETY: the libraries are only used to read their declaration, which does not outlive
+        // them.
+        unsafe {
+            check(
+                "source",
+                &self.source_lib,
+                Loader::check_declaration::<SourceDeclaration>(
+                    &self.source_lib,
+                    b"zfsource_declaration\0",
+                    b"zfsource_c_declaration\0",
+                ),
+            )?;
+            check(
+                "sink",
+                &self.sink_lib,
+                Loader::check_declaration::<SinkDeclaration>(
+                    &self.sink_lib,
+                    b"zfsink_declaration\0",
+                    b"zfsink_c_declaration\0",
+                ),
+            )?;
+            check(
+                "operator",
+                &self.operator_lib,
+                Loader::check_declaration::<OperatorDeclaration>(
+                    &self.operator_lib,
+                    b"zfoperator_declaration\0",
+                    b"zfoperator_c_declaration\0",
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The versions a declaration was built with.
+trait Declaration {
+    fn versions(&self) -> (&str, &str);
+}
+
+impl Declaration for OperatorDeclaration {
+    fn versions(&self) -> (&str, &str) {
+        (self.rustc_version, self.core_version)
+    }
+}
+
+impl Declaration for SourceDeclaration {
+    fn versions(&self) -> (&str, &str) {
+        (self.rustc_version, self.core_version)
+    }
+}
+
+impl Declaration for SinkDeclaration {
+    fn versions(&self) -> (&str, &str) {
+        (self.rustc_version, self.core_version)
+    }
 }
 
 /// Loader configuration files, it includes the extensions.
@@ -142,11 +259,19 @@ impl LoaderConfig {
     /// Adds the given extension.
     ///
     /// # Errors
-    /// It returns an error variant if the extension is already present.
+    /// It returns an error variant if:
+    /// - an extension with the same name is already present
+    /// - the file extension is already handled by another extension
     pub fn try_add_extension(&mut self, ext: ExtensibleImplementation) -> ZFResult<()> {
         if self.extensions.iter().any(|e| e.name == ext.name) {
             return Err(ZFError::Duplicate);
         }
+        if let Some(other) = self.get_extension_by_file_extension(&ext.file_extension) {
+            return Err(ZFError::InvalidData(format!(
+                "File extension < {} > of < {} > is already handled by extension < {} >",
+                ext.file_extension, ext.name, other.name
+            )));
+        }
         self.extensions.push(ext);
         Ok(())
     }
@@ -160,6 +285,11 @@ impl LoaderConfig {
         None
     }
 
+    /// Returns the extensions.
+    pub fn get_extensions(&self) -> &[ExtensibleImplementation] {
+        &self.extensions
+    }
+
     /// Gets the extension that matches the given `file_extension`.
     pub fn get_extension_by_file_extension(
         &self,
@@ -190,6 +320,38 @@ impl Default for LoaderConfig {
     }
 }
 
+/// Where the dynamically loaded nodes are executed.
+///
+/// Example:
+///
+/// ```yaml
+/// isolation: process
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IsolationMode {
+    /// The nodes are executed within the runtime (default).
+    InProcess,
+    /// Each node is executed by its own child process, see
+    /// [`Loader::with_node_host`](`Loader::with_node_host`).
+    Process,
+}
+
+impl Default for IsolationMode {
+    fn default() -> Self {
+        Self::InProcess
+    }
+}
+
+/// The command starting a node host: the process executing an isolated node.
+///
+/// The path of the socket through which the host must reach the runtime is appended to `args`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeHostCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
 /// The dynamic library loader.
 /// Before loading it verifies if the versions are compatible
 /// and if the symbols are presents.
@@ -201,15 +363,83 @@ impl Default for LoaderConfig {
 /// - `RTLD_NOW` load all the symbols when loading the library.
 /// - `RTLD_LOCAL` keep all the symbols local.
 ///
+/// The extensions can be changed while the loader is in use, see
+/// [`reload_extensions`](`Loader::reload_extensions`).
+///
+/// On Unix systems, the nodes can be executed out of the runtime process, see
+/// [`with_node_host`](`Loader::with_node_host`).
 ///
 pub struct Loader {
-    pub(crate) config: LoaderConfig,
+    pub(crate) config: RwLock<LoaderConfig>,
+    pub(crate) node_host: Option<NodeHostCommand>,
 }
 
 impl Loader {
     /// Creates a new `Loader` with the given `config`.
     pub fn new(config: LoaderConfig) -> Self {
-        Self { config }
+        Self {
+            config: RwLock::new(config),
+            node_host: None,
+        }
+    }
+
+    /// Executes each node loaded afterward by its own node host, a child process started with
+    /// the `command`, instead of within the runtime: a node that crashes only takes down its
+    /// host. The host loads the node with a loader using the configuration of this one.
+    ///
+    /// The `command` is expected to call
+    /// [`run_node_host`](`super::isolation::run_node_host`).
+    #[cfg(unix)]
+    pub fn with_node_host(mut self, command: NodeHostCommand) -> Self {
+        self.node_host = Some(command);
+        self
+    }
+
+    /// Returns a copy of the configuration of the loader.
+    pub fn get_config(&self) -> LoaderConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replaces the extensions of the loader with the ones of `config`.
+    ///
+    /// The nodes already loaded keep using the libraries they were loaded with, only the nodes
+    /// loaded afterward use the new extensions.
+    /// The names of the extensions added, removed or changed are returned.
+    pub fn reload_extensions(&self, config: LoaderConfig) -> Vec<String> {
+        let mut current = self
+            .config
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut changed: Vec<String> = current
+            .extensions
+            .iter()
+            .filter(|ext| !config.extensions.contains(ext))
+            .chain(
+                config
+                    .extensions
+                    .iter()
+                    .filter(|ext| !current.extensions.contains(ext)),
+            )
+            .map(|ext| ext.name.clone())
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        *current = config;
+        changed
+    }
+
+    /// Returns a copy of the extension handling the files with the given extension.
+    fn get_extension(&self, file_extension: &str) -> Option<ExtensibleImplementation> {
+        self.config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_extension_by_file_extension(file_extension)
+            .cloned()
     }
 
     /// Tries to load an operator from the information passed within
@@ -220,9 +450,18 @@ impl Loader {
     /// - different versions of zenoh flow used to build the operator
     /// - different versions of rust compiler used to build the operator
     /// - the library does not contain the symbols.
+    /// - the WebAssembly module does not implement the host interface.
+    /// - the node host cannot be started, if the nodes are isolated.
     /// - the URI is missing
     /// - the URI scheme is not known ( so far only `file://` is known).
     pub fn load_operator(&self, record: OperatorRecord) -> ZFResult<OperatorLoaded> {
+        #[cfg(unix)]
+        if let Some(command) = &self.node_host {
+            let operator =
+                IsolatedNode::operator(command.clone(), record.clone(), self.get_config());
+            return OperatorLoaded::try_new(record, None, Arc::new(operator));
+        }
+
         let uri = record.uri.clone().ok_or_else(|| {
             ZFError::LoadingError(format!(
                 "Missing URI for dynamically loaded Operator < {} >.",
@@ -248,6 +487,11 @@ impl Loader {
                         let (lib, op) = unsafe { Self::load_lib_operator(file_path) }?;
                         Ok(OperatorLoaded::try_new(record, Some(Arc::new(lib)), op)?)
                     }
+                    #[cfg(feature = "wasm")]
+                    _ if Self::is_wasm(&file_extension) => {
+                        let operator = WasmOperator::try_new(&file_path)?;
+                        Ok(OperatorLoaded::try_new(record, None, Arc::new(operator))?)
+                    }
                     _ => Ok(self.load_operator_from_extension(record, file_path)?),
                 }
             }
@@ -264,9 +508,17 @@ impl Loader {
     /// - different versions of zenoh flow used to build the source
     /// - different versions of rust compiler used to build the source
     /// - the library does not contain the symbols.
+    /// - the WebAssembly module does not implement the host interface.
+    /// - the node host cannot be started, if the nodes are isolated.
     /// - the URI is missing
     /// - the URI scheme is not known ( so far only `file://` is known).
     pub fn load_source(&self, record: SourceRecord) -> ZFResult<SourceLoaded> {
+        #[cfg(unix)]
+        if let Some(command) = &self.node_host {
+            let source = IsolatedNode::source(command.clone(), record.clone(), self.get_config());
+            return SourceLoaded::try_new(record, None, Arc::new(source));
+        }
+
         let uri = record.uri.clone().ok_or_else(|| {
             ZFError::LoadingError(format!(
                 "Missing URI for dynamically loaded Source < {} >.",
@@ -292,6 +544,11 @@ impl Loader {
                         let (lib, op) = unsafe { Self::load_lib_source(file_path) }?;
                         Ok(SourceLoaded::try_new(record, Some(Arc::new(lib)), op)?)
                     }
+                    #[cfg(feature = "wasm")]
+                    _ if Self::is_wasm(&file_extension) => {
+                        let source = WasmSource::try_new(&file_path)?;
+                        Ok(SourceLoaded::try_new(record, None, Arc::new(source))?)
+                    }
                     _ => Ok(self.load_source_from_extension(record, file_path)?),
                 }
             }
@@ -307,9 +564,17 @@ impl Loader {
     /// - different versions of zenoh flow used to build the sink
     /// - different versions of rust compiler used to build the sink
     /// - the library does not contain the symbols.
+    /// - the WebAssembly module does not implement the host interface.
+    /// - the node host cannot be started, if the nodes are isolated.
     /// - the URI is missing
     /// - the URI scheme is not known ( so far only `file://` is known).
     pub fn load_sink(&self, record: SinkRecord) -> ZFResult<SinkLoaded> {
+        #[cfg(unix)]
+        if let Some(command) = &self.node_host {
+            let sink = IsolatedNode::sink(command.clone(), record.clone(), self.get_config());
+            return SinkLoaded::try_new(record, None, Arc::new(sink));
+        }
+
         let uri = record.uri.clone().ok_or_else(|| {
             ZFError::LoadingError(format!(
                 "Missing URI for dynamically loaded Sink < {} >.",
@@ -335,6 +600,11 @@ impl Loader {
                         let (lib, op) = unsafe { Self::load_lib_sink(file_path) }?;
                         Ok(SinkLoaded::try_new(record, Some(Arc::new(lib)), op)?)
                     }
+                    #[cfg(feature = "wasm")]
+                    _ if Self::is_wasm(&file_extension) => {
+                        let sink = WasmSink::try_new(&file_path)?;
+                        Ok(SinkLoaded::try_new(record, None, Arc::new(sink))?)
+                    }
                     _ => Ok(self.load_sink_from_extension(record, file_path)?),
                 }
             }
@@ -349,7 +619,11 @@ impl Loader {
     ///
     /// # Errors
     /// This function dynamically loads an external library, things can go wrong:
-    /// - it fails if the symbol `zfoperator_declaration` is not found,
+    /// - it fails if neither the symbol `zfoperator_c_declaration` nor the symbol
+    ///   `zfoperator_declaration` is found,
+    /// - it fails with `VersionMismatch` if the C declaration implements an incompatible version
+    ///   of the C ABI, or if the Rust declaration was not built with the same versions of the
+    ///   Rust compiler and of Zenoh Flow as the runtime,
     unsafe fn load_lib_operator(path: PathBuf) -> ZFResult<(Library, Arc<dyn Operator>)> {
         log::debug!("Operator Loading {:#?}", path);
 
@@ -359,6 +633,14 @@ impl Loader {
         #[cfg(target_family = "windows")]
         let library = Library::new(path)?;
 
+        if let Some(decl) = Self::get_c_declaration::<FfiOperatorDeclaration>(
+            &library,
+            b"zfoperator_c_declaration\0",
+        )? {
+            let operator: Arc<dyn Operator> = Arc::new(FfiOperator::new(decl));
+            return Ok((library, operator));
+        }
+
         let decl = library
             .get::<*mut OperatorDeclaration>(b"zfoperator_declaration\0")?
             .read();
@@ -378,7 +660,11 @@ impl Loader {
     ///
     /// # Errors
     /// This function dynamically loads an external library, things can go wrong:
-    /// - it fails if the symbol `zfsource_declaration` is not found,
+    /// - it fails if neither the symbol `zfsource_c_declaration` nor the symbol
+    ///   `zfsource_declaration` is found,
+    /// - it fails with `VersionMismatch` if the C declaration implements an incompatible version
+    ///   of the C ABI, or if the Rust declaration was not built with the same versions of the
+    ///   Rust compiler and of Zenoh Flow as the runtime,
     unsafe fn load_lib_source(path: PathBuf) -> ZFResult<(Library, Arc<dyn Source>)> {
         log::debug!("Source Loading {:#?}", path);
 
@@ -388,6 +674,13 @@ impl Loader {
         #[cfg(target_family = "windows")]
         let library = Library::new(path)?;
 
+        if let Some(decl) =
+            Self::get_c_declaration::<FfiSourceDeclaration>(&library, b"zfsource_c_declaration\0")?
+        {
+            let source: Arc<dyn Source> = Arc::new(FfiSource::new(decl));
+            return Ok((library, source));
+        }
+
         let decl = library
             .get::<*mut SourceDeclaration>(b"zfsource_declaration\0")?
             .read();
@@ -407,7 +700,11 @@ impl Loader {
     ///
     /// # Errors
     /// This function dynamically loads an external library, things can go wrong:
-    /// - it fails if the symbol `zfsink_declaration` is not found,
+    /// - it fails if neither the symbol `zfsink_c_declaration` nor the symbol
+    ///   `zfsink_declaration` is found,
+    /// - it fails with `VersionMismatch` if the C declaration implements an incompatible version
+    ///   of the C ABI, or if the Rust declaration was not built with the same versions of the
+    ///   Rust compiler and of Zenoh Flow as the runtime,
     ///
     unsafe fn load_lib_sink(path: PathBuf) -> ZFResult<(Library, Arc<dyn Sink>)> {
         log::debug!("Sink Loading {:#?}", path);
@@ -418,6 +715,13 @@ impl Loader {
         #[cfg(target_family = "windows")]
         let library = Library::new(path)?;
 
+        if let Some(decl) =
+            Self::get_c_declaration::<FfiSinkDeclaration>(&library, b"zfsink_c_declaration\0")?
+        {
+            let sink: Arc<dyn Sink> = Arc::new(FfiSink::new(decl));
+            return Ok((library, sink));
+        }
+
         let decl = library
             .get::<*mut SinkDeclaration>(b"zfsink_declaration\0")?
             .read();
@@ -443,6 +747,84 @@ impl Loader {
         Ok(path)
     }
 
+    /// Reads the declaration `symbol` implementing the C ABI, if the library exports it.
+    ///
+    /// The version of the C ABI, which starts every declaration, is checked before the rest of
+    /// the declaration is read. Only the bytes declared by the library are then read: the fields
+    /// appended by later minor versions are null.
+    ///
+    /// # Safety
+    /// - lookup of symbols.
+    ///
+    /// # Errors
+    /// `VersionMismatch` is returned if the library implements an incompatible version of the C
+    /// ABI, or if its declaration is smaller than what this version requires.
+    unsafe fn get_c_declaration<D: FfiDeclaration>(
+        library: &Library,
+        symbol: &[u8],
+    ) -> ZFResult<Option<D>> {
+        let decl = match library.get::<*const u8>(symbol) {
+            Ok(decl) => *decl,
+            Err(_) => return Ok(None),
+        };
+        (decl as *const AbiVersion).read().negotiate()?;
+
+        let size = (decl as *const FfiDeclarationHeader).read().size;
+        if size < D::required_size() {
+            log::error!(
+                "The C declaration is {} bytes long, at least {} bytes are required",
+                size,
+                D::required_size()
+            );
+            return Err(ZFError::VersionMismatch);
+        }
+
+        let mut declaration = std::mem::MaybeUninit::<D>::zeroed();
+        std::ptr::copy_nonoverlapping(
+            decl,
+            declaration.as_mut_ptr() as *mut u8,
+            size.min(std::mem::size_of::<D>()),
+        );
+        Ok(Some(declaration.assume_init()))
+    }
+
+    /// Checks that the library at `path` exports either the declaration `c_symbol`, implementing
+    /// a compatible version of the C ABI, or the declaration `symbol`, built with the same
+    /// versions as the runtime.
+    ///
+    /// # Safety
+    /// - dynamic loading of library and lookup of symbols.
+    ///
+    /// # Errors
+    /// It fails if the library cannot be loaded, if no symbol is found or if the versions do not
+    /// match.
+    unsafe fn check_declaration<D: Declaration>(
+        path: &str,
+        symbol: &[u8],
+        c_symbol: &[u8],
+    ) -> ZFResult<()> {
+        let path = std::fs::canonicalize(path)
+            .map_err(|e| ZFError::IOError(format!("{}: {}", e, path)))?;
+
+        #[cfg(target_family = "unix")]
+        let library = Library::open(Some(path), LOAD_FLAGS)?;
+
+        #[cfg(target_family = "windows")]
+        let library = Library::new(path)?;
+
+        if Self::get_c_declaration::<FfiDeclarationHeader>(&library, c_symbol)?.is_some() {
+            return Ok(());
+        }
+
+        let decl = library.get::<*mut D>(symbol)?.read();
+        let (rustc_version, core_version) = decl.versions();
+        if rustc_version != RUSTC_VERSION || core_version != CORE_VERSION {
+            return Err(ZFError::VersionMismatch);
+        }
+
+        Ok(())
+    }
+
     /// Checks if the file is a dynamic library.
     fn is_lib(ext: &str) -> bool {
         if ext == std::env::consts::DLL_EXTENSION {
@@ -451,6 +833,13 @@ impl Loader {
         false
     }
 
+    /// Checks if the file is a WebAssembly module, executed by the embedded WebAssembly runtime.
+    ///
+    /// The file extension is reserved even if Zenoh Flow is built without the `wasm` feature.
+    fn is_wasm(ext: &str) -> bool {
+        ext == WASM_FILE_EXTENSION
+    }
+
     /// Returns the file extension, if any.
     fn get_file_extension(file: &Path) -> Option<String> {
         if let Some(ext) = file.extension() {
@@ -486,7 +875,7 @@ impl Loader {
             ))
         })?;
 
-        match self.config.get_extension_by_file_extension(&file_extension) {
+        match self.get_extension(&file_extension) {
             Some(e) => {
                 let wrapper_file_path = std::fs::canonicalize(&e.operator_lib)?;
                 record.configuration = Some(Self::generate_wrapper_config(
@@ -498,7 +887,10 @@ impl Loader {
                 let (lib, op) = unsafe { Self::load_lib_operator(wrapper_file_path) }?;
                 Ok(OperatorLoaded::try_new(record, Some(Arc::new(lib)), op)?)
             }
-            _ => Err(ZFError::Unimplemented),
+            _ => Err(ZFError::LoadingError(format!(
+                "No extension handles the < .{} > file of Operator < {} >.",
+                file_extension, record.id
+            ))),
         }
     }
 
@@ -527,7 +919,7 @@ impl Loader {
             ))
         })?;
 
-        match self.config.get_extension_by_file_extension(&file_extension) {
+        match self.get_extension(&file_extension) {
             Some(e) => {
                 let wrapper_file_path = std::fs::canonicalize(&e.source_lib)?;
                 record.configuration = Some(Self::generate_wrapper_config(
@@ -539,7 +931,10 @@ impl Loader {
                 let (lib, op) = unsafe { Self::load_lib_source(wrapper_file_path) }?;
                 Ok(SourceLoaded::try_new(record, Some(Arc::new(lib)), op)?)
             }
-            _ => Err(ZFError::Unimplemented),
+            _ => Err(ZFError::LoadingError(format!(
+                "No extension handles the < .{} > file of Source < {} >.",
+                file_extension, record.id
+            ))),
         }
     }
 
@@ -568,7 +963,7 @@ impl Loader {
             ))
         })?;
 
-        match self.config.get_extension_by_file_extension(&file_extension) {
+        match self.get_extension(&file_extension) {
             Some(e) => {
                 let wrapper_file_path = std::fs::canonicalize(&e.sink_lib)?;
                 record.configuration = Some(Self::generate_wrapper_config(
@@ -580,7 +975,10 @@ impl Loader {
                 let (lib, op) = unsafe { Self::load_lib_sink(wrapper_file_path) }?;
                 Ok(SinkLoaded::try_new(record, Some(Arc::new(lib)), op)?)
             }
-            _ => Err(ZFError::Unimplemented),
+            _ => Err(ZFError::LoadingError(format!(
+                "No extension handles the < .{} > file of Sink < {} >.",
+                file_extension, record.id
+            ))),
         }
     }
 
@@ -12,9 +12,14 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+pub mod ffi;
 pub mod instance;
+#[cfg(unix)]
+pub mod isolation;
 pub mod loader;
 pub mod node;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use async_std::sync::{Arc, Mutex};
 use std::collections::HashMap;
@@ -24,7 +29,8 @@ use uuid::Uuid;
 use crate::model::connector::ZFConnectorRecord;
 use crate::model::dataflow::record::DataFlowRecord;
 use crate::model::dataflow::validator::DataflowValidator;
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
+use crate::model::executor::ExecutorDescriptor;
 use crate::model::link::{LinkDescriptor, PortDescriptor};
 use crate::model::loops::LoopDescriptor;
 use crate::model::{InputDescriptor, OutputDescriptor};
@@ -182,6 +188,8 @@ impl Dataflow {
                 source,
                 library: None,
                 end_to_end_deadlines: vec![],
+                local_deadline: None,
+                on_deadline_miss: None,
             },
         );
 
@@ -229,13 +237,95 @@ impl Dataflow {
                 operator,
                 library: None,
                 end_to_end_deadlines: vec![],
-                ciclo: None,
+                cicli: vec![],
+                on_deadline_miss: None,
+                executor: None,
             },
         );
 
         Ok(())
     }
 
+    /// Sets the executor on which the `run` of the given static operator is executed.
+    ///
+    /// By default, the operator is executed on the executor shared by all the nodes.
+    ///
+    /// # Errors
+    /// An error variant is returned if the operator was not previously added.
+    pub fn try_set_operator_executor(
+        &mut self,
+        id: &NodeId,
+        executor: ExecutorDescriptor,
+    ) -> ZFResult<()> {
+        let operator = self
+            .operators
+            .get_mut(id)
+            .ok_or_else(|| ZFError::NodeNotFound(id.clone()))?;
+        operator.executor = Some(executor);
+
+        Ok(())
+    }
+
+    /// Sets the local deadline of the given static node, i.e. the maximum duration of each
+    /// execution of its `run`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the node was not previously added.
+    pub fn try_set_local_deadline(&mut self, id: &NodeId, deadline: Duration) -> ZFResult<()> {
+        if let Some(source) = self.sources.get_mut(id) {
+            source.local_deadline = Some(deadline);
+        } else if let Some(operator) = self.operators.get_mut(id) {
+            operator.local_deadline = Some(deadline);
+        } else if let Some(sink) = self.sinks.get_mut(id) {
+            sink.local_deadline = Some(deadline);
+        } else {
+            return Err(ZFError::NodeNotFound(id.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Sets the policy applied when the local deadline of the given static node is missed.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - the node was not previously added
+    /// - the node has no local deadline
+    /// - the policy is `fallback`, which is not supported for local deadlines
+    pub fn try_set_deadline_miss_policy(
+        &mut self,
+        id: &NodeId,
+        policy: DeadlineMissPolicy,
+    ) -> ZFResult<()> {
+        if let DeadlineMissPolicy::Fallback(_) = policy {
+            return Err(ZFError::ConfigurationError(format!(
+                "Node < {} >: the `fallback` policy is only supported for end-to-end deadlines",
+                id
+            )));
+        }
+
+        let (local_deadline, on_deadline_miss) = if let Some(source) = self.sources.get_mut(id) {
+            (&source.local_deadline, &mut source.on_deadline_miss)
+        } else if let Some(operator) = self.operators.get_mut(id) {
+            (&operator.local_deadline, &mut operator.on_deadline_miss)
+        } else if let Some(sink) = self.sinks.get_mut(id) {
+            (&sink.local_deadline, &mut sink.on_deadline_miss)
+        } else {
+            return Err(ZFError::NodeNotFound(id.clone()));
+        };
+
+        if local_deadline.is_none() {
+            return Err(ZFError::ConfigurationError(format!(
+                "Node < {} > has a deadline miss policy but no deadline",
+                id
+            )));
+        }
+
+        *on_deadline_miss = Some(policy);
+
+        Ok(())
+    }
+
     /// Tries to add a static sink to the data flow.
     ///
     /// If the validation fails the sink cannot be added.
@@ -263,6 +353,8 @@ impl Dataflow {
                 sink,
                 library: None,
                 end_to_end_deadlines: vec![],
+                local_deadline: None,
+                on_deadline_miss: None,
             },
         );
 
@@ -313,9 +405,55 @@ impl Dataflow {
         from: OutputDescriptor,
         to: InputDescriptor,
         duration: Duration,
+    ) -> ZFResult<()> {
+        self.try_add_deadline_with_policy(from, to, duration, DeadlineMissPolicy::Log)
+    }
+
+    /// Tries to add a deadline within the dataflow, applying `on_miss` when it is missed.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - validation fails
+    /// - the policy is `fallback` and the output does not exist on the `to` operator, or its
+    ///   type does not match the type of the input
+    pub fn try_add_deadline_with_policy(
+        &mut self,
+        from: OutputDescriptor,
+        to: InputDescriptor,
+        duration: Duration,
+        on_miss: DeadlineMissPolicy,
     ) -> ZFResult<()> {
         self.validator.validate_deadline(&from, &to)?;
-        let deadline = E2EDeadlineRecord { from, to, duration };
+
+        if let DeadlineMissPolicy::Fallback(output) = &on_miss {
+            let operator = self.operators.get(&to.node).ok_or_else(|| {
+                ZFError::ConfigurationError(format!(
+                    "Node < {} >: the `fallback` policy is only supported by operators",
+                    to.node
+                ))
+            })?;
+            let output_type = operator
+                .outputs
+                .get(output)
+                .ok_or_else(|| ZFError::PortNotFound((to.node.clone(), output.clone())))?;
+            let input_type = operator
+                .inputs
+                .get(&to.input)
+                .ok_or_else(|| ZFError::PortNotFound((to.node.clone(), to.input.clone())))?;
+            if output_type != input_type {
+                return Err(ZFError::PortTypeNotMatching((
+                    input_type.clone(),
+                    output_type.clone(),
+                )));
+            }
+        }
+
+        let deadline = E2EDeadlineRecord {
+            from,
+            to,
+            duration,
+            on_miss: Some(on_miss),
+        };
         self.add_end_to_end_deadline(deadline);
 
         Ok(())
@@ -357,19 +495,50 @@ impl Dataflow {
         port_type: PortType,
         is_infinite: bool,
     ) -> ZFResult<()> {
-        log::debug!("Validating loop…");
-        self.validator
-            .validate_loop(&ingress, &egress, &feedback_port)?;
-        log::debug!("Validating loop… OK.");
+        self.try_add_bounded_loop(
+            ingress,
+            egress,
+            feedback_port,
+            port_type,
+            is_infinite,
+            None,
+            None,
+        )
+    }
 
+    /// Tries to add a loop within the dataflow, bounded by a maximum number of iterations and/or
+    /// a maximum duration per iteration.
+    ///
+    /// Once a bound is reached, the Egress stops sending on the `feedback_port`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - validation fails (e.g. `max_iterations` is set for an infinite loop)
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_add_bounded_loop(
+        &mut self,
+        ingress: NodeId,
+        egress: NodeId,
+        feedback_port: PortId,
+        port_type: PortType,
+        is_infinite: bool,
+        max_iterations: Option<u64>,
+        iteration_timeout: Option<Duration>,
+    ) -> ZFResult<()> {
         let loop_descriptor = LoopDescriptor {
             ingress: ingress.clone(),
             egress: egress.clone(),
             feedback_port: feedback_port.clone(),
             is_infinite,
             port_type: port_type.clone(),
+            max_iterations,
+            iteration_timeout: iteration_timeout.map(DurationDescriptor::from),
         };
 
+        log::debug!("Validating loop…");
+        self.validator.validate_loop(&loop_descriptor)?;
+        log::debug!("Validating loop… OK.");
+
         log::debug!("Updating Ingress node < {} >…", &ingress);
         let ingress_op = self
             .operators
@@ -378,7 +547,7 @@ impl Dataflow {
         ingress_op
             .inputs
             .insert(feedback_port.clone(), port_type.clone());
-        ingress_op.ciclo = Some(loop_descriptor.clone());
+        ingress_op.cicli.push(loop_descriptor.clone());
         self.validator.try_add_input(
             ingress.clone(),
             PortDescriptor {
@@ -396,7 +565,7 @@ impl Dataflow {
         egress_op
             .outputs
             .insert(feedback_port.clone(), port_type.clone());
-        egress_op.ciclo = Some(loop_descriptor);
+        egress_op.cicli.push(loop_descriptor);
         self.validator.try_add_output(
             egress.clone(),
             PortDescriptor {
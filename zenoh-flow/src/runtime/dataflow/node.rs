@@ -12,7 +12,8 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
+use crate::model::executor::ExecutorDescriptor;
 use crate::model::link::PortDescriptor;
 use crate::model::loops::LoopDescriptor;
 use crate::model::node::{OperatorRecord, SinkRecord, SourceRecord};
@@ -33,6 +34,8 @@ pub struct SourceLoaded {
     pub(crate) id: NodeId,
     pub(crate) output: PortDescriptor,
     pub(crate) period: Option<Duration>,
+    pub(crate) local_deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) source: Arc<dyn Source>,
     pub(crate) library: Option<Arc<Library>>,
@@ -59,6 +62,8 @@ impl SourceLoaded {
             output: record.output,
             state: Arc::new(Mutex::new(state)),
             period: record.period.map(|dur_desc| dur_desc.to_duration()),
+            local_deadline: record.deadline,
+            on_deadline_miss: record.on_deadline_miss,
             source,
             library: lib,
             end_to_end_deadlines: vec![],
@@ -74,7 +79,9 @@ pub struct OperatorLoaded {
     pub(crate) inputs: HashMap<PortId, PortType>,
     pub(crate) outputs: HashMap<PortId, PortType>,
     pub(crate) local_deadline: Option<Duration>,
-    pub(crate) ciclo: Option<LoopDescriptor>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
+    pub(crate) cicli: Vec<LoopDescriptor>,
+    pub(crate) executor: Option<ExecutorDescriptor>,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) operator: Arc<dyn Operator>,
     pub(crate) library: Option<Arc<Library>>,
@@ -113,11 +120,13 @@ impl OperatorLoaded {
             inputs,
             outputs,
             local_deadline: record.deadline,
+            on_deadline_miss: record.on_deadline_miss,
             state: Arc::new(Mutex::new(state)),
             operator,
             library: lib,
             end_to_end_deadlines: vec![],
-            ciclo: record.ciclo,
+            cicli: record.cicli,
+            executor: record.executor,
         })
     }
 }
@@ -128,6 +137,8 @@ impl OperatorLoaded {
 pub struct SinkLoaded {
     pub(crate) id: NodeId,
     pub(crate) input: PortDescriptor,
+    pub(crate) local_deadline: Option<Duration>,
+    pub(crate) on_deadline_miss: Option<DeadlineMissPolicy>,
     pub(crate) state: Arc<Mutex<State>>,
     pub(crate) sink: Arc<dyn Sink>,
     pub(crate) library: Option<Arc<Library>>,
@@ -152,6 +163,8 @@ impl SinkLoaded {
         Ok(Self {
             id: record.id,
             input: record.input,
+            local_deadline: record.deadline,
+            on_deadline_miss: record.on_deadline_miss,
             state: Arc::new(Mutex::new(state)),
             sink,
             library: lib,
@@ -0,0 +1,680 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! WebAssembly nodes.
+//!
+//! A node whose URI points to a `.wasm` file is compiled and executed by an embedded WebAssembly
+//! runtime instead of being loaded as a native library. Such a node is portable across
+//! architectures and does not depend on the versions of the Rust compiler or of Zenoh Flow. It is
+//! also sandboxed: the module cannot import anything, hence it has no access to the host.
+//!
+//! # Host interface
+//!
+//! The module must export its linear memory as `memory` and the following functions, where `ptr`
+//! and `len` designate a buffer in the linear memory:
+//!
+//! - `zf_alloc(len: i32) -> i32` allocates a buffer of `len` bytes and returns its `ptr`,
+//! - `zf_dealloc(ptr: i32, len: i32)` frees a buffer returned by `zf_alloc`,
+//! - `zf_initialize(ptr: i32, len: i32) -> i32` receives the configuration of the node, serialized
+//!   as JSON (the buffer is empty if the node has no configuration),
+//! - `zf_finalize() -> i32` is called when the node is stopped,
+//! - `zf_run` depends on the kind of the node:
+//!   - source: `zf_run(out: i32) -> i32` produces the bytes of the data,
+//!   - operator: `zf_run(ptr: i32, len: i32, out: i32) -> i32` receives the inputs and produces the
+//!     outputs, both encoded as a list of ports (see below),
+//!   - sink: `zf_run(ptr: i32, len: i32) -> i32` receives the bytes of the data.
+//!
+//! All the functions returning an `i32` return `0` on success, any other value is reported as an
+//! error. When a function produces a buffer, it writes its `ptr` and then its `len`, as two
+//! little-endian `u32`, at the address `out`.
+//!
+//! Every buffer passed to the module is allocated through `zf_alloc` and freed through
+//! `zf_dealloc` by the host once the call returns. Likewise, the host frees the buffers produced
+//! by the module once it has copied them.
+//!
+//! A list of ports is encoded as its number of entries followed by the entries, each entry being
+//! the identifier of the port (UTF-8) followed by the data. The number of entries and the lengths
+//! of the identifiers and of the data are little-endian `u32`:
+//!
+//! ```text
+//! count | id_len | id | data_len | data | id_len | id | data_len | data | ...
+//! ```
+//!
+//! # Limits
+//!
+//! The linear memory of a module cannot grow beyond [`MAX_MEMORY_SIZE`](`MAX_MEMORY_SIZE`) and
+//! each call of an exported function is granted [`FUEL_PER_CALL`](`FUEL_PER_CALL`) units of fuel,
+//! roughly one per WebAssembly instruction executed. A module exceeding its fuel, e.g. stuck in a
+//! loop, traps and the call is reported as an error.
+
+use super::ffi::{decode_ports, encode_ports};
+use crate::runtime::message::DataMessage;
+use crate::{
+    default_input_rule, default_output_rule, Configuration, Context, Data, InputToken,
+    LocalDeadlineMiss, Node, NodeOutput, Operator, PortId, Sink, Source, State, ZFError, ZFResult,
+    ZFState,
+};
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use wasmtime::{
+    Config, Engine, Instance, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+    TypedFunc, WasmParams, WasmResults,
+};
+
+static ZF_RUN: &str = "zf_run";
+
+/// The maximum size, in bytes, of the linear memory of a WebAssembly node.
+pub const MAX_MEMORY_SIZE: usize = 256 * 1024 * 1024;
+
+/// The fuel granted to each call of a function exported by a WebAssembly node.
+pub const FUEL_PER_CALL: u64 = 10_000_000_000;
+
+/// A compiled WebAssembly module, shared by the instances of a node.
+struct WasmModule {
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+}
+
+impl WasmModule {
+    /// Compiles the WebAssembly module contained in `bytes`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the module is invalid or if it has imports.
+    fn try_new(bytes: impl AsRef<[u8]>) -> ZFResult<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| ZFError::WasmError(e.to_string()))?;
+        let module = Module::new(&engine, bytes).map_err(|e| ZFError::WasmError(e.to_string()))?;
+
+        // Nothing is provided to the module: it is sandboxed.
+        if let Some(import) = module.imports().next() {
+            return Err(ZFError::WasmError(format!(
+                "WebAssembly nodes cannot have imports, found < {}::{} >",
+                import.module(),
+                import.name()
+            )));
+        }
+
+        Ok(Self {
+            engine,
+            module,
+            fuel: FUEL_PER_CALL,
+        })
+    }
+
+    /// Reads and compiles the WebAssembly module at `path`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be read, if the module is invalid or if
+    /// it has imports.
+    fn from_file(path: &Path) -> ZFResult<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| ZFError::IOError(format!("{}: {}", e, path.to_string_lossy())))?;
+        Self::try_new(bytes)
+    }
+
+    /// Instantiates the module and initializes it with the `configuration`.
+    ///
+    /// The signature of `zf_run` is checked against `P` and `R`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the module does not implement the host interface, if it
+    /// exceeds its limits or if `zf_initialize` fails.
+    fn instantiate<P, R>(&self, configuration: &Option<Configuration>) -> ZFResult<State>
+    where
+        P: WasmParams,
+        R: WasmResults,
+    {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_SIZE)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        // The start function of the module, if any, is executed by the instantiation.
+        store
+            .add_fuel(self.fuel)
+            .map_err(|e| ZFError::WasmError(e.to_string()))?;
+        let instance = Instance::new(&mut store, &self.module, &[])
+            .map_err(|e| ZFError::WasmError(e.to_string()))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| ZFError::WasmError("Missing export < memory >".to_string()))?;
+
+        let mut wasm_instance = WasmInstance {
+            alloc: get_func(&instance, &mut store, "zf_alloc")?,
+            dealloc: get_func(&instance, &mut store, "zf_dealloc")?,
+            finalize: get_func(&instance, &mut store, "zf_finalize")?,
+            fuel: self.fuel,
+            store,
+            instance,
+            memory,
+        };
+        let _: TypedFunc<P, R> = wasm_instance.get_run()?;
+
+        let configuration = match configuration {
+            Some(configuration) => {
+                serde_json::to_vec(configuration).map_err(|_| ZFError::SerializationError)?
+            }
+            None => vec![],
+        };
+        let initialize: TypedFunc<(i32, i32), i32> =
+            get_func(&instance, &mut wasm_instance.store, "zf_initialize")?;
+        let (ptr, len) = wasm_instance.write(&configuration)?;
+        let status = wasm_instance.call(&initialize, (ptr, len));
+        wasm_instance.free(ptr, len)?;
+        check_status("zf_initialize", status)?;
+
+        Ok(State::from(wasm_instance))
+    }
+}
+
+/// An instance of a WebAssembly module: the state of a WebAssembly node.
+///
+/// The whole state of the node lives in the linear memory of the instance.
+pub struct WasmInstance {
+    store: Store<StoreLimits>,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: TypedFunc<(i32, i32), ()>,
+    finalize: TypedFunc<(), i32>,
+    fuel: u64,
+}
+
+impl std::fmt::Debug for WasmInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmInstance")
+            .field("memory_size", &self.memory.data_size(&self.store))
+            .finish()
+    }
+}
+
+impl ZFState for WasmInstance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl WasmInstance {
+    /// Returns `zf_run`, with the signature expected for the kind of the node.
+    fn get_run<P, R>(&mut self) -> ZFResult<TypedFunc<P, R>>
+    where
+        P: WasmParams,
+        R: WasmResults,
+    {
+        get_func(&self.instance, &mut self.store, ZF_RUN)
+    }
+
+    /// Calls `func` with a fresh budget of fuel.
+    fn call<P, R>(&mut self, func: &TypedFunc<P, R>, params: P) -> Result<R, Trap>
+    where
+        P: WasmParams,
+        R: WasmResults,
+    {
+        // Fuel left by a previous call is not carried over.
+        let remaining = self
+            .store
+            .consume_fuel(0)
+            .map_err(|e| Trap::new(e.to_string()))?;
+        self.store
+            .add_fuel(self.fuel.saturating_sub(remaining))
+            .map_err(|e| Trap::new(e.to_string()))?;
+        func.call(&mut self.store, params)
+    }
+
+    /// Allocates a buffer in the linear memory and copies `bytes` in it.
+    ///
+    /// # Errors
+    /// An error variant is returned if the allocation fails or if the buffer is out of bounds.
+    fn write(&mut self, bytes: &[u8]) -> ZFResult<(i32, i32)> {
+        let len = i32::try_from(bytes.len()).map_err(|_| {
+            ZFError::WasmError(format!("Buffer of {} bytes is too large", bytes.len()))
+        })?;
+        let alloc = self.alloc.clone();
+        let ptr = self
+            .call(&alloc, len)
+            .map_err(|e| ZFError::WasmError(format!("zf_alloc: {}", e)))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| ZFError::WasmError(format!("zf_alloc: {}", e)))?;
+        Ok((ptr, len))
+    }
+
+    /// Frees a buffer of the linear memory.
+    ///
+    /// # Errors
+    /// An error variant is returned if `zf_dealloc` traps.
+    fn free(&mut self, ptr: i32, len: i32) -> ZFResult<()> {
+        let dealloc = self.dealloc.clone();
+        self.call(&dealloc, (ptr, len))
+            .map_err(|e| ZFError::WasmError(format!("zf_dealloc: {}", e)))
+    }
+
+    /// Allocates the slot in which the module writes the buffer it produces.
+    fn alloc_out(&mut self) -> ZFResult<i32> {
+        Ok(self.write(&[0u8; 8])?.0)
+    }
+
+    /// Copies, and then frees, the buffer described by the slot `out`, the slot is freed as well.
+    ///
+    /// # Errors
+    /// An error variant is returned if the buffer is out of bounds, which is checked before
+    /// allocating the copy: its length is controlled by the module.
+    fn take_out(&mut self, out: i32) -> ZFResult<Vec<u8>> {
+        let mut slot = [0u8; 8];
+        self.memory
+            .read(&self.store, out as u32 as usize, &mut slot)
+            .map_err(|e| ZFError::WasmError(format!("zf_run: {}", e)))?;
+        self.free(out, 8)?;
+
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&slot[..4]);
+        let ptr = u32::from_le_bytes(word);
+        word.copy_from_slice(&slot[4..]);
+        let len = u32::from_le_bytes(word);
+
+        let in_bounds = (ptr as usize)
+            .checked_add(len as usize)
+            .map_or(false, |end| end <= self.memory.data_size(&self.store));
+        if !in_bounds {
+            return Err(ZFError::WasmError(format!(
+                "zf_run: buffer of {} bytes at {} is out of bounds",
+                len, ptr
+            )));
+        }
+
+        let mut bytes = vec![0u8; len as usize];
+        self.memory
+            .read(&self.store, ptr as usize, &mut bytes)
+            .map_err(|e| ZFError::WasmError(format!("zf_run: {}", e)))?;
+        self.free(ptr as i32, len as i32)?;
+
+        Ok(bytes)
+    }
+
+    /// Runs a WebAssembly source.
+    fn run_source(&mut self) -> ZFResult<Vec<u8>> {
+        let run: TypedFunc<i32, i32> = self.get_run()?;
+        let out = self.alloc_out()?;
+        let status = self.call(&run, out);
+        if let Err(e) = check_status(ZF_RUN, status) {
+            self.free(out, 8)?;
+            return Err(e);
+        }
+        self.take_out(out)
+    }
+
+    /// Runs a WebAssembly operator, `inputs` and the result are encoded lists of ports.
+    fn run_operator(&mut self, inputs: &[u8]) -> ZFResult<Vec<u8>> {
+        let run: TypedFunc<(i32, i32, i32), i32> = self.get_run()?;
+        let (ptr, len) = self.write(inputs)?;
+        let out = self.alloc_out()?;
+        let status = self.call(&run, (ptr, len, out));
+        self.free(ptr, len)?;
+        if let Err(e) = check_status(ZF_RUN, status) {
+            self.free(out, 8)?;
+            return Err(e);
+        }
+        self.take_out(out)
+    }
+
+    /// Runs a WebAssembly sink.
+    fn run_sink(&mut self, input: &[u8]) -> ZFResult<()> {
+        let run: TypedFunc<(i32, i32), i32> = self.get_run()?;
+        let (ptr, len) = self.write(input)?;
+        let status = self.call(&run, (ptr, len));
+        self.free(ptr, len)?;
+        check_status(ZF_RUN, status)
+    }
+
+    /// Finalizes the instance.
+    fn finalize(&mut self) -> ZFResult<()> {
+        let finalize = self.finalize.clone();
+        let status = self.call(&finalize, ());
+        check_status("zf_finalize", status)
+    }
+}
+
+/// A Source implemented by a WebAssembly module.
+pub struct WasmSource {
+    module: WasmModule,
+}
+
+impl WasmSource {
+    /// Compiles the WebAssembly source at `path`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be read, if the module is invalid or if
+    /// it has imports.
+    pub fn try_new(path: &Path) -> ZFResult<Self> {
+        Ok(Self {
+            module: WasmModule::from_file(path)?,
+        })
+    }
+}
+
+impl Node for WasmSource {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.module.instantiate::<i32, i32>(configuration)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        state.try_get::<WasmInstance>()?.finalize()
+    }
+}
+
+#[async_trait]
+impl Source for WasmSource {
+    async fn run(&self, _context: &mut Context, state: &mut State) -> ZFResult<Data> {
+        let bytes = state.try_get::<WasmInstance>()?.run_source()?;
+        Ok(Data::from_bytes(bytes))
+    }
+}
+
+/// An Operator implemented by a WebAssembly module.
+///
+/// The operator is triggered once all its inputs are present, the outputs it produces are sent
+/// as they are.
+pub struct WasmOperator {
+    module: WasmModule,
+}
+
+impl WasmOperator {
+    /// Compiles the WebAssembly operator at `path`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be read, if the module is invalid or if
+    /// it has imports.
+    pub fn try_new(path: &Path) -> ZFResult<Self> {
+        Ok(Self {
+            module: WasmModule::from_file(path)?,
+        })
+    }
+}
+
+impl Node for WasmOperator {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.module
+            .instantiate::<(i32, i32, i32), i32>(configuration)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        state.try_get::<WasmInstance>()?.finalize()
+    }
+}
+
+impl Operator for WasmOperator {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let mut data = Vec::with_capacity(inputs.len());
+        for (id, message) in inputs.iter() {
+            data.push((id.clone(), message.data.try_as_bytes()?));
+        }
+        let ports: Vec<(PortId, &[u8])> = data
+            .iter()
+            .map(|(id, bytes)| (id.clone(), bytes.as_slice()))
+            .collect();
+
+        let outputs = state
+            .try_get::<WasmInstance>()?
+            .run_operator(&encode_ports(&ports))?;
+
+        Ok(decode_ports(&outputs)?
+            .into_iter()
+            .map(|(id, bytes)| (id, Data::from_bytes(bytes)))
+            .collect())
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+/// A Sink implemented by a WebAssembly module.
+pub struct WasmSink {
+    module: WasmModule,
+}
+
+impl WasmSink {
+    /// Compiles the WebAssembly sink at `path`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be read, if the module is invalid or if
+    /// it has imports.
+    pub fn try_new(path: &Path) -> ZFResult<Self> {
+        Ok(Self {
+            module: WasmModule::from_file(path)?,
+        })
+    }
+}
+
+impl Node for WasmSink {
+    fn initialize(&self, configuration: &Option<Configuration>) -> ZFResult<State> {
+        self.module.instantiate::<(i32, i32), i32>(configuration)
+    }
+
+    fn finalize(&self, state: &mut State) -> ZFResult<()> {
+        state.try_get::<WasmInstance>()?.finalize()
+    }
+}
+
+#[async_trait]
+impl Sink for WasmSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        input: DataMessage,
+    ) -> ZFResult<()> {
+        let bytes = input.data.try_as_bytes()?;
+        state.try_get::<WasmInstance>()?.run_sink(&bytes)
+    }
+}
+
+/// Looks up the function `name` exported by the instance and checks its signature.
+///
+/// # Errors
+/// An error variant is returned if the function is missing or if its signature does not match.
+fn get_func<P, R>(
+    instance: &Instance,
+    store: &mut Store<StoreLimits>,
+    name: &str,
+) -> ZFResult<TypedFunc<P, R>>
+where
+    P: WasmParams,
+    R: WasmResults,
+{
+    instance
+        .get_typed_func::<P, R, _>(store, name)
+        .map_err(|e| ZFError::WasmError(format!("Invalid export < {} >: {}", name, e)))
+}
+
+/// Converts the status returned by the function `name` into a `ZFResult`.
+fn check_status<E: std::fmt::Display>(name: &str, status: Result<i32, E>) -> ZFResult<()> {
+    match status {
+        Ok(0) => Ok(()),
+        Ok(status) => Err(ZFError::WasmError(format!(
+            "< {} > returned the status {}",
+            name, status
+        ))),
+        Err(e) => Err(ZFError::WasmError(format!("< {} > trapped: {}", name, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An operator copying its inputs to its outputs, its configuration must not be empty.
+    static ECHO: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 16))
+  (func $alloc (export "zf_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "zf_dealloc") (param i32 i32))
+  (func (export "zf_initialize") (param $ptr i32) (param $len i32) (result i32)
+    (i32.eqz (local.get $len)))
+  (func (export "zf_finalize") (result i32)
+    (i32.const 0))
+  (func (export "zf_run") (param $ptr i32) (param $len i32) (param $out i32) (result i32)
+    (local $copy i32)
+    (local.set $copy (call $alloc (local.get $len)))
+    (memory.copy (local.get $copy) (local.get $ptr) (local.get $len))
+    (i32.store (local.get $out) (local.get $copy))
+    (i32.store offset=4 (local.get $out) (local.get $len))
+    (i32.const 0)))
+"#;
+
+    #[test]
+    fn run_operator() {
+        let module = WasmModule::try_new(ECHO).unwrap();
+
+        // `zf_initialize` fails without configuration.
+        match module.instantiate::<(i32, i32, i32), i32>(&None) {
+            Err(ZFError::WasmError(e)) => assert!(e.contains("zf_initialize")),
+            _ => panic!("The initialization should fail"),
+        }
+
+        // The signature of `zf_run` is the one of an operator.
+        assert!(module
+            .instantiate::<(i32, i32), i32>(&Some(serde_json::json!({"key": "value"})))
+            .is_err());
+
+        let mut state = module
+            .instantiate::<(i32, i32, i32), i32>(&Some(serde_json::json!({"key": "value"})))
+            .unwrap();
+        let instance = state.try_get::<WasmInstance>().unwrap();
+        let inputs: Vec<(PortId, &[u8])> = vec![("in".into(), &b"hello"[..])];
+        let outputs = instance.run_operator(&encode_ports(&inputs)).unwrap();
+        assert_eq!(
+            decode_ports(&outputs).unwrap(),
+            vec![(PortId::from("in"), b"hello".to_vec())]
+        );
+        assert!(instance.finalize().is_ok());
+    }
+
+    // A source producing either a buffer larger than its memory or never returning, depending
+    // on its configuration (empty or not).
+    static ROGUE: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 16))
+  (global $looping (mut i32) (i32.const 0))
+  (func (export "zf_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "zf_dealloc") (param i32 i32))
+  (func (export "zf_initialize") (param $ptr i32) (param $len i32) (result i32)
+    (global.set $looping (local.get $len))
+    (i32.const 0))
+  (func (export "zf_finalize") (result i32)
+    (i32.const 0))
+  (func (export "zf_run") (param $out i32) (result i32)
+    (if (global.get $looping)
+      (then (loop $forever (br $forever))))
+    (i32.store (local.get $out) (i32.const 16))
+    (i32.store offset=4 (local.get $out) (i32.const -1))
+    (i32.const 0)))
+"#;
+
+    #[test]
+    fn reject_out_of_bounds_buffer() {
+        let module = WasmModule::try_new(ROGUE).unwrap();
+        let mut state = module.instantiate::<i32, i32>(&None).unwrap();
+        match state.try_get::<WasmInstance>().unwrap().run_source() {
+            Err(ZFError::WasmError(e)) => assert!(e.contains("out of bounds")),
+            _ => panic!("The buffer should be rejected"),
+        }
+    }
+
+    #[test]
+    fn interrupt_exhausted_fuel() {
+        let mut module = WasmModule::try_new(ROGUE).unwrap();
+        module.fuel = 100_000;
+        let mut state = module
+            .instantiate::<i32, i32>(&Some(serde_json::json!({"loop": true})))
+            .unwrap();
+        let instance = state.try_get::<WasmInstance>().unwrap();
+        match instance.run_source() {
+            Err(ZFError::WasmError(e)) => assert!(e.contains("trapped")),
+            _ => panic!("The run should be interrupted"),
+        }
+        // The next call is granted a fresh budget.
+        assert!(instance.finalize().is_ok());
+    }
+
+    #[test]
+    fn limit_memory() {
+        // 8192 pages of 64 KiB: 512 MiB.
+        let module = r#"
+(module
+  (memory (export "memory") 8192))
+"#;
+        // Rejected by the instantiation, before looking for the exports.
+        match WasmModule::try_new(module)
+            .unwrap()
+            .instantiate::<i32, i32>(&None)
+        {
+            Err(ZFError::WasmError(e)) => assert!(!e.contains("export")),
+            _ => panic!("The memory should exceed the limits"),
+        }
+    }
+
+    #[test]
+    fn reject_imports() {
+        let module = r#"
+(module
+  (import "env" "open" (func (param i32 i32) (result i32)))
+  (memory (export "memory") 1))
+"#;
+        match WasmModule::try_new(module) {
+            Err(ZFError::WasmError(e)) => assert!(e.contains("env::open")),
+            _ => panic!("A module with imports should be rejected"),
+        }
+    }
+}
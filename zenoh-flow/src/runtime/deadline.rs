@@ -12,9 +12,11 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use crate::model::deadline::E2EDeadlineRecord;
+use crate::async_std::sync::Mutex;
+use crate::model::deadline::{DeadlineMissPolicy, E2EDeadlineRecord};
 use crate::model::{InputDescriptor, OutputDescriptor};
-use crate::{NodeId, PortId};
+use crate::runtime::InstanceContext;
+use crate::{NodeId, PortId, ZFResult};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use uhlc::Timestamp;
@@ -31,6 +33,127 @@ pub struct LocalDeadlineMiss {
     pub elapsed: Duration,
 }
 
+/// A deadline miss, as provided to [`Node::on_deadline_miss`](`crate::Node::on_deadline_miss`).
+#[derive(Clone)]
+pub enum DeadlineMiss {
+    Local(LocalDeadlineMiss),
+    EndToEnd(E2EDeadlineMiss),
+}
+
+/// The counters of the deadline misses of a node, per applied policy.
+///
+/// `missed` counts all the misses, the other counters only the misses whose policy matched.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadlineMissStats {
+    pub missed: u64,
+    pub dropped: u64,
+    pub fallback: u64,
+    pub callback: u64,
+    pub escalated: u64,
+}
+
+impl DeadlineMissStats {
+    /// Accounts for a miss handled with the given `policy`.
+    pub(crate) fn record(&mut self, policy: &DeadlineMissPolicy) {
+        self.missed += 1;
+        match policy {
+            DeadlineMissPolicy::Log => (),
+            DeadlineMissPolicy::Drop => self.dropped += 1,
+            DeadlineMissPolicy::Fallback(_) => self.fallback += 1,
+            DeadlineMissPolicy::Callback => self.callback += 1,
+            DeadlineMissPolicy::Escalate => self.escalated += 1,
+        }
+    }
+}
+
+/// The kind of deadline that was missed, as published in a [`DeadlineMissEvent`](`DeadlineMissEvent`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadlineMissEventKind {
+    Local {
+        deadline: Duration,
+        elapsed: Duration,
+    },
+    EndToEnd(E2EDeadlineMiss),
+}
+
+/// The event published, in JSON, when a deadline with the `escalate` policy is missed.
+///
+/// It is published on `/zf/event/{flow_id}/{instance_id}/deadline_miss/{node_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlineMissEvent {
+    pub flow_id: String,
+    pub instance_id: uuid::Uuid,
+    pub node_id: NodeId,
+    pub kind: DeadlineMissEventKind,
+}
+
+/// Publishes a [`DeadlineMissEvent`](`DeadlineMissEvent`) for the node `node_id`.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - serialization fails
+/// - unable to put on zenoh
+async fn escalate_deadline_miss(
+    context: &InstanceContext,
+    node_id: &NodeId,
+    miss: &DeadlineMiss,
+) -> ZFResult<()> {
+    let kind = match miss {
+        DeadlineMiss::Local(miss) => DeadlineMissEventKind::Local {
+            deadline: miss.deadline,
+            elapsed: miss.elapsed,
+        },
+        DeadlineMiss::EndToEnd(miss) => DeadlineMissEventKind::EndToEnd(miss.clone()),
+    };
+
+    let event = DeadlineMissEvent {
+        flow_id: context.flow_id.to_string(),
+        instance_id: context.instance_id,
+        node_id: node_id.clone(),
+        kind,
+    };
+
+    let resource_name = format!(
+        "/zf/event/{}/{}/deadline_miss/{}",
+        &context.flow_id, &context.instance_id, node_id
+    );
+    let payload = serde_json::to_vec(&event)?;
+    context
+        .runtime
+        .session
+        .get()
+        .await?
+        .put(&resource_name, payload)
+        .await?;
+
+    Ok(())
+}
+
+/// Accounts for the `miss` of the node `node_id` in `stats` and, if the `policy` is
+/// `escalate`, publishes the corresponding event.
+///
+/// Failing to publish the event is only logged: the node keeps running.
+pub(crate) async fn report_deadline_miss(
+    context: &InstanceContext,
+    node_id: &NodeId,
+    stats: &Mutex<DeadlineMissStats>,
+    policy: &DeadlineMissPolicy,
+    miss: &DeadlineMiss,
+) {
+    stats.lock().await.record(policy);
+
+    if *policy == DeadlineMissPolicy::Escalate {
+        if let Err(e) = escalate_deadline_miss(context, node_id, miss).await {
+            log::error!(
+                "[Node: {}] Could not escalate deadline miss: {:?}",
+                node_id,
+                e
+            );
+        }
+    }
+}
+
 /// A End to End Deadline.
 /// A deadline can apply for a whole graph or for a subpart of it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +187,8 @@ pub struct E2EDeadline {
     pub from: OutputDescriptor,
     pub to: InputDescriptor,
     pub start: Timestamp,
+    #[serde(default)]
+    pub on_miss: Option<DeadlineMissPolicy>,
 }
 
 impl PartialEq<E2EDeadlineRecord> for E2EDeadline {
@@ -80,6 +205,7 @@ impl E2EDeadline {
             from: record.from,
             to: record.to,
             start,
+            on_miss: record.on_miss,
         }
     }
 
@@ -0,0 +1,250 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{DurationDescriptor, RuntimeId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uhlc::Timestamp;
+use uuid::Uuid;
+
+/// The configuration of the liveliness of a runtime.
+///
+/// Every `period`, the runtime publishes its heartbeat and looks at the heartbeats of the other
+/// runtimes. A runtime whose heartbeat did not change for longer than `lease` is considered as
+/// failed: its entries are removed from the
+/// [`DataStore`](`crate::runtime::resources::DataStore`) and the nodes it was running are
+/// handled according to the [`RuntimeFailurePolicy`](`RuntimeFailurePolicy`) of their instance.
+///
+/// Only the runtimes with a liveliness configuration publish a heartbeat, and only those can be
+/// detected as failed.
+///
+/// Example:
+///
+/// ```yaml
+/// liveliness:
+///   period:
+///     length: 1
+///     unit: s
+///   lease:
+///     length: 10
+///     unit: s
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LivelinessConfig {
+    pub period: DurationDescriptor,
+    pub lease: DurationDescriptor,
+}
+
+/// The heartbeat of a runtime, as kept in the
+/// [`DataStore`](`crate::runtime::resources::DataStore`).
+///
+/// Only changes of the timestamp are observed, the clocks of the runtimes do not need to be
+/// synchronized.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RuntimeHeartbeat {
+    pub id: Uuid,
+    pub timestamp: Timestamp,
+}
+
+/// What happens to the nodes of an instance mapped to a failed runtime.
+///
+/// With `ignore`, the default, the nodes are reported as orphaned and the rest of the instance
+/// keeps running. With `remap`, the nodes are loaded on the runtimes that are still alive, among
+/// `runtimes` if it is not empty, and restored from their latest checkpoint if any.
+///
+/// Example:
+///
+/// ```yaml
+/// on_runtime_failure:
+///   remap:
+///     runtimes:
+///       - runtime0
+///       - runtime1
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeFailurePolicy {
+    Ignore,
+    Remap {
+        #[serde(default)]
+        runtimes: Vec<RuntimeId>,
+    },
+}
+
+impl Default for RuntimeFailurePolicy {
+    fn default() -> Self {
+        Self::Ignore
+    }
+}
+
+/// What happens to the local part of an instance when its runtime starts again after a stop or
+/// a failure.
+///
+/// With `never`, the default, the record of the instance kept for the runtime is removed. With
+/// `prepare`, the nodes are loaded again, and restored from their checkpoints when the runtime is
+/// configured to, but not started. With `always`, they are also started.
+///
+/// Example:
+///
+/// ```yaml
+/// on_runtime_restart: always
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeRestartPolicy {
+    Never,
+    Prepare,
+    Always,
+}
+
+impl Default for RuntimeRestartPolicy {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+/// Keeps track of the heartbeats of the runtimes to detect the failed ones.
+#[derive(Debug, Default)]
+pub struct LivelinessTracker {
+    last_seen: HashMap<Uuid, (Timestamp, Instant)>,
+}
+
+impl LivelinessTracker {
+    /// Updates the tracker with the heartbeats observed at `now` and returns the runtimes whose
+    /// heartbeat did not change for longer than `lease`.
+    ///
+    /// The returned runtimes are no longer tracked, neither are the runtimes whose heartbeat was
+    /// removed, i.e. the runtimes that stopped.
+    pub fn update(
+        &mut self,
+        heartbeats: &[RuntimeHeartbeat],
+        now: Instant,
+        lease: Duration,
+    ) -> Vec<Uuid> {
+        self.last_seen
+            .retain(|id, _| heartbeats.iter().any(|heartbeat| heartbeat.id == *id));
+
+        let mut failed = Vec::new();
+        for heartbeat in heartbeats {
+            match self.last_seen.get_mut(&heartbeat.id) {
+                Some((timestamp, seen)) if *timestamp == heartbeat.timestamp => {
+                    if now.saturating_duration_since(*seen) > lease {
+                        failed.push(heartbeat.id);
+                    }
+                }
+                Some(last) => *last = (heartbeat.timestamp, now),
+                None => {
+                    self.last_seen
+                        .insert(heartbeat.id, (heartbeat.timestamp, now));
+                }
+            }
+        }
+
+        for id in failed.iter() {
+            self.last_seen.remove(id);
+        }
+
+        failed
+    }
+
+    /// Returns the runtimes considered as alive.
+    pub fn get_alive(&self) -> Vec<Uuid> {
+        self.last_seen.keys().cloned().collect()
+    }
+
+    /// Returns `true` if the runtime `id` has the lowest identifier among the alive runtimes.
+    ///
+    /// The failures are handled by this runtime only, so that the nodes of a failed runtime are
+    /// not re-mapped several times.
+    pub fn is_coordinator(&self, id: &Uuid) -> bool {
+        self.last_seen.keys().all(|alive| alive >= id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uhlc::HLC;
+
+    #[test]
+    fn tracker_detects_failed_runtimes() {
+        let hlc = HLC::default();
+        let lease = Duration::from_secs(10);
+        let start = Instant::now();
+        let alive = Uuid::new_v4();
+        let failed = Uuid::new_v4();
+
+        let failed_heartbeat = RuntimeHeartbeat {
+            id: failed,
+            timestamp: hlc.new_timestamp(),
+        };
+        let mut tracker = LivelinessTracker::default();
+
+        let heartbeats = vec![
+            RuntimeHeartbeat {
+                id: alive,
+                timestamp: hlc.new_timestamp(),
+            },
+            failed_heartbeat.clone(),
+        ];
+        assert!(tracker.update(&heartbeats, start, lease).is_empty());
+
+        let heartbeats = vec![
+            RuntimeHeartbeat {
+                id: alive,
+                timestamp: hlc.new_timestamp(),
+            },
+            failed_heartbeat.clone(),
+        ];
+        assert!(tracker
+            .update(&heartbeats, start + Duration::from_secs(5), lease)
+            .is_empty());
+
+        let heartbeats = vec![
+            RuntimeHeartbeat {
+                id: alive,
+                timestamp: hlc.new_timestamp(),
+            },
+            failed_heartbeat,
+        ];
+        assert_eq!(
+            tracker.update(&heartbeats, start + Duration::from_secs(11), lease),
+            vec![failed]
+        );
+        assert_eq!(tracker.get_alive(), vec![alive]);
+        assert!(tracker.is_coordinator(&alive));
+    }
+
+    #[test]
+    fn tracker_forgets_stopped_runtimes() {
+        let hlc = HLC::default();
+        let lease = Duration::from_secs(10);
+        let start = Instant::now();
+        let stopped = Uuid::new_v4();
+
+        let mut tracker = LivelinessTracker::default();
+        let heartbeats = vec![RuntimeHeartbeat {
+            id: stopped,
+            timestamp: hlc.new_timestamp(),
+        }];
+        assert!(tracker.update(&heartbeats, start, lease).is_empty());
+        assert!(!tracker.is_coordinator(&Uuid::from_u128(u128::MAX)));
+
+        assert!(tracker
+            .update(&[], start + Duration::from_secs(20), lease)
+            .is_empty());
+        assert!(tracker.get_alive().is_empty());
+    }
+}
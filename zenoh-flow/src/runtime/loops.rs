@@ -16,12 +16,13 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use uhlc::Timestamp;
 
-use crate::{model::loops::LoopDescriptor, NodeId};
+use crate::{model::loops::LoopDescriptor, NodeId, PortId};
 
 /// A `LoopContext` is associated to each message that goes through a Loop.
 ///
 /// It gives the following information:
 /// - the ingress / egress,
+/// - the feedback port (that, together with the ingress, identifies the Loop),
 /// - an iteration counter (if the Loop is finite),
 /// - the starting timestamp of the first iteration,
 /// - the starting timestamp of the current iteration,
@@ -30,6 +31,7 @@ use crate::{model::loops::LoopDescriptor, NodeId};
 pub struct LoopContext {
     pub(crate) ingress: NodeId,
     pub(crate) egress: NodeId,
+    pub(crate) feedback_port: PortId,
     pub(crate) iteration: LoopIteration,
     pub(crate) timestamp_start_first_iteration: Timestamp,
     pub(crate) timestamp_start_current_iteration: Option<Timestamp>,
@@ -56,6 +58,7 @@ impl LoopContext {
         Self {
             ingress: descriptor.ingress.clone(),
             egress: descriptor.egress.clone(),
+            feedback_port: descriptor.feedback_port.clone(),
             iteration,
             timestamp_start_first_iteration: start,
             timestamp_start_current_iteration: Some(start),
@@ -63,6 +66,69 @@ impl LoopContext {
         }
     }
 
+    /// Returns `true` if this `LoopContext` belongs to the Loop described by `descriptor`.
+    pub(crate) fn is_context_of(&self, descriptor: &LoopDescriptor) -> bool {
+        self.ingress == descriptor.ingress && self.feedback_port == descriptor.feedback_port
+    }
+
+    /// Returns `true` if, once the current iteration is over, one of the bounds of the Loop
+    /// described by `descriptor` is reached: either the maximum number of iterations or the
+    /// duration of an iteration.
+    ///
+    /// This is called by the Egress, after `update_egress`.
+    pub(crate) fn is_bound_reached(&self, descriptor: &LoopDescriptor) -> bool {
+        if let (Some(max_iterations), LoopIteration::Finite(counter)) =
+            (descriptor.max_iterations, self.iteration)
+        {
+            if counter >= max_iterations {
+                log::debug!(
+                    "Loop < {} > -> < {} >: maximum number of iterations reached ({})",
+                    self.ingress,
+                    self.egress,
+                    max_iterations
+                );
+                return true;
+            }
+        }
+
+        if let (Some(timeout), Some(duration)) =
+            (&descriptor.iteration_timeout, self.duration_last_iteration)
+        {
+            let timeout = timeout.to_duration();
+            if duration > timeout {
+                log::warn!(
+                    "Loop < {} > -> < {} >: iteration took {} µs (expected < {} µs)",
+                    self.ingress,
+                    self.egress,
+                    duration.as_micros(),
+                    timeout.as_micros()
+                );
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the time left, at `now`, before the current iteration of the Loop described by
+    /// `descriptor` exceeds its `iteration_timeout`.
+    ///
+    /// `None` is returned if the Loop has no `iteration_timeout` or if no iteration is in progress.
+    pub(crate) fn remaining_iteration_time(
+        &self,
+        descriptor: &LoopDescriptor,
+        now: Timestamp,
+    ) -> Option<Duration> {
+        let timeout = descriptor.iteration_timeout.as_ref()?.to_duration();
+        let start = self.timestamp_start_current_iteration?;
+        let elapsed = if now > start {
+            now.get_diff_duration(&start)
+        } else {
+            Duration::ZERO
+        };
+        Some(timeout.saturating_sub(elapsed))
+    }
+
     /// Updates the start timestamp for the current iteration.
     /// This is called when the iteration begins.
     pub(crate) fn update_ingress(&mut self, now: Timestamp) {
@@ -97,6 +163,11 @@ impl LoopContext {
         &self.egress
     }
 
+    /// Returns the feedback port of the loop.
+    pub fn get_feedback_port(&self) -> &PortId {
+        &self.feedback_port
+    }
+
     /// Returns the [`LoopIteration`](`LoopIteration`).
     pub fn get_iteration(&self) -> LoopIteration {
         self.iteration
@@ -16,7 +16,7 @@ extern crate serde;
 
 use crate::runtime::deadline::{E2EDeadline, E2EDeadlineMiss};
 use crate::runtime::loops::LoopContext;
-use crate::{Data, FlowId, NodeId, NodeOutput, PortId, ZFData, ZFError, ZFResult};
+use crate::{Data, FlowId, NodeId, NodeOutput, PortId, PortType, ZFData, ZFError, ZFResult};
 use async_std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, fmt::Debug};
@@ -114,19 +114,95 @@ impl DataMessage {
     }
 }
 
+/// The version of the [`RecordingMetadata`](`RecordingMetadata`), it is
+/// incremented every time its layout changes.
+pub const RECORDING_METADATA_VERSION: u32 = 1;
+
 /// Metadata stored in Zenoh's time series storages.
 /// It contains information about the recording.
 /// Multiple [`RecordingMetadata`](`RecordingMetadata`) can be used
 /// to synchronize the recording from different Ports.
+///
+/// The metadata starts with its version: a recording whose metadata has
+/// another layout is rejected instead of being misread.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RecordingMetadata {
+    pub(crate) version: u32,
     pub(crate) timestamp: Timestamp,
     pub(crate) port_id: PortId,
+    pub(crate) port_type: PortType,
     pub(crate) node_id: NodeId,
     pub(crate) flow_id: FlowId,
     pub(crate) instance_id: Uuid,
 }
 
+impl RecordingMetadata {
+    /// Returns the version of the layout of the metadata.
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Checks that the metadata has the layout of this version of Zenoh Flow.
+    ///
+    /// # Errors
+    /// `VersionMismatch` is returned if the version is not
+    /// [`RECORDING_METADATA_VERSION`](`RECORDING_METADATA_VERSION`).
+    pub fn check_version(&self) -> ZFResult<()> {
+        if self.version != RECORDING_METADATA_VERSION {
+            log::error!(
+                "The recording has the version {} of the metadata, expected {}",
+                self.version,
+                RECORDING_METADATA_VERSION
+            );
+            return Err(ZFError::VersionMismatch);
+        }
+        Ok(())
+    }
+
+    /// Returns the `Timestamp` at which the recording started.
+    pub fn get_timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the recorded port.
+    pub fn get_port_id(&self) -> &PortId {
+        &self.port_id
+    }
+
+    /// Returns the type of the recorded port.
+    pub fn get_port_type(&self) -> &PortType {
+        &self.port_type
+    }
+
+    /// Returns the node whose port is recorded.
+    pub fn get_node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// Returns the flow of the recorded node.
+    pub fn get_flow_id(&self) -> &FlowId {
+        &self.flow_id
+    }
+
+    /// Returns the instance of the recorded node.
+    pub fn get_instance_id(&self) -> &Uuid {
+        &self.instance_id
+    }
+
+    /// Returns the key expression where the recording is stored on Zenoh:
+    /// `/zf/record/{flow_id}/{instance_id}/{node_id}/{port_id}/{start_time}`.
+    pub fn get_resource_name(&self) -> String {
+        format!(
+            "/zf/record/{}/{}/{}/{}/{}",
+            self.flow_id,
+            self.instance_id,
+            self.node_id,
+            self.port_id,
+            self.timestamp.get_time()
+        )
+    }
+}
+
 /// Zenoh Flow control messages.
 /// It contains the control messages used within Zenoh Flow.
 /// For the time being only the `RecordingStart` and `RecordingStop` messages
@@ -220,6 +296,22 @@ impl Message {
         }
     }
 
+    /// Deserializes a `Message` serialized using bincode.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize, e.g. a recording made with a previous layout
+    ///   of the metadata
+    /// - the message starts a recording with another version of the metadata
+    pub fn deserialize_bincode(bytes: &[u8]) -> ZFResult<Self> {
+        let message: Self =
+            bincode::deserialize(bytes).map_err(|_| ZFError::DeseralizationError)?;
+        if let Self::Control(ControlMessage::RecordingStart(metadata)) = &message {
+            metadata.check_version()?;
+        }
+        Ok(message)
+    }
+
     /// Returns the `Timestamp` associated with the message.
     pub fn get_timestamp(&self) -> Timestamp {
         match self {
@@ -15,6 +15,8 @@
 #![allow(clippy::manual_async_fn)]
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use crate::model::dataflow::descriptor::DataFlowDescriptor;
 use crate::{
@@ -23,39 +25,55 @@ use crate::{
         node::{OperatorDescriptor, SinkDescriptor, SourceDescriptor},
     },
     serde::{Deserialize, Serialize},
-    FlowId,
+    DurationDescriptor, FlowId,
 };
 use async_std::sync::Arc;
 use uuid::Uuid;
 
-use self::dataflow::loader::LoaderConfig;
+use self::dataflow::instance::runners::replay::ReplayOptions;
+use self::dataflow::instance::runners::RunnerKind;
+use self::dataflow::loader::{ExtensibleImplementation, IsolationMode, LoaderConfig};
+use self::recording::RecordingInfo;
+use crate::runtime::checkpoint::CheckpointConfig;
 use crate::runtime::dataflow::loader::Loader;
+use crate::runtime::deadline::DeadlineMissStats;
+use crate::runtime::liveliness::LivelinessConfig;
 use crate::runtime::message::ControlMessage;
-use crate::{NodeId, RuntimeId, ZFError, ZFResult};
+use crate::runtime::session::LazySession;
+use crate::runtime::watchdog::{NodeHealth, WatchdogConfig};
+use crate::{NodeId, PortId, RuntimeId, ZFError, ZFResult};
 use uhlc::HLC;
 use zenoh::config::Config as ZenohConfig;
-use zenoh::Session;
 use zrpc::zrpcresult::{ZRPCError, ZRPCResult};
 use zrpc_macros::znservice;
 
+pub mod checkpoint;
 pub mod dataflow;
 pub mod deadline;
+pub mod liveliness;
 pub mod loops;
 pub mod message;
+pub mod recording;
 pub mod resources;
+pub mod session;
 pub mod token;
+pub mod watchdog;
 
 /// The context of a Zenoh Flow runtime.
 /// This is shared across all the instances in a runtime.
 /// It allows sharing the `zenoh::Session`, the `Loader`,
 /// the `HLC` and other relevant singletons.
+///
+/// The session is opened lazily, see [`LazySession`](`LazySession`).
 #[derive(Clone)]
 pub struct RuntimeContext {
-    pub session: Arc<Session>,
+    pub session: LazySession,
     pub loader: Arc<Loader>,
     pub hlc: Arc<HLC>,
     pub runtime_name: RuntimeId,
     pub runtime_uuid: Uuid,
+    /// Where the recordings are also written as files, if `Some`.
+    pub recordings_dir: Option<PathBuf>,
 }
 
 /// The context of a Zenoh Flow graph instance.
@@ -141,6 +159,38 @@ pub struct RuntimeStatus {
     pub running_connectors: usize,
 }
 
+impl RuntimeStatus {
+    /// Updates the counters of running nodes with the nodes of an instance that were started, or
+    /// stopped, between `before` and `after`.
+    ///
+    /// Both are obtained with
+    /// [`get_started_nodes`](`dataflow::instance::DataflowInstance::get_started_nodes`). The
+    /// counters never go below zero.
+    pub fn update_running_nodes(
+        &mut self,
+        before: &[(NodeId, RunnerKind)],
+        after: &[(NodeId, RunnerKind)],
+    ) {
+        for (_, kind) in before.iter().filter(|node| !after.contains(node)) {
+            let counter = self.running_nodes_mut(kind);
+            *counter = counter.saturating_sub(1);
+        }
+
+        for (_, kind) in after.iter().filter(|node| !before.contains(node)) {
+            *self.running_nodes_mut(kind) += 1;
+        }
+    }
+
+    fn running_nodes_mut(&mut self, kind: &RunnerKind) -> &mut usize {
+        match kind {
+            RunnerKind::Source => &mut self.running_sources,
+            RunnerKind::Operator => &mut self.running_operators,
+            RunnerKind::Sink => &mut self.running_sinks,
+            RunnerKind::Connector => &mut self.running_connectors,
+        }
+    }
+}
+
 /// Wrapper for Zenoh kind.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -188,6 +238,42 @@ pub struct RuntimeConfig {
     pub uuid: Uuid,
     pub zenoh: ZenohConfig,
     pub loader: LoaderConfig,
+    #[serde(default)]
+    pub watchdog: Option<WatchdogConfig>,
+    #[serde(default)]
+    pub recordings_dir: Option<String>, //Where the recordings are also written as files
+    #[serde(default)]
+    pub checkpoint: Option<CheckpointConfig>,
+    #[serde(default)]
+    pub liveliness: Option<LivelinessConfig>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub reload: Option<ReloadConfig>,
+    #[serde(default)]
+    pub extensions: Option<String>, //Where the extension files are located
+    #[serde(default)]
+    pub isolation: Option<IsolationMode>, //Where the dynamically loaded nodes are executed
+}
+
+/// The configuration of the hot reload of a runtime.
+///
+/// Every `period`, the configuration file and the extensions directory are checked for changes.
+/// Changes to the configuration stored in Zenoh for the runtime are applied as they arrive.
+/// Only the extensions and the tags are applied while the runtime is running, the other changes
+/// require a restart.
+///
+/// Example:
+///
+/// ```yaml
+/// reload:
+///   period:
+///     length: 5
+///     unit: s
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReloadConfig {
+    pub period: DurationDescriptor,
 }
 
 /// The interface the Runtime expose to a client
@@ -264,13 +350,19 @@ pub trait Runtime {
     /// return immediately, but the teardown process will run asynchronously in the runtime.
     ///
     /// It is equivalent to calling `stop_instance` and then `delete_delete`.
+    /// If `drain_timeout` is provided, the messages in flight are processed
+    /// before the nodes are stopped, see `stop_instance`.
     ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     /// - unable to teardown
     /// - instance not found
-    async fn teardown(&self, record_id: Uuid) -> ZFResult<DataFlowRecord>;
+    async fn teardown(
+        &self,
+        record_id: Uuid,
+        drain_timeout: Option<Duration>,
+    ) -> ZFResult<DataFlowRecord>;
 
     /// Prepares the runtime host the instance identified by the [`Uuid`].
     /// Preparing a runtime means, fetch the operators/source/sinks libraries,
@@ -307,11 +399,21 @@ pub trait Runtime {
     ///
     /// It first stops the sources then the other nodes.
     ///
+    /// If `drain_timeout` is provided, the other nodes are given up to this
+    /// duration to process the messages in flight, they are then stopped
+    /// upstream first. Otherwise these messages are discarded. The nodes of
+    /// all the involved runtimes are only stopped once all of them are idle,
+    /// so that none discards the messages another one still sends it.
+    ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     /// - unable to clean
-    async fn stop_instance(&self, record_id: Uuid) -> ZFResult<DataFlowRecord>;
+    async fn stop_instance(
+        &self,
+        record_id: Uuid,
+        drain_timeout: Option<Duration>,
+    ) -> ZFResult<DataFlowRecord>;
 
     /// Starts the sinks, connectors, and operators for the given record.
     ///
@@ -337,12 +439,27 @@ pub trait Runtime {
     /// Note that this should be called after the `stop_sources(record)` has returned
     /// successfully otherwise data may be lost.
     ///
+    /// If `drain_timeout` is provided, the nodes are given up to this duration
+    /// to process the messages in flight, they are then stopped upstream first.
+    /// This only drains the nodes of this runtime: an instance spanning several
+    /// runtimes is drained on all of them at once by `stop_instance`.
+    ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     /// - record not found
     /// - record already stopped
-    async fn stop(&self, record_id: Uuid) -> ZFResult<()>;
+    async fn stop(&self, record_id: Uuid, drain_timeout: Option<Duration>) -> ZFResult<()>;
+
+    /// Checks if no message is waiting in the links of the local nodes of the
+    /// given record and if none of them is executing its `run` or holding
+    /// inputs its input rule did not accept yet.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    async fn is_idle(&self, record_id: Uuid) -> ZFResult<bool>;
 
     /// Stops the sources for the given record.
     ///
@@ -375,31 +492,219 @@ pub trait Runtime {
     /// - node not found
     async fn stop_node(&self, record_id: Uuid, node: String) -> ZFResult<()>;
 
-    /// Start a recording for the given source.
+    /// Pauses the instance on all involved nodes.
+    ///
+    /// The sources stop producing and the other nodes stop once done with
+    /// their current execution of `run`. Their state and the messages
+    /// waiting in their links are kept.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    async fn pause_instance(&self, record_id: Uuid) -> ZFResult<()>;
+
+    /// Resumes the instance on all involved nodes, where it was paused.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    async fn resume_instance(&self, record_id: Uuid) -> ZFResult<()>;
+
+    /// Pauses the sources, sinks and operators for the given record.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    async fn pause(&self, record_id: Uuid) -> ZFResult<()>;
+
+    /// Resumes the sources, sinks and operators for the given record.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    async fn resume(&self, record_id: Uuid) -> ZFResult<()>;
+
+    /// Pauses the given graph node from the given instance.
+    /// A graph node can be a source, a sink, or an operator.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    /// - node is a connector
+    async fn pause_node(&self, record_id: Uuid, node: String) -> ZFResult<()>;
+
+    /// Resumes the given graph node from the given instance.
+    /// A graph node can be a source, a sink, or an operator.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    /// - node is a connector
+    async fn resume_node(&self, record_id: Uuid, node: String) -> ZFResult<()>;
+
+    /// Takes a checkpoint of the nodes of the instance on all involved runtimes.
+    ///
+    /// Only the nodes whose state supports checkpointing are checkpointed,
+    /// their identifiers are returned.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - a node does not release its state in time
+    async fn checkpoint_instance(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>>;
+
+    /// Takes a checkpoint of the local nodes of the given record and stores
+    /// it in Zenoh.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - a node does not release its state in time
+    async fn checkpoint(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>>;
+
+    /// Takes a checkpoint of the given graph node from the given instance and
+    /// stores it in Zenoh.
+    ///
+    /// Returns `false` if the state of the node does not support
+    /// checkpointing.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    /// - the node does not release its state in time
+    async fn checkpoint_node(&self, record_id: Uuid, node: String) -> ZFResult<bool>;
+
+    /// Restores the nodes of the instance on all involved runtimes from the
+    /// latest checkpoints.
+    ///
+    /// The identifiers of the restored nodes are returned.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - a checkpoint cannot be restored
+    async fn restore_instance(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>>;
+
+    /// Restores the local nodes of the given record from the latest
+    /// checkpoints.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - a checkpoint cannot be restored
+    async fn restore(&self, record_id: Uuid) -> ZFResult<Vec<NodeId>>;
+
+    /// Restores the given graph node from the given instance from its latest
+    /// checkpoint.
+    ///
+    /// If the node was migrated, the messages it already received on its
+    /// previous runtime are skipped first, checkpointed or not.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    /// - no checkpoint found
+    /// - the checkpoint cannot be restored
+    async fn restore_node(&self, record_id: Uuid, node: String) -> ZFResult<()>;
+
+    /// Migrates the given node of the given instance to the runtime named
+    /// `runtime`, without stopping the instance.
+    ///
+    /// The node is loaded on the target runtime, the connectors are
+    /// re-created for the new mapping, its checkpointed state is restored
+    /// and the copy on the previous runtime is removed.
+    /// The updated record is returned.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    /// - runtime not found
+    /// - the node cannot be loaded on the target runtime
+    async fn migrate_node(
+        &self,
+        record_id: Uuid,
+        node: String,
+        runtime: String,
+    ) -> ZFResult<DataFlowRecord>;
+
+    /// Reconfigures the local part of the given instance to match `record`.
+    ///
+    /// The nodes and connectors that are no longer mapped to this runtime
+    /// are removed, after their state was checkpointed; the new ones are
+    /// loaded. If `start` is set the new connectors are started, the new
+    /// nodes never are. The new nodes and connectors are returned.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - a node cannot be loaded
+    /// - the instance cannot be relinked
+    async fn reconfigure(
+        &self,
+        record_id: Uuid,
+        record: DataFlowRecord,
+        start: bool,
+    ) -> ZFResult<Vec<NodeId>>;
+
+    /// Start a recording for the given output port of the given node.
+    /// If no port is provided, the node must have a single output (e.g. a
+    /// source).
     ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     /// - record not found
     /// - record already started
-    /// - source not found
-    /// - node is not a source
-    async fn start_record(&self, instance_id: Uuid, source_id: NodeId) -> ZFResult<String>;
+    /// - node not found
+    /// - port not found or not provided when the node has several outputs
+    async fn start_record(
+        &self,
+        instance_id: Uuid,
+        node_id: NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String>;
 
-    /// Stops the recording for the given source.
+    /// Stops the recording for the given output port of the given node.
+    /// If no port is provided, the node must have a single output (e.g. a
+    /// source).
     ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - error on zenoh-rpc
     /// - record not found
     /// - record already stopped
-    /// - source not found
-    /// - node is not a source
-    async fn stop_record(&self, instance_id: Uuid, source_id: NodeId) -> ZFResult<String>;
+    /// - node not found
+    /// - port not found or not provided when the node has several outputs
+    async fn stop_record(
+        &self,
+        instance_id: Uuid,
+        node_id: NodeId,
+        port_id: Option<PortId>,
+    ) -> ZFResult<String>;
 
     /// Starts the replay for the given source.
     /// The replay creates a new node that has the same port and links as the
     /// source is replaying.
+    /// The `options` control the speed, the time window and the looping of
+    /// the replay, and whether the recording is streamed.
     ///
     /// # Errors
     /// An error variant is returned in case of:
@@ -408,11 +713,13 @@ pub trait Runtime {
     /// - replay already started
     /// - source not found
     /// - node is not a source
+    /// - invalid replay options
     async fn start_replay(
         &self,
         instance_id: Uuid,
         source_id: NodeId,
         key_expr: String,
+        options: ReplayOptions,
     ) -> ZFResult<NodeId>;
 
     /// Stops the replay for the given source.
@@ -432,11 +739,61 @@ pub trait Runtime {
         replay_id: NodeId,
     ) -> ZFResult<NodeId>;
 
+    /// Lists the recordings of the given instance or, if `None`, all the
+    /// recordings.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - unable to read the catalog of the recordings
+    async fn list_recordings(&self, instance_id: Option<Uuid>) -> ZFResult<Vec<RecordingInfo>>;
+
+    /// Gets the description of the given recording.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - recording not found
+    async fn get_recording(&self, recording_id: Uuid) -> ZFResult<RecordingInfo>;
+
+    /// Deletes the given recording: its data stored on Zenoh, its file if
+    /// any, and its entry in the catalog. Returns the deleted recording.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - recording not found
+    /// - recording in progress
+    /// - unable to delete the file
+    async fn delete_recording(&self, recording_id: Uuid) -> ZFResult<RecordingInfo>;
+
     /// Gets the state of the given graph node for the given instance.
     /// A graph node can be a source, a sink, a connector, or an operator.
-    /// The node state represents the current state of the node:
-    /// `enum NodeState { Running, Stopped, Error(err) }`
-    // async fn get_node_state(&self, record_id: Uuid, node: String) -> ZFResult<NodeState>;
+    /// The node state represents the current state of the node, see
+    /// [`NodeHealth`](`NodeHealth`). A node is reported as hung only if the
+    /// watchdog of the runtime is configured.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    async fn get_node_state(&self, record_id: Uuid, node: String) -> ZFResult<NodeHealth>;
+
+    /// Gets the counters of the deadline misses of the given node for the given instance, see
+    /// [`DeadlineMissStats`](`DeadlineMissStats`). The request is forwarded to the runtime
+    /// executing the node.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - record not found
+    /// - node not found
+    async fn get_deadline_miss_stats(
+        &self,
+        record_id: Uuid,
+        node: String,
+    ) -> ZFResult<DeadlineMissStats>;
 
     /// Sends the `message` to `node` for the given record.
     /// This is useful for sending out-of-band notification to a node.
@@ -453,6 +810,45 @@ pub trait Runtime {
         message: ControlMessage,
     ) -> ZFResult<()>;
 
+    /// Lists the extensions of the runtime.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    async fn list_extensions(&self) -> ZFResult<Vec<ExtensibleImplementation>>;
+
+    /// Gets the extension of the runtime with the given `name`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - extension not found
+    async fn get_extension(&self, name: String) -> ZFResult<ExtensibleImplementation>;
+
+    /// Installs the given extension on the runtime, after validating it.
+    /// The extension is also written in the extensions directory of the
+    /// runtime, if any, so that it is kept after a restart.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - invalid extension, see [`ExtensibleImplementation::validate`](`ExtensibleImplementation::validate`)
+    /// - an extension with the same name is already installed
+    /// - the file extension is already handled by another installed extension
+    /// - unable to write the extension file
+    async fn install_extension(&self, extension: ExtensibleImplementation) -> ZFResult<()>;
+
+    /// Removes the extension with the given `name` from the runtime, and its
+    /// file from the extensions directory.
+    /// The nodes already loaded with the extension keep running.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - error on zenoh-rpc
+    /// - extension not found
+    /// - unable to delete the extension file
+    async fn remove_extension(&self, name: String) -> ZFResult<ExtensibleImplementation>;
+
     /// Checks the compatibility for the given `operator`
     /// Compatibility is based on tags and some machine characteristics (eg. CPU architecture, OS)
     ///
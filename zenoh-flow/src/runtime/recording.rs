@@ -0,0 +1,698 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The file format of the recordings.
+//!
+//! A recording file is self-describing, it can be written directly by a
+//! runtime and replayed without any Zenoh storage. Its layout is:
+//!
+//! ```text
+//! +-----------+---------+------------+--------------------+
+//! | "ZFRECORD" | version | header len | header (bincode)   |
+//! +-----------+---------+------------+--------------------+
+//! | frame len | frame: a `Message` (bincode)              | * N
+//! +-----------+-------------------------------------------+
+//! | index (bincode) | index offset | "ZFRINDEX"           | optional
+//! +-----------------+--------------+----------------------+
+//! ```
+//!
+//! The version is a little endian `u32`, the lengths and offsets are little
+//! endian `u64`. The index, that maps each frame to its offset and
+//! timestamp, is written when the recording is finished: a recording that
+//! was interrupted can still be read, its index is then rebuilt by scanning
+//! the frames.
+//!
+//! The header holds the [`RecordingMetadata`](`RecordingMetadata`), which
+//! starts with its own version: a recording whose metadata has another
+//! layout is rejected.
+//!
+//! The readers and the writers do blocking I/O: the asynchronous functions of
+//! this module and the runners call them on blocking threads.
+
+use crate::runtime::message::Message;
+use crate::{
+    ControlMessage, FlowId, NodeId, PortId, PortType, RecordingMetadata, ZFError, ZFResult,
+};
+use async_std::task;
+use futures::prelude::*;
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use uhlc::Timestamp;
+use uuid::Uuid;
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh::publication::CongestionControl;
+use zenoh::query::*;
+use zenoh::*;
+
+/// The extension of the recording files.
+pub const RECORDING_FILE_EXTENSION: &str = "zfrec";
+
+/// The version of the recording file format.
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// The prefix of a key expression designating a recording file instead of a
+/// Zenoh storage, e.g. `file:///var/zenoh-flow/recordings/cam.zfrec`.
+pub const RECORDING_FILE_SCHEME: &str = "file://";
+
+const MAGIC: &[u8; 8] = b"ZFRECORD";
+const INDEX_MAGIC: &[u8; 8] = b"ZFRINDEX";
+// The index offset followed by the index magic.
+const FOOTER_LEN: u64 = 16;
+// The number of messages read ahead while importing a recording file.
+const IMPORT_READ_AHEAD: usize = 64;
+
+/// The header of a recording file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub metadata: RecordingMetadata,
+}
+
+/// The description of a recording, as kept in the catalog of the
+/// [`DataStore`](`crate::runtime::resources::DataStore`).
+///
+/// The recording is in progress as long as `stop` is `None`, the number of
+/// messages and the size, in bytes, are updated when it stops.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordingInfo {
+    pub id: Uuid,
+    pub key_expr: String,
+    pub flow_id: FlowId,
+    pub instance_id: Uuid,
+    pub node_id: NodeId,
+    pub port_id: PortId,
+    pub port_type: PortType,
+    pub runtime_id: Uuid,
+    pub start: Timestamp,
+    pub stop: Option<Timestamp>,
+    pub messages: u64,
+    pub size: u64,
+    pub file: Option<String>,
+}
+
+/// The position and the timestamp of a frame in a recording file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameIndexEntry {
+    pub offset: u64,
+    pub timestamp: Timestamp,
+}
+
+/// Writes a recording file.
+///
+/// The index is only written by [`finish`](`RecordingWriter::finish`).
+pub struct RecordingWriter {
+    writer: BufWriter<File>,
+    position: u64,
+    index: Vec<FrameIndexEntry>,
+}
+
+impl RecordingWriter {
+    /// Creates the recording file at `path` and writes its header.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - the file cannot be created
+    /// - serialization fails
+    pub fn create(path: &Path, header: &RecordingHeader) -> ZFResult<Self> {
+        let serialized_header =
+            bincode::serialize(header).map_err(|_| ZFError::SerializationError)?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&RECORDING_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&(serialized_header.len() as u64).to_le_bytes())?;
+        writer.write_all(&serialized_header)?;
+
+        Ok(Self {
+            writer,
+            position: (MAGIC.len() + 4 + 8 + serialized_header.len()) as u64,
+            index: vec![],
+        })
+    }
+
+    /// Appends the `message` to the recording.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - serialization fails
+    /// - the file cannot be written
+    pub fn write(&mut self, message: &Message) -> ZFResult<()> {
+        let serialized = message.serialize_bincode()?;
+        self.write_serialized(&serialized, message.get_timestamp())
+    }
+
+    /// Appends a message, already serialized with
+    /// [`serialize_bincode`](`Message::serialize_bincode`), to the recording.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be written.
+    pub fn write_serialized(&mut self, serialized: &[u8], timestamp: Timestamp) -> ZFResult<()> {
+        self.writer
+            .write_all(&(serialized.len() as u64).to_le_bytes())?;
+        self.writer.write_all(serialized)?;
+
+        self.index.push(FrameIndexEntry {
+            offset: self.position,
+            timestamp,
+        });
+        self.position += 8 + serialized.len() as u64;
+
+        Ok(())
+    }
+
+    /// Writes the index and flushes the recording.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - serialization fails
+    /// - the file cannot be written
+    pub fn finish(mut self) -> ZFResult<()> {
+        let serialized_index =
+            bincode::serialize(&self.index).map_err(|_| ZFError::SerializationError)?;
+
+        self.writer.write_all(&serialized_index)?;
+        self.writer.write_all(&self.position.to_le_bytes())?;
+        self.writer.write_all(INDEX_MAGIC)?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reads a recording file.
+pub struct RecordingReader {
+    reader: BufReader<File>,
+    header: RecordingHeader,
+    // Where the first frame starts.
+    frames_start: u64,
+    // Where the last frame ends.
+    frames_end: u64,
+    index: Option<Vec<FrameIndexEntry>>,
+}
+
+impl RecordingReader {
+    /// Opens the recording file at `path` and reads its header and, if
+    /// present, its index.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - the file cannot be read
+    /// - the file is not a recording
+    /// - the version of the format or of the metadata is not supported
+    /// - deserialization fails
+    pub fn open(path: &Path) -> ZFResult<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic).map_err(truncated)?;
+        if &magic != MAGIC {
+            return Err(ZFError::InvalidData(format!(
+                "{} is not a Zenoh Flow recording",
+                path.display()
+            )));
+        }
+
+        if read_u32(&mut reader)? != RECORDING_FORMAT_VERSION {
+            return Err(ZFError::VersionMismatch);
+        }
+
+        let header_len = read_u64(&mut reader)?;
+        let file_len = reader.get_ref().metadata()?.len();
+        let frames_start = (MAGIC.len() as u64 + 4 + 8)
+            .checked_add(header_len)
+            .filter(|frames_start| *frames_start <= file_len)
+            .ok_or_else(|| ZFError::InvalidData("Truncated recording".to_string()))?;
+
+        let header: RecordingHeader = bincode::deserialize_from((&mut reader).take(header_len))
+            .map_err(|_| ZFError::DeseralizationError)?;
+        header.metadata.check_version()?;
+
+        let mut recording = Self {
+            reader,
+            header,
+            frames_start,
+            frames_end: file_len,
+            index: None,
+        };
+        recording.read_index(file_len)?;
+
+        Ok(recording)
+    }
+
+    /// Reads the index at the end of the file, if there is one.
+    fn read_index(&mut self, file_len: u64) -> ZFResult<()> {
+        if file_len - self.frames_start < FOOTER_LEN {
+            return Ok(());
+        }
+
+        self.reader.seek(SeekFrom::Start(file_len - FOOTER_LEN))?;
+        let index_offset = read_u64(&mut self.reader)?;
+        let mut magic = [0u8; 8];
+        self.reader.read_exact(&mut magic)?;
+
+        if &magic != INDEX_MAGIC
+            || index_offset < self.frames_start
+            || index_offset > file_len - FOOTER_LEN
+        {
+            return Ok(());
+        }
+
+        self.reader.seek(SeekFrom::Start(index_offset))?;
+        let index_len = file_len - FOOTER_LEN - index_offset;
+        if let Ok(index) = bincode::deserialize_from((&mut self.reader).take(index_len)) {
+            self.index = Some(index);
+            self.frames_end = index_offset;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the header of the recording.
+    pub fn header(&self) -> &RecordingHeader {
+        &self.header
+    }
+
+    /// Checks if the recording was finished, i.e. if its index was written.
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Returns the index of the recording, rebuilding it from the frames if
+    /// the recording was not finished.
+    ///
+    /// # Errors
+    /// An error variant is returned if the file cannot be read.
+    pub fn index(&mut self) -> ZFResult<Vec<FrameIndexEntry>> {
+        if let Some(index) = &self.index {
+            return Ok(index.clone());
+        }
+
+        let mut index = vec![];
+        let mut offset = self.frames_start;
+        while let Some((message, len)) = self.read_frame(offset)? {
+            index.push(FrameIndexEntry {
+                offset,
+                timestamp: message.get_timestamp(),
+            });
+            offset += 8 + len;
+        }
+
+        Ok(index)
+    }
+
+    /// Reads the frame starting at `offset`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - there is no frame at `offset`
+    /// - the file cannot be read
+    /// - deserialization fails
+    pub fn read_at(&mut self, offset: u64) -> ZFResult<Message> {
+        self.read_frame(offset)?
+            .map(|(message, _)| message)
+            .ok_or(ZFError::NotFound)
+    }
+
+    /// Returns an iterator over the messages of the recording, in the order
+    /// they were written.
+    ///
+    /// The messages are read one at a time, a frame that cannot be read ends
+    /// the iteration with an error.
+    pub fn into_messages(self) -> RecordingMessages {
+        RecordingMessages {
+            offset: self.frames_start,
+            recording: self,
+            done: false,
+        }
+    }
+
+    /// Reads the frame starting at `offset`, returns the message and the
+    /// length of the frame, without its length prefix.
+    ///
+    /// Returns `None` if there is no complete frame at `offset`, i.e. the
+    /// end of the recording or a frame that was not fully written.
+    fn read_frame(&mut self, offset: u64) -> ZFResult<Option<(Message, u64)>> {
+        // The offset can come from the caller, and the length from the file: neither can overflow.
+        match offset.checked_add(8) {
+            Some(start) if start <= self.frames_end => (),
+            _ => return Ok(None),
+        }
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let len = read_u64(&mut self.reader)?;
+        match offset
+            .checked_add(8)
+            .and_then(|start| start.checked_add(len))
+        {
+            Some(end) if end <= self.frames_end => (),
+            _ => {
+                log::warn!("Truncated frame at offset {} in the recording", offset);
+                return Ok(None);
+            }
+        }
+
+        // The length is bounded by the size of the file.
+        let mut frame = vec![0u8; len as usize];
+        self.reader.read_exact(&mut frame).map_err(truncated)?;
+        let message = Message::deserialize_bincode(&frame)?;
+
+        Ok(Some((message, len)))
+    }
+}
+
+/// An iterator over the messages of a recording file.
+pub struct RecordingMessages {
+    recording: RecordingReader,
+    offset: u64,
+    done: bool,
+}
+
+impl Iterator for RecordingMessages {
+    type Item = ZFResult<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.recording.read_frame(self.offset) {
+            Ok(Some((message, len))) => {
+                self.offset += 8 + len;
+                Some(Ok(message))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads the messages of the recording file at `path` on a blocking thread,
+/// returns the header of the recording and the stream of its messages.
+///
+/// At most `capacity` messages are read ahead. The stream ends with an error
+/// if a frame cannot be read.
+///
+/// # Errors
+/// An error variant is returned if the recording cannot be opened, see
+/// [`RecordingReader::open`](`RecordingReader::open`).
+pub async fn read_recording(
+    path: PathBuf,
+    capacity: usize,
+) -> ZFResult<(RecordingHeader, BoxStream<'static, ZFResult<Message>>)> {
+    let recording = task::spawn_blocking(move || RecordingReader::open(&path)).await?;
+    let header = recording.header().clone();
+
+    let (sender, receiver) = flume::bounded(capacity);
+    task::spawn_blocking(move || {
+        for message in recording.into_messages() {
+            // The stream was dropped, e.g. the replay was stopped.
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok((header, receiver.into_stream().boxed()))
+}
+
+/// Exports the recording stored on Zenoh under `key_expr` to a recording
+/// file at `path`, returns the header of the recording.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - zenoh query fails
+/// - a message cannot be deserialized, e.g. a recording made with another
+///   version of the metadata
+/// - there is no recording under `key_expr`
+/// - the file cannot be written
+pub async fn export_recording(
+    session: &Session,
+    key_expr: &str,
+    path: &Path,
+) -> ZFResult<RecordingHeader> {
+    let query_target = QueryTarget {
+        kind: queryable::STORAGE,
+        target: Target::default(),
+    };
+    let replies = session
+        .get(&format!("{}?(starttime=0)", key_expr))
+        .target(query_target)
+        .consolidation(QueryConsolidation::none())
+        .await?;
+
+    let mut messages: Vec<Message> = replies
+        .map(|reply| Message::deserialize_bincode(&reply.sample.value.payload.contiguous()))
+        .try_collect()
+        .await?;
+    messages.sort();
+
+    let metadata = messages
+        .iter()
+        .find_map(|message| match message {
+            Message::Control(ControlMessage::RecordingStart(metadata)) => Some(metadata.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            ZFError::InvalidData(format!("No recording found under < {} >", key_expr))
+        })?;
+
+    let header = RecordingHeader { metadata };
+    let path = path.to_path_buf();
+    let file_header = header.clone();
+    task::spawn_blocking(move || {
+        let mut writer = RecordingWriter::create(&path, &file_header)?;
+        for message in messages.iter() {
+            writer.write(message)?;
+        }
+        writer.finish()
+    })
+    .await?;
+
+    Ok(header)
+}
+
+/// Imports the recording file at `path` on Zenoh, under `key_expr` or, if
+/// `None`, under the key expression of the original recording. Returns the
+/// key expression where the recording is stored.
+///
+/// A Zenoh storage must be configured on the key expression for the
+/// recording to be replayed.
+///
+/// # Errors
+/// An error variant is returned in case of:
+/// - the file cannot be read
+/// - unable to put on zenoh
+pub async fn import_recording(
+    session: &Session,
+    path: &Path,
+    key_expr: Option<String>,
+) -> ZFResult<String> {
+    let (header, mut messages) = read_recording(path.to_path_buf(), IMPORT_READ_AHEAD).await?;
+    let key_expr = key_expr.unwrap_or_else(|| header.metadata.get_resource_name());
+
+    while let Some(message) = messages.next().await {
+        session
+            .put(&key_expr, message?.serialize_bincode()?)
+            .congestion_control(CongestionControl::Block)
+            .await?;
+    }
+
+    Ok(key_expr)
+}
+
+fn read_u32(reader: &mut impl Read) -> ZFResult<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> ZFResult<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn truncated(err: std::io::Error) -> ZFError {
+    match err.kind() {
+        ErrorKind::UnexpectedEof => ZFError::InvalidData("Truncated recording".to_string()),
+        _ => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::message::DataMessage;
+    use crate::{NodeId, RECORDING_METADATA_VERSION};
+    use async_std::sync::Arc;
+    use std::path::PathBuf;
+    use uhlc::HLC;
+    use uuid::Uuid;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "zf-recording-{}.{}",
+            Uuid::new_v4(),
+            RECORDING_FILE_EXTENSION
+        ))
+    }
+
+    fn write_recording(path: &Path, finish: bool) -> Vec<Message> {
+        let hlc = HLC::default();
+        let metadata = RecordingMetadata {
+            version: RECORDING_METADATA_VERSION,
+            timestamp: hlc.new_timestamp(),
+            port_id: "out".into(),
+            port_type: "int".into(),
+            node_id: "source".into(),
+            flow_id: "flow".into(),
+            instance_id: Uuid::new_v4(),
+        };
+
+        let mut messages = vec![Message::Control(ControlMessage::RecordingStart(
+            metadata.clone(),
+        ))];
+        for i in 0u8..10 {
+            messages.push(Message::Data(DataMessage::new_serialized(
+                Arc::new(vec![i]),
+                hlc.new_timestamp(),
+                vec![],
+                vec![],
+            )));
+        }
+
+        let mut writer = RecordingWriter::create(path, &RecordingHeader { metadata }).unwrap();
+        for message in messages.iter() {
+            writer.write(message).unwrap();
+        }
+        if finish {
+            writer.finish().unwrap();
+        }
+
+        messages
+    }
+
+    #[test]
+    fn recording_file_round_trip() {
+        let path = temp_path();
+        let messages = write_recording(&path, true);
+
+        let mut recording = RecordingReader::open(&path).unwrap();
+        assert!(recording.is_indexed());
+        assert_eq!(
+            recording.header().metadata.get_node_id(),
+            &NodeId::from("source")
+        );
+
+        let index = recording.index().unwrap();
+        assert_eq!(index.len(), messages.len());
+        assert_eq!(recording.read_at(index[3].offset).unwrap(), messages[3]);
+
+        let read: Vec<Message> = recording.into_messages().collect::<ZFResult<_>>().unwrap();
+        assert_eq!(read, messages);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recording_file_not_finished() {
+        let path = temp_path();
+        let messages = write_recording(&path, false);
+
+        let mut recording = RecordingReader::open(&path).unwrap();
+        assert!(!recording.is_indexed());
+        assert_eq!(recording.index().unwrap().len(), messages.len());
+        assert_eq!(recording.into_messages().count(), messages.len());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recording_file_invalid() {
+        let path = temp_path();
+        std::fs::write(&path, b"not a recording").unwrap();
+
+        assert!(RecordingReader::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recording_file_invalid_lengths() {
+        let path = temp_path();
+        let messages = write_recording(&path, false);
+
+        // A frame whose length overflows ends the recording.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 8]);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut recording = RecordingReader::open(&path).unwrap();
+        assert_eq!(recording.read_at(u64::MAX).err(), Some(ZFError::NotFound));
+        assert_eq!(recording.into_messages().count(), messages.len());
+
+        // A header whose length overflows is rejected.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(RecordingReader::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recording_metadata_version() {
+        let hlc = HLC::default();
+        let metadata = RecordingMetadata {
+            version: RECORDING_METADATA_VERSION + 1,
+            timestamp: hlc.new_timestamp(),
+            port_id: "out".into(),
+            port_type: "int".into(),
+            node_id: "source".into(),
+            flow_id: "flow".into(),
+            instance_id: Uuid::new_v4(),
+        };
+
+        let message = Message::Control(ControlMessage::RecordingStart(metadata.clone()));
+        assert_eq!(
+            Message::deserialize_bincode(&message.serialize_bincode().unwrap()).err(),
+            Some(ZFError::VersionMismatch)
+        );
+        assert_eq!(
+            Message::deserialize_bincode(b"not a message").err(),
+            Some(ZFError::DeseralizationError)
+        );
+
+        let path = temp_path();
+        RecordingWriter::create(&path, &RecordingHeader { metadata })
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            RecordingReader::open(&path).err(),
+            Some(ZFError::VersionMismatch)
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -25,6 +25,9 @@ extern crate serde_json;
 
 use crate::model::dataflow::record::DataFlowRecord;
 use crate::model::RegistryNode;
+use crate::runtime::checkpoint::{InputWatermarks, NodeCheckpoint};
+use crate::runtime::liveliness::RuntimeHeartbeat;
+use crate::runtime::recording::RecordingInfo;
 use crate::runtime::{RuntimeConfig, RuntimeInfo, RuntimeStatus};
 use crate::serde::{de::DeserializeOwned, Serialize};
 use crate::{async_std::sync::Arc, ZFError, ZFResult};
@@ -57,6 +60,12 @@ pub static KEY_REGISTRY: &str = "registry";
 pub static KEY_FLOWS: &str = "flows";
 /// Token for the graphs in the key expression.
 pub static KEY_GRAPHS: &str = "graphs";
+/// Token for the recordings in the key expression.
+pub static KEY_RECORDINGS: &str = "recordings";
+/// Key for the checkpoints.
+pub static KEY_CHECKPOINTS: &str = "checkpoints";
+/// Key for the input watermarks of the migrated nodes.
+pub static KEY_WATERMARKS: &str = "watermarks";
 
 /// Token for the leaf with information in the key expression.
 pub static KEY_INFO: &str = "info";
@@ -64,6 +73,8 @@ pub static KEY_INFO: &str = "info";
 pub static KEY_STATUS: &str = "status";
 /// Token for the leaf with configuration in the key expression.
 pub static KEY_CONFIGURATION: &str = "configuration";
+/// Token for the leaf with the heartbeat in the key expression.
+pub static KEY_HEARTBEAT: &str = "heartbeat";
 
 /// Generates the runtime info key expression.
 #[macro_export]
@@ -106,6 +117,20 @@ macro_rules! RT_CONFIGURATION_PATH {
     };
 }
 
+/// Generates the runtime heartbeat key expression.
+#[macro_export]
+macro_rules! RT_HEARTBEAT_PATH {
+    ($prefix:expr, $rtid:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_RUNTIMES,
+            $rtid,
+            $crate::runtime::resources::KEY_HEARTBEAT
+        )
+    };
+}
+
 /// Generates the flow instance key expression.
 #[macro_export]
 macro_rules! RT_FLOW_PATH {
@@ -207,6 +232,48 @@ macro_rules! REG_GRAPH_SELECTOR {
     };
 }
 
+/// Generates the recording key expression.
+#[macro_export]
+macro_rules! RECORDING_PATH {
+    ($prefix:expr, $iid:expr, $rid:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_RECORDINGS,
+            $iid,
+            $rid
+        )
+    };
+}
+
+/// Generates the checkpoint key expression.
+#[macro_export]
+macro_rules! CHECKPOINT_PATH {
+    ($prefix:expr, $iid:expr, $nid:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_CHECKPOINTS,
+            $iid,
+            $nid
+        )
+    };
+}
+
+/// Generates the input watermarks key expression.
+#[macro_export]
+macro_rules! WATERMARKS_PATH {
+    ($prefix:expr, $iid:expr, $nid:expr) => {
+        format!(
+            "{}/{}/{}/{}",
+            $prefix,
+            $crate::runtime::resources::KEY_WATERMARKS,
+            $iid,
+            $nid
+        )
+    };
+}
+
 /// Deserialize data from Zenoh storage.
 /// The format used depends on the features.
 /// It can be JSON (default), bincode or CBOR.
@@ -273,7 +340,7 @@ pin_project! {
     /// Custom stream to lister for Runtime Configuration changes.
     pub struct ZFRuntimeConfigStream {
         #[pin]
-        sample_stream: zenoh::subscriber::SampleReceiver,
+        sample_stream: flume::r#async::RecvStream<'static, Sample>,
     }
 }
 
@@ -288,18 +355,24 @@ impl Stream for ZFRuntimeConfigStream {
 
     #[inline(always)]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match async_std::pin::Pin::new(self)
-            .sample_stream
-            .poll_next_unpin(cx)
-        {
-            Poll::Ready(Some(sample)) => match sample.kind {
+        let mut this = self.project();
+        // The samples that cannot be deserialized are dropped, the stream then waits for the
+        // next one.
+        loop {
+            let sample = match this.sample_stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(sample)) => sample,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            match sample.kind {
                 SampleKind::Put | SampleKind::Patch => match sample.value.encoding {
                     Encoding::APP_OCTET_STREAM => {
                         match deserialize_data::<crate::runtime::RuntimeConfig>(
                             &sample.value.payload.contiguous(),
                         ) {
-                            Ok(info) => Poll::Ready(Some(info)),
-                            Err(_) => Poll::Pending,
+                            Ok(info) => return Poll::Ready(Some(info)),
+                            Err(e) => log::warn!("Unable to deserialize sample: {:?}", e),
                         }
                     }
                     _ => {
@@ -307,16 +380,12 @@ impl Stream for ZFRuntimeConfigStream {
                             "Received sample with wrong encoding {:?}, dropping",
                             sample.value.encoding
                         );
-                        Poll::Pending
                     }
                 },
                 SampleKind::Delete => {
                     log::warn!("Received delete sample drop it");
-                    Poll::Pending
                 }
-            },
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
+            }
         }
     }
 }
@@ -413,21 +482,46 @@ impl DataStore {
         self.get_from_zenoh::<RuntimeConfig>(&selector).await
     }
 
-    /// Subscribes to configuration changes for the given `rtid`
-    /// **NOTE:** not implemented.
+    /// Subscribes to configuration changes for the given `rtid`.
+    ///
+    /// The subscription lasts until the next change received after the stream is dropped.
     ///
     /// # Errors
     /// An error variant is returned in case of:
     /// - zenoh subscribe fails
-    /// - fails to deserialize
     pub async fn subscribe_runtime_config(&self, rtid: &Uuid) -> ZFResult<ZFRuntimeConfigStream> {
-        // let selector = RT_CONFIGURATION_PATH!(ROOT_STANDALONE, rtid))?;
-        //
-        // Ok(self.z
-        //     .subscribe(&selector)
-        //     .await
-        //     .map(|change_stream| ZFRuntimeConfigStream { change_stream })?)
-        Err(ZFError::Unimplemented)
+        let selector = RT_CONFIGURATION_PATH!(ROOT_STANDALONE, rtid);
+        let (sender, receiver) = flume::unbounded();
+        let (ready_tx, ready_rx) = flume::bounded::<ZFResult<()>>(1);
+
+        let z = self.z.clone();
+        async_std::task::spawn(async move {
+            let mut subscriber = match z.subscribe(&selector).await {
+                Ok(subscriber) => {
+                    let _ = ready_tx.send_async(Ok(())).await;
+                    subscriber
+                }
+                Err(e) => {
+                    let _ = ready_tx.send_async(Err(e.into())).await;
+                    return;
+                }
+            };
+
+            while let Some(sample) = subscriber.receiver().next().await {
+                if sender.send_async(sample).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        ready_rx
+            .recv_async()
+            .await
+            .map_err(|e| ZFError::RecvError(format!("{}", e)))??;
+
+        Ok(ZFRuntimeConfigStream {
+            sample_stream: receiver.into_stream(),
+        })
     }
 
     /// Removes the configuration for the given `rtid`.
@@ -490,6 +584,53 @@ impl DataStore {
         Ok(self.z.put(&path, encoded_info).await?)
     }
 
+    /// Gets the [`RuntimeHeartbeat`](`RuntimeHeartbeat`) of all the runtimes
+    /// in the infrastructure.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    pub async fn get_all_runtime_heartbeats(&self) -> ZFResult<Vec<RuntimeHeartbeat>> {
+        let selector = RT_HEARTBEAT_PATH!(ROOT_STANDALONE, "*");
+
+        self.get_vec_from_zenoh::<RuntimeHeartbeat>(&selector).await
+    }
+
+    /// Removes the heartbeat of the given runtime `rtid`.
+    ///
+    /// # Errors
+    /// If zenoh delete fails an error variant is returned.
+    pub async fn remove_runtime_heartbeat(&self, rtid: &Uuid) -> ZFResult<()> {
+        let path = RT_HEARTBEAT_PATH!(ROOT_STANDALONE, rtid);
+
+        Ok(self.z.delete(&path).await?)
+    }
+
+    /// Stores the given [`RuntimeHeartbeat`](`RuntimeHeartbeat`) in Zenoh.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to serialize
+    /// - zenoh put fails
+    pub async fn add_runtime_heartbeat(&self, heartbeat: &RuntimeHeartbeat) -> ZFResult<()> {
+        let path = RT_HEARTBEAT_PATH!(ROOT_STANDALONE, heartbeat.id);
+
+        let encoded_info = serialize_data(heartbeat)?;
+        Ok(self.z.put(&path, encoded_info).await?)
+    }
+
+    /// Gets all the [`DataFlowRecord`](`DataFlowRecord`) running on the
+    /// given runtime `rtid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    pub async fn get_runtime_flows(&self, rtid: &Uuid) -> ZFResult<Vec<DataFlowRecord>> {
+        let selector = RT_FLOW_SELECTOR_ALL!(ROOT_STANDALONE, rtid);
+
+        self.get_vec_from_zenoh::<DataFlowRecord>(&selector).await
+    }
+
     /// Gets the [`DataFlowRecord`](`DataFlowRecord`) running on the given
     /// runtime `rtid` for the given instance `iid`.
     ///
@@ -625,6 +766,164 @@ impl DataStore {
         Ok(self.z.put(&path, encoded_info).await?)
     }
 
+    /// Stores the given [`RecordingInfo`](`RecordingInfo`) in the catalog of
+    /// the recordings.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to serialize
+    /// - zenoh put fails
+    pub async fn add_recording(&self, recording: &RecordingInfo) -> ZFResult<()> {
+        let path = RECORDING_PATH!(ROOT_STANDALONE, recording.instance_id, recording.id);
+
+        let encoded_info = serialize_data(recording)?;
+        Ok(self.z.put(&path, encoded_info).await?)
+    }
+
+    /// Gets the [`RecordingInfo`](`RecordingInfo`) of the recording `rid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - no data present in zenoh
+    /// - fails to deserialize
+    pub async fn get_recording(&self, rid: &Uuid) -> ZFResult<RecordingInfo> {
+        let selector = RECORDING_PATH!(ROOT_STANDALONE, "*", rid);
+        self.get_from_zenoh::<RecordingInfo>(&selector).await
+    }
+
+    /// Gets the [`RecordingInfo`](`RecordingInfo`) of all the recordings of
+    /// the given instance `iid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    pub async fn get_instance_recordings(&self, iid: &Uuid) -> ZFResult<Vec<RecordingInfo>> {
+        let selector = RECORDING_PATH!(ROOT_STANDALONE, iid, "*");
+        self.get_vec_from_zenoh::<RecordingInfo>(&selector).await
+    }
+
+    /// Gets the [`RecordingInfo`](`RecordingInfo`) of all the recordings.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    pub async fn get_all_recordings(&self) -> ZFResult<Vec<RecordingInfo>> {
+        let selector = RECORDING_PATH!(ROOT_STANDALONE, "*", "*");
+        self.get_vec_from_zenoh::<RecordingInfo>(&selector).await
+    }
+
+    /// Removes the recording `rid`, of the instance `iid`, from the catalog.
+    ///
+    /// # Errors
+    /// If zenoh delete fails an error variant is returned.
+    pub async fn remove_recording(&self, iid: &Uuid, rid: &Uuid) -> ZFResult<()> {
+        let path = RECORDING_PATH!(ROOT_STANDALONE, iid, rid);
+
+        Ok(self.z.delete(&path).await?)
+    }
+
+    /// Stores the given [`NodeCheckpoint`](`NodeCheckpoint`), replacing the
+    /// previous checkpoint of the node in its instance.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to serialize
+    /// - zenoh put fails
+    pub async fn add_node_checkpoint(&self, checkpoint: &NodeCheckpoint) -> ZFResult<()> {
+        let path = CHECKPOINT_PATH!(ROOT_STANDALONE, checkpoint.instance_id, checkpoint.node_id);
+
+        let encoded_info = serialize_data(checkpoint)?;
+        Ok(self.z.put(&path, encoded_info).await?)
+    }
+
+    /// Gets the latest [`NodeCheckpoint`](`NodeCheckpoint`) of the node
+    /// `node_id` of the instance `iid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - no data present in zenoh
+    /// - fails to deserialize
+    pub async fn get_node_checkpoint(&self, iid: &Uuid, node_id: &str) -> ZFResult<NodeCheckpoint> {
+        let selector = CHECKPOINT_PATH!(ROOT_STANDALONE, iid, node_id);
+        self.get_from_zenoh::<NodeCheckpoint>(&selector).await
+    }
+
+    /// Gets the latest [`NodeCheckpoint`](`NodeCheckpoint`) of all the nodes
+    /// of the instance `iid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    pub async fn get_instance_checkpoints(&self, iid: &Uuid) -> ZFResult<Vec<NodeCheckpoint>> {
+        let selector = CHECKPOINT_PATH!(ROOT_STANDALONE, iid, "*");
+        self.get_vec_from_zenoh::<NodeCheckpoint>(&selector).await
+    }
+
+    /// Removes the checkpoint of the node `node_id` of the instance `iid`.
+    ///
+    /// # Errors
+    /// If zenoh delete fails an error variant is returned.
+    pub async fn remove_node_checkpoint(&self, iid: &Uuid, node_id: &str) -> ZFResult<()> {
+        let path = CHECKPOINT_PATH!(ROOT_STANDALONE, iid, node_id);
+
+        Ok(self.z.delete(&path).await?)
+    }
+
+    /// Removes the checkpoints of all the nodes of the instance `iid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to deserialize
+    /// - zenoh delete fails
+    pub async fn remove_instance_checkpoints(&self, iid: &Uuid) -> ZFResult<()> {
+        for checkpoint in self.get_instance_checkpoints(iid).await? {
+            self.remove_node_checkpoint(iid, &checkpoint.node_id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Stores the given [`InputWatermarks`](`InputWatermarks`), replacing the
+    /// previous ones of the node in its instance.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - fails to serialize
+    /// - zenoh put fails
+    pub async fn add_input_watermarks(&self, watermarks: &InputWatermarks) -> ZFResult<()> {
+        let path = WATERMARKS_PATH!(ROOT_STANDALONE, watermarks.instance_id, watermarks.node_id);
+
+        let encoded_info = serialize_data(watermarks)?;
+        Ok(self.z.put(&path, encoded_info).await?)
+    }
+
+    /// Gets the [`InputWatermarks`](`InputWatermarks`) of the node `node_id`
+    /// of the instance `iid`.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - no data present in zenoh
+    /// - fails to deserialize
+    pub async fn get_input_watermarks(
+        &self,
+        iid: &Uuid,
+        node_id: &str,
+    ) -> ZFResult<InputWatermarks> {
+        let selector = WATERMARKS_PATH!(ROOT_STANDALONE, iid, node_id);
+        self.get_from_zenoh::<InputWatermarks>(&selector).await
+    }
+
+    /// Removes the [`InputWatermarks`](`InputWatermarks`) of the node `node_id`
+    /// of the instance `iid`.
+    ///
+    /// # Errors
+    /// If zenoh delete fails an error variant is returned.
+    pub async fn remove_input_watermarks(&self, iid: &Uuid, node_id: &str) -> ZFResult<()> {
+        let path = WATERMARKS_PATH!(ROOT_STANDALONE, iid, node_id);
+
+        Ok(self.z.delete(&path).await?)
+    }
+
     // Registry Related, registry is not yet in place.
 
     /// Stores the given [`RegistryNode`](`RegistryNode`) in the registry's
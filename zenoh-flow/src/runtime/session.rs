@@ -0,0 +1,107 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::{ZFError, ZFResult};
+use async_std::sync::{Arc, Mutex};
+use zenoh::config::Config as ZenohConfig;
+use zenoh::Session;
+
+/// A Zenoh session that is only opened when something needs it.
+///
+/// Flows whose nodes are all local, and that do not record, never touch
+/// the network: the session is opened the first time a connector, a
+/// recorder, a replay or a deadline escalation asks for it.
+/// A `LazySession` created with [`LazySession::disabled`](`LazySession::disabled`)
+/// never opens a session, such flows can then run embedded or in CI.
+///
+/// Clones share the same underlying session.
+#[derive(Clone)]
+pub struct LazySession {
+    config: Option<Arc<ZenohConfig>>,
+    session: Arc<Mutex<Option<Arc<Session>>>>,
+}
+
+impl LazySession {
+    /// Creates a `LazySession` that opens a session with the given
+    /// configuration on first use.
+    pub fn new(config: ZenohConfig) -> Self {
+        Self {
+            config: Some(Arc::new(config)),
+            session: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Creates a `LazySession` that never opens a session.
+    ///
+    /// Any attempt to use it fails, so only local-only flows that do not
+    /// record can run with it.
+    pub fn disabled() -> Self {
+        Self {
+            config: None,
+            session: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the session, opening it if it is the first use.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - the session is disabled
+    /// - unable to open the session
+    pub async fn get(&self) -> ZFResult<Arc<Session>> {
+        let mut guard = self.session.lock().await;
+        if let Some(session) = &*guard {
+            return Ok(session.clone());
+        }
+
+        let config = self.config.as_ref().ok_or_else(|| {
+            ZFError::ZenohError(
+                "No Zenoh session available: this runtime runs without network".to_string(),
+            )
+        })?;
+
+        log::debug!("Opening the Zenoh session on first use");
+        let session = Arc::new(zenoh::open((**config).clone()).await?);
+        *guard = Some(session.clone());
+        Ok(session)
+    }
+
+    /// Returns `true` if the session has been opened.
+    pub async fn is_open(&self) -> bool {
+        self.session.lock().await.is_some()
+    }
+}
+
+impl From<Arc<Session>> for LazySession {
+    fn from(session: Arc<Session>) -> Self {
+        Self {
+            config: None,
+            session: Arc::new(Mutex::new(Some(session))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LazySession;
+
+    #[test]
+    fn disabled_session_never_opens() {
+        let session = LazySession::disabled();
+        async_std::task::block_on(async {
+            assert!(session.get().await.is_err());
+            assert!(!session.is_open().await);
+        });
+    }
+}
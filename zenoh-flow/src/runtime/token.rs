@@ -13,6 +13,7 @@
 //
 
 use crate::runtime::deadline::E2EDeadlineMiss;
+use crate::serde::{Deserialize, Serialize};
 use crate::{Data, DataMessage, LoopContext, PortId};
 use std::collections::HashMap;
 use uhlc::Timestamp;
@@ -50,7 +51,7 @@ impl InputTokens {
 /// - Drop the data will be dropped
 /// - Keep the data will be kept for the current and the next
 /// time the run is triggered, it has to be explicitly set back to `Consume`
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TokenAction {
     Consume,
     Drop,
@@ -0,0 +1,128 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use crate::async_std::sync::Arc;
+use crate::DurationDescriptor;
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The configuration of the watchdog of a runtime.
+///
+/// Every `period`, the watchdog looks for the nodes whose current execution of `run` lasts for
+/// more than `hang_threshold`. These nodes are reported as hung and, if `restart` is set, they
+/// are restarted.
+///
+/// A node waiting inside its `run`, e.g. a source waiting for data, is not distinguished from a
+/// hung node: the threshold should be set above the longest expected wait.
+///
+/// Example:
+///
+/// ```yaml
+/// watchdog:
+///   period:
+///     length: 1
+///     unit: s
+///   hang_threshold:
+///     length: 30
+///     unit: s
+///   restart: true
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchdogConfig {
+    pub period: DurationDescriptor,
+    pub hang_threshold: DurationDescriptor,
+    #[serde(default)]
+    pub restart: bool,
+}
+
+/// The health of a node.
+///
+/// A node is `hung` when its current execution of `run` lasts for more than the hang threshold,
+/// `elapsed` is the duration of this execution. A node is `paused` when it is running but waits to
+/// be resumed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeHealth {
+    Stopped,
+    Running,
+    Paused,
+    Hung { elapsed: Duration },
+}
+
+impl std::fmt::Display for NodeHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeHealth::Stopped => write!(f, "stopped"),
+            NodeHealth::Running => write!(f, "running"),
+            NodeHealth::Paused => write!(f, "paused"),
+            NodeHealth::Hung { elapsed } => write!(f, "hung ({:?})", elapsed),
+        }
+    }
+}
+
+/// Keeps track of the current execution of the `run` of a node.
+#[derive(Clone, Default)]
+pub(crate) struct RunTracker {
+    started: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RunTracker {
+    /// Marks the beginning of an execution of `run`, its end is marked when the returned guard is
+    /// dropped.
+    ///
+    /// As the guard is dropped with the future executing `run`, a node whose `run` is cancelled
+    /// (e.g. when it is stopped or restarted) is not considered as still running.
+    pub(crate) fn enter(&self) -> RunGuard<'_> {
+        let started = Instant::now();
+        *self.lock() = Some(started);
+        RunGuard {
+            tracker: self,
+            started,
+        }
+    }
+
+    /// Forgets the current execution of `run`, if any.
+    ///
+    /// Called when a node is (re)started: an execution abandoned because it never acknowledged
+    /// the stop must not be accounted to the new one.
+    pub(crate) fn reset(&self) {
+        *self.lock() = None;
+    }
+
+    /// Returns the duration of the current execution of `run`, if any.
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        self.lock().map(|started| started.elapsed())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.started.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Marks the end of an execution of `run` when dropped, see [`RunTracker::enter`].
+pub(crate) struct RunGuard<'a> {
+    tracker: &'a RunTracker,
+    started: Instant,
+}
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        let mut started = self.tracker.lock();
+        // An abandoned execution finishing late must not clear the execution that replaced it.
+        if *started == Some(self.started) {
+            *started = None;
+        }
+    }
+}
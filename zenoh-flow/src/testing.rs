@@ -0,0 +1,485 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Drives a single node, without any runtime, daemon or Zenoh session.
+//!
+//! The harnesses call the methods of the node directly, in the same order
+//! and with the same token semantics as the runners:
+//!
+//! ```no_run
+//! use zenoh_flow::testing::OperatorHarness;
+//! use zenoh_flow::{Data, ZFResult};
+//! # fn test(operator: impl zenoh_flow::Operator + 'static, data: Data) -> ZFResult<()> {
+//!
+//! let mut harness = OperatorHarness::try_new(operator, None, &["in".into()])?;
+//! harness.send(&"in".into(), data)?;
+//! let outputs = harness.step()?.expect("the input rule should be satisfied");
+//! assert!(outputs.contains_key("out"));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::model::deadline::DeadlineMissPolicy;
+use crate::model::loops::LoopDescriptor;
+use crate::runtime::deadline::E2EDeadlineMiss;
+use crate::{
+    Configuration, Context, Data, DataMessage, DeadlineMiss, InputToken, LocalDeadlineMiss,
+    LoopContext, LoopIteration, NodeId, NodeOutput, Operator, PortId, Sink, Source, State,
+    TokenAction, ZFError, ZFResult, ZFState,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uhlc::{Timestamp, HLC, NTP64};
+
+/// The clock of a harness: the current time is either provided by an `HLC`
+/// or set manually.
+struct Clock {
+    hlc: HLC,
+    now: Option<Timestamp>,
+}
+
+impl Clock {
+    fn new() -> Self {
+        Self {
+            hlc: HLC::default(),
+            now: None,
+        }
+    }
+
+    fn now(&self) -> Timestamp {
+        match self.now {
+            Some(now) => now,
+            None => self.hlc.new_timestamp(),
+        }
+    }
+
+    fn at(&self, time: Duration) -> Timestamp {
+        // NTP64: the seconds in the upper 32 bits, the fraction of second in the lower 32 bits.
+        let fraction = ((time.subsec_nanos() as u64) << 32) / 1_000_000_000;
+        Timestamp::new(NTP64((time.as_secs() << 32) + fraction), *self.hlc.get_id())
+    }
+}
+
+/// Creates a [`LoopContext`](`LoopContext`), as if the Loop described by
+/// `descriptor` started at `start` and performed `iteration` iterations.
+///
+/// The iteration is ignored if the Loop is infinite.
+pub fn loop_context(descriptor: &LoopDescriptor, start: Timestamp, iteration: u64) -> LoopContext {
+    let mut context = LoopContext::new(descriptor, start);
+    if let LoopIteration::Finite(_) = context.iteration {
+        context.iteration = LoopIteration::Finite(iteration);
+    }
+    context
+}
+
+/// Creates a [`LocalDeadlineMiss`](`LocalDeadlineMiss`) of a run that
+/// started now and lasted `elapsed`, with the given `deadline`.
+pub fn local_deadline_miss(deadline: Duration, elapsed: Duration) -> LocalDeadlineMiss {
+    LocalDeadlineMiss {
+        start: Instant::now(),
+        deadline,
+        elapsed,
+    }
+}
+
+/// Drives an [`Operator`](`Operator`).
+///
+/// The harness keeps an [`InputToken`](`InputToken`) per input: data sent on
+/// an input makes its token `Ready`, the tokens are then consumed, kept or
+/// dropped according to the actions set by the `input_rule`.
+pub struct OperatorHarness {
+    id: NodeId,
+    operator: Box<dyn Operator>,
+    context: Context,
+    state: State,
+    tokens: HashMap<PortId, InputToken>,
+    local_deadline: Option<Duration>,
+    clock: Clock,
+}
+
+impl OperatorHarness {
+    /// Initializes the `operator` with the `configuration`, its `inputs` are
+    /// all `Pending`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the operator fails to initialize.
+    pub fn try_new(
+        operator: impl Operator + 'static,
+        configuration: Option<Configuration>,
+        inputs: &[PortId],
+    ) -> ZFResult<Self> {
+        let state = operator.initialize(&configuration)?;
+
+        Ok(Self {
+            id: "operator-under-test".into(),
+            operator: Box::new(operator),
+            context: Context::default(),
+            state,
+            tokens: inputs
+                .iter()
+                .map(|port_id| (port_id.clone(), InputToken::Pending))
+                .collect(),
+            local_deadline: None,
+            clock: Clock::new(),
+        })
+    }
+
+    /// Sets the identifier of the operator, used to check the end to end
+    /// deadlines. Defaults to `operator-under-test`.
+    pub fn with_id(mut self, id: NodeId) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Sets the local deadline checked by [`step`](`OperatorHarness::step`).
+    pub fn with_local_deadline(mut self, deadline: Duration) -> Self {
+        self.local_deadline = Some(deadline);
+        self
+    }
+
+    /// Freezes the time of the harness at `now` or, if `None`, lets it flow.
+    pub fn set_now(&mut self, now: Option<Timestamp>) {
+        self.clock.now = now;
+    }
+
+    /// Returns the current time of the harness.
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    /// Returns a `Timestamp` at `time` since the UNIX epoch.
+    pub fn timestamp_at(&self, time: Duration) -> Timestamp {
+        self.clock.at(time)
+    }
+
+    /// Sends `data`, timestamped now, on the input `port_id`.
+    ///
+    /// # Errors
+    /// See [`send_message`](`OperatorHarness::send_message`).
+    pub fn send(&mut self, port_id: &PortId, data: Data) -> ZFResult<bool> {
+        let timestamp = self.now();
+        self.send_at(port_id, data, timestamp)
+    }
+
+    /// Sends `data`, with the given `timestamp`, on the input `port_id`.
+    ///
+    /// # Errors
+    /// See [`send_message`](`OperatorHarness::send_message`).
+    pub fn send_at(
+        &mut self,
+        port_id: &PortId,
+        data: Data,
+        timestamp: Timestamp,
+    ) -> ZFResult<bool> {
+        self.send_message(port_id, DataMessage::new(data, timestamp, vec![]))
+    }
+
+    /// Sends the `message` on the input `port_id`, which makes its token
+    /// `Ready`.
+    ///
+    /// As in a runtime, the end to end deadlines of the message are checked
+    /// first: the misses are added to the message and the `on_deadline_miss`
+    /// callback is called if that is the policy. Returns `false` if, because
+    /// of the policy of a miss, the message is not delivered to the operator.
+    ///
+    /// # Errors
+    /// An error variant is returned in case of:
+    /// - the input does not exist
+    /// - the `on_deadline_miss` callback fails
+    pub fn send_message(&mut self, port_id: &PortId, mut message: DataMessage) -> ZFResult<bool> {
+        if !self.tokens.contains_key(port_id) {
+            return Err(ZFError::PortNotFound((self.id.clone(), port_id.clone())));
+        }
+
+        let now = self.now();
+        let mut delivered = true;
+        let misses: Vec<(E2EDeadlineMiss, DeadlineMissPolicy)> = message
+            .end_to_end_deadlines
+            .iter()
+            .filter_map(|deadline| {
+                deadline.check(&self.id, port_id, &now).map(|miss| {
+                    let policy = deadline.on_miss.clone().unwrap_or(DeadlineMissPolicy::Log);
+                    (miss, policy)
+                })
+            })
+            .collect();
+
+        for (miss, policy) in misses {
+            match policy {
+                DeadlineMissPolicy::Callback => self
+                    .operator
+                    .on_deadline_miss(&mut self.state, &DeadlineMiss::EndToEnd(miss.clone()))?,
+                DeadlineMissPolicy::Drop | DeadlineMissPolicy::Fallback(_) => delivered = false,
+                _ => (),
+            }
+            message.missed_end_to_end_deadlines.push(miss);
+        }
+
+        if delivered {
+            self.tokens
+                .insert(port_id.clone(), InputToken::from(message));
+        }
+
+        Ok(delivered)
+    }
+
+    /// Returns the token of the input `port_id`.
+    pub fn token(&self, port_id: &PortId) -> Option<&InputToken> {
+        self.tokens.get(port_id)
+    }
+
+    /// Calls the `input_rule` of the operator, the tokens it dropped are then
+    /// reset to `Pending`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `input_rule` fails.
+    pub fn input_rule(&mut self) -> ZFResult<bool> {
+        let res = self
+            .operator
+            .input_rule(&mut self.context, &mut self.state, &mut self.tokens)?;
+
+        for token in self.tokens.values_mut() {
+            if token.should_drop() {
+                *token = InputToken::Pending;
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Calls the `run` of the operator with the data of the `Ready` tokens.
+    /// The tokens are consumed unless their action is `Keep`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `run` fails.
+    pub fn run(&mut self) -> ZFResult<HashMap<PortId, Data>> {
+        let mut inputs = HashMap::with_capacity(self.tokens.len());
+        for (port_id, token) in self.tokens.iter_mut() {
+            if let InputToken::Ready(data_token) = token {
+                if data_token.action != TokenAction::Drop {
+                    inputs.insert(port_id.clone(), data_token.data.clone());
+                }
+                if data_token.action != TokenAction::Keep {
+                    *token = InputToken::Pending;
+                }
+            }
+        }
+
+        self.operator
+            .run(&mut self.context, &mut self.state, &mut inputs)
+    }
+
+    /// Calls the `output_rule` of the operator with the `outputs` of a `run`
+    /// and, possibly, a simulated `deadline_miss`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `output_rule` fails.
+    pub fn output_rule(
+        &mut self,
+        outputs: HashMap<PortId, Data>,
+        deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        self.operator
+            .output_rule(&mut self.context, &mut self.state, outputs, deadline_miss)
+    }
+
+    /// Performs an iteration, as a runner would: calls the `input_rule` and,
+    /// if it is satisfied, the `run` and the `output_rule`. The local
+    /// deadline, if any, is checked on the duration of the `run`.
+    ///
+    /// Returns `None` if the `input_rule` is not satisfied.
+    ///
+    /// # Errors
+    /// An error variant is returned if one of the methods of the operator
+    /// fails.
+    pub fn step(&mut self) -> ZFResult<Option<HashMap<PortId, NodeOutput>>> {
+        if !self.input_rule()? {
+            return Ok(None);
+        }
+
+        let start = Instant::now();
+        let outputs = self.run()?;
+        let elapsed = start.elapsed();
+
+        let deadline_miss = match self.local_deadline {
+            Some(deadline) if elapsed > deadline => Some(LocalDeadlineMiss {
+                start,
+                deadline,
+                elapsed,
+            }),
+            _ => None,
+        };
+
+        self.output_rule(outputs, deadline_miss).map(Some)
+    }
+
+    /// Calls the `on_deadline_miss` callback of the operator.
+    ///
+    /// # Errors
+    /// An error variant is returned if the callback fails.
+    pub fn on_deadline_miss(&mut self, miss: &DeadlineMiss) -> ZFResult<()> {
+        self.operator.on_deadline_miss(&mut self.state, miss)
+    }
+
+    /// Returns the state of the operator, cast to `S`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the state is not an `S`.
+    pub fn state<S: ZFState + 'static>(&mut self) -> ZFResult<&mut S> {
+        self.state.try_get::<S>()
+    }
+
+    /// Returns the context of the operator.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    /// Finalizes the operator.
+    ///
+    /// # Errors
+    /// An error variant is returned if the operator fails to finalize.
+    pub fn finalize(mut self) -> ZFResult<()> {
+        self.operator.finalize(&mut self.state)
+    }
+}
+
+/// Drives a [`Source`](`Source`).
+pub struct SourceHarness {
+    source: Box<dyn Source>,
+    context: Context,
+    state: State,
+}
+
+impl SourceHarness {
+    /// Initializes the `source` with the `configuration`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the source fails to initialize.
+    pub fn try_new(
+        source: impl Source + 'static,
+        configuration: Option<Configuration>,
+    ) -> ZFResult<Self> {
+        let state = source.initialize(&configuration)?;
+
+        Ok(Self {
+            source: Box::new(source),
+            context: Context::default(),
+            state,
+        })
+    }
+
+    /// Calls the `run` of the source, returns the data it produced.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `run` fails.
+    pub async fn run(&mut self) -> ZFResult<Data> {
+        self.source.run(&mut self.context, &mut self.state).await
+    }
+
+    /// Calls the `on_deadline_miss` callback of the source.
+    ///
+    /// # Errors
+    /// An error variant is returned if the callback fails.
+    pub fn on_deadline_miss(&mut self, miss: &DeadlineMiss) -> ZFResult<()> {
+        self.source.on_deadline_miss(&mut self.state, miss)
+    }
+
+    /// Returns the state of the source, cast to `S`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the state is not an `S`.
+    pub fn state<S: ZFState + 'static>(&mut self) -> ZFResult<&mut S> {
+        self.state.try_get::<S>()
+    }
+
+    /// Finalizes the source.
+    ///
+    /// # Errors
+    /// An error variant is returned if the source fails to finalize.
+    pub fn finalize(mut self) -> ZFResult<()> {
+        self.source.finalize(&mut self.state)
+    }
+}
+
+/// Drives a [`Sink`](`Sink`).
+pub struct SinkHarness {
+    sink: Box<dyn Sink>,
+    context: Context,
+    state: State,
+    clock: Clock,
+}
+
+impl SinkHarness {
+    /// Initializes the `sink` with the `configuration`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the sink fails to initialize.
+    pub fn try_new(
+        sink: impl Sink + 'static,
+        configuration: Option<Configuration>,
+    ) -> ZFResult<Self> {
+        let state = sink.initialize(&configuration)?;
+
+        Ok(Self {
+            sink: Box::new(sink),
+            context: Context::default(),
+            state,
+            clock: Clock::new(),
+        })
+    }
+
+    /// Calls the `run` of the sink with `data`, timestamped now.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `run` fails.
+    pub async fn run(&mut self, data: Data) -> ZFResult<()> {
+        let timestamp = self.clock.now();
+        self.run_message(DataMessage::new(data, timestamp, vec![]))
+            .await
+    }
+
+    /// Calls the `run` of the sink with the `message`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the `run` fails.
+    pub async fn run_message(&mut self, message: DataMessage) -> ZFResult<()> {
+        self.sink
+            .run(&mut self.context, &mut self.state, message)
+            .await
+    }
+
+    /// Calls the `on_deadline_miss` callback of the sink.
+    ///
+    /// # Errors
+    /// An error variant is returned if the callback fails.
+    pub fn on_deadline_miss(&mut self, miss: &DeadlineMiss) -> ZFResult<()> {
+        self.sink.on_deadline_miss(&mut self.state, miss)
+    }
+
+    /// Returns the state of the sink, cast to `S`.
+    ///
+    /// # Errors
+    /// An error variant is returned if the state is not an `S`.
+    pub fn state<S: ZFState + 'static>(&mut self) -> ZFResult<&mut S> {
+        self.state.try_get::<S>()
+    }
+
+    /// Finalizes the sink.
+    ///
+    /// # Errors
+    /// An error variant is returned if the sink fails to finalize.
+    pub fn finalize(mut self) -> ZFResult<()> {
+        self.sink.finalize(&mut self.state)
+    }
+}
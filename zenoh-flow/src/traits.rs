@@ -14,8 +14,8 @@
 
 use crate::runtime::message::DataMessage;
 use crate::{
-    Configuration, Context, Data, InputToken, LocalDeadlineMiss, NodeOutput, PortId, State,
-    ZFResult,
+    Configuration, Context, Data, DeadlineMiss, InputToken, LocalDeadlineMiss, NodeOutput, PortId,
+    State, ZFError, ZFResult,
 };
 use async_trait::async_trait;
 use std::any::Any;
@@ -118,6 +118,22 @@ pub trait Deserializable {
 /// pub struct MyState;
 /// ```
 ///
+/// A state can opt-in to checkpointing by providing `try_serialize` and
+/// `try_restore`. When derived, adding `#[zf_state(checkpoint)]` generates
+/// both using `bincode`, the type then has to implement `Serialize` and
+/// `Deserialize`:
+///
+/// ```no_run
+/// use zenoh_flow::serde::{Deserialize, Serialize};
+/// use zenoh_flow::zenoh_flow_derive::ZFState;
+/// #[derive(Debug, Clone, Serialize, Deserialize, ZFState)]
+/// #[serde(crate = "zenoh_flow::serde")]
+/// #[zf_state(checkpoint)]
+/// pub struct MyCounter {
+///     count: u64,
+/// }
+/// ```
+///
 
 pub trait ZFState: Debug + Send + Sync {
     /// Donwcast as reference to [`Any`](`Any`)
@@ -125,6 +141,26 @@ pub trait ZFState: Debug + Send + Sync {
 
     /// Donwcast as mutable reference to [`Any`](`Any`)
     fn as_mut_any(&mut self) -> &mut dyn Any;
+
+    /// Serializes the state so that it can be checkpointed.
+    ///
+    /// Returns `None` if the state does not support checkpointing, which is
+    /// the default.
+    ///
+    /// # Errors
+    /// An error variant is returned if the serialization fails.
+    fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Restores the state from the bytes produced by `try_serialize`.
+    ///
+    /// # Errors
+    /// By default `Unsupported` is returned. An error variant is also
+    /// returned if the deserialization fails.
+    fn try_restore(&mut self, _bytes: &[u8]) -> ZFResult<()> {
+        Err(ZFError::Unsupported)
+    }
 }
 
 /// The `Node` trait represents a generic node in the data flow graph.
@@ -151,6 +187,19 @@ pub trait Node {
     /// # Errors
     /// If it fails to finalize an error variant will be returned.
     fn finalize(&self, state: &mut State) -> ZFResult<()>;
+
+    /// This method is called by the Zenoh Flow runtime when a deadline of
+    /// the node, whose policy is `callback`, is missed.
+    ///
+    /// It is called before the node processes the data. The default
+    /// implementation does nothing.
+    ///
+    /// # Errors
+    /// If it fails an error variant will be returned, the run of the node
+    /// is then restarted.
+    fn on_deadline_miss(&self, _state: &mut State, _miss: &DeadlineMiss) -> ZFResult<()> {
+        Ok(())
+    }
 }
 
 /// The `Operator` trait represents an Operator inside Zenoh Flow.
@@ -186,6 +186,24 @@ impl State {
             .downcast_mut::<S>()
             .ok_or_else(|| ZFError::InvalidData("Could not downcast.".to_string()))
     }
+
+    /// Serializes the internal state, `None` is returned if the state does
+    /// not support checkpointing.
+    ///
+    /// # Errors
+    /// If the serialization fails an error variant will be returned.
+    pub fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+        self.state.try_serialize()
+    }
+
+    /// Restores the internal state from a checkpoint.
+    ///
+    /// # Errors
+    /// If the state does not support checkpointing or if the
+    /// deserialization fails an error variant will be returned.
+    pub fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+        self.state.try_restore(bytes)
+    }
 }
 
 /// Represents the output of a node.
@@ -195,7 +213,7 @@ impl State {
 ///
 ///
 /// *NOTE:* Handling of control messages is not yet implemented.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeOutput {
     Data(Data),
     // TODO Users should not have access to all control messages. When implementing the control
@@ -302,29 +320,60 @@ pub type Configuration = serde_json::Value;
 /// Merges two configurations, keeping the values of the `local` Configuration in case of duplicated
 /// keys.
 ///
+/// The merge is deep: when a key maps to an object in both configurations, the two objects are
+/// merged recursively. For any other kind of value (string, number, array, …) the value of `local`
+/// replaces the one of `global`.
+///
 /// This function was created with the idea of merging (and overwriting) a `global` Configuration,
-/// common to all nodes of a graph, with a `local` one, i.e. specific to a node.
+/// common to all nodes of a graph, with a `local` one, i.e. specific to a node. When creating a
+/// [`DataFlowRecord`](`crate::model::dataflow::record::DataFlowRecord`) it is applied twice,
+/// yielding the following precedence: global < runtime < node.
+///
+/// # Errors
+/// An error variant is returned if both configurations are provided and at least one of them is
+/// not an object.
 pub(crate) fn merge_configurations(
     global: Option<Configuration>,
     local: Option<Configuration>,
-) -> Option<Configuration> {
+) -> ZFResult<Option<Configuration>> {
     match (global, local) {
-        (None, None) => None,
-        (None, Some(local)) => Some(local),
-        (Some(global), None) => Some(global),
-        (Some(mut global), Some(mut local)) => {
-            global
-                .as_object_mut()
-                .unwrap()
-                .append(local.as_object_mut().unwrap());
-            Some(global)
+        (None, None) => Ok(None),
+        (None, Some(local)) => Ok(Some(local)),
+        (Some(global), None) => Ok(Some(global)),
+        (Some(mut global), Some(local)) => {
+            if !global.is_object() || !local.is_object() {
+                return Err(ZFError::ConfigurationError(format!(
+                    "Cannot merge configurations that are not objects: {} — {}",
+                    global, local
+                )));
+            }
+
+            deep_merge(&mut global, local);
+            Ok(Some(global))
+        }
+    }
+}
+
+/// Recursively merges `local` into `global`, `local` taking precedence.
+fn deep_merge(global: &mut Configuration, local: Configuration) {
+    match (global, local) {
+        (Configuration::Object(global), Configuration::Object(local)) => {
+            for (key, local_value) in local {
+                match global.get_mut(&key) {
+                    Some(global_value) => deep_merge(global_value, local_value),
+                    None => {
+                        global.insert(key, local_value);
+                    }
+                }
+            }
         }
+        (global, local) => *global = local,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::merge_configurations;
+    use crate::{merge_configurations, ZFError};
     use serde_json::json;
 
     #[test]
@@ -333,16 +382,47 @@ mod tests {
         let local = json!({ "a": { "not-nested": false }, "c": 1 });
 
         assert_eq!(
-            merge_configurations(Some(global.clone()), Some(local.clone())),
-            Some(json!({ "a": { "not-nested": false }, "b": ["an", "array"], "c": 1 }))
+            merge_configurations(Some(global.clone()), Some(local.clone())).unwrap(),
+            Some(
+                json!({ "a": { "nested": true, "not-nested": false }, "b": ["an", "array"], "c": 1 })
+            )
         );
 
-        assert_eq!(merge_configurations(None, Some(local.clone())), Some(local));
         assert_eq!(
-            merge_configurations(Some(global.clone()), None),
+            merge_configurations(None, Some(local.clone())).unwrap(),
+            Some(local)
+        );
+        assert_eq!(
+            merge_configurations(Some(global.clone()), None).unwrap(),
             Some(global)
         );
     }
+
+    #[test]
+    fn test_merge_configurations_overwrite() {
+        let global = json!({ "a": { "nested": { "x": 1, "y": 2 } }, "b": ["an", "array"] });
+        let local = json!({ "a": { "nested": { "y": 3 } }, "b": "not-an-array" });
+
+        assert_eq!(
+            merge_configurations(Some(global), Some(local)).unwrap(),
+            Some(json!({ "a": { "nested": { "x": 1, "y": 3 } }, "b": "not-an-array" }))
+        );
+    }
+
+    #[test]
+    fn test_merge_configurations_not_objects() {
+        let global = json!({ "a": 1 });
+        let local = json!(42);
+
+        assert!(matches!(
+            merge_configurations(Some(global.clone()), Some(local.clone())),
+            Err(ZFError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            merge_configurations(Some(local), Some(global)),
+            Err(ZFError::ConfigurationError(_))
+        ));
+    }
 }
 
 /// The unit of duration used in different descriptors.
@@ -381,3 +461,12 @@ impl DurationDescriptor {
         }
     }
 }
+
+impl From<Duration> for DurationDescriptor {
+    fn from(duration: Duration) -> Self {
+        Self {
+            length: duration.as_micros() as u64,
+            unit: DurationUnit::Microsecond,
+        }
+    }
+}
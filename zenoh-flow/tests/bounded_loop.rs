@@ -0,0 +1,409 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use std::collections::HashMap;
+use std::time::Duration;
+use types::{VecSource, ZFUsize};
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::node::OperatorRecord;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::Dataflow;
+use zenoh_flow::runtime::loops::LoopIteration;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::{
+    default_input_rule, default_output_rule, zf_empty_state, Configuration, Context, Data,
+    DataMessage, InputToken, LocalDeadlineMiss, Node, NodeOutput, Operator, PortId, Sink, State,
+    ZFError, ZFResult,
+};
+
+static SOURCE: &str = "Source";
+static REFINE_START: &str = "refine-start";
+static REFINE_END: &str = "refine-end";
+static FEEDBACK: &str = "refine";
+static DEBUG: &str = "debug";
+static RESULT: &str = "result";
+
+// The Ingress has two outputs: one for the body of the loop and one for debugging purposes.
+struct RefineStart;
+
+impl Node for RefineStart {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for RefineStart {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        Ok(tokens
+            .values()
+            .any(|token| matches!(token, InputToken::Ready(_))))
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let value = match inputs.remove(SOURCE) {
+            Some(mut source_input) => source_input.get_inner_data().try_get::<ZFUsize>()?.0,
+            None => {
+                let mut feedback_input = inputs.remove(FEEDBACK).unwrap();
+                feedback_input.get_inner_data().try_get::<ZFUsize>()?.0 + 1
+            }
+        };
+
+        let mut results: HashMap<PortId, Data> = HashMap::with_capacity(2);
+        results.insert(REFINE_END.into(), Data::from::<ZFUsize>(ZFUsize(value)));
+        results.insert(DEBUG.into(), Data::from::<ZFUsize>(ZFUsize(value)));
+        Ok(results)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+// The Egress always sends on the feedback link, the runtime is in charge of ending the loop.
+struct RefineEnd {
+    max_iterations: u64,
+    delay: Option<Duration>,
+}
+
+impl Node for RefineEnd {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for RefineEnd {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let mut input = inputs.remove(REFINE_END).unwrap();
+
+        let loop_contexts = input.get_loop_contexts();
+        assert_eq!(loop_contexts.len(), 1);
+        assert_eq!(loop_contexts[0].get_feedback_port().as_ref(), FEEDBACK);
+        match loop_contexts[0].get_iteration() {
+            LoopIteration::Finite(counter) => assert!(counter < self.max_iterations),
+            LoopIteration::Infinite => return Err(ZFError::GenericError),
+        }
+
+        if let Some(delay) = self.delay {
+            std::thread::sleep(delay);
+        }
+
+        let data = input.get_inner_data().clone();
+        let mut results: HashMap<PortId, Data> = HashMap::with_capacity(2);
+        results.insert(FEEDBACK.into(), data.clone());
+        results.insert(RESULT.into(), data);
+        Ok(results)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+struct ChannelSink {
+    tx: Sender<usize>,
+}
+
+impl Node for ChannelSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for ChannelSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        mut input: DataMessage,
+    ) -> ZFResult<()> {
+        let data = input.get_inner_data().try_get::<ZFUsize>()?;
+        self.tx
+            .send_async(data.0)
+            .await
+            .map_err(|e| ZFError::IOError(e.to_string()))
+    }
+}
+
+/// Receives all the values sent on `rx` until nothing is received for 500ms.
+async fn collect(rx: &Receiver<usize>) -> Vec<usize> {
+    let mut values = vec![];
+    while let Ok(Ok(value)) =
+        async_std::future::timeout(Duration::from_millis(500), rx.recv_async()).await
+    {
+        values.push(value);
+    }
+    values
+}
+
+async fn run_refinement(
+    max_iterations: Option<u64>,
+    iteration_timeout: Option<Duration>,
+    delay: Option<Duration>,
+) -> (Vec<usize>, Vec<usize>) {
+    let session = Arc::new(zenoh::open(zenoh::config::Config::default()).await.unwrap());
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: session.into(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow = Dataflow::new(ctx.clone(), "test".into(), None);
+
+    let (tx_debug, rx_debug) = flume::unbounded::<usize>();
+    let (tx_result, rx_result) = flume::unbounded::<usize>();
+
+    let source = Arc::new(VecSource::new(vec![0]));
+    let refine_start = Arc::new(RefineStart);
+    let refine_end = Arc::new(RefineEnd {
+        max_iterations: max_iterations.unwrap_or(u64::MAX),
+        delay,
+    });
+    let sink_debug = Arc::new(ChannelSink { tx: tx_debug });
+    let sink_result = Arc::new(ChannelSink { tx: tx_result });
+
+    let port = |port_id: &str| PortDescriptor {
+        port_id: port_id.into(),
+        port_type: "int".into(),
+    };
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            port(SOURCE),
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_operator(
+            REFINE_START.into(),
+            vec![port(SOURCE)],
+            vec![port(REFINE_END), port(DEBUG)],
+            None,
+            refine_start.initialize(&None).unwrap(),
+            refine_start,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_operator(
+            REFINE_END.into(),
+            vec![port(REFINE_END)],
+            vec![port(RESULT)],
+            None,
+            refine_end.initialize(&None).unwrap(),
+            refine_end,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            DEBUG.into(),
+            port(DEBUG),
+            sink_debug.initialize(&None).unwrap(),
+            sink_debug,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            RESULT.into(),
+            port(RESULT),
+            sink_result.initialize(&None).unwrap(),
+            sink_result,
+        )
+        .unwrap();
+
+    let links = [
+        (SOURCE, SOURCE, REFINE_START, SOURCE),
+        (REFINE_START, REFINE_END, REFINE_END, REFINE_END),
+        (REFINE_START, DEBUG, DEBUG, DEBUG),
+        (REFINE_END, RESULT, RESULT, RESULT),
+    ];
+    for (from, output, to, input) in links {
+        dataflow
+            .try_add_link(
+                OutputDescriptor {
+                    node: from.into(),
+                    output: output.into(),
+                },
+                InputDescriptor {
+                    node: to.into(),
+                    input: input.into(),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    dataflow
+        .try_add_bounded_loop(
+            REFINE_START.into(),
+            REFINE_END.into(),
+            FEEDBACK.into(),
+            "int".into(),
+            false,
+            max_iterations,
+            iteration_timeout,
+        )
+        .unwrap();
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+
+    for id in &instance.get_nodes() {
+        instance.start_node(id).await.unwrap();
+    }
+
+    let results = collect(&rx_result).await;
+    let debug = collect(&rx_debug).await;
+
+    for id in &instance.get_nodes() {
+        instance.stop_node(id).await.unwrap()
+    }
+
+    (results, debug)
+}
+
+#[test]
+fn bounded_loop() {
+    let _ = env_logger::try_init();
+
+    let h = async_std::task::spawn(async move {
+        let (results, debug) = run_refinement(Some(5), None, None).await;
+        assert_eq!(results, vec![0, 1, 2, 3, 4]);
+        assert_eq!(debug, vec![0, 1, 2, 3, 4]);
+
+        // The first iteration already takes longer than the timeout: the loop ends right away.
+        let (results, debug) = run_refinement(
+            Some(5),
+            Some(Duration::from_millis(10)),
+            Some(Duration::from_millis(50)),
+        )
+        .await;
+        assert_eq!(results, vec![0]);
+        assert_eq!(debug, vec![0]);
+    });
+
+    assert!(
+        async_std::task::block_on(async_std::future::timeout(Duration::from_secs(10), h)).is_ok(),
+        "Deadlock detected."
+    );
+}
+
+#[test]
+fn legacy_ciclo() {
+    let record = |ciclo: &str| {
+        format!(
+            r#"
+id: refine-end
+inputs: []
+outputs: []
+runtime: runtime
+{}
+"#,
+            ciclo
+        )
+    };
+    let cicli = |record: &OperatorRecord| {
+        serde_yaml::to_value(record).unwrap()["cicli"]
+            .as_sequence()
+            .unwrap()
+            .len()
+    };
+
+    let without_loop: OperatorRecord = serde_yaml::from_str(&record("ciclo: ~")).unwrap();
+    assert_eq!(cicli(&without_loop), 0);
+
+    let with_loop: OperatorRecord = serde_yaml::from_str(&record(
+        r#"
+ciclo:
+  ingress: refine-start
+  egress: refine-end
+  feedback_port: refine
+  is_infinite: false
+  port_type: int
+"#,
+    ))
+    .unwrap();
+    assert_eq!(cicli(&with_loop), 1);
+
+    let bytes = zenoh_flow::bincode::serialize(&with_loop).unwrap();
+    let with_loop: OperatorRecord = zenoh_flow::bincode::deserialize(&bytes).unwrap();
+    assert_eq!(cicli(&with_loop), 1);
+}
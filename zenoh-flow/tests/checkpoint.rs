@@ -0,0 +1,185 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use std::time::Duration;
+use types::VecSource;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::checkpoint::NodeCheckpoint;
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::session::LazySession;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::serde::{Deserialize, Serialize};
+use zenoh_flow::zenoh_flow_derive::ZFState;
+use zenoh_flow::{Configuration, Context, DataMessage, Node, Sink, State, ZFResult};
+
+static SOURCE: &str = "Source";
+static SINK: &str = "Sink";
+
+const MESSAGES: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize, ZFState)]
+#[serde(crate = "zenoh_flow::serde")]
+#[zf_state(checkpoint)]
+struct ReceivedState {
+    received: usize,
+}
+
+struct CountSink;
+
+impl Node for CountSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        Ok(State::from(ReceivedState { received: 0 }))
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for CountSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        _input: DataMessage,
+    ) -> ZFResult<()> {
+        state.try_get::<ReceivedState>()?.received += 1;
+        Ok(())
+    }
+}
+
+fn build_instance(ctx: RuntimeContext) -> DataflowInstance {
+    let mut dataflow = zenoh_flow::runtime::dataflow::Dataflow::new(ctx, "test".into(), None);
+
+    let source = Arc::new(VecSource::new((0..MESSAGES).collect()));
+    let sink = Arc::new(CountSink);
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    DataflowInstance::try_instantiate(dataflow).unwrap()
+}
+
+async fn run_and_checkpoint(
+    instance: &mut DataflowInstance,
+    restore: Option<&NodeCheckpoint>,
+) -> NodeCheckpoint {
+    let sink_id = SINK.into();
+
+    if let Some(checkpoint) = restore {
+        instance.restore_node(checkpoint).await.unwrap();
+    }
+
+    instance.start_nodes().await.unwrap();
+    instance.start_sources().await.unwrap();
+
+    async_std::task::sleep(Duration::from_millis(100)).await;
+
+    // The sink waits for its input without holding its state.
+    let checkpoint = instance.checkpoint_node(&sink_id).await.unwrap().unwrap();
+    assert_eq!(checkpoint.node_id, sink_id);
+    assert_eq!(checkpoint.instance_id, instance.get_uuid());
+
+    // A checkpointer takes the same checkpoint without holding the instance.
+    let checkpointer = instance
+        .get_checkpointers()
+        .into_iter()
+        .find(|checkpointer| checkpointer.get_id() == sink_id)
+        .unwrap();
+    assert_eq!(checkpointer.get_instance_id(), instance.get_uuid());
+    let other = checkpointer.checkpoint().await.unwrap().unwrap();
+    assert_eq!(other.state, checkpoint.state);
+
+    instance.stop_sources().await.unwrap();
+    instance.stop_nodes().await.unwrap();
+
+    checkpoint
+}
+
+async fn single_runtime() {
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: LazySession::disabled(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut first = build_instance(ctx.clone());
+    let checkpoint = run_and_checkpoint(&mut first, None).await;
+    let state: ReceivedState = zenoh_flow::bincode::deserialize(&checkpoint.state).unwrap();
+    assert_eq!(state.received, MESSAGES);
+
+    // A re-created instance of the same flow continues from the checkpoint.
+    let mut second = build_instance(ctx);
+    let checkpoint = run_and_checkpoint(&mut second, Some(&checkpoint)).await;
+    let state: ReceivedState = zenoh_flow::bincode::deserialize(&checkpoint.state).unwrap();
+    assert_eq!(state.received, 2 * MESSAGES);
+    assert_eq!(checkpoint.flow_id, first.get_flow());
+}
+
+#[test]
+fn checkpoint_and_restore_node_state() {
+    let h1 = async_std::task::spawn(async move { single_runtime().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
@@ -12,7 +12,11 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use zenoh_flow::Configuration;
+use std::convert::TryFrom;
+use uuid::Uuid;
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::dataflow::record::DataFlowRecord;
+use zenoh_flow::{Configuration, ZFError};
 
 static EXAMPLE_CONFIG: &str = r#"
 map1:
@@ -57,3 +61,94 @@ fn configuration() {
 
     assert_eq!("a", deserialized_config["list1"][2].as_str().unwrap());
 }
+
+static DESCRIPTOR_SCHEMA: &str = r#"
+flow: SchemaPipeline
+configuration:
+  file: /tmp/global.txt
+  options:
+    append: true
+    flush: false
+runtime_configurations:
+  runtime0:
+    options:
+      flush: true
+operators: []
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    output:
+      id: Counter
+      type: usize
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    configuration:
+      file: /tmp/generic-sink.txt
+    schema:
+      type: object
+      properties:
+        file:
+          type: string
+        options:
+          type: object
+          properties:
+            append:
+              type: boolean
+            flush:
+              type: boolean
+          additionalProperties: false
+      required: [file]
+      additionalProperties: false
+    input:
+      id: Data
+      type: usize
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : PrintSink
+    input : Data
+mapping:
+  Counter: runtime0
+  PrintSink: runtime0
+"#;
+
+#[test]
+fn configuration_merge_and_schema() {
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR_SCHEMA).unwrap();
+    let record = DataFlowRecord::try_from((descriptor, Uuid::nil())).unwrap();
+
+    let sink_configuration = record
+        .sinks
+        .get("PrintSink")
+        .unwrap()
+        .configuration
+        .clone()
+        .unwrap();
+    assert_eq!("/tmp/generic-sink.txt", sink_configuration["file"]);
+    assert!(sink_configuration["options"]["append"].as_bool().unwrap());
+    assert!(sink_configuration["options"]["flush"].as_bool().unwrap());
+
+    let misspelled = DESCRIPTOR_SCHEMA.replace("file: /tmp/generic-sink.txt", "fiel: /tmp/x.txt");
+    assert!(matches!(
+        DataFlowDescriptor::from_yaml(&misspelled),
+        Err(ZFError::ConfigurationError(_))
+    ));
+
+    let wrong_runtime =
+        DESCRIPTOR_SCHEMA.replace("      flush: true", "      flush: true\n      fluhs: true");
+    let descriptor = DataFlowDescriptor::from_yaml(&wrong_runtime).unwrap();
+    assert!(matches!(
+        DataFlowRecord::try_from((descriptor, Uuid::nil())),
+        Err(ZFError::ConfigurationError(_))
+    ));
+
+    let scalar = DESCRIPTOR_SCHEMA.replace("      file: /tmp/generic-sink.txt", "      42");
+    let scalar = scalar.replace("    configuration:\n      42", "    configuration: 42");
+    assert!(matches!(
+        DataFlowDescriptor::from_yaml(&scalar),
+        Err(ZFError::ConfigurationError(_))
+    ));
+}
@@ -22,6 +22,7 @@ use zenoh_flow::model::link::PortDescriptor;
 use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
 use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
 use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::session::LazySession;
 use zenoh_flow::runtime::RuntimeContext;
 use zenoh_flow::zenoh_flow_derive::ZFData;
 use zenoh_flow::{
@@ -190,15 +191,16 @@ async fn single_runtime() {
 
     let (tx, rx) = bounded::<()>(1); // Channel used to trigger source
 
-    let session = Arc::new(zenoh::open(zenoh::config::Config::default()).await.unwrap());
+    // All the nodes are local: the flow must run without any Zenoh session.
     let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
     let rt_uuid = uuid::Uuid::new_v4();
     let ctx = RuntimeContext {
-        session,
+        session: LazySession::disabled(),
         hlc,
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: format!("test-runtime-{}", rt_uuid).into(),
         runtime_uuid: rt_uuid,
+        recordings_dir: None,
     };
 
     let mut dataflow =
@@ -303,6 +305,8 @@ async fn single_runtime() {
     for id in &instance.get_sinks() {
         instance.stop_node(id).await.unwrap()
     }
+
+    assert!(!ctx.session.is_open().await);
 }
 
 #[test]
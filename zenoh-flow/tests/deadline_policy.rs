@@ -0,0 +1,134 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::ZFError;
+
+static DESCRIPTOR: &str = r#"
+flow: DeadlinePolicies
+operators:
+  - id : SumOperator
+    uri: file://./target/release/libsum_and_send.dylib
+    inputs:
+      - id: Number
+        type: usize
+    outputs:
+      - id: Sum
+        type: usize
+      - id: Late
+        type: usize
+    deadline:
+      length: 10
+      unit: ms
+    on_deadline_miss: LOCAL_POLICY
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    output:
+      id: Counter
+      type: usize
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    input:
+      id: Data
+      type: usize
+  - id : LateSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    input:
+      id: Data
+      type: usize
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : SumOperator
+    input : Number
+- from:
+    node : SumOperator
+    output : Sum
+  to:
+    node : PrintSink
+    input : Data
+- from:
+    node : SumOperator
+    output : Late
+  to:
+    node : LateSink
+    input : Data
+deadlines:
+- from:
+    node: Counter
+    output: Counter
+  to:
+    node: DEADLINE_TO
+    input: DEADLINE_INPUT
+  duration:
+    length: 100
+    unit: ms
+  on_miss: E2E_POLICY
+"#;
+
+fn descriptor(
+    local_policy: &str,
+    deadline_to: (&str, &str),
+    e2e_policy: &str,
+) -> Result<DataFlowDescriptor, ZFError> {
+    DataFlowDescriptor::from_yaml(
+        &DESCRIPTOR
+            .replace("LOCAL_POLICY", local_policy)
+            .replace("DEADLINE_TO", deadline_to.0)
+            .replace("DEADLINE_INPUT", deadline_to.1)
+            .replace("E2E_POLICY", e2e_policy),
+    )
+}
+
+#[test]
+fn deadline_miss_policies() {
+    for policy in ["log", "drop", "callback", "escalate", "{ fallback: Late }"].iter() {
+        assert!(descriptor("drop", ("SumOperator", "Number"), policy).is_ok());
+    }
+
+    assert!(descriptor("escalate", ("PrintSink", "Data"), "drop").is_ok());
+
+    // A sink has no output to fall back to.
+    assert!(matches!(
+        descriptor("drop", ("PrintSink", "Data"), "{ fallback: Late }"),
+        Err(ZFError::ConfigurationError(_))
+    ));
+
+    assert!(matches!(
+        descriptor("drop", ("SumOperator", "Number"), "{ fallback: Missing }"),
+        Err(ZFError::PortNotFound(_))
+    ));
+
+    // Local deadlines cannot fall back.
+    assert!(matches!(
+        descriptor("{ fallback: Late }", ("SumOperator", "Number"), "log"),
+        Err(ZFError::ConfigurationError(_))
+    ));
+
+    // A local policy without a local deadline.
+    let no_deadline = DESCRIPTOR
+        .replace("    deadline:\n      length: 10\n      unit: ms\n", "")
+        .replace("LOCAL_POLICY", "drop")
+        .replace("DEADLINE_TO", "SumOperator")
+        .replace("DEADLINE_INPUT", "Number")
+        .replace("E2E_POLICY", "log");
+    assert!(matches!(
+        DataFlowDescriptor::from_yaml(&no_deadline),
+        Err(ZFError::ConfigurationError(_))
+    ));
+}
@@ -0,0 +1,491 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use flume::{bounded, Receiver};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use types::{VecSource, ZFUsize};
+use uuid::Uuid;
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::dataflow::record::DataFlowRecord;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::{wait_all_idle, DataflowInstance};
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::Dataflow;
+use zenoh_flow::runtime::session::LazySession;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::{
+    default_output_rule, zf_empty_state, Configuration, Context, Data, DataMessage, InputToken,
+    LocalDeadlineMiss, Node, NodeOutput, Operator, PortId, Sink, Source, State, ZFError, ZFResult,
+};
+
+static SOURCE: &str = "Source";
+static SINK: &str = "Sink";
+static LEFT: &str = "Left";
+static RIGHT: &str = "Right";
+static JOIN: &str = "Join";
+
+const MESSAGES: usize = 20;
+
+// The source and the sink run on two runtimes, linked by connectors.
+static DESCRIPTOR: &str = r#"
+flow: Drain
+operators: []
+sources:
+  - id : Source
+    uri: file://./target/release/libsource.so
+    output:
+      id: Source
+      type: int
+sinks:
+  - id : Sink
+    uri: file://./target/release/libsink.so
+    input:
+      id: Sink
+      type: int
+links:
+- from:
+    node : Source
+    output : Source
+  to:
+    node : Sink
+    input : Sink
+mapping:
+  Source: runtime-upstream
+  Sink: runtime-downstream
+"#;
+
+struct SlowSink {
+    received: Arc<AtomicUsize>,
+    finalized: Arc<AtomicUsize>,
+}
+
+impl Node for SlowSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        self.finalized.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for SlowSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        _input: DataMessage,
+    ) -> ZFResult<()> {
+        async_std::task::sleep(Duration::from_millis(20)).await;
+        self.received.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+// Sends a single value, once triggered.
+struct TriggeredSource {
+    rx: Receiver<()>,
+}
+
+impl Node for TriggeredSource {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Source for TriggeredSource {
+    async fn run(&self, _context: &mut Context, _state: &mut State) -> ZFResult<Data> {
+        self.rx
+            .recv_async()
+            .await
+            .map_err(|e| ZFError::IOError(e.to_string()))?;
+        Ok(Data::from::<ZFUsize>(ZFUsize(2)))
+    }
+}
+
+// Waits for a value on each of its inputs: the first one received is buffered until the other
+// one arrives.
+struct Join;
+
+impl Node for Join {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for Join {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        Ok(tokens
+            .values()
+            .all(|token| matches!(token, InputToken::Ready(_))))
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let mut sum = 0;
+        for input in inputs.values_mut() {
+            sum += input.get_inner_data().try_get::<ZFUsize>()?.0;
+        }
+
+        let mut results = HashMap::new();
+        results.insert(JOIN.into(), Data::from::<ZFUsize>(ZFUsize(sum)));
+        Ok(results)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+fn port(id: &str) -> PortDescriptor {
+    PortDescriptor {
+        port_id: id.into(),
+        port_type: "int".into(),
+    }
+}
+
+async fn single_runtime() {
+    let received = Arc::new(AtomicUsize::new(0));
+    let finalized = Arc::new(AtomicUsize::new(0));
+
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: LazySession::disabled(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow = zenoh_flow::runtime::dataflow::Dataflow::new(ctx, "test".into(), None);
+
+    let source = Arc::new(VecSource::new((0..MESSAGES).collect()));
+    let sink = Arc::new(SlowSink {
+        received: received.clone(),
+        finalized: finalized.clone(),
+    });
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+
+    instance.start_nodes().await.unwrap();
+    instance.start_sources().await.unwrap();
+
+    // The source is much faster than the sink: the messages pile up in the link.
+    async_std::task::sleep(Duration::from_millis(100)).await;
+    assert!(instance.pending_messages() > 0);
+    assert!(!instance.is_idle().await);
+
+    assert!(instance.drain(Duration::from_secs(5)).await.unwrap());
+    assert_eq!(received.load(Ordering::SeqCst), MESSAGES);
+    assert_eq!(instance.pending_messages(), 0);
+
+    for id in instance.get_nodes() {
+        assert!(!instance.is_node_running(&id).await.unwrap());
+        instance.clean_node(&id).await.unwrap();
+    }
+    assert_eq!(finalized.load(Ordering::SeqCst), 1);
+}
+
+async fn buffered_tokens() {
+    let received = Arc::new(AtomicUsize::new(0));
+    let finalized = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = bounded::<()>(1);
+
+    let mut dataflow = Dataflow::new(
+        runtime_context("test-runtime", LazySession::disabled()),
+        "test".into(),
+        None,
+    );
+
+    let left = Arc::new(VecSource::new(vec![1]));
+    dataflow
+        .try_add_static_source(
+            LEFT.into(),
+            None,
+            port(LEFT),
+            left.initialize(&None).unwrap(),
+            left,
+        )
+        .unwrap();
+
+    let right = Arc::new(TriggeredSource { rx });
+    dataflow
+        .try_add_static_source(
+            RIGHT.into(),
+            None,
+            port(RIGHT),
+            right.initialize(&None).unwrap(),
+            right,
+        )
+        .unwrap();
+
+    let join = Arc::new(Join);
+    dataflow
+        .try_add_static_operator(
+            JOIN.into(),
+            vec![port(LEFT), port(RIGHT)],
+            vec![port(JOIN)],
+            None,
+            join.initialize(&None).unwrap(),
+            join,
+        )
+        .unwrap();
+
+    let sink = Arc::new(SlowSink {
+        received: received.clone(),
+        finalized,
+    });
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            port(SINK),
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    for (from, (to, input)) in [
+        (LEFT, (JOIN, LEFT)),
+        (RIGHT, (JOIN, RIGHT)),
+        (JOIN, (SINK, SINK)),
+    ] {
+        dataflow
+            .try_add_link(
+                OutputDescriptor {
+                    node: from.into(),
+                    output: from.into(),
+                },
+                InputDescriptor {
+                    node: to.into(),
+                    input: input.into(),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+
+    instance.start_nodes().await.unwrap();
+    instance.start_sources().await.unwrap();
+
+    // The value of `Left` was taken out of its link but waits, in the operator, for the one of
+    // `Right`: the instance is not idle although no message is pending.
+    async_std::task::sleep(Duration::from_millis(100)).await;
+    assert_eq!(instance.pending_messages(), 0);
+    assert!(!instance.is_idle().await);
+    assert!(!instance.wait_idle(Duration::from_millis(100)).await);
+
+    tx.send_async(()).await.unwrap();
+    assert!(instance.wait_idle(Duration::from_secs(5)).await);
+    assert!(instance.drain(Duration::from_secs(5)).await.unwrap());
+    assert_eq!(received.load(Ordering::SeqCst), 1);
+}
+
+fn runtime_context(name: &str, session: LazySession) -> RuntimeContext {
+    RuntimeContext {
+        session,
+        hlc: async_std::sync::Arc::new(uhlc::HLC::default()),
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: name.into(),
+        runtime_uuid: Uuid::new_v4(),
+        recordings_dir: None,
+    }
+}
+
+async fn multiple_runtimes() {
+    let received = Arc::new(AtomicUsize::new(0));
+    let finalized = Arc::new(AtomicUsize::new(0));
+
+    // The runtimes share a session, the connectors still go through Zenoh.
+    let session = LazySession::new(zenoh::config::Config::default());
+
+    // Only the connectors are loaded from the record, the nodes are static.
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    let mut record = DataFlowRecord::try_from((descriptor, Uuid::new_v4())).unwrap();
+    record.sources.clear();
+    record.sinks.clear();
+
+    let mut upstream = Dataflow::try_new(
+        runtime_context("runtime-upstream", session.clone()),
+        record.clone(),
+    )
+    .unwrap();
+    let source = Arc::new(VecSource::new((0..MESSAGES).collect()));
+    upstream
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    let mut downstream =
+        Dataflow::try_new(runtime_context("runtime-downstream", session), record).unwrap();
+    let sink = Arc::new(SlowSink {
+        received: received.clone(),
+        finalized: finalized.clone(),
+    });
+    downstream
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    let mut upstream = DataflowInstance::try_instantiate(upstream).unwrap();
+    let mut downstream = DataflowInstance::try_instantiate(downstream).unwrap();
+
+    downstream.start_nodes().await.unwrap();
+    upstream.start_nodes().await.unwrap();
+    upstream.start_sources().await.unwrap();
+
+    // The messages pile up in the link of the downstream runtime: draining only the upstream one
+    // would not wait for them.
+    async_std::task::sleep(Duration::from_millis(100)).await;
+    assert!(downstream.pending_messages() > 0);
+    assert_eq!(upstream.pending_messages(), 0);
+
+    // Both runtimes are drained together, as `stop_instance` does.
+    upstream.stop_running_sources().await.unwrap();
+    let (up, down) = (&upstream, &downstream);
+    assert!(wait_all_idle(Duration::from_secs(5), move || async move {
+        Ok(up.is_idle().await && down.is_idle().await)
+    })
+    .await
+    .unwrap());
+    upstream.stop_running_nodes().await.unwrap();
+    downstream.stop_running_nodes().await.unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), MESSAGES);
+    for instance in [&upstream, &downstream] {
+        for id in instance.get_nodes() {
+            assert!(!instance.is_node_running(&id).await.unwrap());
+        }
+    }
+    for id in downstream.get_nodes() {
+        downstream.clean_node(&id).await.unwrap();
+    }
+    assert_eq!(finalized.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn drain_multiple_runtimes() {
+    let h1 = async_std::task::spawn(async move { multiple_runtimes().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
+
+#[test]
+fn drain_waits_for_buffered_tokens() {
+    let h1 = async_std::task::spawn(async move { buffered_tokens().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
+
+#[test]
+fn drain_processes_messages_in_flight() {
+    let h1 = async_std::task::spawn(async move { single_runtime().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
@@ -145,11 +145,12 @@ async fn single_runtime() {
     let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
     let rt_uuid = uuid::Uuid::new_v4();
     let ctx = RuntimeContext {
-        session,
+        session: session.into(),
         hlc,
         loader: Arc::new(Loader::new(LoaderConfig::new())),
         runtime_name: format!("test-runtime-{}", rt_uuid).into(),
         runtime_uuid: rt_uuid,
+        recordings_dir: None,
     };
 
     let mut dataflow =
@@ -0,0 +1,141 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use std::convert::TryFrom;
+use uuid::Uuid;
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::dataflow::record::DataFlowRecord;
+
+static DESCRIPTOR: &str = r#"
+flow: GraphPipeline
+operators:
+  - id : SumOperator
+    uri: file://./target/release/libsum_and_send.dylib
+    inputs:
+      - id: Number
+        type: usize
+    outputs:
+      - id: Sum
+        type: usize
+  - id : Debug
+    uri: file://./target/release/libdebug.dylib
+    inputs:
+      - id: Number
+        type: usize
+    outputs:
+      - id: Sum
+        type: usize
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    output:
+      id: Counter
+      type: usize
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    input:
+      id: Data
+      type: usize
+  - id : DebugSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    input:
+      id: Data
+      type: usize
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : SumOperator
+    input : Number
+- from:
+    node : SumOperator
+    output : Sum
+  to:
+    node : PrintSink
+    input : Data
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : Debug
+    input : Number
+- from:
+    node : Debug
+    output : Sum
+  to:
+    node : DebugSink
+    input : Data
+deadlines:
+- from:
+    node: Counter
+    output: Counter
+  to:
+    node: PrintSink
+    input: Data
+  duration:
+    length: 50
+    unit: ms
+flags:
+- id: debug
+  toggle: false
+  nodes:
+  - Debug
+  - DebugSink
+mapping:
+  Counter: runtime0
+  SumOperator: runtime1
+  Debug: runtime1
+  PrintSink: runtime0
+  DebugSink: runtime0
+"#;
+
+#[test]
+fn descriptor_to_dot_and_mermaid() {
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+
+    let dot = descriptor.to_dot();
+    assert!(dot.starts_with("digraph \"GraphPipeline\""));
+    assert!(dot.contains("subgraph \"cluster_runtime0\""));
+    assert!(dot.contains("subgraph \"cluster_runtime1\""));
+    assert!(dot.contains("SumOperator\\n(Operator)"));
+    assert!(dot.contains("Number: usize"));
+    assert!(dot.contains("E2E deadline: 50ms"));
+    assert!(dot.contains("rounded,dashed"));
+
+    let mermaid = descriptor.to_mermaid();
+    assert!(mermaid.contains("flowchart LR"));
+    assert!(mermaid.contains("subgraph rt_runtime0[\"runtime: runtime0\"]"));
+    assert!(mermaid.contains("E2E deadline: 50ms"));
+    assert!(mermaid.contains("class "));
+}
+
+#[test]
+fn record_to_dot_and_mermaid() {
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    let record = DataFlowRecord::try_from((descriptor, Uuid::nil())).unwrap();
+
+    let dot = record.to_dot();
+    assert!(dot.contains("(ZenohSender)"));
+    assert!(dot.contains("(ZenohReceiver)"));
+    assert!(dot.contains("/zf/data/GraphPipeline/"));
+    // Nodes disabled by a flag are not part of the record.
+    assert!(!dot.contains("DebugSink"));
+
+    let mermaid = record.to_mermaid();
+    assert!(mermaid.contains("ZenohSender"));
+    assert!(mermaid.contains("ZenohReceiver"));
+    assert!(!mermaid.contains("classDef disabled"));
+}
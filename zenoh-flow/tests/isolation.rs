@@ -0,0 +1,202 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use async_std::sync::Arc;
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use zenoh_flow::model::node::OperatorRecord;
+use zenoh_flow::runtime::dataflow::isolation::{run_static_node_host, IsolatedNode};
+use zenoh_flow::runtime::dataflow::loader::{LoaderConfig, NodeHostCommand};
+use zenoh_flow::{
+    default_input_rule, default_output_rule, Configuration, Context, Data, DataMessage, InputToken,
+    LocalDeadlineMiss, Node, NodeOutput, Operator, PortId, State, ZFError, ZFResult, ZFState,
+};
+
+// The byte making the operator crash its host.
+static CRASH: u8 = 0xFF;
+
+static RECORD: &str = r#"
+id: crash
+inputs:
+  - port_id: in
+    port_type: u8
+outputs:
+  - port_id: out
+    port_type: u64
+runtime: runtime
+cicli: []
+"#;
+
+#[derive(Debug)]
+struct Counter(u64);
+
+impl ZFState for Counter {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn try_serialize(&self) -> ZFResult<Option<Vec<u8>>> {
+        Ok(Some(self.0.to_le_bytes().to_vec()))
+    }
+
+    fn try_restore(&mut self, bytes: &[u8]) -> ZFResult<()> {
+        let mut count = [0u8; 8];
+        count.copy_from_slice(bytes);
+        self.0 = u64::from_le_bytes(count);
+        Ok(())
+    }
+}
+
+// Counts its inputs, and aborts the process upon `CRASH`.
+struct CrashOperator;
+
+impl Node for CrashOperator {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        Ok(State::from(Counter(0)))
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for CrashOperator {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        for message in inputs.values_mut() {
+            if message.get_inner_data().try_as_bytes()?.first() == Some(&CRASH) {
+                std::process::abort();
+            }
+        }
+
+        let counter = state.try_get::<Counter>()?;
+        counter.0 += 1;
+        let mut outputs = HashMap::new();
+        outputs.insert(
+            "out".into(),
+            Data::from_bytes(counter.0.to_le_bytes().to_vec()),
+        );
+        Ok(outputs)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+// Executed by the node hosts started by `crash_node_host`: the socket is the last argument.
+#[test]
+fn node_host() {
+    if let Some(socket) = std::env::args().last().filter(|arg| arg.ends_with(".sock")) {
+        let result = async_std::task::block_on(run_static_node_host(
+            Path::new(&socket),
+            Arc::new(CrashOperator),
+        ));
+        std::process::exit(if result.is_ok() { 0 } else { 1 });
+    }
+}
+
+// Executes an iteration with `byte` as input, returns the outputs of `run` if any.
+fn iteration(
+    operator: &IsolatedNode,
+    state: &mut State,
+    byte: u8,
+) -> ZFResult<Option<HashMap<PortId, Data>>> {
+    let mut context = Context::default();
+    let mut tokens = HashMap::new();
+    tokens.insert(
+        PortId::from("in"),
+        InputToken::from(DataMessage::new(
+            Data::from_bytes(vec![byte]),
+            uhlc::HLC::default().new_timestamp(),
+            vec![],
+        )),
+    );
+    if !operator.input_rule(&mut context, state, &mut tokens)? {
+        return Ok(None);
+    }
+    Operator::run(operator, &mut context, state, &mut HashMap::new()).map(Some)
+}
+
+fn count(outputs: &HashMap<PortId, Data>) -> u64 {
+    let mut count = [0u8; 8];
+    count.copy_from_slice(&outputs[&PortId::from("out")].try_as_bytes().unwrap());
+    u64::from_le_bytes(count)
+}
+
+#[test]
+fn crash_node_host() {
+    // The host is this test binary, only executing `node_host`: the socket appended to the
+    // arguments is the value of `--skip`.
+    let command = NodeHostCommand {
+        program: std::env::current_exe().unwrap(),
+        args: vec!["node_host".into(), "--exact".into(), "--skip".into()],
+    };
+    let record: OperatorRecord = serde_yaml::from_str(RECORD).unwrap();
+    let operator = IsolatedNode::operator(command, record, LoaderConfig::new());
+    let mut state = operator.initialize(&None).unwrap();
+
+    let outputs = iteration(&operator, &mut state, 0).unwrap().unwrap();
+    assert_eq!(count(&outputs), 1);
+    assert_eq!(
+        state.try_serialize().unwrap(),
+        Some(1u64.to_le_bytes().to_vec())
+    );
+
+    // The host crashes: the iteration fails, the host is restarted in the background.
+    assert!(matches!(
+        iteration(&operator, &mut state, CRASH),
+        Err(ZFError::IOError(_))
+    ));
+
+    let started = Instant::now();
+    let outputs = loop {
+        match iteration(&operator, &mut state, 0) {
+            Ok(outputs) => break outputs.unwrap(),
+            Err(ZFError::IOError(_)) if started.elapsed() < Duration::from_secs(10) => {
+                std::thread::sleep(Duration::from_millis(100))
+            }
+            Err(e) => panic!("The node host was not restarted: {:?}", e),
+        }
+    };
+    // The new host restored the checkpoint.
+    assert_eq!(count(&outputs), 2);
+
+    assert!(operator.finalize(&mut state).is_ok());
+}
@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use zenoh_flow::runtime::dataflow::loader::{
+    Loader, LoaderConfig, EXT_FILE_EXTENSION, WASM_FILE_EXTENSION,
+};
+use zenoh_flow::ZFError;
+
+static EXTENSIONS: &str = r#"
+extensions:
+  - name: python
+    file_extension: py
+    source_lib: ./target/release/libpy_source.so
+    sink_lib: ./target/release/libpy_sink.so
+    operator_lib: ./target/release/libpy_op.so
+    config_lib_key: python-script
+  - name: lua
+    file_extension: lua
+    source_lib: ./target/release/liblua_source.so
+    sink_lib: ./target/release/liblua_sink.so
+    operator_lib: ./target/release/liblua_op.so
+    config_lib_key: lua-script
+"#;
+
+#[test]
+fn reload_extensions() {
+    let loader = Loader::new(LoaderConfig::new());
+
+    let config: LoaderConfig = serde_yaml::from_str(EXTENSIONS).unwrap();
+    let changed = loader.reload_extensions(config.clone());
+    assert_eq!(changed, vec!["lua".to_string(), "python".to_string()]);
+    assert!(loader
+        .get_config()
+        .get_extension_by_name("python")
+        .is_some());
+
+    // Reloading the same extensions changes nothing.
+    assert!(loader.reload_extensions(config.clone()).is_empty());
+
+    let mut config = config;
+    let python = config.remove_extension("python").unwrap();
+    assert_eq!(loader.reload_extensions(config.clone()), vec!["python"]);
+    assert!(loader
+        .get_config()
+        .get_extension_by_name("python")
+        .is_none());
+    assert!(loader
+        .get_config()
+        .get_extension_by_file_extension("lua")
+        .is_some());
+
+    config.try_add_extension(python).unwrap();
+    assert_eq!(loader.reload_extensions(config), vec!["python"]);
+    assert!(loader
+        .get_config()
+        .get_extension_by_file_extension("py")
+        .is_some());
+}
+
+#[test]
+fn add_conflicting_extensions() {
+    let mut config: LoaderConfig = serde_yaml::from_str(EXTENSIONS).unwrap();
+    let python = config.get_extension_by_name("python").unwrap().clone();
+
+    // Same name.
+    let mut duplicate = python.clone();
+    duplicate.file_extension = "py3".to_string();
+    assert!(matches!(
+        config.try_add_extension(duplicate),
+        Err(ZFError::Duplicate)
+    ));
+
+    // Same file extension, under another name.
+    let mut conflicting = python;
+    conflicting.name = "python3".to_string();
+    match config.try_add_extension(conflicting) {
+        Err(ZFError::InvalidData(e)) => assert!(e.contains("python3") && e.contains("< py >")),
+        _ => panic!("Two extensions should not handle the same file extension"),
+    }
+    assert_eq!(config.get_extensions().len(), 2);
+}
+
+#[test]
+fn validate_extensions() {
+    let config: LoaderConfig = serde_yaml::from_str(EXTENSIONS).unwrap();
+    let mut python = config.get_extension_by_name("python").unwrap().clone();
+
+    // The libraries do not exist.
+    match python.validate() {
+        Err(ZFError::LoadingError(e)) => assert!(e.contains("libpy_source.so")),
+        _ => panic!("An extension with missing libraries should be invalid"),
+    }
+
+    python.name = "../python".to_string();
+    assert!(matches!(python.validate(), Err(ZFError::LoadingError(_))));
+
+    python.name = "python".to_string();
+    python.file_extension = EXT_FILE_EXTENSION.to_string();
+    assert!(matches!(python.validate(), Err(ZFError::LoadingError(_))));
+
+    // WebAssembly nodes are handled by the runtime itself.
+    python.file_extension = WASM_FILE_EXTENSION.to_string();
+    assert!(matches!(python.validate(), Err(ZFError::LoadingError(_))));
+}
@@ -0,0 +1,473 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+use types::{VecSink, VecSource};
+use uuid::Uuid;
+use zenoh_flow::model::connector::ZFConnectorKind;
+use zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor;
+use zenoh_flow::model::dataflow::record::DataFlowRecord;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::liveliness::{RuntimeFailurePolicy, RuntimeRestartPolicy};
+use zenoh_flow::runtime::session::LazySession;
+use zenoh_flow::runtime::{RuntimeContext, RuntimeStatus, RuntimeStatusKind};
+use zenoh_flow::{Node, NodeId, PortId, RuntimeId, ZFError};
+
+static DESCRIPTOR: &str = r#"
+flow: Migration
+operators:
+  - id : Doubler
+    uri: file://./target/release/libdoubler.so
+    inputs:
+      - id: Number
+        type: usize
+    outputs:
+      - id: Double
+        type: usize
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.so
+    output:
+      id: Counter
+      type: usize
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.so
+    input:
+      id: Data
+      type: usize
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : Doubler
+    input : Number
+- from:
+    node : Doubler
+    output : Double
+  to:
+    node : PrintSink
+    input : Data
+mapping:
+  Counter: runtime0
+  Doubler: runtime0
+  PrintSink: runtime1
+on_runtime_failure:
+  remap:
+    runtimes:
+      - runtime2
+on_runtime_restart: prepare
+"#;
+
+fn record() -> DataFlowRecord {
+    let descriptor = DataFlowDescriptor::from_yaml(DESCRIPTOR).unwrap();
+    DataFlowRecord::try_from((descriptor, Uuid::nil())).unwrap()
+}
+
+#[test]
+fn node_links_merge_connectors() {
+    let record = record();
+    assert_eq!(record.connectors.len(), 2);
+    assert_eq!(record.links.len(), 3);
+
+    let links = record.get_node_links().unwrap();
+    assert_eq!(links.len(), 2);
+    assert!(links.iter().any(|l| l.from.node.as_ref() == "Counter"
+        && l.from.output.as_ref() == "Counter"
+        && l.to.node.as_ref() == "Doubler"
+        && l.to.input.as_ref() == "Number"));
+    assert!(links.iter().any(|l| l.from.node.as_ref() == "Doubler"
+        && l.from.output.as_ref() == "Double"
+        && l.to.node.as_ref() == "PrintSink"
+        && l.to.input.as_ref() == "Data"));
+}
+
+#[test]
+fn migrate_node_recreates_connectors() {
+    let record = record();
+    let runtime1: RuntimeId = "runtime1".into();
+    let migrated = record
+        .try_migrate_node(&"Doubler".into(), runtime1.clone())
+        .unwrap();
+
+    assert_eq!(
+        migrated.find_node_runtime("Doubler"),
+        Some(runtime1.clone())
+    );
+    // The original record is left untouched.
+    assert_eq!(record.find_node_runtime("Doubler"), Some("runtime0".into()));
+
+    assert_eq!(migrated.connectors.len(), 2);
+    let sender = migrated
+        .connectors
+        .iter()
+        .find(|c| c.kind == ZFConnectorKind::Sender)
+        .unwrap();
+    let receiver = migrated
+        .connectors
+        .iter()
+        .find(|c| c.kind == ZFConnectorKind::Receiver)
+        .unwrap();
+    assert_eq!(sender.runtime.as_ref(), "runtime0");
+    assert_eq!(receiver.runtime, runtime1);
+    assert_eq!(sender.resource, receiver.resource);
+    assert!(sender.resource.ends_with("/Counter/Counter"));
+
+    // The key expression only depends on the upstream node and port.
+    let before = record
+        .connectors
+        .iter()
+        .find(|c| c.kind == ZFConnectorKind::Sender)
+        .unwrap();
+    assert!(before.resource.ends_with("/Doubler/Double"));
+    assert_eq!(
+        before
+            .resource
+            .replace("/Doubler/Double", "/Counter/Counter"),
+        sender.resource
+    );
+
+    assert_eq!(
+        migrated.get_node_links().unwrap().len(),
+        record.get_node_links().unwrap().len()
+    );
+}
+
+#[test]
+fn migrate_node_removes_connectors() {
+    let record = record();
+    let runtime0: RuntimeId = "runtime0".into();
+    let migrated = record
+        .try_migrate_node(&"PrintSink".into(), runtime0.clone())
+        .unwrap();
+
+    assert!(migrated.connectors.is_empty());
+    assert_eq!(migrated.links.len(), 2);
+    assert_eq!(migrated.get_runtimes(), vec![runtime0]);
+}
+
+#[test]
+fn migrate_unknown_node() {
+    let record = record();
+    let unknown: NodeId = "Unknown".into();
+    match record.try_migrate_node(&unknown, "runtime1".into()) {
+        Err(ZFError::NodeNotFound(id)) => assert_eq!(id, unknown),
+        _ => panic!("Migrating an unknown node should fail"),
+    }
+}
+
+#[test]
+fn remap_orphaned_nodes() {
+    let record = record();
+    assert_eq!(
+        record.on_runtime_failure,
+        Some(RuntimeFailurePolicy::Remap {
+            runtimes: vec!["runtime2".into()]
+        })
+    );
+    assert_eq!(
+        record.on_runtime_restart,
+        Some(RuntimeRestartPolicy::Prepare)
+    );
+
+    let mut orphans = record.get_runtime_nodes("runtime0");
+    orphans.sort();
+    assert_eq!(
+        orphans,
+        vec![NodeId::from("Counter"), NodeId::from("Doubler")]
+    );
+
+    let runtime2: RuntimeId = "runtime2".into();
+    let mapping: HashMap<NodeId, RuntimeId> = orphans
+        .into_iter()
+        .map(|node_id| (node_id, runtime2.clone()))
+        .collect();
+    let remapped = record.try_remap_nodes(&mapping).unwrap();
+
+    assert!(remapped.get_runtime_nodes("runtime0").is_empty());
+    assert_eq!(remapped.get_runtime_nodes("runtime2").len(), 2);
+    assert!(!remapped.get_runtimes().contains(&"runtime0".into()));
+    assert_eq!(remapped.connectors.len(), 2);
+    assert!(remapped
+        .connectors
+        .iter()
+        .any(|c| c.kind == ZFConnectorKind::Sender && c.runtime == runtime2));
+}
+
+#[test]
+fn runtime_policies_default_to_none() {
+    let descriptor = DataFlowDescriptor::from_yaml(
+        &DESCRIPTOR[..DESCRIPTOR.find("on_runtime_failure").unwrap()],
+    )
+    .unwrap();
+    let record = DataFlowRecord::try_from((descriptor, Uuid::nil())).unwrap();
+    assert_eq!(record.on_runtime_failure, None);
+    assert_eq!(record.on_runtime_restart, None);
+    assert_eq!(
+        RuntimeFailurePolicy::default(),
+        RuntimeFailurePolicy::Ignore
+    );
+}
+
+static RUNTIME: &str = "migration-runtime";
+static SOURCE: &str = "Source";
+static SINK: &str = "Sink";
+
+const MESSAGES: usize = 10;
+
+static INSTANCE_DESCRIPTOR: &str = r#"
+flow: Migration
+sources:
+  - id : Source
+    uri: file://./target/release/libcounter_source.so
+    output:
+      id: Source
+      type: int
+sinks:
+  - id : Sink
+    uri: file://./target/release/libgeneric_sink.so
+    input:
+      id: Sink
+      type: int
+links:
+- from:
+    node : Source
+    output : Source
+  to:
+    node : Sink
+    input : Sink
+  size: 16
+  queueing_policy: drop
+  priority: 1
+mapping:
+  Source: migration-runtime
+  Sink: migration-runtime
+"#;
+
+fn instance_record() -> DataFlowRecord {
+    let descriptor = DataFlowDescriptor::from_yaml(INSTANCE_DESCRIPTOR).unwrap();
+    DataFlowRecord::try_from((descriptor, Uuid::nil())).unwrap()
+}
+
+#[test]
+fn migrate_node_keeps_link_attributes() {
+    let record = instance_record();
+    let migrated = record
+        .try_migrate_node(&SINK.into(), "runtime1".into())
+        .unwrap();
+    assert_eq!(migrated.connectors.len(), 2);
+
+    // The link between the receiver and the sink keeps the attributes of the declared link.
+    let receiver_link = migrated
+        .links
+        .iter()
+        .find(|l| l.to.node.as_ref() == SINK)
+        .unwrap();
+    assert_eq!(receiver_link.size, Some(16));
+
+    // They are found back when the connectors are merged, and across migrations.
+    let back = migrated
+        .try_migrate_node(&SINK.into(), RUNTIME.into())
+        .unwrap();
+    for remapped in [&migrated, &back] {
+        let links = remapped.get_node_links().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].size, Some(16));
+        assert_eq!(links[0].queueing_policy.as_deref(), Some("drop"));
+        assert_eq!(links[0].priority, Some(1));
+    }
+    assert!(back.connectors.is_empty());
+    assert_eq!(back.links[0].size, Some(16));
+}
+
+fn build_instance(ctx: RuntimeContext, sink_tx: flume::Sender<()>) -> DataflowInstance {
+    let mut dataflow = zenoh_flow::runtime::dataflow::Dataflow::new(ctx, "Migration".into(), None);
+
+    let source = Arc::new(VecSource::new((0..MESSAGES).collect()));
+    let sink = Arc::new(VecSink::new(sink_tx, (0..MESSAGES).collect()));
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    DataflowInstance::try_instantiate(dataflow).unwrap()
+}
+
+async fn apply_migration_record() {
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let ctx = RuntimeContext {
+        session: LazySession::disabled(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: RUNTIME.into(),
+        runtime_uuid: Uuid::new_v4(),
+        recordings_dir: None,
+    };
+    let sink_id: NodeId = SINK.into();
+    let migrated = instance_record()
+        .try_migrate_node(&sink_id, "runtime1".into())
+        .unwrap();
+
+    // The sink did not start: the messages waiting for it go to the sender connector replacing
+    // it, and there is nothing to skip on the other runtime.
+    let (tx, _rx) = flume::bounded::<()>(1);
+    let mut waiting = build_instance(ctx.clone(), tx);
+    waiting.start_node(&SOURCE.into()).await.unwrap();
+    async_std::task::sleep(Duration::from_millis(100)).await;
+    assert_eq!(waiting.pending_messages(), MESSAGES);
+
+    let changes = waiting.try_apply_record(&migrated).await.unwrap();
+    assert_eq!(changes.removed, vec![sink_id.clone()]);
+    assert_eq!(changes.added.len(), 1);
+    assert_eq!(waiting.get_connectors(), changes.added);
+    assert!(changes.watermarks.is_empty());
+    assert_eq!(waiting.pending_messages(), MESSAGES);
+    waiting.stop_running_nodes().await.unwrap();
+
+    // The sink received all the messages: its copy on the other runtime skips them.
+    let (tx, rx) = flume::bounded::<()>(1);
+    let mut received = build_instance(ctx, tx);
+    received.start_node(&sink_id).await.unwrap();
+    received.start_node(&SOURCE.into()).await.unwrap();
+    rx.recv_async().await.unwrap();
+
+    let changes = received.try_apply_record(&migrated).await.unwrap();
+    assert_eq!(changes.removed, vec![sink_id.clone()]);
+    assert_eq!(changes.watermarks.len(), 1);
+    let watermarks = &changes.watermarks[0];
+    assert_eq!(watermarks.node_id, sink_id);
+    assert_eq!(watermarks.instance_id, received.get_uuid());
+    let input: PortId = SINK.into();
+    assert!(watermarks.inputs.contains_key(&input));
+    assert_eq!(received.pending_messages(), 0);
+    received.stop_running_nodes().await.unwrap();
+}
+
+#[test]
+fn apply_record_forwards_waiting_messages() {
+    let h1 = async_std::task::spawn(async move { apply_migration_record().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
+
+async fn count_migrated_nodes() {
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let ctx = RuntimeContext {
+        session: LazySession::disabled(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: RUNTIME.into(),
+        runtime_uuid: Uuid::new_v4(),
+        recordings_dir: None,
+    };
+    let mut status = RuntimeStatus {
+        id: ctx.runtime_uuid,
+        status: RuntimeStatusKind::Ready,
+        running_flows: 0,
+        running_operators: 0,
+        running_sources: 0,
+        running_sinks: 0,
+        running_connectors: 0,
+    };
+    let migrated = instance_record()
+        .try_migrate_node(&SINK.into(), "runtime1".into())
+        .unwrap();
+
+    let (tx, _rx) = flume::bounded::<()>(1);
+    let mut instance = build_instance(ctx, tx);
+    let before = instance.get_started_nodes();
+    instance.start_nodes().await.unwrap();
+    instance.start_sources().await.unwrap();
+    status.update_running_nodes(&before, &instance.get_started_nodes());
+    assert_eq!(status.running_sources, 1);
+    assert_eq!(status.running_sinks, 1);
+
+    // The sink is replaced by a sender connector, started as the daemon does.
+    let before = instance.get_started_nodes();
+    let changes = instance.try_apply_record(&migrated).await.unwrap();
+    for id in changes.added.iter() {
+        instance.start_node(id).await.unwrap();
+    }
+    status.update_running_nodes(&before, &instance.get_started_nodes());
+    assert_eq!(status.running_sources, 1);
+    assert_eq!(status.running_sinks, 0);
+    assert_eq!(status.running_connectors, 1);
+
+    let before = instance.get_started_nodes();
+    instance.stop_running_nodes().await.unwrap();
+    let after = instance.get_started_nodes();
+    assert!(after.is_empty());
+    status.update_running_nodes(&before, &after);
+    assert_eq!(status.running_sources, 0);
+    assert_eq!(status.running_sinks, 0);
+    assert_eq!(status.running_connectors, 0);
+
+    // Counting the same nodes as stopped twice does not underflow.
+    status.update_running_nodes(&before, &after);
+    assert_eq!(status.running_sources, 0);
+    assert_eq!(status.running_connectors, 0);
+}
+
+#[test]
+fn migrate_node_then_stop_counts_running_nodes() {
+    let h1 = async_std::task::spawn(async move { count_migrated_nodes().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
@@ -0,0 +1,174 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use types::VecSource;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::session::LazySession;
+use zenoh_flow::runtime::watchdog::NodeHealth;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::{
+    zf_empty_state, Configuration, Context, DataMessage, Node, Sink, State, ZFResult,
+};
+
+static SOURCE: &str = "Source";
+static SINK: &str = "Sink";
+
+const MESSAGES: usize = 10;
+
+struct CountSink {
+    received: Arc<AtomicUsize>,
+}
+
+impl Node for CountSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for CountSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        _input: DataMessage,
+    ) -> ZFResult<()> {
+        self.received.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+async fn single_runtime() {
+    let received = Arc::new(AtomicUsize::new(0));
+
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: LazySession::disabled(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow = zenoh_flow::runtime::dataflow::Dataflow::new(ctx, "test".into(), None);
+
+    let source = Arc::new(VecSource::new((0..MESSAGES).collect()));
+    let sink = Arc::new(CountSink {
+        received: received.clone(),
+    });
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+    let sink_id = SINK.into();
+
+    instance.start_nodes().await.unwrap();
+    instance.pause_node(&sink_id).await.unwrap();
+    instance.start_sources().await.unwrap();
+
+    async_std::task::sleep(Duration::from_millis(100)).await;
+
+    // The paused sink holds at most the message it was waiting for, the others wait in the link.
+    assert_eq!(received.load(Ordering::SeqCst), 0);
+    assert!(instance.pending_messages() >= MESSAGES - 1);
+    assert!(instance.is_node_paused(&sink_id).await.unwrap());
+    assert_eq!(
+        instance.get_node_health(&sink_id, None).await.unwrap(),
+        NodeHealth::Paused
+    );
+
+    instance.resume_node(&sink_id).await.unwrap();
+    async_std::task::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(received.load(Ordering::SeqCst), MESSAGES);
+    assert_eq!(instance.pending_messages(), 0);
+    assert_eq!(
+        instance.get_node_health(&sink_id, None).await.unwrap(),
+        NodeHealth::Running
+    );
+
+    instance.pause().await.unwrap();
+    for id in instance.get_nodes() {
+        assert!(instance.is_node_paused(&id).await.unwrap());
+    }
+
+    instance.resume().await.unwrap();
+    for id in instance.get_nodes() {
+        assert!(!instance.is_node_paused(&id).await.unwrap());
+    }
+
+    instance.stop_sources().await.unwrap();
+    instance.stop_nodes().await.unwrap();
+}
+
+#[test]
+fn pause_keeps_queued_messages() {
+    let h1 = async_std::task::spawn(async move { single_runtime().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
@@ -0,0 +1,255 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use futures::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use types::{VecSink, VecSource, ZFUsize};
+use zenoh::net::protocol::io::SplitBuffer;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::dataflow::Dataflow;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::{
+    default_input_rule, default_output_rule, zf_empty_state, Configuration, Context,
+    ControlMessage, Data, DataMessage, InputToken, LocalDeadlineMiss, Message, Node, NodeOutput,
+    Operator, PortId, State, ZFError, ZFResult,
+};
+
+static SOURCE: &str = "Source";
+static OPERATOR: &str = "Doubler";
+static DOUBLE: &str = "double";
+static DEBUG: &str = "debug";
+
+struct Doubler;
+
+impl Node for Doubler {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for Doubler {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        let mut input = inputs.remove(SOURCE).unwrap();
+        let value = input.get_inner_data().try_get::<ZFUsize>()?.0;
+
+        let mut results: HashMap<PortId, Data> = HashMap::with_capacity(2);
+        results.insert(DOUBLE.into(), Data::from::<ZFUsize>(ZFUsize(value * 2)));
+        results.insert(DEBUG.into(), Data::from::<ZFUsize>(ZFUsize(value)));
+        Ok(results)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        _deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        default_output_rule(state, outputs)
+    }
+}
+
+async fn record_operator_output() {
+    let session = Arc::new(zenoh::open(zenoh::config::Config::default()).await.unwrap());
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: session.clone().into(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow = Dataflow::new(ctx.clone(), "test".into(), None);
+
+    let (tx_double, rx_double) = flume::bounded::<()>(1);
+    let (tx_debug, rx_debug) = flume::bounded::<()>(1);
+
+    let source = Arc::new(VecSource::new(vec![3, 2, 1]));
+    let operator = Arc::new(Doubler);
+    let sink_double = Arc::new(VecSink::new(tx_double, vec![6, 4, 2]));
+    let sink_debug = Arc::new(VecSink::new(tx_debug, vec![3, 2, 1]));
+
+    let port = |port_id: &str| PortDescriptor {
+        port_id: port_id.into(),
+        port_type: "int".into(),
+    };
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            port(SOURCE),
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_operator(
+            OPERATOR.into(),
+            vec![port(SOURCE)],
+            vec![port(DOUBLE), port(DEBUG)],
+            None,
+            operator.initialize(&None).unwrap(),
+            operator,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            DOUBLE.into(),
+            port(DOUBLE),
+            sink_double.initialize(&None).unwrap(),
+            sink_double,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            DEBUG.into(),
+            port(DEBUG),
+            sink_debug.initialize(&None).unwrap(),
+            sink_debug,
+        )
+        .unwrap();
+
+    let links = [
+        (SOURCE, SOURCE, OPERATOR, SOURCE),
+        (OPERATOR, DOUBLE, DOUBLE, DOUBLE),
+        (OPERATOR, DEBUG, DEBUG, DEBUG),
+    ];
+    for (from, output, to, input) in links {
+        dataflow
+            .try_add_link(
+                OutputDescriptor {
+                    node: from.into(),
+                    output: output.into(),
+                },
+                InputDescriptor {
+                    node: to.into(),
+                    input: input.into(),
+                },
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+
+    let mut subscriber = session.subscribe("/zf/record/**").await.unwrap();
+
+    // The source is started last: nothing is sent before the recording starts.
+    for id in [OPERATOR, DOUBLE, DEBUG] {
+        instance.start_node(&id.into()).await.unwrap();
+    }
+
+    // A sink does not have outputs, the operator has two.
+    assert!(instance
+        .start_recording(&DOUBLE.into(), None)
+        .await
+        .is_err());
+    assert!(instance
+        .start_recording(&OPERATOR.into(), None)
+        .await
+        .is_err());
+
+    let key_expr = instance
+        .start_recording(&OPERATOR.into(), Some(DOUBLE.into()))
+        .await
+        .unwrap();
+    assert!(key_expr.contains(&format!("/{}/{}/", OPERATOR, DOUBLE)));
+    assert!(matches!(
+        instance
+            .start_recording(&OPERATOR.into(), Some(DOUBLE.into()))
+            .await,
+        Err(ZFError::AlreadyRecording)
+    ));
+
+    instance.start_node(&SOURCE.into()).await.unwrap();
+
+    rx_double.recv_async().await.unwrap();
+    rx_debug.recv_async().await.unwrap();
+
+    assert_eq!(
+        instance
+            .stop_recording(&OPERATOR.into(), Some(DOUBLE.into()))
+            .await
+            .unwrap(),
+        key_expr
+    );
+
+    let mut recorded = vec![];
+    loop {
+        let sample = subscriber.receiver().next().await.unwrap();
+        assert_eq!(sample.key_expr.as_str(), key_expr);
+        let message: Message =
+            zenoh_flow::bincode::deserialize(&sample.value.payload.contiguous()).unwrap();
+        match message {
+            Message::Control(ControlMessage::RecordingStart(_)) => {
+                assert!(recorded.is_empty())
+            }
+            Message::Control(ControlMessage::RecordingStop(_)) => break,
+            Message::Data(mut data) => {
+                recorded.push(data.get_inner_data().try_get::<ZFUsize>().unwrap().0)
+            }
+        }
+    }
+    assert_eq!(recorded, vec![2, 4, 6]);
+
+    for id in &instance.get_nodes() {
+        instance.stop_node(id).await.unwrap()
+    }
+}
+
+#[test]
+fn record_operator() {
+    let _ = env_logger::try_init();
+
+    let h = async_std::task::spawn(async move { record_operator_output().await });
+
+    assert!(
+        async_std::task::block_on(async_std::future::timeout(Duration::from_secs(10), h)).is_ok(),
+        "Deadlock detected."
+    );
+}
@@ -12,8 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use zenoh_flow::serde::{Deserialize, Serialize};
 use zenoh_flow::zenoh_flow_derive::ZFState;
-use zenoh_flow::State;
+use zenoh_flow::{State, ZFError};
 
 #[derive(Debug, ZFState, Clone)]
 struct TestState {
@@ -22,6 +23,14 @@ struct TestState {
     pub field3: f64,
 }
 
+#[derive(Debug, ZFState, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "zenoh_flow::serde")]
+#[zf_state(checkpoint)]
+struct CheckpointState {
+    pub counter: u64,
+    pub label: String,
+}
+
 #[test]
 fn state_wrapping_unwrapping() {
     let test_state = TestState {
@@ -47,3 +56,46 @@ fn state_wrapping_unwrapping() {
     assert_eq!(unwrapped_state.field2, test_state.field2);
     assert!((unwrapped_state.field3 - test_state.field3).abs() < f64::EPSILON);
 }
+
+#[test]
+fn state_checkpoint_restore() {
+    let mut wrapped_state = State::from(CheckpointState {
+        counter: 42,
+        label: String::from("before"),
+    });
+
+    let bytes = wrapped_state.try_serialize().unwrap().unwrap();
+
+    let state = wrapped_state.try_get::<CheckpointState>().unwrap();
+    state.counter = 0;
+    state.label = String::from("after");
+
+    wrapped_state.try_restore(&bytes).unwrap();
+    assert_eq!(
+        wrapped_state.try_get::<CheckpointState>().unwrap(),
+        &mut CheckpointState {
+            counter: 42,
+            label: String::from("before"),
+        }
+    );
+
+    assert!(matches!(
+        wrapped_state.try_restore(&[0xff]),
+        Err(ZFError::DeseralizationError)
+    ));
+}
+
+#[test]
+fn state_without_checkpoint() {
+    let mut wrapped_state = State::from(TestState {
+        field1: 16u8,
+        field2: String::from("TestString"),
+        field3: 123.456f64,
+    });
+
+    assert!(wrapped_state.try_serialize().unwrap().is_none());
+    assert!(matches!(
+        wrapped_state.try_restore(&[]),
+        Err(ZFError::Unsupported)
+    ));
+}
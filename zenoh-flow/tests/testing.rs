@@ -0,0 +1,225 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use std::collections::HashMap;
+use std::time::Duration;
+use types::{CounterState, VecSink, VecSource, ZFUsize};
+use zenoh_flow::model::deadline::DeadlineMissPolicy;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::deadline::E2EDeadline;
+use zenoh_flow::testing::{local_deadline_miss, OperatorHarness, SinkHarness, SourceHarness};
+use zenoh_flow::{
+    default_input_rule, Configuration, Context, Data, DataMessage, InputToken, LocalDeadlineMiss,
+    Node, NodeOutput, Operator, PortId, State, ZFResult,
+};
+
+static INPUT_A: &str = "a";
+static INPUT_B: &str = "b";
+static OUTPUT: &str = "sum";
+
+struct Adder;
+
+impl Node for Adder {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        Ok(CounterState::new_as_state())
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+impl Operator for Adder {
+    fn input_rule(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        tokens: &mut HashMap<PortId, InputToken>,
+    ) -> ZFResult<bool> {
+        default_input_rule(state, tokens)
+    }
+
+    fn run(
+        &self,
+        _context: &mut Context,
+        state: &mut State,
+        inputs: &mut HashMap<PortId, DataMessage>,
+    ) -> ZFResult<HashMap<PortId, Data>> {
+        state.try_get::<CounterState>()?.add_fetch();
+
+        let mut sum = 0;
+        for message in inputs.values_mut() {
+            sum += message.get_inner_data().try_get::<ZFUsize>()?.0;
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert(OUTPUT.into(), Data::from::<ZFUsize>(ZFUsize(sum)));
+        Ok(outputs)
+    }
+
+    fn output_rule(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        outputs: HashMap<PortId, Data>,
+        deadline_miss: Option<LocalDeadlineMiss>,
+    ) -> ZFResult<HashMap<PortId, NodeOutput>> {
+        // Nothing is sent if the run was too long.
+        if deadline_miss.is_some() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(outputs
+            .into_iter()
+            .map(|(port_id, data)| (port_id, NodeOutput::Data(data)))
+            .collect())
+    }
+}
+
+fn adder_harness() -> OperatorHarness {
+    OperatorHarness::try_new(Adder, None, &[INPUT_A.into(), INPUT_B.into()])
+        .unwrap()
+        .with_id("Adder".into())
+}
+
+fn sum_of(mut outputs: HashMap<PortId, NodeOutput>) -> usize {
+    match outputs.remove(OUTPUT).unwrap() {
+        NodeOutput::Data(mut data) => data.try_get::<ZFUsize>().unwrap().0,
+        NodeOutput::Control(_) => panic!("Unexpected control message"),
+    }
+}
+
+#[test]
+fn operator_harness_step() {
+    let mut harness = adder_harness();
+
+    assert!(harness
+        .send(&INPUT_A.into(), Data::from::<ZFUsize>(ZFUsize(1)))
+        .unwrap());
+    assert!(harness.step().unwrap().is_none());
+
+    harness
+        .send(&INPUT_B.into(), Data::from::<ZFUsize>(ZFUsize(2)))
+        .unwrap();
+    let outputs = harness.step().unwrap().unwrap();
+    assert_eq!(sum_of(outputs), 3);
+
+    // The tokens were consumed.
+    assert!(matches!(
+        harness.token(&INPUT_A.into()),
+        Some(InputToken::Pending)
+    ));
+    assert!(harness.step().unwrap().is_none());
+
+    assert_eq!(harness.state::<CounterState>().unwrap().add_fetch(), 2);
+    harness.finalize().unwrap();
+}
+
+#[test]
+fn operator_harness_unknown_input() {
+    let mut harness = adder_harness();
+
+    assert!(harness
+        .send(&"c".into(), Data::from::<ZFUsize>(ZFUsize(1)))
+        .is_err());
+}
+
+#[test]
+fn operator_harness_local_deadline_miss() {
+    let mut harness = adder_harness();
+
+    harness
+        .send(&INPUT_A.into(), Data::from::<ZFUsize>(ZFUsize(1)))
+        .unwrap();
+    harness
+        .send(&INPUT_B.into(), Data::from::<ZFUsize>(ZFUsize(2)))
+        .unwrap();
+    assert!(harness.input_rule().unwrap());
+
+    let outputs = harness.run().unwrap();
+    let miss = local_deadline_miss(Duration::from_millis(1), Duration::from_millis(5));
+    assert!(harness.output_rule(outputs, Some(miss)).unwrap().is_empty());
+}
+
+#[test]
+fn operator_harness_end_to_end_deadline_miss() {
+    let mut harness = adder_harness();
+    let start = harness.timestamp_at(Duration::from_secs(10));
+    harness.set_now(Some(
+        harness.timestamp_at(Duration::from_secs(10) + Duration::from_millis(50)),
+    ));
+
+    let deadline = |on_miss| E2EDeadline {
+        duration: Duration::from_millis(10),
+        from: OutputDescriptor {
+            node: "Source".into(),
+            output: "out".into(),
+        },
+        to: InputDescriptor {
+            node: "Adder".into(),
+            input: INPUT_A.into(),
+        },
+        start,
+        on_miss,
+    };
+
+    // Missed, but delivered with the miss.
+    let message = DataMessage::new(
+        Data::from::<ZFUsize>(ZFUsize(1)),
+        start,
+        vec![deadline(None)],
+    );
+    assert!(harness.send_message(&INPUT_A.into(), message).unwrap());
+    match harness.token(&INPUT_A.into()) {
+        Some(InputToken::Ready(token)) => {
+            assert_eq!(token.get_missed_end_to_end_deadlines().len(), 1)
+        }
+        _ => panic!("The token should be ready"),
+    }
+
+    // Missed and dropped.
+    let mut harness = adder_harness();
+    harness.set_now(Some(
+        harness.timestamp_at(Duration::from_secs(10) + Duration::from_millis(50)),
+    ));
+    let message = DataMessage::new(
+        Data::from::<ZFUsize>(ZFUsize(1)),
+        start,
+        vec![deadline(Some(DeadlineMissPolicy::Drop))],
+    );
+    assert!(!harness.send_message(&INPUT_A.into(), message).unwrap());
+    assert!(matches!(
+        harness.token(&INPUT_A.into()),
+        Some(InputToken::Pending)
+    ));
+}
+
+#[async_std::test]
+async fn source_and_sink_harness() {
+    let mut source = SourceHarness::try_new(VecSource::new(vec![1, 2, 3]), None).unwrap();
+    let (tx, rx) = flume::bounded::<()>(1);
+    let mut sink = SinkHarness::try_new(VecSink::new(tx, vec![1, 2, 3]), None).unwrap();
+
+    for _ in 0..3 {
+        let data = source.run().await.unwrap();
+        sink.run(data).await.unwrap();
+    }
+    // The sink received all the expected values.
+    assert!(rx.try_recv().is_ok());
+
+    source.finalize().unwrap();
+    sink.finalize().unwrap();
+}
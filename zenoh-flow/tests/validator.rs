@@ -1009,9 +1009,9 @@ fn validate_ok_loop() {
     assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
 }
 
-// Operator A has two outputs, it cannot be an Ingress
-static DESCRIPTOR_KO_LOOP_INGRESS_MULTIPLE_OUTPUTS: &str = r#"
-flow: Loop-KO-Ingress-multiple outputs
+// Operator A has two outputs, it can be an Ingress
+static DESCRIPTOR_OK_LOOP_INGRESS_MULTIPLE_OUTPUTS: &str = r#"
+flow: Loop-OK-Ingress-multiple outputs
 sources:
 - id : Source
   uri: file://./source.dylib
@@ -1097,18 +1097,14 @@ loops:
 "#;
 
 #[test]
-fn validate_ko_loop_ingress_multiple_outputs() {
-    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_LOOP_INGRESS_MULTIPLE_OUTPUTS);
-    assert!(
-        r.is_err(),
-        "Expecting err 'Ingress, multiple outputs', have: {:?}",
-        r
-    )
+fn validate_ok_loop_ingress_multiple_outputs() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_LOOP_INGRESS_MULTIPLE_OUTPUTS);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
 }
 
-// Operator C has two outputs, it cannot be an Egress
-static DESCRIPTOR_KO_LOOP_EGRESS_MULTIPLE_INPUTS: &str = r#"
-flow: Loop-KO-Egress-multiple-inputs
+// Operator C has two inputs, it can be an Egress
+static DESCRIPTOR_OK_LOOP_EGRESS_MULTIPLE_INPUTS: &str = r#"
+flow: Loop-OK-Egress-multiple-inputs
 sources:
 - id : Source
   uri: file://./source.dylib
@@ -1197,13 +1193,9 @@ loops:
 "#;
 
 #[test]
-fn validate_ko_loop_egress_multiple_inputs() {
-    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_LOOP_EGRESS_MULTIPLE_INPUTS);
-    assert!(
-        r.is_err(),
-        "Expecting err 'Egress, multiple inputs', have: {:?}",
-        r
-    )
+fn validate_ok_loop_egress_multiple_inputs() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_LOOP_EGRESS_MULTIPLE_INPUTS);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
 }
 
 // Operator A already has an input port called "feedback-AC".
@@ -1592,8 +1584,8 @@ fn validate_ko_cycle_outside_loops_section() {
     )
 }
 
-static DESCRIPTOR_KO_INGRESS_REUSED: &str = r#"
-flow: Loop-KO-Ingress-reused
+static DESCRIPTOR_OK_INGRESS_REUSED: &str = r#"
+flow: Loop-OK-Ingress-reused
 sources:
 - id : Source
   uri: file://./source.dylib
@@ -1674,17 +1666,13 @@ loops:
 "#;
 
 #[test]
-fn validate_ko_ingress_reused() {
-    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_INGRESS_REUSED);
-    assert!(
-        r.is_err(),
-        "Expecting err 'Ingress < A > is already used in another loop', have: {:?}",
-        r
-    )
+fn validate_ok_ingress_reused() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_INGRESS_REUSED);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
 }
 
-static DESCRIPTOR_KO_EGRESS_REUSED: &str = r#"
-flow: Loop-KO-Egress-reused
+static DESCRIPTOR_OK_EGRESS_REUSED: &str = r#"
+flow: Loop-OK-Egress-reused
 sources:
 - id : Source
   uri: file://./source.dylib
@@ -1765,17 +1753,13 @@ loops:
 "#;
 
 #[test]
-fn validate_ko_egress_reused() {
-    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_EGRESS_REUSED);
-    assert!(
-        r.is_err(),
-        "Expecting err 'Egress < C > is already used in another loop', have: {:?}",
-        r
-    )
+fn validate_ok_egress_reused() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_EGRESS_REUSED);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
 }
 
-static DESCRIPTOR_KO_EGRESS_REUSED_AS_INGRESS: &str = r#"
-flow: Loop-KO-Egress-reused-as-ingress
+static DESCRIPTOR_OK_EGRESS_REUSED_AS_INGRESS: &str = r#"
+flow: Loop-OK-Egress-reused-as-ingress
 sources:
 - id : Source
   uri: file://./source.dylib
@@ -1856,11 +1840,358 @@ loops:
 "#;
 
 #[test]
-fn validate_ko_egress_reused_as_ingress() {
-    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_EGRESS_REUSED_AS_INGRESS);
+fn validate_ok_egress_reused_as_ingress() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_EGRESS_REUSED_AS_INGRESS);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
+}
+
+static DESCRIPTOR_KO_FEEDBACK_PORT_REUSED: &str = r#"
+flow: Loop-KO-Feedback-port-reused
+sources:
+- id : Source
+  uri: file://./source.dylib
+  output:
+    id: out-Source
+    type: any
+
+operators:
+- id : A
+  uri: file://./A.dylib
+  inputs:
+    - id: in-A
+      type: any
+  outputs:
+    - id: out-A
+      type: any
+- id : B
+  uri: file://./B.dylib
+  inputs:
+    - id: in-B
+      type: any
+  outputs:
+    - id: out-B
+      type: any
+- id : C
+  uri: file://./C.dylib
+  inputs:
+    - id: in-C
+      type: any
+  outputs:
+    - id: out-C
+      type: any
+
+sinks:
+  - id : Sink
+    uri: file://./sink.dylib
+    input:
+      id: in-Sink
+      type: any
+
+links:
+- from:
+    node : Source
+    output : out-Source
+  to:
+    node : A
+    input : in-A
+- from:
+    node : A
+    output : out-A
+  to:
+    node : B
+    input : in-B
+- from:
+    node : B
+    output : out-B
+  to:
+    node : C
+    input : in-C
+- from:
+    node : C
+    output : out-C
+  to:
+    node : Sink
+    input : in-Sink
+
+loops:
+- ingress: A
+  egress: B
+  feedback_port: feedback
+  is_infinite: false
+  port_type: any
+- ingress: A
+  egress: C
+  feedback_port: feedback
+  is_infinite: false
+  port_type: any
+"#;
+
+#[test]
+fn validate_ko_feedback_port_reused() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_FEEDBACK_PORT_REUSED);
+    assert!(
+        r.is_err(),
+        "Expecting err 'Port < feedback > already exists for Ingress < A >', have: {:?}",
+        r
+    )
+}
+
+static DESCRIPTOR_OK_BOUNDED_LOOP: &str = r#"
+flow: Loop-OK-Bounded
+sources:
+- id : Source
+  uri: file://./source.dylib
+  output:
+    id: out-Source
+    type: any
+
+operators:
+- id : A
+  uri: file://./A.dylib
+  inputs:
+    - id: in-A
+      type: any
+  outputs:
+    - id: out-A
+      type: any
+- id : B
+  uri: file://./B.dylib
+  inputs:
+    - id: in-B
+      type: any
+  outputs:
+    - id: out-B
+      type: any
+- id : C
+  uri: file://./C.dylib
+  inputs:
+    - id: in-C
+      type: any
+  outputs:
+    - id: out-C
+      type: any
+
+sinks:
+  - id : Sink
+    uri: file://./sink.dylib
+    input:
+      id: in-Sink
+      type: any
+
+links:
+- from:
+    node : Source
+    output : out-Source
+  to:
+    node : A
+    input : in-A
+- from:
+    node : A
+    output : out-A
+  to:
+    node : B
+    input : in-B
+- from:
+    node : B
+    output : out-B
+  to:
+    node : C
+    input : in-C
+- from:
+    node : C
+    output : out-C
+  to:
+    node : Sink
+    input : in-Sink
+
+loops:
+- ingress: A
+  egress: C
+  feedback_port: feedback-AC
+  is_infinite: false
+  port_type: any
+  max_iterations: 5
+  iteration_timeout:
+    length: 100
+    unit: ms
+"#;
+
+#[test]
+fn validate_ok_bounded_loop() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_OK_BOUNDED_LOOP);
+    assert!(r.is_ok(), "Expecting ok, have err: {:?}", r)
+}
+
+static DESCRIPTOR_KO_INFINITE_LOOP_MAX_ITERATIONS: &str = r#"
+flow: Loop-KO-Infinite-max-iterations
+sources:
+- id : Source
+  uri: file://./source.dylib
+  output:
+    id: out-Source
+    type: any
+
+operators:
+- id : A
+  uri: file://./A.dylib
+  inputs:
+    - id: in-A
+      type: any
+  outputs:
+    - id: out-A
+      type: any
+- id : B
+  uri: file://./B.dylib
+  inputs:
+    - id: in-B
+      type: any
+  outputs:
+    - id: out-B
+      type: any
+- id : C
+  uri: file://./C.dylib
+  inputs:
+    - id: in-C
+      type: any
+  outputs:
+    - id: out-C
+      type: any
+
+sinks:
+  - id : Sink
+    uri: file://./sink.dylib
+    input:
+      id: in-Sink
+      type: any
+
+links:
+- from:
+    node : Source
+    output : out-Source
+  to:
+    node : A
+    input : in-A
+- from:
+    node : A
+    output : out-A
+  to:
+    node : B
+    input : in-B
+- from:
+    node : B
+    output : out-B
+  to:
+    node : C
+    input : in-C
+- from:
+    node : C
+    output : out-C
+  to:
+    node : Sink
+    input : in-Sink
+
+loops:
+- ingress: A
+  egress: C
+  feedback_port: feedback-AC
+  is_infinite: true
+  port_type: any
+  max_iterations: 5
+"#;
+
+#[test]
+fn validate_ko_infinite_loop_max_iterations() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_INFINITE_LOOP_MAX_ITERATIONS);
+    assert!(
+        r.is_err(),
+        "Expecting err 'The loop between < A > and < C > is infinite, it cannot have a maximum number of iterations', have: {:?}",
+        r
+    )
+}
+
+static DESCRIPTOR_KO_LOOP_ZERO_ITERATION: &str = r#"
+flow: Loop-KO-Zero-iteration
+sources:
+- id : Source
+  uri: file://./source.dylib
+  output:
+    id: out-Source
+    type: any
+
+operators:
+- id : A
+  uri: file://./A.dylib
+  inputs:
+    - id: in-A
+      type: any
+  outputs:
+    - id: out-A
+      type: any
+- id : B
+  uri: file://./B.dylib
+  inputs:
+    - id: in-B
+      type: any
+  outputs:
+    - id: out-B
+      type: any
+- id : C
+  uri: file://./C.dylib
+  inputs:
+    - id: in-C
+      type: any
+  outputs:
+    - id: out-C
+      type: any
+
+sinks:
+  - id : Sink
+    uri: file://./sink.dylib
+    input:
+      id: in-Sink
+      type: any
+
+links:
+- from:
+    node : Source
+    output : out-Source
+  to:
+    node : A
+    input : in-A
+- from:
+    node : A
+    output : out-A
+  to:
+    node : B
+    input : in-B
+- from:
+    node : B
+    output : out-B
+  to:
+    node : C
+    input : in-C
+- from:
+    node : C
+    output : out-C
+  to:
+    node : Sink
+    input : in-Sink
+
+loops:
+- ingress: A
+  egress: C
+  feedback_port: feedback-AC
+  is_infinite: false
+  port_type: any
+  max_iterations: 0
+"#;
+
+#[test]
+fn validate_ko_loop_zero_iteration() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_LOOP_ZERO_ITERATION);
     assert!(
         r.is_err(),
-        "Expecting err 'Ingress < B > is already used in another loop', have: {:?}",
+        "Expecting err 'The loop between < A > and < C > must allow at least one iteration', have: {:?}",
         r
     )
 }
@@ -2024,3 +2355,55 @@ fn validate_ko_flags_node_not_found() {
         r
     )
 }
+
+static DESCRIPTOR_KO_EXECUTOR_CPU: &str = r#"
+flow: SimplePipeline
+operators:
+  - id : SumOperator
+    uri: file://./target/release/libsum_and_send.dylib
+    inputs:
+      - id: Number
+        type: usize
+    outputs:
+      - id: Sum
+        type: usize
+    executor:
+      dedicated:
+        cpu_affinity: [0, 1000000]
+sources:
+  - id : Counter
+    uri: file://./target/release/libcounter_source.dylib
+    output:
+      id: Counter
+      type: usize
+sinks:
+  - id : PrintSink
+    uri: file://./target/release/libgeneric_sink.dylib
+    input:
+      id: Data
+      type: usize
+
+links:
+- from:
+    node : Counter
+    output : Counter
+  to:
+    node : SumOperator
+    input : Number
+- from:
+    node : SumOperator
+    output : Sum
+  to:
+    node : PrintSink
+    input : Data
+"#;
+
+#[test]
+fn validate_ko_executor_cpu() {
+    let r = DataFlowDescriptor::from_yaml(DESCRIPTOR_KO_EXECUTOR_CPU);
+    assert!(
+        matches!(r, Err(ZFError::ConfigurationError(_))),
+        "Expecting error 'Err(ConfigurationError(_))', have: {:?}",
+        r
+    )
+}
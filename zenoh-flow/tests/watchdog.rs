@@ -0,0 +1,382 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+mod types;
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use flume::Sender;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use types::VecSource;
+use zenoh_flow::model::deadline::DeadlineMissPolicy;
+use zenoh_flow::model::link::PortDescriptor;
+use zenoh_flow::model::{InputDescriptor, OutputDescriptor};
+use zenoh_flow::runtime::dataflow::instance::DataflowInstance;
+use zenoh_flow::runtime::dataflow::loader::{Loader, LoaderConfig};
+use zenoh_flow::runtime::watchdog::NodeHealth;
+use zenoh_flow::runtime::RuntimeContext;
+use zenoh_flow::{
+    zf_empty_state, Configuration, Context, DataMessage, DeadlineMiss, Node, Sink, State, ZFError,
+    ZFResult,
+};
+
+static SOURCE: &str = "Source";
+static SINK: &str = "Sink";
+
+struct SlowSink {
+    tx: Sender<()>,
+    callbacks: Arc<AtomicUsize>,
+}
+
+impl Node for SlowSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+
+    fn on_deadline_miss(&self, _state: &mut State, miss: &DeadlineMiss) -> ZFResult<()> {
+        assert!(matches!(miss, DeadlineMiss::Local(_)));
+        self.callbacks.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for SlowSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        _input: DataMessage,
+    ) -> ZFResult<()> {
+        // Way above both the deadline and the hang threshold.
+        async_std::task::sleep(Duration::from_millis(500)).await;
+        self.tx
+            .send_async(())
+            .await
+            .map_err(|e| ZFError::SendError(e.to_string()))
+    }
+}
+
+async fn single_runtime() {
+    let (tx_sink, rx_sink) = flume::bounded::<()>(1);
+    let callbacks = Arc::new(AtomicUsize::new(0));
+
+    let session = Arc::new(zenoh::open(zenoh::config::Config::default()).await.unwrap());
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: session.into(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow =
+        zenoh_flow::runtime::dataflow::Dataflow::new(ctx.clone(), "test".into(), None);
+
+    let source = Arc::new(VecSource::new(vec![1]));
+    let sink = Arc::new(SlowSink {
+        tx: tx_sink,
+        callbacks: callbacks.clone(),
+    });
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    assert!(dataflow
+        .try_set_deadline_miss_policy(&SINK.into(), DeadlineMissPolicy::Callback)
+        .is_err());
+    dataflow
+        .try_set_local_deadline(&SINK.into(), Duration::from_millis(100))
+        .unwrap();
+    dataflow
+        .try_set_deadline_miss_policy(&SINK.into(), DeadlineMissPolicy::Callback)
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+    let sink_id = SINK.into();
+    let hang_threshold = Duration::from_millis(100);
+
+    assert_eq!(
+        instance
+            .get_node_health(&sink_id, Some(hang_threshold))
+            .await
+            .unwrap(),
+        NodeHealth::Stopped
+    );
+
+    for id in &instance.get_nodes() {
+        instance.start_node(id).await.unwrap();
+    }
+
+    // The sink is now in the middle of its `run`.
+    async_std::task::sleep(Duration::from_millis(250)).await;
+
+    assert!(matches!(
+        instance
+            .get_node_health(&sink_id, Some(hang_threshold))
+            .await
+            .unwrap(),
+        NodeHealth::Hung { .. }
+    ));
+    assert_eq!(
+        instance.get_node_health(&sink_id, None).await.unwrap(),
+        NodeHealth::Running
+    );
+    assert!(instance
+        .get_hung_nodes(hang_threshold)
+        .await
+        .iter()
+        .any(|(id, _)| *id == sink_id));
+
+    // Once `run` returns, the miss of the local deadline is accounted for.
+    rx_sink.recv_async().await.unwrap();
+    async_std::task::sleep(Duration::from_millis(50)).await;
+
+    let stats = instance.get_deadline_miss_stats(&sink_id).await.unwrap();
+    assert_eq!(stats.missed, 1);
+    assert_eq!(stats.callback, 1);
+    assert_eq!(callbacks.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        instance
+            .get_node_health(&sink_id, Some(hang_threshold))
+            .await
+            .unwrap(),
+        NodeHealth::Running
+    );
+
+    instance
+        .restart_node(&sink_id, Duration::from_millis(100))
+        .await
+        .unwrap();
+    async_std::task::sleep(Duration::from_millis(50)).await;
+    assert!(instance.is_node_running(&sink_id).await.unwrap());
+
+    for id in &instance.get_nodes() {
+        instance.stop_node(id).await.unwrap()
+    }
+}
+
+struct HangingSink {
+    blocking: bool,
+    runs: Arc<AtomicUsize>,
+}
+
+impl Node for HangingSink {
+    fn initialize(&self, _configuration: &Option<Configuration>) -> ZFResult<State> {
+        zf_empty_state!()
+    }
+
+    fn finalize(&self, _state: &mut State) -> ZFResult<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for HangingSink {
+    async fn run(
+        &self,
+        _context: &mut Context,
+        _state: &mut State,
+        _input: DataMessage,
+    ) -> ZFResult<()> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        if self.blocking {
+            // Never acknowledges the stop: the execution is abandoned by the restart.
+            std::thread::sleep(Duration::from_secs(1));
+        } else {
+            async_std::task::sleep(Duration::from_secs(60)).await;
+        }
+        Ok(())
+    }
+}
+
+async fn restart_hung_node(blocking: bool) {
+    let runs = Arc::new(AtomicUsize::new(0));
+
+    let session = Arc::new(zenoh::open(zenoh::config::Config::default()).await.unwrap());
+    let hlc = async_std::sync::Arc::new(uhlc::HLC::default());
+    let rt_uuid = uuid::Uuid::new_v4();
+    let ctx = RuntimeContext {
+        session: session.into(),
+        hlc,
+        loader: Arc::new(Loader::new(LoaderConfig::new())),
+        runtime_name: format!("test-runtime-{}", rt_uuid).into(),
+        runtime_uuid: rt_uuid,
+        recordings_dir: None,
+    };
+
+    let mut dataflow =
+        zenoh_flow::runtime::dataflow::Dataflow::new(ctx.clone(), "test".into(), None);
+
+    let source = Arc::new(VecSource::new(vec![1]));
+    let sink = Arc::new(HangingSink {
+        blocking,
+        runs: runs.clone(),
+    });
+
+    dataflow
+        .try_add_static_source(
+            SOURCE.into(),
+            None,
+            PortDescriptor {
+                port_id: SOURCE.into(),
+                port_type: "int".into(),
+            },
+            source.initialize(&None).unwrap(),
+            source,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_static_sink(
+            SINK.into(),
+            PortDescriptor {
+                port_id: SINK.into(),
+                port_type: "int".into(),
+            },
+            sink.initialize(&None).unwrap(),
+            sink,
+        )
+        .unwrap();
+
+    dataflow
+        .try_add_link(
+            OutputDescriptor {
+                node: SOURCE.into(),
+                output: SOURCE.into(),
+            },
+            InputDescriptor {
+                node: SINK.into(),
+                input: SINK.into(),
+            },
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut instance = DataflowInstance::try_instantiate(dataflow).unwrap();
+    let sink_id = SINK.into();
+    let hang_threshold = Duration::from_millis(100);
+
+    for id in &instance.get_nodes() {
+        instance.start_node(id).await.unwrap();
+    }
+
+    async_std::task::sleep(Duration::from_millis(250)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert!(matches!(
+        instance
+            .get_node_health(&sink_id, Some(hang_threshold))
+            .await
+            .unwrap(),
+        NodeHealth::Hung { .. }
+    ));
+
+    instance
+        .restart_node(&sink_id, Duration::from_millis(100))
+        .await
+        .unwrap();
+    async_std::task::sleep(Duration::from_millis(250)).await;
+
+    // The restarted sink waits for a new input: the previous execution of `run`, cancelled or
+    // abandoned, must not be accounted to it.
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        instance
+            .get_node_health(&sink_id, Some(hang_threshold))
+            .await
+            .unwrap(),
+        NodeHealth::Running
+    );
+    assert!(!instance
+        .get_hung_nodes(hang_threshold)
+        .await
+        .iter()
+        .any(|(id, _)| *id == sink_id));
+
+    // An abandoned execution finishing late does not affect the new one either.
+    if blocking {
+        async_std::task::sleep(Duration::from_secs(1)).await;
+        assert_eq!(
+            instance.get_node_health(&sink_id, None).await.unwrap(),
+            NodeHealth::Running
+        );
+    }
+
+    for id in &instance.get_nodes() {
+        instance.stop_node(id).await.unwrap()
+    }
+}
+
+#[test]
+fn watchdog_restart_hung_node() {
+    async_std::task::block_on(async move {
+        restart_hung_node(false).await;
+        restart_hung_node(true).await;
+    })
+}
+
+#[test]
+fn watchdog() {
+    let h1 = async_std::task::spawn(async move { single_runtime().await });
+
+    async_std::task::block_on(async move { h1.await })
+}
@@ -22,16 +22,22 @@ extern crate prettytable;
 extern crate base64;
 extern crate exitfailure;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgEnum, Parser, Subcommand};
 use git_version::git_version;
 use prettytable::Table;
 use rand::seq::SliceRandom;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fs::read_to_string;
+use std::time::Duration;
 use uuid::Uuid;
 use zenoh::Session;
 use zenoh_flow::async_std::sync::Arc;
+use zenoh_flow::runtime::dataflow::instance::runners::replay::{ReplayOptions, ReplaySpeed};
+use zenoh_flow::runtime::dataflow::loader::ExtensibleImplementation;
+use zenoh_flow::runtime::recording::{
+    export_recording, import_recording, RecordingInfo, RecordingReader,
+};
 use zenoh_flow::runtime::resources::DataStore;
 use zenoh_flow::runtime::RuntimeClient;
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
@@ -108,20 +114,55 @@ pub enum StartKind {
             help = "The key expression where the record is stored"
         )]
         key_expr: String,
+        #[clap(
+            long,
+            name = "speed factor",
+            help = "Replays faster (> 1.0) or slower (< 1.0) than the recording",
+            conflicts_with = "fast"
+        )]
+        speed: Option<f64>,
+        #[clap(long, name = "fast", help = "Replays as fast as possible")]
+        fast: bool,
+        #[clap(
+            long,
+            name = "start ms",
+            help = "Skips the first milliseconds of the recording"
+        )]
+        start_ms: Option<u64>,
+        #[clap(
+            long,
+            name = "end ms",
+            help = "Stops the replay after the given milliseconds of the recording"
+        )]
+        end_ms: Option<u64>,
+        #[clap(long = "loop", help = "Replays the recording until stopped")]
+        looping: bool,
+        #[clap(
+            long,
+            help = "Streams the recording instead of retrieving it before replaying"
+        )]
+        streaming: bool,
     },
     #[clap(
-        about = "Starts recording the given source, in the given instance, returns the key expression containing the recording"
+        about = "Starts recording an output of the given node, in the given instance, returns the key expression containing the recording"
     )]
     Record {
         #[clap(
             short,
             long,
             name = "instance uuid",
-            help = "The instance containing the source"
+            help = "The instance containing the node"
         )]
         instance_id: Uuid,
-        #[clap(short, long, name = "source id", help = "The source identifier")]
-        source_id: String,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+        #[clap(
+            short,
+            long,
+            name = "port id",
+            help = "The output to record, optional if the node has a single output"
+        )]
+        port_id: Option<String>,
     },
     #[clap(about = "Starts the given flow instance")]
     Instance {
@@ -160,23 +201,124 @@ pub enum StopKind {
         replay_id: String,
     },
     #[clap(
-        about = "Stops recording the given source, in the given instance, returns the key expression containing the recording"
+        about = "Stops recording an output of the given node, in the given instance, returns the key expression containing the recording"
     )]
     Record {
         #[clap(
             short,
             long,
             name = "instance uuid",
-            help = "The instance containing the source"
+            help = "The instance containing the node"
         )]
         instance_id: Uuid,
-        #[clap(short, long, name = "source id", help = "The source identifier")]
+        #[clap(short, long, name = "node id", help = "The node identifier")]
         node_id: String,
+        #[clap(
+            short,
+            long,
+            name = "port id",
+            help = "The recorded output, optional if the node has a single output"
+        )]
+        port_id: Option<String>,
     },
     #[clap(about = "Stops the given flow instance")]
     Instance {
         #[clap(name = "instance uuid", help = "The instance to be stopped")]
         instance_id: Uuid,
+        #[clap(
+            long,
+            name = "drain ms",
+            help = "Processes the messages in flight, for at most these milliseconds, before stopping"
+        )]
+        drain_ms: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Pauses entities in Zenoh Flow")]
+pub enum PauseKind {
+    #[clap(about = "Pauses the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Pauses the given flow instance")]
+    Instance {
+        #[clap(name = "instance uuid", help = "The instance to be paused")]
+        instance_id: Uuid,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Resumes entities in Zenoh Flow")]
+pub enum ResumeKind {
+    #[clap(about = "Resumes the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Resumes the given flow instance")]
+    Instance {
+        #[clap(name = "instance uuid", help = "The instance to be resumed")]
+        instance_id: Uuid,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Checkpoints the state of entities in Zenoh Flow")]
+pub enum CheckpointKind {
+    #[clap(about = "Checkpoints the state of the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Checkpoints the state of the nodes of the given flow instance")]
+    Instance {
+        #[clap(name = "instance uuid", help = "The instance to be checkpointed")]
+        instance_id: Uuid,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Restores entities in Zenoh Flow from their latest checkpoints")]
+pub enum RestoreKind {
+    #[clap(about = "Restores the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Restores the nodes of the given flow instance")]
+    Instance {
+        #[clap(name = "instance uuid", help = "The instance to be restored")]
+        instance_id: Uuid,
     },
 }
 
@@ -198,6 +340,35 @@ pub enum GetKind {
         #[clap(name = "runtime uuid", help = "The runtime you are interested in")]
         id: Uuid,
     },
+    #[clap(about = "Gets the state of the given node in the given instance")]
+    Node {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Gets the counters of the deadline misses of the given node")]
+    DeadlineMisses {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node identifier")]
+        node_id: String,
+    },
+    #[clap(about = "Gets information about the given recording")]
+    Recording {
+        #[clap(name = "recording uuid", help = "The recording you are interested in")]
+        id: Uuid,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -209,6 +380,16 @@ pub enum ListKind {
     Instances,
     #[clap(about = "Lists the runtimes")]
     Runtimes,
+    #[clap(about = "Lists the recordings")]
+    Recordings {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "Only lists the recordings of the given instance"
+        )]
+        instance_id: Option<Uuid>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -224,6 +405,102 @@ pub enum DeleteKind {
         #[clap(name = "instance uuid", help = "The instance to be deleted")]
         id: Uuid,
     },
+    #[clap(about = "Deletes the given recording, its data and its file")]
+    Recording {
+        #[clap(name = "recording uuid", help = "The recording to be deleted")]
+        id: Uuid,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Moves recordings between Zenoh storages and files")]
+pub enum RecordingKind {
+    #[clap(about = "Exports a recording stored on Zenoh into a file")]
+    Export {
+        #[clap(
+            short,
+            long,
+            name = "zenoh key expression",
+            help = "The key expression where the recording is stored"
+        )]
+        key_expr: String,
+        #[clap(
+            parse(from_os_str),
+            name = "recording file",
+            help = "The file to write the recording to"
+        )]
+        path: std::path::PathBuf,
+    },
+    #[clap(
+        about = "Imports a recording file on Zenoh, returns the key expression where it is stored"
+    )]
+    Import {
+        #[clap(
+            parse(from_os_str),
+            name = "recording file",
+            help = "The recording file to import"
+        )]
+        path: std::path::PathBuf,
+        #[clap(
+            short,
+            long,
+            name = "zenoh key expression",
+            help = "Where to store the recording, defaults to the key expression of the original recording"
+        )]
+        key_expr: Option<String>,
+    },
+    #[clap(about = "Shows the content of a recording file")]
+    Inspect {
+        #[clap(
+            parse(from_os_str),
+            name = "recording file",
+            help = "The recording file to inspect"
+        )]
+        path: std::path::PathBuf,
+        #[clap(long, help = "Lists the offset and timestamp of each frame")]
+        frames: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+#[clap(about = "Manages the extensions of a runtime")]
+pub enum ExtensionKind {
+    #[clap(about = "Lists the extensions of the given runtime")]
+    List {
+        #[clap(name = "runtime uuid", help = "The runtime")]
+        runtime_id: Uuid,
+    },
+    #[clap(about = "Describes the given extension of the given runtime")]
+    Get {
+        #[clap(short, long, name = "runtime uuid", help = "The runtime")]
+        runtime_id: Uuid,
+        #[clap(name = "extension name", help = "The extension")]
+        name: String,
+    },
+    #[clap(about = "Validates and installs an extension on the given runtime")]
+    Install {
+        #[clap(short, long, name = "runtime uuid", help = "The runtime")]
+        runtime_id: Uuid,
+        #[clap(
+            parse(from_os_str),
+            name = "extension file",
+            help = "The extension file (.zfext)"
+        )]
+        path: std::path::PathBuf,
+    },
+    #[clap(about = "Removes the given extension from the given runtime")]
+    Remove {
+        #[clap(short, long, name = "runtime uuid", help = "The runtime")]
+        runtime_id: Uuid,
+        #[clap(name = "extension name", help = "The extension")]
+        name: String,
+    },
+}
+
+#[derive(ArgEnum, Clone, Debug)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
 }
 
 #[derive(Parser, Debug)]
@@ -242,6 +519,32 @@ pub enum ZFCtl {
     Start(StartKind),
     #[clap(subcommand)]
     Stop(StopKind),
+    #[clap(subcommand)]
+    Pause(PauseKind),
+    #[clap(subcommand)]
+    Resume(ResumeKind),
+    #[clap(subcommand)]
+    Checkpoint(CheckpointKind),
+    #[clap(subcommand)]
+    Restore(RestoreKind),
+    #[clap(about = "Migrates a node of a flow instance to another runtime")]
+    Migrate {
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance containing the node"
+        )]
+        instance_id: Uuid,
+        #[clap(short, long, name = "node id", help = "The node to be migrated")]
+        node_id: String,
+        #[clap(short, long, name = "runtime name", help = "The target runtime")]
+        runtime: String,
+    },
+    #[clap(subcommand)]
+    Recording(RecordingKind),
+    #[clap(subcommand)]
+    Extension(ExtensionKind),
     #[clap(about = "Creates and starts a flow instance")]
     Launch {
         #[clap(
@@ -255,6 +558,38 @@ pub enum ZFCtl {
     Destroy {
         #[clap(name = "instance uuid", help = "The instance to be destroyed")]
         id: Uuid,
+        #[clap(
+            long,
+            name = "drain ms",
+            help = "Processes the messages in flight, for at most these milliseconds, before stopping"
+        )]
+        drain_ms: Option<u64>,
+    },
+    #[clap(about = "Exports a flow, or a flow instance, as a diagram")]
+    Graph {
+        #[clap(
+            parse(from_os_str),
+            name = "Flow descriptor path",
+            help = "The flow to export",
+            required_unless_present = "instance uuid"
+        )]
+        descriptor_path: Option<std::path::PathBuf>,
+        #[clap(
+            short,
+            long,
+            name = "instance uuid",
+            help = "The instance to export, showing the runtimes and connectors",
+            conflicts_with = "Flow descriptor path"
+        )]
+        instance_id: Option<Uuid>,
+        #[clap(
+            short,
+            long,
+            arg_enum,
+            default_value = "dot",
+            help = "The format of the diagram"
+        )]
+        format: GraphFormat,
     },
 }
 
@@ -380,6 +715,69 @@ async fn main() {
                 ]);
                 table.printstd();
             }
+            GetKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                let state = client
+                    .get_node_state(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row!["UUID", "Name", "Status",]);
+                table.add_row(row![instance_id, node_id, state]);
+                table.printstd();
+            }
+            GetKind::DeadlineMisses {
+                instance_id,
+                node_id,
+            } => {
+                let client = get_client(zsession.clone()).await;
+                let stats = client
+                    .get_deadline_miss_stats(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["Node", node_id]);
+                table.add_row(row!["Missed", stats.missed]);
+                table.add_row(row!["Dropped", stats.dropped]);
+                table.add_row(row!["Fallback", stats.fallback]);
+                table.add_row(row!["Callback", stats.callback]);
+                table.add_row(row!["Escalated", stats.escalated]);
+                table.printstd();
+            }
+            GetKind::Recording { id } => {
+                let client = get_client(zsession.clone()).await;
+                let recording = client.get_recording(id).await.unwrap().unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["UUID", recording.id]);
+                table.add_row(row!["Key Expr", recording.key_expr]);
+                table.add_row(row!["Flow", recording.flow_id]);
+                table.add_row(row!["Instance", recording.instance_id]);
+                table.add_row(row!["Node", recording.node_id]);
+                table.add_row(row!["Port", recording.port_id]);
+                table.add_row(row!["Port Type", recording.port_type]);
+                table.add_row(row!["Runtime", recording.runtime_id]);
+                table.add_row(row!["Started", recording.start]);
+                table.add_row(row![
+                    "Stopped",
+                    recording
+                        .stop
+                        .map(|stop| stop.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                ]);
+                table.add_row(row!["Duration", recording_duration(&recording)]);
+                table.add_row(row!["Messages", recording.messages]);
+                table.add_row(row!["Size (bytes)", recording.size]);
+                table.add_row(row![
+                    "File",
+                    recording.file.unwrap_or_else(|| "-".to_string())
+                ]);
+                table.printstd();
+            }
         },
         ZFCtl::Delete(dk) => match dk {
             DeleteKind::Flow { id } => {
@@ -393,6 +791,14 @@ async fn main() {
                 log::debug!("Deleted: {:?}", record);
                 println!("{}", record.uuid);
             }
+            DeleteKind::Recording { id } => {
+                log::debug!("This is going to delete the recording {:?}", id);
+                let client = get_client(zsession.clone()).await;
+                let recording = client.delete_recording(id).await.unwrap().unwrap();
+
+                log::debug!("Deleted: {:?}", recording);
+                println!("{}", recording.id);
+            }
         },
         ZFCtl::Start(sk) => match sk {
             StartKind::Node {
@@ -412,29 +818,52 @@ async fn main() {
             }
             StartKind::Record {
                 instance_id,
-                source_id,
+                node_id,
+                port_id,
             } => {
                 let mut table = Table::new();
                 let client = get_client(zsession.clone()).await;
                 table.add_row(row!["UUID", "Name", "Key Expression",]);
                 let key_expr = client
-                    .start_record(instance_id, source_id.clone().into())
+                    .start_record(
+                        instance_id,
+                        node_id.clone().into(),
+                        port_id.map(|port_id| port_id.into()),
+                    )
                     .await
                     .unwrap()
                     .unwrap();
-                table.add_row(row![instance_id, source_id, key_expr,]);
+                table.add_row(row![instance_id, node_id, key_expr,]);
                 table.printstd();
             }
             StartKind::Replay {
                 instance_id,
                 source_id,
                 key_expr,
+                speed,
+                fast,
+                start_ms,
+                end_ms,
+                looping,
+                streaming,
             } => {
+                let speed = match (fast, speed) {
+                    (true, _) => ReplaySpeed::AsFastAsPossible,
+                    (false, Some(factor)) => ReplaySpeed::Factor(factor),
+                    (false, None) => ReplaySpeed::RealTime,
+                };
+                let options = ReplayOptions {
+                    speed,
+                    start: start_ms.map(Duration::from_millis),
+                    end: end_ms.map(Duration::from_millis),
+                    looping,
+                    streaming,
+                };
                 let mut table = Table::new();
                 let client = get_client(zsession.clone()).await;
                 table.add_row(row!["UUID", "Name", "Replay Id",]);
                 let replay_id = client
-                    .start_replay(instance_id, source_id.clone().into(), key_expr)
+                    .start_replay(instance_id, source_id.clone().into(), key_expr, options)
                     .await
                     .unwrap()
                     .unwrap();
@@ -468,12 +897,17 @@ async fn main() {
             StopKind::Record {
                 instance_id,
                 node_id,
+                port_id,
             } => {
                 let mut table = Table::new();
                 let client = get_client(zsession.clone()).await;
                 table.add_row(row!["UUID", "Name", "Key Expression",]);
                 let key_expr = client
-                    .stop_record(instance_id, node_id.clone().into())
+                    .stop_record(
+                        instance_id,
+                        node_id.clone().into(),
+                        port_id.map(|port_id| port_id.into()),
+                    )
                     .await
                     .unwrap()
                     .unwrap();
@@ -500,14 +934,152 @@ async fn main() {
                 table.add_row(row![instance_id, node_id, replay_id,]);
                 table.printstd();
             }
-            StopKind::Instance { instance_id } => {
+            StopKind::Instance {
+                instance_id,
+                drain_ms,
+            } => {
                 log::debug!("This is going to stop the instance {:?}", instance_id);
                 let client = get_client(zsession.clone()).await;
-                let record = client.stop_instance(instance_id).await.unwrap().unwrap();
+                let record = client
+                    .stop_instance(instance_id, drain_ms.map(Duration::from_millis))
+                    .await
+                    .unwrap()
+                    .unwrap();
                 log::debug!("stopeed: {:?}", record);
                 println!("{}", record.uuid);
             }
         },
+        ZFCtl::Pause(k) => match k {
+            PauseKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name", "Status",]);
+                client
+                    .pause_node(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row![instance_id, node_id, String::from("Paused"),]);
+                table.printstd();
+            }
+            PauseKind::Instance { instance_id } => {
+                log::debug!("This is going to pause the instance {:?}", instance_id);
+                let client = get_client(zsession.clone()).await;
+                client.pause_instance(instance_id).await.unwrap().unwrap();
+                log::debug!("Paused: {:?}", instance_id);
+                println!("{}", instance_id);
+            }
+        },
+        ZFCtl::Resume(k) => match k {
+            ResumeKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name", "Status",]);
+                client
+                    .resume_node(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row![instance_id, node_id, String::from("Running"),]);
+                table.printstd();
+            }
+            ResumeKind::Instance { instance_id } => {
+                log::debug!("This is going to resume the instance {:?}", instance_id);
+                let client = get_client(zsession.clone()).await;
+                client.resume_instance(instance_id).await.unwrap().unwrap();
+                log::debug!("Running: {:?}", instance_id);
+                println!("{}", instance_id);
+            }
+        },
+        ZFCtl::Checkpoint(k) => match k {
+            CheckpointKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name", "Checkpointed",]);
+                let checkpointed = client
+                    .checkpoint_node(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row![instance_id, node_id, checkpointed,]);
+                table.printstd();
+            }
+            CheckpointKind::Instance { instance_id } => {
+                log::debug!("This is going to checkpoint the instance {:?}", instance_id);
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name",]);
+                let nodes = client
+                    .checkpoint_instance(instance_id)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                for node_id in nodes {
+                    table.add_row(row![instance_id, node_id,]);
+                }
+                table.printstd();
+            }
+        },
+        ZFCtl::Restore(k) => match k {
+            RestoreKind::Node {
+                instance_id,
+                node_id,
+            } => {
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name", "Status",]);
+                client
+                    .restore_node(instance_id, node_id.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                table.add_row(row![instance_id, node_id, String::from("Restored"),]);
+                table.printstd();
+            }
+            RestoreKind::Instance { instance_id } => {
+                log::debug!("This is going to restore the instance {:?}", instance_id);
+                let mut table = Table::new();
+                let client = get_client(zsession.clone()).await;
+                table.add_row(row!["UUID", "Name",]);
+                let nodes = client.restore_instance(instance_id).await.unwrap().unwrap();
+                for node_id in nodes {
+                    table.add_row(row![instance_id, node_id,]);
+                }
+                table.printstd();
+            }
+        },
+        ZFCtl::Migrate {
+            instance_id,
+            node_id,
+            runtime,
+        } => {
+            log::debug!(
+                "This is going to migrate the node {} of the instance {:?} to {}",
+                node_id,
+                instance_id,
+                runtime
+            );
+            let mut table = Table::new();
+            let client = get_client(zsession.clone()).await;
+            table.add_row(row!["UUID", "Node", "Runtime",]);
+            let record = client
+                .migrate_node(instance_id, node_id.clone(), runtime)
+                .await
+                .unwrap()
+                .unwrap();
+            let rt = record.find_node_runtime(&node_id).unwrap();
+            table.add_row(row![instance_id, node_id, rt,]);
+            table.printstd();
+        }
         ZFCtl::List(lk) => {
             let mut table = Table::new();
             match lk {
@@ -545,6 +1117,32 @@ async fn main() {
                         table.add_row(row![r.id, r.name, format!("{:?}", r.status),]);
                     }
                 }
+                ListKind::Recordings { instance_id } => {
+                    let client = get_client(zsession.clone()).await;
+                    let mut recordings =
+                        client.list_recordings(instance_id).await.unwrap().unwrap();
+                    recordings.sort_by_key(|recording| recording.start);
+                    table.add_row(row![
+                        "UUID",
+                        "Instance",
+                        "Node",
+                        "Port",
+                        "Duration",
+                        "# Messages",
+                        "Size (bytes)",
+                    ]);
+                    for recording in recordings {
+                        table.add_row(row![
+                            recording.id,
+                            recording.instance_id,
+                            recording.node_id,
+                            recording.port_id,
+                            recording_duration(&recording),
+                            recording.messages,
+                            recording.size,
+                        ]);
+                    }
+                }
             };
             table.printstd();
         }
@@ -562,13 +1160,149 @@ async fn main() {
             log::debug!("Launched: {:?}", record);
             println!("{}", record.uuid);
         }
-        ZFCtl::Destroy { id } => {
+        ZFCtl::Destroy { id, drain_ms } => {
             log::debug!("This is going to destroy the instance {}", id);
             let client = get_client(zsession.clone()).await;
-            let record = client.teardown(id).await.unwrap().unwrap();
+            let record = client
+                .teardown(id, drain_ms.map(Duration::from_millis))
+                .await
+                .unwrap()
+                .unwrap();
             log::debug!("Destroyed: {:?}", record);
             println!("{}", record.uuid);
         }
+        ZFCtl::Graph {
+            descriptor_path,
+            instance_id,
+            format,
+        } => {
+            let graph = match (descriptor_path, instance_id) {
+                (_, Some(instance_id)) => {
+                    log::debug!("This is going to export the instance {}", instance_id);
+                    let record = store.get_flow_by_instance(&instance_id).await.unwrap();
+                    match format {
+                        GraphFormat::Dot => record.to_dot(),
+                        GraphFormat::Mermaid => record.to_mermaid(),
+                    }
+                }
+                (Some(descriptor_path), None) => {
+                    log::debug!("This is going to export the flow {:?}", descriptor_path);
+                    let yaml_df = read_to_string(descriptor_path).unwrap();
+                    let df =
+                        zenoh_flow::model::dataflow::descriptor::DataFlowDescriptor::from_yaml(
+                            &yaml_df,
+                        )
+                        .unwrap();
+                    match format {
+                        GraphFormat::Dot => df.to_dot(),
+                        GraphFormat::Mermaid => df.to_mermaid(),
+                    }
+                }
+                (None, None) => unreachable!(),
+            };
+            print!("{}", graph);
+        }
+        ZFCtl::Extension(ek) => match ek {
+            ExtensionKind::List { runtime_id } => {
+                let client = RuntimeClient::new(zsession.clone(), runtime_id);
+                let extensions = client.list_extensions().await.unwrap().unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["Name", "File Extension",]);
+                for extension in extensions {
+                    table.add_row(row![extension.name, extension.file_extension,]);
+                }
+                table.printstd();
+            }
+            ExtensionKind::Get { runtime_id, name } => {
+                let client = RuntimeClient::new(zsession.clone(), runtime_id);
+                let extension = client.get_extension(name).await.unwrap().unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["Name", extension.name]);
+                table.add_row(row!["File Extension", extension.file_extension]);
+                table.add_row(row!["Source Library", extension.source_lib]);
+                table.add_row(row!["Sink Library", extension.sink_lib]);
+                table.add_row(row!["Operator Library", extension.operator_lib]);
+                table.add_row(row!["Configuration Key", extension.config_lib_key]);
+                table.printstd();
+            }
+            ExtensionKind::Install { runtime_id, path } => {
+                log::debug!("This is going to install {:?} on {:?}", path, runtime_id);
+                let extension: ExtensibleImplementation =
+                    serde_yaml::from_str(&read_to_string(&path).unwrap()).unwrap();
+                let client = RuntimeClient::new(zsession.clone(), runtime_id);
+                client
+                    .install_extension(extension.clone())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["Runtime", "Name", "Status",]);
+                table.add_row(row![runtime_id, extension.name, String::from("Installed"),]);
+                table.printstd();
+            }
+            ExtensionKind::Remove { runtime_id, name } => {
+                log::debug!("This is going to remove {} from {:?}", name, runtime_id);
+                let client = RuntimeClient::new(zsession.clone(), runtime_id);
+                let extension = client.remove_extension(name).await.unwrap().unwrap();
+                let mut table = Table::new();
+                table.add_row(row!["Runtime", "Name", "Status",]);
+                table.add_row(row![runtime_id, extension.name, String::from("Removed"),]);
+                table.printstd();
+            }
+        },
+        ZFCtl::Recording(rk) => match rk {
+            RecordingKind::Export { key_expr, path } => {
+                log::debug!("This is going to export {} into {:?}", key_expr, path);
+                let header = export_recording(&zsession, &key_expr, &path).await.unwrap();
+                let metadata = &header.metadata;
+                let mut table = Table::new();
+                table.add_row(row!["Key Expr", "Node", "Port", "File",]);
+                table.add_row(row![
+                    key_expr,
+                    metadata.get_node_id(),
+                    metadata.get_port_id(),
+                    path.display(),
+                ]);
+                table.printstd();
+            }
+            RecordingKind::Import { path, key_expr } => {
+                log::debug!("This is going to import {:?}", path);
+                let key_expr = import_recording(&zsession, &path, key_expr).await.unwrap();
+                println!("{}", key_expr);
+            }
+            RecordingKind::Inspect { path, frames } => {
+                let mut recording = RecordingReader::open(&path).unwrap();
+                let index = recording.index().unwrap();
+                let metadata = &recording.header().metadata;
+                let duration = match (index.first(), index.last()) {
+                    (Some(first), Some(last)) => {
+                        format!("{:?}", last.timestamp.get_diff_duration(&first.timestamp))
+                    }
+                    _ => "-".to_string(),
+                };
+                let mut table = Table::new();
+                table.add_row(row!["Flow", metadata.get_flow_id()]);
+                table.add_row(row!["Instance", metadata.get_instance_id()]);
+                table.add_row(row!["Node", metadata.get_node_id()]);
+                table.add_row(row!["Port", metadata.get_port_id()]);
+                table.add_row(row!["Port Type", metadata.get_port_type()]);
+                table.add_row(row!["Key Expr", metadata.get_resource_name()]);
+                table.add_row(row!["Started", metadata.get_timestamp()]);
+                table.add_row(row!["Frames", index.len()]);
+                table.add_row(row!["Duration", duration]);
+                table.add_row(row!["Indexed", recording.is_indexed()]);
+                table.printstd();
+
+                if frames {
+                    let mut table = Table::new();
+                    table.add_row(row!["Offset", "Timestamp",]);
+                    for entry in index {
+                        table.add_row(row![entry.offset, entry.timestamp,]);
+                    }
+                    table.printstd();
+                }
+            }
+        },
     }
 }
 
@@ -587,6 +1321,13 @@ async fn get_zenoh() -> Result<Session, Box<dyn Error + Send + Sync + 'static>>
     Ok(zenoh::open(zconfig).await.unwrap())
 }
 
+fn recording_duration(recording: &RecordingInfo) -> String {
+    match recording.stop {
+        Some(stop) => format!("{:?}", stop.get_diff_duration(&recording.start)),
+        None => "recording".to_string(),
+    }
+}
+
 async fn get_client(zsession: Arc<Session>) -> RuntimeClient {
     let servers = RuntimeClient::find_servers(zsession.clone()).await.unwrap();
     let entry_point = servers.choose(&mut rand::thread_rng()).unwrap();